@@ -497,6 +497,19 @@ impl Color {
         Ok(rgb)
     }
 
+    /// Get the name of this color.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the color is currently mutably borrowed.
+    pub fn name(&self) -> Result<String> {
+        let name = match self {
+            Self::SpotColor(ref_cell) => ref_cell.try_borrow()?.name().to_owned(),
+            Self::MixedColor(ref_cell) => ref_cell.try_borrow()?.name().to_owned(),
+        };
+        Ok(name)
+    }
+
     /// Returns `true` if this color knocks out colors beneath it.
     ///
     /// # Errors
@@ -0,0 +1,987 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    hash::{DefaultHasher, Hash, Hasher as _},
+};
+
+use quick_xml::Writer;
+
+use crate::{
+    Code, NonNegativeF64, Omap, Result,
+    colors::{Cmyk, CmykMode, Color, RgbMode, SymbolColor},
+    symbols::{
+        AreaOrLineSymbol, AreaSymbol, BorderStyle, DashStyle, Element, FillPattern, FramingMode,
+        GroupDashes, LineSymbol, LineSymbolBorder, PointSymbol, PublicOrPrivateSymbol, Symbol,
+        SymbolCommon, TextSymbol, WeakSymbol,
+    },
+};
+
+/// Decimals of lengths in mm, which the file stores in micrometres.
+const LENGTH_DECIMALS: i32 = 3;
+/// Decimals of CMYK values and color factors in the file.
+const FACTOR_DECIMALS: i32 = 3;
+/// Decimals of screen angles and frequencies in the file.
+const SCREEN_DECIMALS: i32 = 1;
+/// Decimals of angles and factors the file stores at full precision, enough to
+/// hide the noise of arithmetic on them.
+const FLOAT_DECIMALS: i32 = 9;
+
+/// A field of a symbol or color that differs between two maps.
+///
+/// Fields of nested definitions are named by their path, e.g.
+/// `border.left.width` or `patterns[0].line_spacing`. Numbers are compared
+/// at the precision of the file, e.g. lengths in micrometres.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDifference {
+    /// The path of the field.
+    pub field: String,
+    /// The value in the first map, `None` if the field does not exist there.
+    pub old: Option<String>,
+    /// The value in the second map, `None` if the field does not exist there.
+    pub new: Option<String>,
+}
+
+/// A difference between the symbol sets of two maps, matched by [`Code`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolDifference {
+    /// A symbol only in the second map.
+    Added {
+        /// The code of the symbol.
+        code: Code,
+        /// The name of the symbol.
+        name: String,
+    },
+    /// A symbol only in the first map.
+    Removed {
+        /// The code of the symbol.
+        code: Code,
+        /// The name of the symbol.
+        name: String,
+    },
+    /// A symbol in both maps with different definitions.
+    Changed {
+        /// The code of the symbol.
+        code: Code,
+        /// The name of the symbol in the first map.
+        name: String,
+        /// The fields that differ.
+        fields: Vec<FieldDifference>,
+    },
+}
+
+/// A difference between the color sets of two maps, matched by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorDifference {
+    /// A color only in the second map.
+    Added {
+        /// The name of the color.
+        name: String,
+        /// The priority of the color in the second map.
+        priority: usize,
+    },
+    /// A color only in the first map.
+    Removed {
+        /// The name of the color.
+        name: String,
+        /// The priority of the color in the first map.
+        priority: usize,
+    },
+    /// A color in both maps with different definitions or order.
+    Changed {
+        /// The name of the color.
+        name: String,
+        /// The fields that differ. The `priority` field differs if the color
+        /// is in another order relative to the colors both maps have.
+        fields: Vec<FieldDifference>,
+    },
+}
+
+/// The differences between the symbol sets and color sets of two maps, see
+/// [`Omap::diff_symbol_sets`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolSetDiff {
+    /// The differences of the symbols, in the order of the first map followed
+    /// by the added symbols.
+    pub symbols: Vec<SymbolDifference>,
+    /// The differences of the colors, in the priority order of the first map
+    /// followed by the added colors.
+    pub colors: Vec<ColorDifference>,
+}
+
+impl SymbolSetDiff {
+    /// Whether the symbol sets and color sets are the same.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty() && self.colors.is_empty()
+    }
+}
+
+impl Omap {
+    /// Compare the symbol set and color set of the map with those of `other`,
+    /// e.g. before merging the maps of two mappers.
+    ///
+    /// Symbols are matched by [`Code`] and colors by name, and repeated codes
+    /// or names are matched in order of appearance. Matched symbols are
+    /// compared field by field, including their colors by name, their fill
+    /// patterns and the elements of point symbols, and matched colors by their
+    /// CMYK and RGB values, spot color names and order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol or color is mutably borrowed somewhere else.
+    pub fn diff_symbol_sets(&self, other: &Self) -> Result<SymbolSetDiff> {
+        Ok(SymbolSetDiff {
+            symbols: diff_symbols(self, other)?,
+            colors: diff_colors(self, other)?,
+        })
+    }
+}
+
+fn diff_symbols(old: &Omap, new: &Omap) -> Result<Vec<SymbolDifference>> {
+    let keys = |map: &Omap| {
+        map.symbols
+            .iter()
+            .map(|symbol| symbol.code())
+            .collect::<Result<Vec<_>>>()
+    };
+    let (old_codes, new_codes) = (keys(old)?, keys(new)?);
+    let new_symbols = new.symbols.iter().collect::<Vec<_>>();
+    let matches = match_in_order(&old_codes, &new_codes);
+
+    let mut differences = Vec::new();
+    for (old_symbol, (code, matched)) in old.symbols.iter().zip(old_codes.iter().zip(&matches)) {
+        let name = old_symbol.common()?.name.clone();
+        let Some(index) = matched else {
+            differences.push(SymbolDifference::Removed { code: *code, name });
+            continue;
+        };
+        let fields = diff_fields(
+            &symbol_fields(old_symbol)?,
+            &symbol_fields(new_symbols[*index])?,
+        );
+        if !fields.is_empty() {
+            differences.push(SymbolDifference::Changed {
+                code: *code,
+                name,
+                fields,
+            });
+        }
+    }
+    for (index, symbol) in new_symbols.iter().enumerate() {
+        if !matches.contains(&Some(index)) {
+            differences.push(SymbolDifference::Added {
+                code: new_codes[index],
+                name: symbol.common()?.name.clone(),
+            });
+        }
+    }
+    Ok(differences)
+}
+
+fn diff_colors(old: &Omap, new: &Omap) -> Result<Vec<ColorDifference>> {
+    let keys = |map: &Omap| {
+        map.colors
+            .iter()
+            .map(Color::name)
+            .collect::<Result<Vec<_>>>()
+    };
+    let (old_names, new_names) = (keys(old)?, keys(new)?);
+    let new_colors = new.colors.iter().collect::<Vec<_>>();
+    let matches = match_in_order(&old_names, &new_names);
+
+    // the order of the colors in both maps among themselves
+    let mut shared_order = matches.iter().flatten().copied().collect::<Vec<_>>();
+    shared_order.sort_unstable();
+
+    let mut differences = Vec::new();
+    let mut rank = 0;
+    for (priority, (color, matched)) in old.colors.iter().zip(&matches).enumerate() {
+        let name = old_names[priority].clone();
+        let Some(index) = *matched else {
+            differences.push(ColorDifference::Removed { name, priority });
+            continue;
+        };
+        let mut fields = diff_fields(&color_fields(color)?, &color_fields(new_colors[index])?);
+        if shared_order[rank] != index {
+            fields.insert(
+                0,
+                FieldDifference {
+                    field: "priority".to_owned(),
+                    old: Some(priority.to_string()),
+                    new: Some(index.to_string()),
+                },
+            );
+        }
+        rank += 1;
+        if !fields.is_empty() {
+            differences.push(ColorDifference::Changed { name, fields });
+        }
+    }
+    for (priority, name) in new_names.into_iter().enumerate() {
+        if !matches.contains(&Some(priority)) {
+            differences.push(ColorDifference::Added { name, priority });
+        }
+    }
+    Ok(differences)
+}
+
+/// The index in `new` of the key at each position of `old`, matching repeated
+/// keys in order of appearance.
+fn match_in_order<K: Eq + Hash>(old: &[K], new: &[K]) -> Vec<Option<usize>> {
+    let mut positions = HashMap::<&K, Vec<usize>>::new();
+    for (index, key) in new.iter().enumerate().rev() {
+        positions.entry(key).or_default().push(index);
+    }
+    old.iter()
+        .map(|key| positions.get_mut(key).and_then(Vec::pop))
+        .collect()
+}
+
+/// The differing fields of two lists of fields, in the order of `old`
+/// followed by the fields only in `new`.
+fn diff_fields(old: &[(String, String)], new: &[(String, String)]) -> Vec<FieldDifference> {
+    let new_values = new.iter().cloned().collect::<HashMap<_, _>>();
+    let old_values = old.iter().cloned().collect::<HashMap<_, _>>();
+    let mut differences = Vec::new();
+    for (field, value) in old {
+        let new_value = new_values.get(field);
+        if new_value != Some(value) {
+            differences.push(FieldDifference {
+                field: field.clone(),
+                old: Some(value.clone()),
+                new: new_value.cloned(),
+            });
+        }
+    }
+    for (field, value) in new {
+        if !old_values.contains_key(field) {
+            differences.push(FieldDifference {
+                field: field.clone(),
+                old: None,
+                new: Some(value.clone()),
+            });
+        }
+    }
+    differences
+}
+
+/// A list of the fields of a definition with their values as text.
+#[derive(Default)]
+struct Fields {
+    prefix: String,
+    values: Vec<(String, String)>,
+}
+
+impl Fields {
+    fn push(&mut self, field: &str, value: impl Display) {
+        self.values
+            .push((format!("{}{field}", self.prefix), value.to_string()));
+    }
+
+    fn length(&mut self, field: &str, value: NonNegativeF64) {
+        self.number(field, value.get(), LENGTH_DECIMALS);
+    }
+
+    fn number(&mut self, field: &str, value: f64, decimals: i32) {
+        self.push(field, rounded(value, decimals));
+    }
+
+    fn color(&mut self, field: &str, color: &SymbolColor) -> Result<()> {
+        let value = match color {
+            SymbolColor::Color(weak) => match weak.upgrade() {
+                Some(color) => color.name()?,
+                None => "missing color".to_owned(),
+            },
+            SymbolColor::RegistrationBlack => "registration black".to_owned(),
+            SymbolColor::NoColor => "no color".to_owned(),
+        };
+        self.push(field, value);
+        Ok(())
+    }
+
+    /// Add the fields of a nested definition under `prefix`.
+    fn nested<F>(&mut self, prefix: &str, fields: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        let outer = self.prefix.clone();
+        self.prefix = format!("{outer}{prefix}.");
+        let result = fields(self);
+        self.prefix = outer;
+        result
+    }
+}
+
+fn symbol_fields(symbol: &Symbol) -> Result<Vec<(String, String)>> {
+    let mut fields = Fields::default();
+    common_fields(&mut fields, &*symbol.common()?);
+    match symbol {
+        Symbol::Line(line) => {
+            fields.push("type", "line");
+            line_fields(&mut fields, &*line.try_borrow()?)?;
+        }
+        Symbol::Area(area) => {
+            fields.push("type", "area");
+            area_fields(&mut fields, &*area.try_borrow()?)?;
+        }
+        Symbol::Point(point) => {
+            fields.push("type", "point");
+            point_fields(&mut fields, &*point.try_borrow()?)?;
+        }
+        Symbol::Text(text) => {
+            fields.push("type", "text");
+            text_fields(&mut fields, &*text.try_borrow()?)?;
+        }
+        Symbol::CombinedArea(combined) => {
+            fields.push("type", "combined area");
+            for (index, component) in combined.try_borrow()?.components().enumerate() {
+                let field = format!("parts[{index}]");
+                match component {
+                    PublicOrPrivateSymbol::Public(weak) => {
+                        public_component(&mut fields, &field, weak.upgrade())?;
+                    }
+                    PublicOrPrivateSymbol::Private(AreaOrLineSymbol::Area(area)) => {
+                        fields.push(&field, "private area");
+                        fields.nested(&field, |fields| area_fields(fields, area))?;
+                    }
+                    PublicOrPrivateSymbol::Private(AreaOrLineSymbol::Line(line)) => {
+                        fields.push(&field, "private line");
+                        fields.nested(&field, |fields| line_fields(fields, line))?;
+                    }
+                }
+            }
+        }
+        Symbol::CombinedLine(combined) => {
+            fields.push("type", "combined line");
+            for (index, component) in combined.try_borrow()?.components().enumerate() {
+                let field = format!("parts[{index}]");
+                match component {
+                    PublicOrPrivateSymbol::Public(weak) => {
+                        let symbol = WeakSymbol::from(weak.clone()).upgrade();
+                        public_component(&mut fields, &field, symbol)?;
+                    }
+                    PublicOrPrivateSymbol::Private(line) => {
+                        fields.push(&field, "private line");
+                        fields.nested(&field, |fields| line_fields(fields, line))?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(fields.values)
+}
+
+fn public_component(fields: &mut Fields, field: &str, symbol: Option<Symbol>) -> Result<()> {
+    match symbol {
+        Some(symbol) => fields.push(field, format!("public {}", symbol.code()?)),
+        None => fields.push(field, "missing symbol"),
+    }
+    Ok(())
+}
+
+fn common_fields(fields: &mut Fields, common: &SymbolCommon) {
+    fields.push("name", &common.name);
+    fields.push("description", &common.description);
+    fields.push("is_helper_symbol", common.is_helper_symbol);
+    fields.push("is_hidden", common.is_hidden);
+    fields.push("is_protected", common.is_protected);
+    // icons are long base64 strings, so only a hash of them is compared
+    let icon = common.custom_icon.as_ref().map_or_else(
+        || "none".to_owned(),
+        |icon| {
+            let mut hasher = DefaultHasher::new();
+            icon.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        },
+    );
+    fields.push("custom_icon", icon);
+}
+
+fn line_fields(fields: &mut Fields, line: &LineSymbol) -> Result<()> {
+    fields.color("color", &line.color)?;
+    fields.length("line_width", line.line_width);
+    fields.length("minimum_length", line.minimum_length);
+    fields.length("start_offset", line.start_offset);
+    fields.length("end_offset", line.end_offset);
+    fields.push("cap_style", format!("{:?}", line.cap_style));
+    fields.push("join_style", format!("{:?}", line.join_style));
+    match &line.dash_style {
+        DashStyle::Dashed {
+            dash_length,
+            break_length,
+            dash_group,
+        } => {
+            fields.push("dash_style", "dashed");
+            fields.length("dash_length", *dash_length);
+            fields.length("break_length", *break_length);
+            match dash_group {
+                GroupDashes::Grouped {
+                    dashes_in_group,
+                    in_group_break_length,
+                } => {
+                    fields.push("dashes_in_group", dashes_in_group);
+                    fields.length("in_group_break_length", *in_group_break_length);
+                }
+                GroupDashes::UnGrouped { half_outer_dashes } => {
+                    fields.push("half_outer_dashes", half_outer_dashes);
+                }
+            }
+        }
+        DashStyle::NotDashed {
+            segment_length,
+            end_length,
+        } => {
+            fields.push("dash_style", "not dashed");
+            fields.length("segment_length", *segment_length);
+            fields.length("end_length", *end_length);
+        }
+    }
+
+    match &line.border {
+        Some(BorderStyle::SymmetricBorder { both }) => {
+            fields.push("border", "symmetric");
+            fields.nested("border.both", |fields| border_fields(fields, both))?;
+        }
+        Some(BorderStyle::AsymmetricBorder { left, right }) => {
+            fields.push("border", "asymmetric");
+            fields.nested("border.left", |fields| border_fields(fields, left))?;
+            fields.nested("border.right", |fields| border_fields(fields, right))?;
+        }
+        None => fields.push("border", "none"),
+    }
+
+    if let Some(start) = &line.start_symbol {
+        fields.nested("start_symbol", |fields| point_fields(fields, start))?;
+    }
+    if let Some(mid) = &line.mid_symbol {
+        fields.nested("mid_symbol", |fields| {
+            fields.push("mid_symbols_per_spot", mid.mid_symbols_per_spot);
+            fields.length("mid_symbol_distance", mid.mid_symbol_distance);
+            fields.push("minimum_mid_symbol_count", mid.minimum_mid_symbol_count);
+            fields.push(
+                "minimum_mid_symbol_count_when_closed",
+                mid.minimum_mid_symbol_count_when_closed,
+            );
+            fields.push(
+                "show_at_least_one_mid_symbol",
+                mid.show_at_least_one_mid_symbol,
+            );
+            fields.push(
+                "mid_symbol_placement",
+                format!("{:?}", mid.mid_symbol_placement),
+            );
+            point_fields(fields, &mid.mid_symbol)
+        })?;
+    }
+    if let Some(end) = &line.end_symbol {
+        fields.nested("end_symbol", |fields| point_fields(fields, end))?;
+    }
+    if let Some(dash) = &line.dash_symbol {
+        fields.nested("dash_symbol", |fields| {
+            fields.push(
+                "suppress_dash_symbol_at_ends",
+                dash.suppress_dash_symbol_at_ends,
+            );
+            fields.push("scale_dash_symbol", dash.scale_dash_symbol);
+            point_fields(fields, &dash.dash_symbol)
+        })?;
+    }
+    Ok(())
+}
+
+fn border_fields(fields: &mut Fields, border: &LineSymbolBorder) -> Result<()> {
+    fields.color("color", &border.color)?;
+    fields.length("width", border.width);
+    fields.length("shift", border.shift);
+    if let Some(dashed) = &border.dashed {
+        fields.length("dash_length", dashed.dash_length);
+        fields.length("break_length", dashed.break_length);
+    }
+    Ok(())
+}
+
+fn area_fields(fields: &mut Fields, area: &AreaSymbol) -> Result<()> {
+    fields.color("color", &area.color)?;
+    fields.length("minimum_area", area.minimum_area);
+    fields.push("is_rotatable", area.is_rotatable);
+    for (index, pattern) in area.patterns.iter().enumerate() {
+        fields.nested(&format!("patterns[{index}]"), |fields| match pattern {
+            FillPattern::LinePattern {
+                angle,
+                line_spacing,
+                line_offset,
+                line_color,
+                line_width,
+                rotatable,
+            } => {
+                fields.push("kind", "lines");
+                fields.number("angle", *angle, FLOAT_DECIMALS);
+                fields.length("line_spacing", *line_spacing);
+                fields.length("line_offset", *line_offset);
+                fields.color("line_color", line_color)?;
+                fields.length("line_width", *line_width);
+                fields.push("rotatable", rotatable);
+                Ok(())
+            }
+            FillPattern::PointPattern {
+                clip_options,
+                angle,
+                line_spacing,
+                line_offset,
+                offset_along_line,
+                point_distance,
+                point,
+                rotatable,
+            } => {
+                fields.push("kind", "points");
+                fields.push("clip_options", format!("{clip_options:?}"));
+                fields.number("angle", *angle, FLOAT_DECIMALS);
+                fields.length("line_spacing", *line_spacing);
+                fields.length("line_offset", *line_offset);
+                fields.length("offset_along_line", *offset_along_line);
+                fields.length("point_distance", *point_distance);
+                fields.push("rotatable", rotatable);
+                fields.nested("point", |fields| point_fields(fields, point))
+            }
+        })?;
+    }
+    Ok(())
+}
+
+fn point_fields(fields: &mut Fields, point: &PointSymbol) -> Result<()> {
+    fields.push("is_rotatable", point.is_rotatable);
+    fields.color("inner_color", &point.inner_color)?;
+    fields.length("inner_radius", point.inner_radius);
+    fields.color("outer_color", &point.outer_color)?;
+    fields.length("outer_width", point.outer_width);
+    for (index, element) in point.elements.iter().enumerate() {
+        fields.nested(&format!("elements[{index}]"), |fields| match element {
+            Element::Point { symbol, object } => {
+                fields.push("kind", "point");
+                let object =
+                    element_object(|writer| object.write_as_element(writer, symbol.is_rotatable))?;
+                fields.push("object", object);
+                point_fields(fields, symbol)
+            }
+            Element::Line { symbol, object } => {
+                fields.push("kind", "line");
+                fields.push(
+                    "object",
+                    element_object(|writer| object.write_as_element(writer))?,
+                );
+                line_fields(fields, symbol)
+            }
+            Element::Area { symbol, object } => {
+                fields.push("kind", "area");
+                fields.push(
+                    "object",
+                    element_object(|writer| object.write_as_element(writer))?,
+                );
+                area_fields(fields, symbol)
+            }
+        })?;
+    }
+    Ok(())
+}
+
+fn text_fields(fields: &mut Fields, text: &TextSymbol) -> Result<()> {
+    fields.push("font_family", &text.font_family);
+    fields.push("icon_text", &text.icon_text);
+    fields.color("color", &text.color)?;
+    fields.length("font_size", text.font_size);
+    fields.number("line_spacing", text.line_spacing.get(), FLOAT_DECIMALS);
+    fields.number("character_spacing", text.character_spacing, FLOAT_DECIMALS);
+    fields.number("paragraph_spacing", text.paragraph_spacing, LENGTH_DECIMALS);
+    fields.push("is_rotatable", text.is_rotatable);
+    fields.push("bold", text.bold);
+    fields.push("italic", text.italic);
+    fields.push("underline", text.underline);
+    fields.push("kerning", text.kerning);
+    let tabs = text
+        .custom_tabs
+        .iter()
+        .map(|tab| rounded(tab.get(), LENGTH_DECIMALS))
+        .collect::<Vec<_>>();
+    fields.push("custom_tabs", tabs.join(", "));
+    if let Some(line_below) = &text.line_below {
+        fields.nested("line_below", |fields| {
+            fields.color("color", &line_below.color)?;
+            fields.length("width", line_below.width);
+            fields.length("distance", line_below.distance);
+            Ok(())
+        })?;
+    }
+    match &text.framing_mode {
+        Some(FramingMode::LineFraming(framing)) => {
+            fields.push("framing", "line");
+            fields.color("framing_color", &framing.color)?;
+            fields.length("framing_line_half_width", framing.framing_line_half_width);
+        }
+        Some(FramingMode::ShadowFraming(framing)) => {
+            fields.push("framing", "shadow");
+            fields.color("framing_color", &framing.color)?;
+            let offset = framing.shadow_offset;
+            let offset = [offset.x, offset.y].map(|value| rounded(value, LENGTH_DECIMALS));
+            fields.push("shadow_offset", offset.join(" "));
+        }
+        Some(FramingMode::NoFraming) | None => fields.push("framing", "none"),
+    }
+    Ok(())
+}
+
+fn color_fields(color: &Color) -> Result<Vec<(String, String)>> {
+    let mut fields = Fields::default();
+    fields.push("knockout", color.is_knockout()?);
+    let cmyk = match color.cmyk_mode()? {
+        CmykMode::FromSpotColors => "from spot colors".to_owned(),
+        CmykMode::FromRgb => "from rgb".to_owned(),
+        CmykMode::Cmyk(cmyk) => cmyk_value(cmyk),
+    };
+    fields.push("cmyk", cmyk);
+    let rgb = match color.rgb_mode()? {
+        RgbMode::FromSpotColors => "from spot colors".to_owned(),
+        RgbMode::FromCmyk => "from cmyk".to_owned(),
+        RgbMode::Rgb(rgb) => rgb.to_string(),
+    };
+    fields.push("rgb", rgb);
+    match color {
+        Color::SpotColor(spot) => {
+            let spot = spot.try_borrow()?;
+            fields.push("kind", "spot");
+            fields.push("spotcolor_name", &spot.spotcolor_name);
+            let frequency = spot.screen_frequency.get();
+            fields.number("screen_frequency", frequency, SCREEN_DECIMALS);
+            fields.number("screen_angle_deg", spot.screen_angle_deg, SCREEN_DECIMALS);
+        }
+        Color::MixedColor(mixed) => {
+            fields.push("kind", "mixed");
+            let mut components = Vec::new();
+            for component in &mixed.try_borrow()?.components {
+                let name = match component.color.upgrade() {
+                    Some(spot) => spot.try_borrow()?.color_name.clone(),
+                    None => "missing color".to_owned(),
+                };
+                let factor = rounded(component.factor.get(), FACTOR_DECIMALS);
+                components.push(format!("{name} {factor}"));
+            }
+            fields.push("components", components.join(", "));
+        }
+    }
+    Ok(fields.values)
+}
+
+fn cmyk_value(cmyk: Cmyk) -> String {
+    [cmyk.c, cmyk.m, cmyk.y, cmyk.k]
+        .map(|value| rounded(value.get(), FACTOR_DECIMALS))
+        .join(" ")
+}
+
+/// A number rounded to `decimals` places, so that values written the same in
+/// the file compare equal.
+fn rounded(value: f64, decimals: i32) -> String {
+    let factor = 10_f64.powi(decimals);
+    // avoid writing -0
+    format!("{}", (value * factor).round() / factor + 0.)
+}
+
+/// The object of a point symbol element as written in the file, which keeps
+/// its coordinates in micrometres.
+fn element_object(write: impl FnOnce(&mut Writer<Vec<u8>>) -> Result<()>) -> Result<String> {
+    let mut writer = Writer::new(Vec::new());
+    write(&mut writer)?;
+    Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, num::NonZeroU32, rc::Rc};
+
+    use geo_types::Point;
+
+    use super::{ColorDifference, FieldDifference, SymbolDifference};
+    use crate::{
+        Code, Error, NonNegativeF64, Omap, Result, UnitF64,
+        colors::{Cmyk, CmykMode, Color, ColorComponent, MixedColor, SpotColor, SymbolColor},
+        objects::PointObject,
+        symbols::{
+            CombinedLineSymbol, DashStyle, Element, GroupDashes, LineSymbol, PointSymbol,
+            PublicOrPrivateSymbol, Symbol, WeakLinePathSymbol,
+        },
+    };
+
+    fn map(colors: &[(&str, f64)], symbols: &[(Code, &str, f64)]) -> Result<Omap> {
+        let mut map = Omap::new(NonZeroU32::new(10_000).ok_or(Error::ObjectError)?);
+        let mut weak = Vec::new();
+        for (name, k) in colors {
+            weak.push(
+                map.colors
+                    .push(SpotColor::new(*name, *name, Cmyk::new(0., 0., 0., *k)?)),
+            );
+        }
+        for (code, name, width) in symbols {
+            let mut symbol = LineSymbol::new(*code, *name);
+            symbol.color = SymbolColor::Color(weak[0].clone());
+            symbol.line_width = NonNegativeF64::try_from(*width)?;
+            map.symbols
+                .add_symbol(Symbol::Line(Rc::new(RefCell::new(symbol))));
+        }
+        Ok(map)
+    }
+
+    fn field(field: &str, old: &str, new: &str) -> FieldDifference {
+        FieldDifference {
+            field: field.to_owned(),
+            old: Some(old.to_owned()),
+            new: Some(new.to_owned()),
+        }
+    }
+
+    #[test]
+    fn symbols_are_matched_by_code_and_colors_by_name() -> Result<()> {
+        let old = map(
+            &[("Black", 1.), ("Brown", 0.5), ("Blue", 0.)],
+            &[
+                (Code::new(505, 0, 0), "Path", 0.35),
+                (Code::new(506, 0, 0), "Small path", 0.25),
+                (Code::new(507, 0, 0), "Less distinct path", 0.25),
+            ],
+        )?;
+        let new = map(
+            &[("Black", 1.), ("Blue", 0.), ("Brown", 0.4), ("Green", 0.)],
+            &[
+                (Code::new(505, 0, 0), "Path", 0.35),
+                (Code::new(506, 0, 0), "Small path", 0.3),
+                (Code::new(508, 0, 0), "Narrow ride", 0.25),
+            ],
+        )?;
+        let Some(Symbol::Line(path)) = new.symbols.symbol_by_code(Code::new(505, 0, 0))? else {
+            panic!("expected the path");
+        };
+        path.try_borrow_mut()?.dash_style = DashStyle::Dashed {
+            dash_length: NonNegativeF64::try_from(2.)?,
+            break_length: NonNegativeF64::try_from(0.25)?,
+            dash_group: GroupDashes::UnGrouped {
+                half_outer_dashes: false,
+            },
+        };
+
+        assert!(old.diff_symbol_sets(&old)?.is_empty());
+        let diff = old.diff_symbol_sets(&new)?;
+
+        let SymbolDifference::Changed { code, fields, .. } = &diff.symbols[0] else {
+            panic!("expected the path to change");
+        };
+        assert_eq!(*code, Code::new(505, 0, 0));
+        let changed = fields
+            .iter()
+            .map(|field| field.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changed,
+            [
+                "dash_style",
+                "segment_length",
+                "end_length",
+                "dash_length",
+                "break_length",
+                "half_outer_dashes"
+            ]
+        );
+        let SymbolDifference::Changed { fields, .. } = &diff.symbols[1] else {
+            panic!("expected the small path to change");
+        };
+        assert_eq!(fields, &[field("line_width", "0.25", "0.3")]);
+        assert_eq!(
+            diff.symbols[2..],
+            [
+                SymbolDifference::Removed {
+                    code: Code::new(507, 0, 0),
+                    name: "Less distinct path".to_owned(),
+                },
+                SymbolDifference::Added {
+                    code: Code::new(508, 0, 0),
+                    name: "Narrow ride".to_owned(),
+                },
+            ]
+        );
+
+        assert_eq!(
+            diff.colors,
+            [
+                ColorDifference::Changed {
+                    name: "Brown".to_owned(),
+                    fields: vec![
+                        field("priority", "1", "2"),
+                        field("cmyk", "0 0 0 0.5", "0 0 0 0.4"),
+                    ],
+                },
+                ColorDifference::Changed {
+                    name: "Blue".to_owned(),
+                    fields: vec![field("priority", "2", "1")],
+                },
+                ColorDifference::Added {
+                    name: "Green".to_owned(),
+                    priority: 3,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn numbers_are_compared_at_file_resolution() -> Result<()> {
+        let code = Code::new(505, 0, 0);
+        let old = map(&[("Black", 0.3)], &[(code, "Path", 0.21)])?;
+        // 0.30000000000000004 and 0.21000000000000002
+        let new = map(&[("Black", 0.1 + 0.2)], &[(code, "Path", 0.07 * 3.)])?;
+        assert!(old.diff_symbol_sets(&new)?.is_empty());
+
+        let new = map(&[("Black", 0.3)], &[(code, "Path", 0.211)])?;
+        assert_eq!(
+            old.diff_symbol_sets(&new)?.symbols,
+            [SymbolDifference::Changed {
+                code,
+                name: "Path".to_owned(),
+                fields: vec![field("line_width", "0.21", "0.211")],
+            }]
+        );
+        Ok(())
+    }
+
+    /// Add a mixed color of the first spot color of the map.
+    fn add_mixed_color(map: &mut Omap, factor: f64) -> Result<()> {
+        let Some(spot) = map.colors.iter().next().and_then(|color| match color {
+            Color::SpotColor(spot) => Some(Rc::downgrade(spot)),
+            Color::MixedColor(_) => None,
+        }) else {
+            panic!("expected a spot color first");
+        };
+        map.colors.push(MixedColor::new(
+            "Brown 50%",
+            vec![ColorComponent {
+                factor: UnitF64::try_from(factor)?,
+                color: spot,
+            }],
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn reports_spot_and_mixed_color_changes() -> Result<()> {
+        let mut old = map(&[("Brown", 0.5), ("Grey", 0.2)], &[])?;
+        let mut new = map(&[("Brown", 0.5)], &[])?;
+        add_mixed_color(&mut old, 0.5)?;
+        add_mixed_color(&mut new, 0.25)?;
+        let Some(Color::SpotColor(brown)) = new.colors.iter().next() else {
+            panic!("expected the brown spot color");
+        };
+        let mut brown = brown.try_borrow_mut()?;
+        brown.spotcolor_name = "BROWN".to_owned();
+        brown.knockout = true;
+        brown.set_cmyk_mode(CmykMode::Cmyk(Cmyk::new(0., 0.56, 0.94, 0.18)?))?;
+        drop(brown);
+
+        assert_eq!(
+            old.diff_symbol_sets(&new)?.colors,
+            [
+                ColorDifference::Changed {
+                    name: "Brown".to_owned(),
+                    fields: vec![
+                        field("knockout", "false", "true"),
+                        field("cmyk", "0 0 0 0.5", "0 0.56 0.94 0.18"),
+                        field("spotcolor_name", "Brown", "BROWN"),
+                    ],
+                },
+                ColorDifference::Removed {
+                    name: "Grey".to_owned(),
+                    priority: 1,
+                },
+                ColorDifference::Changed {
+                    name: "Brown 50%".to_owned(),
+                    fields: vec![field("components", "Brown 0.5", "Brown 0.25")],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    fn point_map(element_x: f64, inner_radius: f64) -> Result<Omap> {
+        let mut map = map(&[("Black", 1.)], &[])?;
+        let element = Element::Point {
+            symbol: Box::new(
+                PointSymbol::new(Code::default(), "")
+                    .with_inner_color(SymbolColor::RegistrationBlack)
+                    .with_inner_radius(NonNegativeF64::try_from(inner_radius)?),
+            ),
+            object: Box::new(PointObject::new(
+                std::rc::Weak::new(),
+                Point::new(element_x, 0.),
+            )),
+        };
+        map.symbols
+            .add_symbol(PointSymbol::new(Code::new(312, 0, 0), "Boulder").with_element(element));
+        Ok(map)
+    }
+
+    #[test]
+    fn reports_point_element_changes() -> Result<()> {
+        let old = point_map(0.2, 0.1)?;
+        assert!(
+            old.diff_symbol_sets(&point_map(0.2 + 1e-7, 0.1)?)?
+                .is_empty()
+        );
+
+        let diff = old.diff_symbol_sets(&point_map(0.3, 0.15)?)?;
+        let [SymbolDifference::Changed { fields, .. }] = diff.symbols.as_slice() else {
+            panic!("expected the boulder to change");
+        };
+        let changed = fields
+            .iter()
+            .map(|field| field.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(changed, ["elements[0].object", "elements[0].inner_radius"]);
+        assert_eq!(fields[1], field("elements[0].inner_radius", "0.1", "0.15"));
+        Ok(())
+    }
+
+    fn combined_map(public: Code, private_width: f64) -> Result<Omap> {
+        let mut map = map(
+            &[("Black", 1.)],
+            &[
+                (Code::new(505, 0, 0), "Path", 0.35),
+                (Code::new(506, 0, 0), "Small path", 0.25),
+            ],
+        )?;
+        let Some(Symbol::Line(line)) = map.symbols.symbol_by_code(public)? else {
+            panic!("expected the public line");
+        };
+        let mut private = LineSymbol::new(Code::default(), "");
+        private.line_width = NonNegativeF64::try_from(private_width)?;
+        let mut combined = CombinedLineSymbol::new(Code::new(509, 0, 0), "Track");
+        combined.add_component(PublicOrPrivateSymbol::Public(WeakLinePathSymbol::Line(
+            Rc::downgrade(line),
+        )))?;
+        combined.add_component(PublicOrPrivateSymbol::Private(Box::new(private)))?;
+        map.symbols.add_symbol(combined);
+        Ok(map)
+    }
+
+    #[test]
+    fn reports_combined_symbol_changes() -> Result<()> {
+        let old = combined_map(Code::new(505, 0, 0), 0.1)?;
+        assert!(old.diff_symbol_sets(&old)?.is_empty());
+
+        let diff = old.diff_symbol_sets(&combined_map(Code::new(506, 0, 0), 0.15)?)?;
+        assert_eq!(
+            diff.symbols,
+            [SymbolDifference::Changed {
+                code: Code::new(509, 0, 0),
+                name: "Track".to_owned(),
+                fields: vec![
+                    field("parts[0]", "public 505", "public 506"),
+                    field("parts[1].line_width", "0.1", "0.15"),
+                ],
+            }]
+        );
+        Ok(())
+    }
+}
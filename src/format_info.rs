@@ -1,7 +1,7 @@
-use std::str::FromStr;
+use std::{path::Path, str::FromStr};
 
 use quick_xml::{
-    Writer, XmlVersion,
+    Reader, Writer, XmlVersion,
     events::{BytesDecl, BytesStart, Event},
 };
 
@@ -9,10 +9,19 @@ use crate::utils::try_get_attr_raw;
 use crate::{Error, Result};
 
 /// The OMAP file format version.
+///
+/// Files from version 6 (Mapper 0.6) onwards are read. Differences of the
+/// older versions are translated into the current model while parsing, so a
+/// map is always written as the current version.
 #[derive(Debug, Clone)]
 pub(crate) struct OmapVersion;
 
 impl<'writer> OmapVersion {
+    /// The oldest file version that can be read.
+    const OLDEST_SUPPORTED: u8 = 6;
+    /// The version every map is written as.
+    const CURRENT: u8 = 9;
+
     pub(crate) fn parse(element: &BytesStart<'_>) -> Result<()> {
         let xmlns = try_get_attr_raw(element, "xmlns")?;
         let version = try_get_attr_raw::<u8>(element, "version")?;
@@ -21,7 +30,7 @@ impl<'writer> OmapVersion {
             return Err(Error::UnsupportedOmapNamespace);
         }
         let version = version.ok_or(Error::MissingOmapVersion)?;
-        if version != 9_u8 {
+        if !(Self::OLDEST_SUPPORTED..=Self::CURRENT).contains(&version) {
             return Err(Error::UnsupportedOmapVersion(version));
         }
         Ok(())
@@ -30,7 +39,7 @@ impl<'writer> OmapVersion {
     pub(crate) fn write<W: std::io::Write>(writer: &'writer mut Writer<W>) -> Result<()> {
         writer.write_event(Event::Start(BytesStart::new("map").with_attributes([
             ("xmlns", "http://openorienteering.org/apps/mapper/xml/v2"),
-            ("version", "9"),
+            ("version", Self::CURRENT.to_string().as_str()),
         ])))?;
         Ok(())
     }
@@ -76,3 +85,162 @@ impl FromStr for Encoding {
         }
     }
 }
+
+/// The XML layout used when writing a map.
+///
+/// Both layouts describe the same document and are read back identically.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OutputStyle {
+    /// The compact `.omap` layout, one line per object with all coordinates
+    /// of an object on a single line.
+    #[default]
+    Compact,
+    /// Mapper's human-readable `.xmap` layout, indented XML with one
+    /// `<coord>` element per coordinate. Suited for keeping maps under version
+    /// control.
+    Pretty,
+}
+
+impl OutputStyle {
+    /// The style matching a file extension: [`Self::Pretty`] for `.xmap`,
+    /// [`Self::Compact`] for anything else.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("xmap") => Self::Pretty,
+            _ => Self::Compact,
+        }
+    }
+}
+
+/// Indentation added per nesting level in the pretty layout.
+const PRETTY_INDENT: &[u8] = b"  ";
+
+/// Re-lay out a compactly written map document in the [`OutputStyle::Pretty`] style.
+///
+/// Elements that only contain other elements get each child on its own
+/// indented line. Elements with text content are written unchanged, except
+/// that the content of `coords` elements becomes one `coord` element per
+/// coordinate.
+pub(crate) fn write_pretty<W: std::io::Write>(compact: &[u8], writer: &mut W) -> Result<()> {
+    let mut reader = Reader::from_reader(compact);
+    let mut events = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Eof => break,
+            event => events.push(event),
+        }
+    }
+    let structured = structured_elements(&events);
+
+    let mut writer = Writer::new(writer);
+    // the stack of open elements, with whether each holds only child elements
+    let mut open: Vec<(bool, bool)> = Vec::new();
+    let mut at_start = true;
+    for (index, event) in events.into_iter().enumerate() {
+        let parent_is_structured = open.last().is_none_or(|(structured, _)| *structured);
+        match event {
+            Event::Start(_) | Event::Empty(_) => {
+                if parent_is_structured && !at_start {
+                    write_line_break(&mut writer, open.len())?;
+                }
+                if let Event::Start(bytes_start) = &event {
+                    // the coordinates are written as `<coord>` child elements
+                    let is_coords = bytes_start.local_name().as_ref() == b"coords";
+                    open.push((structured[index] || is_coords, is_coords));
+                }
+                writer.write_event(event)?;
+            }
+            Event::End(_) => {
+                if let Some((true, _)) = open.pop() {
+                    write_line_break(&mut writer, open.len())?;
+                }
+                writer.write_event(event)?;
+            }
+            Event::Text(text) => {
+                if open.last().is_some_and(|(_, is_coords)| *is_coords) {
+                    write_coord_elements(&mut writer, str::from_utf8(text.as_ref())?, open.len())?;
+                } else if !parent_is_structured {
+                    writer.write_event(Event::Text(text))?;
+                }
+                // else only whitespace between elements, replaced by the indentation
+            }
+            event => writer.write_event(event)?,
+        }
+        at_start = false;
+    }
+    writer.get_mut().write_all(b"\n")?;
+    Ok(())
+}
+
+/// Write the `x y flags;` content of a compact `<coords>` element as one
+/// `<coord x=".." y=".." flags=".."/>` element per vertex, like Mapper does.
+fn write_coord_elements<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    content: &str,
+    depth: usize,
+) -> Result<()> {
+    for vertex in content
+        .split_terminator(';')
+        .filter(|vertex| !vertex.trim().is_empty())
+    {
+        let mut parts = vertex.split_whitespace();
+        let mut coord = BytesStart::new("coord");
+        for (key, value) in ["x", "y", "flags"].into_iter().zip(&mut parts) {
+            coord.push_attribute((key, value));
+        }
+        write_line_break(writer, depth)?;
+        writer.write_event(Event::Empty(coord))?;
+    }
+    Ok(())
+}
+
+fn write_line_break<W: std::io::Write>(writer: &mut Writer<W>, depth: usize) -> Result<()> {
+    let inner = writer.get_mut();
+    inner.write_all(b"\n")?;
+    for _ in 0..depth {
+        inner.write_all(PRETTY_INDENT)?;
+    }
+    Ok(())
+}
+
+/// For every event, whether it starts an element whose content is only child
+/// elements separated by whitespace.
+fn structured_elements(events: &[Event<'_>]) -> Vec<bool> {
+    let mut structured = vec![false; events.len()];
+    // open elements as (event index, has child elements, has text content)
+    let mut open: Vec<(usize, bool, bool)> = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(_) => {
+                if let Some(parent) = open.last_mut() {
+                    parent.1 = true;
+                }
+                open.push((index, false, false));
+            }
+            Event::Empty(_) => {
+                if let Some(parent) = open.last_mut() {
+                    parent.1 = true;
+                }
+            }
+            Event::End(_) => {
+                if let Some((start, has_children, has_text)) = open.pop() {
+                    structured[start] = has_children && !has_text;
+                }
+            }
+            Event::Text(text) => {
+                if let Some(parent) = open.last_mut()
+                    && !text.iter().all(u8::is_ascii_whitespace)
+                {
+                    parent.2 = true;
+                }
+            }
+            Event::CData(_) | Event::GeneralRef(_) => {
+                if let Some(parent) = open.last_mut() {
+                    parent.2 = true;
+                }
+            }
+            _ => (),
+        }
+    }
+    structured
+}
@@ -310,19 +310,28 @@ fn parse_projected_crs<R: std::io::BufRead>(
 fn parse_geographic_crs<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<Coord> {
     let mut buf = Vec::new();
 
-    let mut geo_ref_point = Coord::zero();
+    let mut geo_ref_point_deg = None;
+    let mut geo_ref_point_rad = None;
     loop {
         let event = reader.read_event_into(&mut buf)?;
 
         match event {
-            Event::Start(bs) => {
-                if matches!(bs.local_name().as_ref(), b"ref_point_deg") {
-                    geo_ref_point = Coord {
-                        x: try_get_attr_raw(&bs, "lon")?.unwrap_or(geo_ref_point.x),
-                        y: try_get_attr_raw(&bs, "lat")?.unwrap_or(geo_ref_point.y),
-                    }
+            Event::Start(bs) => match bs.local_name().as_ref() {
+                b"ref_point_deg" => {
+                    geo_ref_point_deg = Some(Coord {
+                        x: try_get_attr_raw(&bs, "lon")?.unwrap_or(0.),
+                        y: try_get_attr_raw(&bs, "lat")?.unwrap_or(0.),
+                    });
                 }
-            }
+                b"ref_point" => {
+                    // older files store the point in radians, possibly next to `ref_point_deg`
+                    geo_ref_point_rad = Some(Coord {
+                        x: try_get_attr_raw(&bs, "lon")?.unwrap_or(0_f64).to_degrees(),
+                        y: try_get_attr_raw(&bs, "lat")?.unwrap_or(0_f64).to_degrees(),
+                    });
+                }
+                _ => (),
+            },
             Event::End(bytes_end) => {
                 if matches!(bytes_end.local_name().as_ref(), b"geographic_crs") {
                     break;
@@ -334,7 +343,9 @@ fn parse_geographic_crs<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<C
             _ => (),
         }
     }
-    Ok(geo_ref_point)
+    Ok(geo_ref_point_deg
+        .or(geo_ref_point_rad)
+        .unwrap_or_else(Coord::zero))
 }
 
 fn get_projected_crs_spec<R: std::io::BufRead>(
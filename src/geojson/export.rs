@@ -0,0 +1,409 @@
+use std::{io::Write, path::Path};
+
+use geo_types::{Coord, LineString};
+
+use super::{
+    GeoJsonCoordinates, MAP_PART_PROPERTY, SYMBOL_CODE_PROPERTY, SYMBOL_NAME_PROPERTY,
+    TEXT_PROPERTY, json::string,
+};
+use crate::{
+    Error, NonNegativeF64, Omap, Result, geo_referencing::MapTransform, objects::MapObject,
+};
+
+/// The default largest deviation of flattened curves, in mm of paper.
+const DEFAULT_ALLOWED_ERROR: f64 = 0.01;
+
+/// Settings for writing the objects of a map as a `GeoJSON` `FeatureCollection`.
+#[derive(Debug, Clone, Default)]
+pub struct GeoJsonExport {
+    coordinates: GeoJsonCoordinates,
+    allowed_error: Option<NonNegativeF64>,
+}
+
+impl GeoJsonExport {
+    /// Create an export writing positions in `coordinates`, flattening curves to
+    /// within 0.01 mm of paper.
+    pub fn new(coordinates: GeoJsonCoordinates) -> Self {
+        Self {
+            coordinates,
+            allowed_error: None,
+        }
+    }
+
+    /// Set the largest deviation of flattened curves from the exact geometry, in mm of paper.
+    pub fn with_allowed_error(mut self, allowed_error: NonNegativeF64) -> Self {
+        self.allowed_error = Some(allowed_error);
+        self
+    }
+
+    /// Write every object of the map as a feature, one feature per line.
+    ///
+    /// Lines with fewer than two points and areas without a ring of at least three
+    /// points are left out. Polygon rings are oriented as RFC 7946 recommends,
+    /// counterclockwise exteriors and clockwise holes. Tags named like one of the
+    /// fixed properties, see [`super::SYMBOL_CODE_PROPERTY`] and the other
+    /// constants, are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol is already mutably borrowed, an object has an
+    /// invalid geometry, a position cannot be transformed or is not finite, or the
+    /// `GeoJSON` cannot be written.
+    pub fn write<W: Write>(&self, omap: &Omap, writer: &mut W) -> Result<()> {
+        let transform = omap.geo_referencing.create_transform();
+        let allowed_error = match self.allowed_error {
+            Some(allowed_error) => allowed_error,
+            None => NonNegativeF64::try_from(DEFAULT_ALLOWED_ERROR)?,
+        };
+
+        writer.write_all(b"{\"type\":\"FeatureCollection\",")?;
+        if self.coordinates == GeoJsonCoordinates::Projected
+            && let Some(code) = omap.geo_referencing.epsg_code()
+        {
+            write!(
+                writer,
+                "\"crs\":{{\"type\":\"name\",\"properties\":{{\"name\":\"urn:ogc:def:crs:EPSG::{code}\"}}}},"
+            )?;
+        }
+        writer.write_all(b"\"features\":[")?;
+        let mut separator = "\n";
+        for part in &omap.parts {
+            for object in part.iter_all_objects() {
+                let Some(geometry) = self.geometry(object, &transform, allowed_error)? else {
+                    continue;
+                };
+                let properties = properties(object, &part.name)?;
+                write!(
+                    writer,
+                    "{separator}{{\"type\":\"Feature\",\"geometry\":{geometry},\"properties\":{properties}}}"
+                )?;
+                separator = ",\n";
+            }
+        }
+        writer.write_all(b"\n]}\n")?;
+        Ok(())
+    }
+
+    /// Write the objects of the map as a `GeoJSON` file at the given path, see [`Self::write`].
+    ///
+    /// The file is replaced atomically like in [`Omap::to_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `GeoJSON` cannot be written, the temporary file cannot
+    /// be created or written, or the temporary file cannot replace `path`.
+    pub fn write_file(&self, omap: &Omap, path: impl AsRef<Path>) -> Result<()> {
+        crate::omap::write_atomically(path.as_ref(), |writer| self.write(omap, writer))
+    }
+
+    /// The `GeoJSON` geometry of an object, `None` if it has too few points.
+    fn geometry(
+        &self,
+        object: &MapObject,
+        transform: &MapTransform,
+        allowed_error: NonNegativeF64,
+    ) -> Result<Option<String>> {
+        let geometry = match object {
+            MapObject::Point(point) => {
+                let position = self.position(transform, point.geometry().0)?;
+                format!("{{\"type\":\"Point\",\"coordinates\":{position}}}")
+            }
+            MapObject::Text(text) => {
+                let position = self.position(transform, *text.geometry().anchor_coord())?;
+                format!("{{\"type\":\"Point\",\"coordinates\":{position}}}")
+            }
+            MapObject::Line(line) => {
+                let path = line.flatten(allowed_error)?;
+                if path.geometry().0.len() < 2 {
+                    return Ok(None);
+                }
+                let positions = self.positions(transform, path.geometry())?.join(",");
+                format!("{{\"type\":\"LineString\",\"coordinates\":[{positions}]}}")
+            }
+            MapObject::Area(area) => {
+                let polygon = area.flatten(allowed_error)?;
+                // a closed ring repeats its first point
+                if polygon.exterior().geometry().0.len() < 4 {
+                    return Ok(None);
+                }
+                let mut rings = Vec::with_capacity(polygon.interiors().len() + 1);
+                for (index, ring) in std::iter::once(polygon.exterior())
+                    .chain(polygon.interiors())
+                    .enumerate()
+                {
+                    if ring.geometry().0.len() < 4 {
+                        continue;
+                    }
+                    let mut positions = ring
+                        .geometry()
+                        .0
+                        .iter()
+                        .map(|&coord| self.convert(transform, coord))
+                        .collect::<Result<Vec<_>>>()?;
+                    // the exterior runs counterclockwise and holes clockwise
+                    if (signed_area(&positions) > 0.) != (index == 0) {
+                        positions.reverse();
+                    }
+                    let positions = positions
+                        .into_iter()
+                        .map(position)
+                        .collect::<Result<Vec<_>>>()?;
+                    rings.push(format!("[{}]", positions.join(",")));
+                }
+                format!(
+                    "{{\"type\":\"Polygon\",\"coordinates\":[{}]}}",
+                    rings.join(",")
+                )
+            }
+        };
+        Ok(Some(geometry))
+    }
+
+    #[cfg_attr(
+        not(feature = "geo_ref"),
+        expect(
+            clippy::unnecessary_wraps,
+            reason = "only the WGS84 conversion of the geo_ref feature can fail"
+        )
+    )]
+    fn convert(&self, transform: &MapTransform, coord: Coord) -> Result<Coord> {
+        match self.coordinates {
+            GeoJsonCoordinates::Projected => Ok(transform.to_projected(coord)),
+            #[cfg(feature = "geo_ref")]
+            GeoJsonCoordinates::Wgs84 => transform.to_wgs84(coord),
+        }
+    }
+
+    fn position(&self, transform: &MapTransform, coord: Coord) -> Result<String> {
+        position(self.convert(transform, coord)?)
+    }
+
+    fn positions(&self, transform: &MapTransform, line: &LineString) -> Result<Vec<String>> {
+        line.0
+            .iter()
+            .map(|&coord| self.position(transform, coord))
+            .collect()
+    }
+}
+
+impl Omap {
+    /// Write every object of the map as a `GeoJSON` `FeatureCollection` to anything that
+    /// implements [`Write`], with curves flattened to within 0.01 mm of paper.
+    /// See [`GeoJsonExport::write`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol is already mutably borrowed, an object has an
+    /// invalid geometry, a position cannot be transformed or is not finite, or the
+    /// `GeoJSON` cannot be written.
+    pub fn to_geojson_writer<W: Write>(
+        &self,
+        writer: &mut W,
+        coordinates: GeoJsonCoordinates,
+    ) -> Result<()> {
+        GeoJsonExport::new(coordinates).write(self, writer)
+    }
+
+    /// Write every object of the map as a `GeoJSON` file at the given path.
+    /// See [`GeoJsonExport::write`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `GeoJSON` cannot be written, the temporary file cannot
+    /// be created or written, or the temporary file cannot replace `path`.
+    pub fn to_geojson_file(
+        &self,
+        path: impl AsRef<Path>,
+        coordinates: GeoJsonCoordinates,
+    ) -> Result<()> {
+        GeoJsonExport::new(coordinates).write_file(self, path)
+    }
+}
+
+/// The properties object of a feature.
+fn properties(object: &MapObject, part_name: &str) -> Result<String> {
+    let mut members = Vec::new();
+    if let Some(symbol) = object.symbol().upgrade() {
+        let common = symbol.common()?;
+        members.push((SYMBOL_CODE_PROPERTY, string(&common.code.to_string())));
+        members.push((SYMBOL_NAME_PROPERTY, string(&common.name)));
+    } else {
+        members.push((SYMBOL_CODE_PROPERTY, "null".to_owned()));
+        members.push((SYMBOL_NAME_PROPERTY, "null".to_owned()));
+    }
+    members.push((MAP_PART_PROPERTY, string(part_name)));
+    if let MapObject::Text(text) = object {
+        members.push((TEXT_PROPERTY, string(&text.text)));
+    }
+
+    let fixed = members.len();
+    let mut tags = object
+        .tags()
+        .iter()
+        .filter(|(key, _)| !members[..fixed].iter().any(|(fixed, _)| fixed == key))
+        .collect::<Vec<_>>();
+    // sorted for reproducible files
+    tags.sort_unstable();
+    let mut properties = members
+        .iter()
+        .map(|(key, value)| format!("{}:{value}", string(key)))
+        .chain(
+            tags.into_iter()
+                .map(|(key, value)| format!("{}:{}", string(key), string(value))),
+        )
+        .collect::<Vec<_>>()
+        .join(",");
+    properties.insert(0, '{');
+    properties.push('}');
+    Ok(properties)
+}
+
+/// A `GeoJSON` position, failing for coordinates that JSON numbers cannot hold.
+fn position(coord: Coord) -> Result<String> {
+    if !(coord.x.is_finite() && coord.y.is_finite()) {
+        return Err(Error::MapCoordOutOfBounds);
+    }
+    Ok(format!("[{},{}]", coord.x, coord.y))
+}
+
+/// Twice the signed area of a closed ring, positive for counterclockwise rings.
+fn signed_area(ring: &[Coord]) -> f64 {
+    ring.windows(2)
+        .map(|pair| pair[0].x * pair[1].y - pair[1].x * pair[0].y)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroU32, rc::Rc};
+
+    use geo_types::{Coord, LineString, Point, Polygon, coord};
+
+    use super::GeoJsonExport;
+    use crate::{
+        Code, Omap, Result,
+        geojson::GeoJsonCoordinates,
+        objects::{AreaObject, LineObject, PointObject, TextGeometry, TextObject},
+        symbols::{AreaSymbol, LineSymbol, PointSymbol, Symbol, TextSymbol},
+    };
+
+    /// A map with a tagged point, a line, a clockwise area and a text.
+    fn test_map() -> Omap {
+        let mut omap = Omap::new(NonZeroU32::new(10_000).unwrap_or(NonZeroU32::MIN));
+        omap.geo_referencing.projected_ref_point = Coord {
+            x: 500_000.,
+            y: 6_600_000.,
+        };
+        let symbols = [
+            omap.symbols
+                .add_symbol(PointSymbol::new(Code::new(418, 0, 0), "Distinct tree")),
+            omap.symbols
+                .add_symbol(LineSymbol::new(Code::new(505, 1, 0), "Path")),
+            omap.symbols
+                .add_symbol(AreaSymbol::new(Code::new(401, 0, 0), "Open land")),
+            omap.symbols
+                .add_symbol(TextSymbol::new(Code::new(999, 0, 0), "Label")),
+        ];
+        let [
+            Some(Symbol::Point(point)),
+            Some(Symbol::Line(line)),
+            Some(Symbol::Area(area)),
+            Some(Symbol::Text(text)),
+        ] = symbols.map(|symbol| symbol.upgrade())
+        else {
+            panic!("the symbols are in the symbol set with their types");
+        };
+
+        let Some(part) = omap.parts.get_mut(0) else {
+            panic!("a new map has a part");
+        };
+        let mut tree = PointObject::new(Rc::downgrade(&point), Point::new(1., 2.));
+        tree.tags
+            .insert("name".to_owned(), "Big \"oak\"".to_owned());
+        tree.tags
+            .insert("symbol_code".to_owned(), "shadowed".to_owned());
+        part.add_object(tree);
+        part.add_object(LineObject::new(
+            Rc::downgrade(&line),
+            LineString::from(vec![(0., 0.), (10., 0.)]),
+        ));
+        part.add_object(AreaObject::new(
+            Rc::downgrade(&area),
+            Polygon::new(
+                LineString::from(vec![(0., 0.), (0., 10.), (10., 10.), (0., 0.)]),
+                Vec::new(),
+            ),
+        ));
+        part.add_object(TextObject::new(
+            Rc::downgrade(&text),
+            TextGeometry::SingleAnchor(coord! { x: 5., y: 5. }),
+            "Hill\ntop".to_owned(),
+        ));
+        omap
+    }
+
+    #[test]
+    fn writes_features_with_projected_coordinates() -> Result<()> {
+        let omap = test_map();
+        let mut bytes = Vec::new();
+        omap.to_geojson_writer(&mut bytes, GeoJsonCoordinates::Projected)?;
+        let geojson = String::from_utf8_lossy(&bytes);
+        let lines = geojson.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 6, "a line per feature: {geojson}");
+        assert_eq!(lines[0], r#"{"type":"FeatureCollection","features":["#);
+        assert_eq!(lines[5], "]}");
+
+        let transform = omap.geo_referencing.create_transform();
+        let tree = transform.to_projected(coord! { x: 1., y: 2. });
+        assert_eq!(
+            lines[1],
+            format!(
+                r#"{{"type":"Feature","geometry":{{"type":"Point","coordinates":[{},{}]}},"properties":{{"symbol_code":"418","symbol_name":"Distinct tree","map_part":"Map","name":"Big \"oak\""}}}},"#,
+                tree.x, tree.y
+            )
+        );
+        assert!(lines[2].contains(r#""type":"LineString""#));
+        assert!(lines[2].contains(r#""symbol_code":"505.1""#));
+
+        // the clockwise ring is reversed
+        let corner = transform.to_projected(coord! { x: 10., y: 10. });
+        let start = transform.to_projected(coord! { x: 0., y: 0. });
+        assert!(lines[3].contains(&format!(
+            r#""coordinates":[[[{},{}],[{},{}],"#,
+            start.x, start.y, corner.x, corner.y
+        )));
+        assert!(lines[4].contains(r#""text":"Hill\ntop""#));
+        Ok(())
+    }
+
+    #[test]
+    fn names_epsg_crs() -> Result<()> {
+        let mut omap = test_map();
+        omap.geo_referencing.crs_type = crate::geo_referencing::CrsType::Epsg(25832);
+        let mut bytes = Vec::new();
+        GeoJsonExport::new(GeoJsonCoordinates::Projected).write(&omap, &mut bytes)?;
+        assert!(bytes.starts_with(
+            br#"{"type":"FeatureCollection","crs":{"type":"name","properties":{"name":"urn:ogc:def:crs:EPSG::25832"}},"features":["#
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "geo_ref")]
+    #[test]
+    fn writes_wgs84_coordinates() -> Result<()> {
+        let mut omap = test_map();
+        omap.geo_referencing.crs_type = crate::geo_referencing::CrsType::Epsg(25832);
+        let mut bytes = Vec::new();
+        omap.to_geojson_writer(&mut bytes, GeoJsonCoordinates::Wgs84)?;
+        let geojson = String::from_utf8_lossy(&bytes);
+        assert!(!geojson.contains(r#""crs""#), "RFC 7946 has no crs member");
+
+        let tree = omap
+            .geo_referencing
+            .create_transform()
+            .to_wgs84(coord! { x: 1., y: 2. })?;
+        assert!(geojson.contains(&format!(r#""coordinates":[{},{}]"#, tree.x, tree.y)));
+        assert!((8. ..10.).contains(&tree.x), "UTM 32 lies around 9° east");
+        Ok(())
+    }
+}
@@ -0,0 +1,580 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+    rc::Rc,
+};
+
+use geo_types::{Coord, LineString, Point, Polygon};
+
+use super::{
+    GeoJsonCoordinates, GeoJsonWarning, MAP_PART_PROPERTY, SYMBOL_CODE_PROPERTY,
+    SYMBOL_NAME_PROPERTY, TEXT_PROPERTY, json::Value,
+};
+use crate::{
+    Code, Error, NonNegativeF64, Omap, Result,
+    geo_referencing::MapTransform,
+    objects::{
+        AreaObject, BezierPath, BezierPolygon, LineObject, MapObject, PointObject, TextGeometry,
+        TextObject,
+    },
+    parts::MapPart,
+    symbols::Symbol,
+};
+
+/// A single geometry of a feature in map coordinates.
+enum Geometry {
+    Point(Coord),
+    Line(LineString),
+    Polygon(Polygon),
+}
+
+/// Settings for adding the features of a `GeoJSON` `FeatureCollection` to a map as objects.
+#[derive(Debug, Clone)]
+pub struct GeoJsonImport {
+    coordinates: GeoJsonCoordinates,
+    symbol_property: String,
+    symbol_codes: HashMap<String, Code>,
+    allowed_error: Option<NonNegativeF64>,
+}
+
+impl GeoJsonImport {
+    /// Create an import reading positions in `coordinates` and choosing the symbol of
+    /// every feature by the value of its `symbol_property`.
+    ///
+    /// Values without a mapping from [`Self::with_symbol_code`] are read as symbol codes,
+    /// so files written by [`super::GeoJsonExport`] are read back with
+    /// [`super::SYMBOL_CODE_PROPERTY`].
+    pub fn new(coordinates: GeoJsonCoordinates, symbol_property: impl Into<String>) -> Self {
+        Self {
+            coordinates,
+            symbol_property: symbol_property.into(),
+            symbol_codes: HashMap::new(),
+            allowed_error: None,
+        }
+    }
+
+    /// Give features whose symbol property has the given value the symbol with `code`.
+    ///
+    /// Numbers and booleans match their JSON text, e.g. `"2"` for a class of 2.
+    pub fn with_symbol_code(mut self, value: impl Into<String>, code: Code) -> Self {
+        let _previous = self.symbol_codes.insert(value.into(), code);
+        self
+    }
+
+    /// Fit smooth Bézier curves to lines and area rings within `allowed_error` mm of
+    /// paper, instead of keeping every segment straight.
+    pub fn with_curve_fitting(mut self, allowed_error: NonNegativeF64) -> Self {
+        self.allowed_error = Some(allowed_error);
+        self
+    }
+
+    /// Add the features of a `GeoJSON` `FeatureCollection`, or of a single `Feature`, to
+    /// the map.
+    ///
+    /// `Point`s become point objects, or text objects with the [`TEXT_PROPERTY`] as
+    /// their text for text symbols. `LineString`s become line objects and `Polygon`s area
+    /// objects, and the members of multi-geometries and geometry collections become one
+    /// object each. Every object gets the properties of its feature as tags, except
+    /// null values and the properties written by the export for the symbol and map part.
+    /// Strings are kept as they are and other values as their JSON text.
+    ///
+    /// Objects are added to the map part named by the [`MAP_PART_PROPERTY`] if the map
+    /// has one, otherwise to the first part. Features whose symbol or geometry does
+    /// not fit are skipped and reported in the returned list of warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidJson`] if the input is not JSON,
+    /// [`Error::InvalidGeoJson`] if it is not a feature collection or has a malformed
+    /// geometry, or an error if a position cannot be transformed. The map is left
+    /// unchanged on errors.
+    pub fn read<R: Read>(&self, omap: &mut Omap, mut reader: R) -> Result<Vec<GeoJsonWarning>> {
+        let mut bytes = Vec::new();
+        let _size = reader.read_to_end(&mut bytes)?;
+        let text = std::str::from_utf8(&bytes)?;
+        // RFC 8259 allows parsers to ignore a byte order mark
+        let root = Value::parse(text.strip_prefix('\u{feff}').unwrap_or(text))?;
+        let features = match root.get("type").and_then(Value::as_str) {
+            Some("FeatureCollection") => root
+                .get("features")
+                .and_then(Value::as_array)
+                .ok_or(Error::InvalidGeoJson("the features are not an array"))?,
+            Some("Feature") => std::slice::from_ref(&root),
+            _ => {
+                return Err(Error::InvalidGeoJson(
+                    "expected a FeatureCollection or a Feature",
+                ));
+            }
+        };
+
+        let transform = omap.geo_referencing.create_transform();
+        let mut warnings = Vec::new();
+        let mut objects = Vec::new();
+        for (feature_index, feature) in features.iter().enumerate() {
+            let properties = match feature.get("properties") {
+                Some(Value::Object(members)) => members.as_slice(),
+                Some(Value::Null) | None => &[],
+                Some(_) => return Err(Error::InvalidGeoJson("the properties are not an object")),
+            };
+            let property = |key: &str| {
+                properties
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| name == key)
+                    .and_then(|(_, value)| text_value(value))
+            };
+
+            let value = property(&self.symbol_property);
+            let Some(symbol) = self.symbol(omap, value.as_deref())? else {
+                warnings.push(GeoJsonWarning::UnknownSymbol {
+                    feature_index,
+                    value,
+                });
+                continue;
+            };
+            let mut geometries = Vec::new();
+            match feature.get("geometry") {
+                Some(Value::Null) | None => (),
+                Some(geometry) => self.geometries(&transform, geometry, &mut geometries)?,
+            }
+            let is_valid = |geometry: &Geometry| match geometry {
+                Geometry::Point(_) => true,
+                Geometry::Line(line) => line.0.len() >= 2,
+                // a closed ring repeats its first point
+                Geometry::Polygon(polygon) => polygon.exterior().0.len() >= 4,
+            };
+            if geometries.is_empty() || !geometries.iter().all(is_valid) {
+                warnings.push(GeoJsonWarning::InvalidGeometry { feature_index });
+                continue;
+            }
+
+            let text = property(TEXT_PROPERTY).unwrap_or_default();
+            let Some(feature_objects) = geometries
+                .into_iter()
+                .map(|geometry| self.object(&symbol, geometry, &text))
+                .collect::<Result<Option<Vec<_>>>>()?
+            else {
+                warnings.push(GeoJsonWarning::SymbolTypeMismatch {
+                    feature_index,
+                    code: symbol.common()?.code,
+                });
+                continue;
+            };
+            let part = property(MAP_PART_PROPERTY);
+            for mut object in feature_objects {
+                let is_text = matches!(object, MapObject::Text(_));
+                let is_fixed = |key: &str| {
+                    [
+                        SYMBOL_CODE_PROPERTY,
+                        SYMBOL_NAME_PROPERTY,
+                        MAP_PART_PROPERTY,
+                    ]
+                    .contains(&key)
+                        || (is_text && key == TEXT_PROPERTY)
+                };
+                *object.tags_mut() = properties
+                    .iter()
+                    .filter(|(key, _)| !is_fixed(key))
+                    .filter_map(|(key, value)| Some((key.clone(), text_value(value)?)))
+                    .collect();
+                objects.push((part.clone(), object));
+            }
+        }
+
+        if omap.parts.is_empty() {
+            omap.parts.push(MapPart::new("Map"));
+        }
+        for (part, object) in objects {
+            let part = match part.and_then(|name| omap.parts.by_name_mut(&name)) {
+                Some(part) => part,
+                None => omap
+                    .parts
+                    .get_mut(0)
+                    .ok_or(Error::InvalidGeoJson("the map has no part"))?,
+            };
+            part.add_object(object);
+        }
+        Ok(warnings)
+    }
+
+    /// Add the features of a `GeoJSON` file at the given path to the map, see
+    /// [`Self::read`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or its features cannot be read.
+    pub fn read_file(
+        &self,
+        omap: &mut Omap,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<GeoJsonWarning>> {
+        let file = File::open(path)?;
+        self.read(omap, BufReader::new(file))
+    }
+
+    /// The symbol selected by a value of the symbol property.
+    fn symbol(&self, omap: &Omap, value: Option<&str>) -> Result<Option<Symbol>> {
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        let code = match self.symbol_codes.get(value) {
+            Some(&code) => code,
+            None => match value.parse() {
+                Ok(code) => code,
+                Err(_) => return Ok(None),
+            },
+        };
+        Ok(omap.symbols.symbol_by_code(code)?.cloned())
+    }
+
+    /// The object of a geometry with the symbol, `None` if the symbol type does not fit.
+    fn object(&self, symbol: &Symbol, geometry: Geometry, text: &str) -> Result<Option<MapObject>> {
+        let object = match (geometry, symbol) {
+            (Geometry::Point(coord), Symbol::Point(symbol)) => {
+                PointObject::new(Rc::downgrade(symbol), Point(coord)).into()
+            }
+            (Geometry::Point(coord), Symbol::Text(symbol)) => TextObject::new(
+                Rc::downgrade(symbol),
+                TextGeometry::SingleAnchor(coord),
+                text.to_owned(),
+            )
+            .into(),
+            (Geometry::Line(line), Symbol::Line(symbol)) => {
+                LineObject::new(Rc::downgrade(symbol), self.path(line)?).into()
+            }
+            (Geometry::Line(line), Symbol::CombinedLine(symbol)) => {
+                LineObject::new(Rc::downgrade(symbol), self.path(line)?).into()
+            }
+            (Geometry::Polygon(polygon), Symbol::Area(symbol)) => {
+                AreaObject::new(Rc::downgrade(symbol), self.polygon(polygon)?).into()
+            }
+            (Geometry::Polygon(polygon), Symbol::CombinedArea(symbol)) => {
+                AreaObject::new(Rc::downgrade(symbol), self.polygon(polygon)?).into()
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(object))
+    }
+
+    fn path(&self, line: LineString) -> Result<BezierPath> {
+        match self.allowed_error {
+            Some(allowed_error) => BezierPath::fit_line_string(line, allowed_error),
+            None => Ok(line.into()),
+        }
+    }
+
+    fn polygon(&self, polygon: Polygon) -> Result<BezierPolygon> {
+        match self.allowed_error {
+            Some(allowed_error) => BezierPolygon::fit_polygon(polygon, allowed_error),
+            None => Ok(polygon.into()),
+        }
+    }
+
+    /// Collect the geometries of a `GeoJSON` geometry object in map coordinates.
+    fn geometries(
+        &self,
+        transform: &MapTransform,
+        geometry: &Value,
+        geometries: &mut Vec<Geometry>,
+    ) -> Result<()> {
+        let coordinates = geometry.get("coordinates");
+        match geometry.get("type").and_then(Value::as_str) {
+            Some("Point") => {
+                geometries.push(Geometry::Point(self.position(transform, coordinates)?));
+            }
+            Some("MultiPoint") => {
+                for position in array(coordinates)? {
+                    geometries.push(Geometry::Point(self.position(transform, Some(position))?));
+                }
+            }
+            Some("LineString") => {
+                geometries.push(Geometry::Line(self.line_string(transform, coordinates)?));
+            }
+            Some("MultiLineString") => {
+                for line in array(coordinates)? {
+                    geometries.push(Geometry::Line(self.line_string(transform, Some(line))?));
+                }
+            }
+            Some("Polygon") => {
+                geometries.push(Geometry::Polygon(
+                    self.polygon_rings(transform, coordinates)?,
+                ));
+            }
+            Some("MultiPolygon") => {
+                for polygon in array(coordinates)? {
+                    geometries.push(Geometry::Polygon(
+                        self.polygon_rings(transform, Some(polygon))?,
+                    ));
+                }
+            }
+            Some("GeometryCollection") => {
+                for member in array(geometry.get("geometries"))? {
+                    self.geometries(transform, member, geometries)?;
+                }
+            }
+            _ => return Err(Error::InvalidGeoJson("unknown geometry type")),
+        }
+        Ok(())
+    }
+
+    fn polygon_rings(&self, transform: &MapTransform, rings: Option<&Value>) -> Result<Polygon> {
+        let mut rings = array(rings)?
+            .iter()
+            .map(|ring| self.line_string(transform, Some(ring)))
+            .collect::<Result<Vec<_>>>()?;
+        if rings.is_empty() {
+            return Ok(Polygon::new(LineString::new(Vec::new()), Vec::new()));
+        }
+        let exterior = rings.remove(0);
+        Ok(Polygon::new(exterior, rings))
+    }
+
+    fn line_string(
+        &self,
+        transform: &MapTransform,
+        positions: Option<&Value>,
+    ) -> Result<LineString> {
+        array(positions)?
+            .iter()
+            .map(|position| self.position(transform, Some(position)))
+            .collect()
+    }
+
+    /// A position in map coordinates, ignoring an altitude.
+    fn position(&self, transform: &MapTransform, position: Option<&Value>) -> Result<Coord> {
+        let position = match array(position)? {
+            [x, y, ..] => x.as_f64().zip(y.as_f64()),
+            _ => None,
+        };
+        let Some((x, y)) = position.filter(|(x, y)| x.is_finite() && y.is_finite()) else {
+            return Err(Error::InvalidGeoJson(
+                "a position is not an array of two or more numbers",
+            ));
+        };
+        self.convert(transform, Coord { x, y })
+    }
+
+    #[cfg_attr(
+        not(feature = "geo_ref"),
+        expect(
+            clippy::unnecessary_wraps,
+            reason = "only the WGS84 conversion of the geo_ref feature can fail"
+        )
+    )]
+    fn convert(&self, transform: &MapTransform, coord: Coord) -> Result<Coord> {
+        match self.coordinates {
+            GeoJsonCoordinates::Projected => Ok(transform.to_map(coord)),
+            #[cfg(feature = "geo_ref")]
+            GeoJsonCoordinates::Wgs84 => transform.from_wgs84(coord),
+        }
+    }
+}
+
+/// The values of a coordinates array.
+fn array(value: Option<&Value>) -> Result<&[Value]> {
+    value
+        .and_then(Value::as_array)
+        .ok_or(Error::InvalidGeoJson("the coordinates are not an array"))
+}
+
+/// A property value as tag text, `None` for null.
+fn text_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(string) => Some(string.clone()),
+        _ => Some(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use geo_types::coord;
+
+    use super::GeoJsonImport;
+    use crate::{
+        Code, Error, NonNegativeF64, Omap, Result,
+        geojson::{GeoJsonCoordinates, GeoJsonWarning, SYMBOL_CODE_PROPERTY},
+        objects::{BezierSegment, MapObject},
+        parts::MapPart,
+        symbols::{AreaSymbol, LineSymbol, PointSymbol, TextSymbol},
+    };
+
+    fn test_map() -> Omap {
+        let mut omap = Omap::new(NonZeroU32::new(10_000).unwrap_or(NonZeroU32::MIN));
+        omap.geo_referencing.projected_ref_point = coord! { x: 500_000., y: 6_600_000. };
+        let _point = omap
+            .symbols
+            .add_symbol(PointSymbol::new(Code::new(418, 0, 0), "Distinct tree"));
+        let _line = omap
+            .symbols
+            .add_symbol(LineSymbol::new(Code::new(505, 1, 0), "Path"));
+        let _area = omap
+            .symbols
+            .add_symbol(AreaSymbol::new(Code::new(401, 0, 0), "Open land"));
+        let _text = omap
+            .symbols
+            .add_symbol(TextSymbol::new(Code::new(999, 0, 0), "Label"));
+        omap
+    }
+
+    #[test]
+    fn maps_properties_to_symbols_and_tags() -> Result<()> {
+        let mut omap = test_map();
+        omap.parts.push(MapPart::new("Trails"));
+        let geojson = br#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","geometry":{"type":"Point","coordinates":[500010,6600020,31.5]},
+             "properties":{"class":2,"height":31.5,"source":"lidar","note":null}},
+            {"type":"Feature","geometry":{"type":"MultiLineString","coordinates":[
+                [[500000,6600000],[500100,6600000]],[[500000,6600100],[500100,6600100]]]},
+             "properties":{"class":"track","map_part":"Trails"}},
+            {"type":"Feature","geometry":{"type":"Point","coordinates":[500050,6600050]},
+             "properties":{"class":"track"}},
+            {"type":"Feature","geometry":{"type":"LineString","coordinates":[[0,0]]},
+             "properties":{"class":"track"}},
+            {"type":"Feature","geometry":null,"properties":{"class":"unknown"}}
+        ]}"#;
+        let warnings = GeoJsonImport::new(GeoJsonCoordinates::Projected, "class")
+            .with_symbol_code("2", Code::new(418, 0, 0))
+            .with_symbol_code("track", Code::new(505, 1, 0))
+            .read(&mut omap, &geojson[..])?;
+        assert_eq!(
+            warnings,
+            vec![
+                GeoJsonWarning::SymbolTypeMismatch {
+                    feature_index: 2,
+                    code: Code::new(505, 1, 0)
+                },
+                GeoJsonWarning::InvalidGeometry { feature_index: 3 },
+                GeoJsonWarning::UnknownSymbol {
+                    feature_index: 4,
+                    value: Some("unknown".to_owned())
+                },
+            ]
+        );
+
+        let (Some(map), Some(trails)) = (omap.parts.get(0), omap.parts.get(1)) else {
+            panic!("the map has two parts");
+        };
+        let [MapObject::Point(tree)] = map.iter_all_objects().collect::<Vec<_>>()[..] else {
+            panic!("the tree is in the first part");
+        };
+        let position = tree.geometry().0;
+        assert!((position.x - 1.).abs() < 1e-9 && (position.y - 2.).abs() < 1e-9);
+        assert_eq!(tree.tags.len(), 3);
+        assert_eq!(tree.tags["class"], "2");
+        assert_eq!(tree.tags["height"], "31.5");
+        assert_eq!(tree.tags["source"], "lidar");
+
+        assert_eq!(trails.len(), 2);
+        assert!(
+            trails
+                .iter_all_objects()
+                .all(|object| object.tags().len() == 1 && object.tags()["class"] == "track")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_back_export() -> Result<()> {
+        let mut omap = test_map();
+        let geojson = br#"{"type":"Feature","geometry":{"type":"Polygon","coordinates":[
+            [[500000,6600000],[500100,6600000],[500100,6600100],[500000,6600100],[500000,6600000]],
+            [[500020,6600020],[500040,6600020],[500040,6600040],[500020,6600020]]]},
+            "properties":{"symbol_code":"401","symbol_name":"Open land","map_part":"Map","id":"a"}}"#;
+        let warnings = GeoJsonImport::new(GeoJsonCoordinates::Projected, SYMBOL_CODE_PROPERTY)
+            .read(&mut omap, &geojson[..])?;
+        assert!(warnings.is_empty());
+
+        let mut bytes = Vec::new();
+        omap.to_geojson_writer(&mut bytes, GeoJsonCoordinates::Projected)?;
+        let exported = String::from_utf8_lossy(&bytes);
+        assert!(exported.contains(
+            r#""properties":{"symbol_code":"401","symbol_name":"Open land","map_part":"Map","id":"a"}"#
+        ));
+        assert!(exported.contains("[500000,6600000],[500100,6600000],[500100,6600100]"));
+        Ok(())
+    }
+
+    #[test]
+    fn fits_curves() -> Result<()> {
+        let mut omap = test_map();
+        let positions = (0..=20)
+            .map(|step| {
+                let x = f64::from(step) * 10.;
+                format!("[{},{}]", 500_000. + x, 6_600_000. + 50. * (x / 50.).sin())
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let geojson = format!(
+            r#"{{"type":"Feature","geometry":{{"type":"LineString","coordinates":[{positions}]}},"properties":{{"symbol_code":"505.1"}}}}"#
+        );
+        let _warnings = GeoJsonImport::new(GeoJsonCoordinates::Projected, SYMBOL_CODE_PROPERTY)
+            .with_curve_fitting(NonNegativeF64::clamped_from(0.01))
+            .read(&mut omap, geojson.as_bytes())?;
+        let Some(MapObject::Line(line)) = omap.iter_all_objects().next() else {
+            panic!("the line was imported");
+        };
+        assert!(line.geometry().num_segments() < 20);
+        assert!(
+            line.geometry()
+                .geometry()
+                .segments()
+                .any(BezierSegment::is_bezier_curve)
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "geo_ref")]
+    #[test]
+    fn reads_wgs84_coordinates() -> Result<()> {
+        let mut omap = test_map();
+        omap.geo_referencing.crs_type = crate::geo_referencing::CrsType::Epsg(25832);
+        let tree = omap
+            .geo_referencing
+            .create_transform()
+            .to_wgs84(coord! { x: 1., y: 2. })?;
+        let geojson = format!(
+            r#"{{"type":"Feature","geometry":{{"type":"Point","coordinates":[{},{}]}},"properties":{{"symbol_code":"418"}}}}"#,
+            tree.x, tree.y
+        );
+        let _warnings = GeoJsonImport::new(GeoJsonCoordinates::Wgs84, SYMBOL_CODE_PROPERTY)
+            .read(&mut omap, geojson.as_bytes())?;
+        let Some(MapObject::Point(point)) = omap.iter_all_objects().next() else {
+            panic!("the tree was imported");
+        };
+        let position = point.geometry().0;
+        assert!((position.x - 1.).abs() < 1e-6 && (position.y - 2.).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let mut omap = test_map();
+        let import = GeoJsonImport::new(GeoJsonCoordinates::Projected, SYMBOL_CODE_PROPERTY);
+        for (geojson, message) in [
+            (
+                r#"{"type":"Topology"}"#,
+                "expected a FeatureCollection or a Feature",
+            ),
+            (
+                r#"{"type":"Feature","geometry":{"type":"Point","coordinates":["1",2]},"properties":{"symbol_code":"418"}}"#,
+                "a position is not an array of two or more numbers",
+            ),
+        ] {
+            let Err(Error::InvalidGeoJson(error)) = import.read(&mut omap, geojson.as_bytes())
+            else {
+                panic!("{geojson} is not valid GeoJSON");
+            };
+            assert_eq!(error, message);
+        }
+        assert!(matches!(
+            import.read(&mut omap, &b"{"[..]),
+            Err(Error::InvalidJson(1))
+        ));
+        assert_eq!(omap.iter_all_objects().count(), 0);
+    }
+}
@@ -0,0 +1,375 @@
+use std::fmt::Display;
+
+use crate::{Error, Result};
+
+/// The deepest nesting of arrays and objects, which keeps the recursive parser
+/// within the stack on hostile input.
+const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Value {
+    Null,
+    Bool(bool),
+    /// A number in its source notation, which keeps large integers exact.
+    Number(String),
+    String(String),
+    Array(Vec<Self>),
+    /// The members of an object in source order.
+    Object(Vec<(String, Self)>),
+}
+
+impl Value {
+    /// Parse a JSON text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidJson`] with the byte offset of the first error.
+    pub(super) fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser {
+            bytes: text.as_bytes(),
+            offset: 0,
+        };
+        let value = parser.value(0)?;
+        parser.whitespace();
+        if parser.offset < parser.bytes.len() {
+            return Err(parser.error());
+        }
+        Ok(value)
+    }
+
+    /// The member of an object with the given key, the last one if it is repeated.
+    pub(super) fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(number) => number.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Compact JSON text.
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(number) => f.write_str(number),
+            Self::String(value) => f.write_str(&string(value)),
+            Self::Array(values) => {
+                f.write_str("[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            }
+            Self::Object(members) => {
+                f.write_str("{")?;
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}:{value}", string(key))?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// A JSON string with the characters JSON does not allow escaped.
+pub(super) fn string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{0}'..='\u{1f}' => escaped.push_str(&format!("\\u{:04x}", u32::from(character))),
+            _ => escaped.push(character),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// A recursive descent parser over the bytes of a JSON text.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> Error {
+        Error::InvalidJson(self.offset)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.offset).copied()
+    }
+
+    /// Step over `byte` if it is next.
+    fn skip(&mut self, byte: u8) -> bool {
+        let next = self.peek() == Some(byte);
+        if next {
+            self.offset += 1;
+        }
+        next
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.offset += 1;
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(self.error());
+        }
+        self.whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => Ok(Value::String(self.string()?)),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value> {
+        if !self.bytes[self.offset..].starts_with(literal.as_bytes()) {
+            return Err(self.error());
+        }
+        self.offset += literal.len();
+        Ok(value)
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value> {
+        self.offset += 1;
+        let mut members = Vec::new();
+        self.whitespace();
+        if self.skip(b'}') {
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error());
+            }
+            let key = self.string()?;
+            self.whitespace();
+            if !self.skip(b':') {
+                return Err(self.error());
+            }
+            members.push((key, self.value(depth + 1)?));
+            self.whitespace();
+            if self.skip(b'}') {
+                return Ok(Value::Object(members));
+            }
+            if !self.skip(b',') {
+                return Err(self.error());
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value> {
+        self.offset += 1;
+        let mut values = Vec::new();
+        self.whitespace();
+        if self.skip(b']') {
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.value(depth + 1)?);
+            self.whitespace();
+            if self.skip(b']') {
+                return Ok(Value::Array(values));
+            }
+            if !self.skip(b',') {
+                return Err(self.error());
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.offset;
+        let _negative = self.skip(b'-');
+        if !self.skip(b'0') && !self.digits() {
+            return Err(self.error());
+        }
+        if self.skip(b'.') && !self.digits() {
+            return Err(self.error());
+        }
+        if self.skip(b'e') || self.skip(b'E') {
+            let _sign = self.skip(b'+') || self.skip(b'-');
+            if !self.digits() {
+                return Err(self.error());
+            }
+        }
+        let number = std::str::from_utf8(&self.bytes[start..self.offset])?;
+        Ok(Value::Number(number.to_owned()))
+    }
+
+    /// Step over a run of digits, returning whether there was one.
+    fn digits(&mut self) -> bool {
+        let start = self.offset;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.offset += 1;
+        }
+        self.offset > start
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.offset += 1;
+        let mut string = String::new();
+        loop {
+            let start = self.offset;
+            while let Some(byte) = self.peek()
+                && byte != b'"'
+                && byte != b'\\'
+                && byte >= 0x20
+            {
+                self.offset += 1;
+            }
+            // the run stops at an ASCII byte, so it ends on a character boundary
+            string.push_str(std::str::from_utf8(&self.bytes[start..self.offset])?);
+            if self.skip(b'"') {
+                return Ok(string);
+            }
+            if !self.skip(b'\\') {
+                return Err(self.error());
+            }
+            string.push(self.escape()?);
+        }
+    }
+
+    /// The character of the escape sequence after a backslash.
+    fn escape(&mut self) -> Result<char> {
+        let Some(byte) = self.peek() else {
+            return Err(self.error());
+        };
+        self.offset += 1;
+        let character = match byte {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let mut code = self.hex()?;
+                // characters outside the basic plane are written as a surrogate pair
+                if (0xd800..0xdc00).contains(&code) {
+                    if !(self.skip(b'\\') && self.skip(b'u')) {
+                        return Err(self.error());
+                    }
+                    let low = self.hex()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error());
+                    }
+                    code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                }
+                char::from_u32(code).ok_or_else(|| self.error())?
+            }
+            _ => return Err(self.error()),
+        };
+        Ok(character)
+    }
+
+    fn hex(&mut self) -> Result<u32> {
+        let bytes = self.bytes;
+        let Some(digits) = bytes.get(self.offset..self.offset + 4) else {
+            return Err(self.error());
+        };
+        if !digits.iter().all(u8::is_ascii_hexdigit) {
+            return Err(self.error());
+        }
+        self.offset += 4;
+        Ok(u32::from_str_radix(std::str::from_utf8(digits)?, 16)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+    use crate::{Error, Result};
+
+    #[test]
+    fn parses_nested_values() -> Result<()> {
+        let value = Value::parse(
+            r#" {"a": [1, -2.5e3, true, null], "b": "x\"\u00e9\ud83c\udf32", "a": {}} "#,
+        )?;
+        assert_eq!(value.get("a"), Some(&Value::Object(Vec::new())));
+        assert_eq!(
+            value.get("b").and_then(Value::as_str),
+            Some("x\"é\u{1f332}")
+        );
+        let Value::Object(members) = &value else {
+            panic!("the text is an object");
+        };
+        assert_eq!(members[0].1.to_string(), "[1,-2.5e3,true,null]");
+        assert_eq!(
+            members[0]
+                .1
+                .as_array()
+                .and_then(|values| values[1].as_f64()),
+            Some(-2500.)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_offset_of_errors() {
+        for (text, offset) in [
+            ("[1,]", 3),
+            ("{\"a\" 1}", 5),
+            ("01", 1),
+            ("\"\\ud83c\"", 7),
+            ("[1] x", 4),
+            ("\"tab\there\"", 4),
+        ] {
+            let Err(Error::InvalidJson(error_offset)) = Value::parse(text) else {
+                panic!("{text} is not valid JSON");
+            };
+            assert_eq!(error_offset, offset, "{text}");
+        }
+        let deep = "[".repeat(1000);
+        assert!(matches!(Value::parse(&deep), Err(Error::InvalidJson(_))));
+    }
+}
@@ -0,0 +1,95 @@
+//! `GeoJSON` export and import of map objects, for web viewers and GIS software.
+//!
+//! Every object becomes a feature of a `FeatureCollection`: points and texts
+//! are `Point`s, lines are `LineString`s and areas are `Polygon`s, with Bézier
+//! curves flattened to straight segments. The symbol code, symbol name, map
+//! part name and all tags of an object are properties of its feature.
+//!
+//! The import goes the other way, choosing the symbol of every feature from
+//! one of its properties and keeping the other properties as tags. Features
+//! that do not fit a symbol of the map are reported as
+//! [`GeoJsonWarning`](crate::geojson::GeoJsonWarning)s.
+
+mod export;
+mod import;
+mod json;
+
+pub use export::GeoJsonExport;
+pub use import::GeoJsonImport;
+
+use crate::Code;
+
+/// The property holding the code of the symbol of an object, e.g. `"101.1"`.
+pub const SYMBOL_CODE_PROPERTY: &str = "symbol_code";
+/// The property holding the name of the symbol of an object.
+pub const SYMBOL_NAME_PROPERTY: &str = "symbol_name";
+/// The property holding the name of the map part of an object.
+pub const MAP_PART_PROPERTY: &str = "map_part";
+/// The property holding the text of a text object.
+pub const TEXT_PROPERTY: &str = "text";
+
+/// The coordinate system of written positions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GeoJsonCoordinates {
+    /// Easting and northing in the projected CRS of the map's georeferencing.
+    ///
+    /// Maps with an EPSG code get the legacy `crs` member naming it, which QGIS and
+    /// GDAL read, although RFC 7946 only allows WGS84.
+    #[default]
+    Projected,
+    /// WGS84 longitude and latitude in degrees, as RFC 7946 requires.
+    #[cfg(feature = "geo_ref")]
+    Wgs84,
+}
+
+/// A feature that was skipped while importing `GeoJSON`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeoJsonWarning {
+    /// The symbol property of the feature is missing or selects no symbol of the map.
+    UnknownSymbol {
+        /// The index of the feature in the collection.
+        feature_index: usize,
+        /// The value of the symbol property, `None` if it is missing or null.
+        value: Option<String>,
+    },
+    /// The geometry does not fit the symbol type, e.g. a polygon for a point symbol.
+    SymbolTypeMismatch {
+        /// The index of the feature in the collection.
+        feature_index: usize,
+        /// The code of the selected symbol.
+        code: Code,
+    },
+    /// The feature has no geometry, or a line or ring with too few positions.
+    InvalidGeometry {
+        /// The index of the feature in the collection.
+        feature_index: usize,
+    },
+}
+
+impl std::fmt::Display for GeoJsonWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSymbol {
+                feature_index,
+                value: Some(value),
+            } => write!(
+                f,
+                "feature {feature_index} selects unknown symbol {value:?}"
+            ),
+            Self::UnknownSymbol {
+                feature_index,
+                value: None,
+            } => write!(f, "feature {feature_index} has no symbol property"),
+            Self::SymbolTypeMismatch {
+                feature_index,
+                code,
+            } => write!(
+                f,
+                "feature {feature_index} has a geometry unfit for symbol {code}"
+            ),
+            Self::InvalidGeometry { feature_index } => {
+                write!(f, "feature {feature_index} has invalid geometry")
+            }
+        }
+    }
+}
@@ -5,16 +5,27 @@
 
 /// Color definitions: color set, spot colors, mixed colors, CMYK, RGB.
 pub mod colors;
+/// Comparisons of the symbol sets and color sets of two maps.
+pub mod diff;
 mod format_info;
 /// Coordinate-reference-system and projection helpers.
 pub mod geo_referencing;
+/// `GeoJSON` export and import of map objects with projected or WGS84 coordinates.
+pub mod geojson;
+/// Generalisation checks of map objects against the minimum sizes of their
+/// symbols and of the mapping standards.
+pub mod lint;
 mod notes;
 /// Map objects: points, lines, areas, and text.
 pub mod objects;
+/// OCAD `.ocd` file import and export.
+pub mod ocad;
 /// The top-level OMAP document type.
 pub mod omap;
 /// Map parts (layers) and their contained objects.
 pub mod parts;
+/// Rasterising maps into RGBA images and PNG files.
+pub mod render;
 /// Symbol definitions: point, line, area, text, and combined symbols.
 pub mod symbols;
 /// Background-template support (images, tracks, GDAL/OGR layers).
@@ -22,9 +33,12 @@ pub mod templates;
 mod utils;
 /// View settings: zoom, grid, template visibility.
 pub mod view;
+/// Well-known text and binary (WKT/WKB) import and export of object geometries.
+pub mod wkt;
 
 use std::{fmt::Debug, io::BufWriter};
 
+pub use format_info::OutputStyle;
 /// Re-export `geo_types` for convenience for downstream users
 pub use geo_types;
 pub use omap::Omap;
@@ -138,9 +152,18 @@ pub enum Error {
     /// The root `map` element is missing its version.
     #[error("missing OMAP version")]
     MissingOmapVersion,
-    /// The root `map` element has an unsupported version.
+    /// The root `map` element has a version outside the supported range of 6 to 9.
     #[error("unsupported OMAP version {0}")]
     UnsupportedOmapVersion(u8),
+    /// The file does not start with the OCAD file mark.
+    #[error("not an OCAD file")]
+    NotAnOcadFile,
+    /// The OCAD file has a version outside the supported versions 8 to 12 and 2018.
+    #[error("unsupported OCAD version {0}")]
+    UnsupportedOcadVersion(u16),
+    /// An OCAD file ends early or contains an offset outside the file.
+    #[error("OCAD file is truncated or contains an invalid offset")]
+    TruncatedOcadFile,
     /// The XML declaration has an unsupported XML version.
     #[error("unsupported XML version")]
     UnsupportedXmlVersion,
@@ -320,6 +343,10 @@ pub enum Error {
     /// A polygon contains a nonempty ring that is not closed.
     #[error("polygon rings must be closed")]
     OpenPolygonRing,
+    /// The boundary of the result of a boolean operation on polygons could not
+    /// be joined into closed rings.
+    #[error("could not join the result of a boolean operation into rings")]
+    BooleanOperationError,
     /// The value is not in the unit interval `[0, 1]`.
     #[error("The value is not in the unit interval and cannot be converted to a UnitF64")]
     NotInUnitInterval,
@@ -335,6 +362,21 @@ pub enum Error {
     /// A map coordinate exceeds the file-format range.
     #[error("A provided map coordinate is outside the range for writing")]
     MapCoordOutOfBounds,
+    /// A render viewport is empty or gives an image with too many pixels.
+    #[error("the render viewport is empty or too large")]
+    InvalidRenderViewport,
+    /// The input is not well-formed JSON.
+    #[error("invalid JSON at byte {0}")]
+    InvalidJson(usize),
+    /// The JSON is not a `GeoJSON` feature collection or has a malformed member.
+    #[error("invalid GeoJSON: {0}")]
+    InvalidGeoJson(&'static str),
+    /// The input is not a well-known text (WKT) geometry.
+    #[error("invalid WKT at byte {0}")]
+    InvalidWkt(usize),
+    /// The input is not a well-known binary (WKB) geometry.
+    #[error("invalid WKB at byte {0}")]
+    InvalidWkb(usize),
     /// An Error when parsing a [Code] from a bad string
     #[error("Tried to parse a Code from a bad string")]
     BadCode,
@@ -363,6 +405,9 @@ pub enum Error {
     /// Only affine transforms within the same projection are available without the `geo_ref` feature
     #[error("Failed to get a coordinate transform between the old and new GeoRef")]
     CannotGetTransformBetweenDifferentGeoRef,
+    /// No default map is bundled for the mapping standard at the scale
+    #[error("no default map is bundled for {0:?} at 1:{1}")]
+    NoDefaultMap(omap::MapStandard, std::num::NonZeroU32),
     /// Tried to call try into on non-compatible symbols
     #[error("Tried to call try into on non-compatible symbols")]
     SymbolConversionError,
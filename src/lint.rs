@@ -0,0 +1,622 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use geo_types::{Coord, LineString, Rect};
+
+use crate::{
+    NonNegativeF64, Omap, Result,
+    colors::{SymbolColor, WeakColor},
+    objects::MapObject,
+    parts::{IndexedObject, SpatialIndex},
+    symbols::{Element, PointSymbol, Symbol},
+};
+
+/// Settings of the generalisation checks of [`Omap::lint`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LintOptions {
+    /// The narrowest gap in mm of paper between lines and areas of the same
+    /// colour.
+    pub minimum_gap: NonNegativeF64,
+    /// The narrowest gap in mm of paper between the footprints of two point
+    /// symbols.
+    pub minimum_point_gap: NonNegativeF64,
+    /// The largest deviation of curves in mm of paper when measuring.
+    pub allowed_error: NonNegativeF64,
+}
+
+impl Default for LintOptions {
+    /// The 0.15 mm minimum gap of ISOM 2017-2 and `ISSprOM` 2019-2 for lines, areas
+    /// and point symbols, measuring curves to within 1 µm.
+    fn default() -> Self {
+        Self {
+            minimum_gap: NonNegativeF64::clamped_from(0.15),
+            minimum_point_gap: NonNegativeF64::clamped_from(0.15),
+            allowed_error: NonNegativeF64::clamped_from(0.001),
+        }
+    }
+}
+
+/// A generalisation problem found by [`Omap::lint`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintIssue {
+    /// An area is smaller than the minimum area of its symbol.
+    AreaTooSmall {
+        /// The area in mm² of paper.
+        area: f64,
+        /// The minimum area of the symbol in mm².
+        minimum: f64,
+    },
+    /// A line is shorter than the minimum length of its symbol.
+    LineTooShort {
+        /// The length in mm of paper.
+        length: f64,
+        /// The minimum length of the symbol in mm.
+        minimum: f64,
+    },
+    /// The footprints of two point symbols are closer than
+    /// [`LintOptions::minimum_point_gap`], or overlap for a negative gap.
+    PointsTooClose {
+        /// The part and object index of the other point.
+        other: (usize, usize),
+        /// The gap in mm of paper.
+        gap: f64,
+    },
+    /// Two separate lines or areas of the same colour leave a gap narrower
+    /// than [`LintOptions::minimum_gap`].
+    GapTooNarrow {
+        /// The part and object index of the other line or area.
+        other: (usize, usize),
+        /// The gap in mm of paper.
+        gap: f64,
+    },
+}
+
+/// A generalisation problem of an object of a map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Finding {
+    /// The index of the object's part in [`Omap::parts`].
+    pub part_index: usize,
+    /// The index of the object in its part, see [`crate::parts::MapPart::get`].
+    pub object_index: usize,
+    /// Where the problem is, in map coordinates: the centre of a small area,
+    /// the start of a short line, the point or the narrowest part of a gap.
+    pub location: Coord,
+    /// The problem.
+    pub issue: LintIssue,
+}
+
+/// A line or area drawn in a single colour, for the gap check.
+struct Feature<'a> {
+    indexed: IndexedObject<'a>,
+    color: WeakColor,
+    half_width: f64,
+    lines: Vec<LineString>,
+    is_area: bool,
+}
+
+impl Omap {
+    /// Check the objects of the map against the minimum sizes of their
+    /// symbols and of the mapping standards.
+    ///
+    /// Reports areas below [`crate::symbols::AreaSymbol::minimum_area`] and lines
+    /// shorter than [`crate::symbols::LineSymbol::minimum_length`], also of
+    /// combined symbols, point symbols whose footprints are closer than
+    /// [`LintOptions::minimum_point_gap`], and gaps narrower than
+    /// [`LintOptions::minimum_gap`] between separate lines and areas of the same
+    /// colour. The gap check compares the main line of line symbols and the
+    /// fill of area symbols, and objects of helper symbols are not checked.
+    /// Pairs are reported once, on the object that comes first in the map.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol is already mutably borrowed or an object has
+    /// an invalid geometry.
+    pub fn lint(&self, options: &LintOptions) -> Result<Vec<Finding>> {
+        let index = self.spatial_index();
+        let mut findings = Vec::new();
+        let mut points = Vec::new();
+        let mut features = Vec::new();
+        for (part_index, part) in self.parts.iter().enumerate() {
+            for (object_index, object) in part.iter_all_objects().enumerate() {
+                let Some(symbol) = object.symbol().upgrade() else {
+                    continue;
+                };
+                if symbol.is_helper_symbol()? {
+                    continue;
+                }
+                let indexed = IndexedObject {
+                    part_index,
+                    object_index,
+                    object,
+                };
+                if let Some(finding) = size_finding(indexed, &symbol, options)? {
+                    findings.push(finding);
+                }
+                match (object, &symbol) {
+                    (MapObject::Point(point), Symbol::Point(point_symbol)) => {
+                        points.push((indexed, point.geometry().0, Rc::clone(point_symbol)));
+                    }
+                    _ => features.extend(feature(indexed, &symbol, options)?),
+                }
+            }
+        }
+
+        let mut radii = HashMap::new();
+        let mut point_radius = |symbol: &Rc<RefCell<PointSymbol>>| -> Result<f64> {
+            if let Some(radius) = radii.get(&Rc::as_ptr(symbol)) {
+                return Ok(*radius);
+            }
+            let point_symbol = symbol.try_borrow()?;
+            let radius = footprint_radius(&point_symbol, options)?;
+            radii.insert(Rc::as_ptr(symbol), radius);
+            Ok(radius)
+        };
+        let mut largest_radius = 0_f64;
+        for (_, _, symbol) in &points {
+            largest_radius = largest_radius.max(point_radius(symbol)?);
+        }
+        let gap = options.minimum_point_gap.get();
+        for (indexed, location, symbol) in &points {
+            let radius = point_radius(symbol)?;
+            let reach = radius + largest_radius + gap;
+            for other in index.intersecting(around(*location, reach)) {
+                let MapObject::Point(other_point) = other.object else {
+                    continue;
+                };
+                let Some(Symbol::Point(other_symbol)) = other.object.symbol().upgrade() else {
+                    continue;
+                };
+                if key(other) <= key(*indexed) || other_symbol.try_borrow()?.common.is_helper_symbol
+                {
+                    continue;
+                }
+                let distance = length(other_point.geometry().0 - *location);
+                let point_gap = distance - radius - point_radius(&other_symbol)?;
+                if point_gap < gap {
+                    findings.push(finding(
+                        *indexed,
+                        *location,
+                        LintIssue::PointsTooClose {
+                            other: key(other),
+                            gap: point_gap,
+                        },
+                    ));
+                }
+            }
+        }
+
+        findings.extend(gap_findings(&features, &index, options));
+        findings.sort_by_key(|finding| (finding.part_index, finding.object_index));
+        Ok(findings)
+    }
+}
+
+/// The finding of an area or line below the minimum size of its symbol.
+fn size_finding(
+    indexed: IndexedObject<'_>,
+    symbol: &Symbol,
+    options: &LintOptions,
+) -> Result<Option<Finding>> {
+    match (indexed.object, symbol) {
+        (MapObject::Area(area), Symbol::Area(_) | Symbol::CombinedArea(_)) => {
+            let minimum = match symbol {
+                Symbol::Area(area_symbol) => area_symbol.try_borrow()?.minimum_area.get(),
+                Symbol::CombinedArea(combined) => combined.try_borrow()?.minimum_area()?,
+                _ => 0.,
+            };
+            let size = area.geometry().area();
+            let Some(rect) = area.geometry().bounding_rect() else {
+                return Ok(None);
+            };
+            Ok((size < minimum).then(|| {
+                finding(
+                    indexed,
+                    area.geometry().centroid().unwrap_or_else(|| rect.center()),
+                    LintIssue::AreaTooSmall {
+                        area: size,
+                        minimum,
+                    },
+                )
+            }))
+        }
+        (MapObject::Line(line), Symbol::Line(_) | Symbol::CombinedLine(_)) => {
+            let minimum = match symbol {
+                Symbol::Line(line_symbol) => line_symbol.try_borrow()?.minimum_length.get(),
+                Symbol::CombinedLine(combined) => combined.try_borrow()?.minimum_length()?,
+                _ => 0.,
+            };
+            let length = line.geometry().length(options.allowed_error).length;
+            let Some(start) = line.geometry().geometry().segments().next() else {
+                return Ok(None);
+            };
+            Ok((length < minimum).then(|| {
+                finding(
+                    indexed,
+                    start.start(),
+                    LintIssue::LineTooShort { length, minimum },
+                )
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The single-coloured line or area of an object for the gap check.
+fn feature<'a>(
+    indexed: IndexedObject<'a>,
+    symbol: &Symbol,
+    options: &LintOptions,
+) -> Result<Option<Feature<'a>>> {
+    let (color, half_width) = match symbol {
+        Symbol::Line(line_symbol) => {
+            let line_symbol = line_symbol.try_borrow()?;
+            (line_symbol.color.clone(), line_symbol.line_width.get() / 2.)
+        }
+        Symbol::Area(area_symbol) => (area_symbol.try_borrow()?.color.clone(), 0.),
+        _ => return Ok(None),
+    };
+    let SymbolColor::Color(color) = color else {
+        return Ok(None);
+    };
+    let (lines, is_area) = match indexed.object {
+        MapObject::Line(line) if half_width > 0. => (
+            vec![line.flatten(options.allowed_error)?.geometry().clone()],
+            false,
+        ),
+        MapObject::Area(area) => {
+            let (exterior, interiors) = area
+                .flatten(options.allowed_error)?
+                .into_polygon()
+                .into_inner();
+            (std::iter::once(exterior).chain(interiors).collect(), true)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(Feature {
+        indexed,
+        color,
+        half_width,
+        lines,
+        is_area,
+    }))
+}
+
+/// The narrow gaps between separate features of the same colour, comparing
+/// every feature with the features the spatial index finds within reach.
+fn gap_findings(
+    features: &[Feature<'_>],
+    index: &SpatialIndex<'_>,
+    options: &LintOptions,
+) -> Vec<Finding> {
+    let minimum_gap = options.minimum_gap.get();
+    let rects = features
+        .iter()
+        .map(|feature| {
+            feature
+                .lines
+                .iter()
+                .filter_map(line_rect)
+                .reduce(crate::objects::union_rects)
+        })
+        .collect::<Vec<_>>();
+    let by_key = features
+        .iter()
+        .enumerate()
+        .map(|(i, feature)| (key(feature.indexed), i))
+        .collect::<HashMap<_, _>>();
+    let largest_half_width = features
+        .iter()
+        .map(|feature| feature.half_width)
+        .fold(0., f64::max);
+    let mut findings = Vec::new();
+    for (i, feature) in features.iter().enumerate() {
+        let Some(rect) = rects[i] else {
+            continue;
+        };
+        let envelope = expand(rect, feature.half_width + largest_half_width + minimum_gap);
+        let mut others = index
+            .intersecting(envelope)
+            .into_iter()
+            .filter(|other| key(*other) > key(feature.indexed))
+            .filter_map(|other| by_key.get(&key(other)).copied())
+            .collect::<Vec<_>>();
+        others.sort_unstable();
+        for j in others {
+            let other = &features[j];
+            let reach = feature.half_width + other.half_width + minimum_gap;
+            let Some(other_rect) = rects[j] else {
+                continue;
+            };
+            if feature.color != other.color || !rects_within(rect, other_rect, reach) {
+                continue;
+            }
+            let Some((distance, location)) = closest_approach(&feature.lines, &other.lines) else {
+                continue;
+            };
+            let gap = distance - feature.half_width - other.half_width;
+            let overlaps = (feature.is_area && contains_any(&feature.lines, &other.lines))
+                || (other.is_area && contains_any(&other.lines, &feature.lines));
+            if gap > 0. && gap < minimum_gap && !overlaps {
+                findings.push(finding(
+                    feature.indexed,
+                    location,
+                    LintIssue::GapTooNarrow {
+                        other: key(other.indexed),
+                        gap,
+                    },
+                ));
+            }
+        }
+    }
+    findings
+}
+
+/// The distance of a point symbol's farthest ink from its anchor.
+fn footprint_radius(symbol: &PointSymbol, options: &LintOptions) -> Result<f64> {
+    let mut radius = if symbol.inner_color == SymbolColor::NoColor {
+        0.
+    } else {
+        symbol.inner_radius.get()
+    };
+    if symbol.outer_color != SymbolColor::NoColor && symbol.outer_width.get() > 0. {
+        radius = radius.max(symbol.inner_radius.get() + symbol.outer_width.get());
+    }
+    for element in &symbol.elements {
+        let element_radius = match element {
+            Element::Point { symbol, object } => {
+                length(object.geometry().0) + footprint_radius(symbol, options)?
+            }
+            Element::Line { symbol, object } => {
+                farthest(object.flatten(options.allowed_error)?.geometry())
+                    + symbol.line_width.get() / 2.
+            }
+            Element::Area { object, .. } => farthest(
+                object
+                    .flatten(options.allowed_error)?
+                    .into_polygon()
+                    .exterior(),
+            ),
+        };
+        radius = radius.max(element_radius);
+    }
+    Ok(radius)
+}
+
+/// The smallest distance between the segments of two sets of lines and the
+/// middle of the closest points, or `None` if they cross.
+fn closest_approach(lines: &[LineString], others: &[LineString]) -> Option<(f64, Coord)> {
+    let mut closest: Option<(f64, Coord)> = None;
+    for line in lines {
+        for segment in line.0.windows(2) {
+            for other in others {
+                for other_segment in other.0.windows(2) {
+                    let (a0, a1, b0, b1) =
+                        (segment[0], segment[1], other_segment[0], other_segment[1]);
+                    if segments_cross(a0, a1, b0, b1) {
+                        return None;
+                    }
+                    let candidates = [
+                        (a0, closest_on_segment(a0, b0, b1)),
+                        (a1, closest_on_segment(a1, b0, b1)),
+                        (closest_on_segment(b0, a0, a1), b0),
+                        (closest_on_segment(b1, a0, a1), b1),
+                    ];
+                    for (a, b) in candidates {
+                        let distance = length(b - a);
+                        if closest.is_none_or(|(closest, _)| distance < closest) {
+                            closest = Some((distance, (a + b) / 2.));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    closest
+}
+
+/// Whether a point of `others` lies inside the rings of `rings` by the even-odd
+/// rule.
+fn contains_any(rings: &[LineString], others: &[LineString]) -> bool {
+    others
+        .iter()
+        .filter_map(|other| other.0.first())
+        .any(|point| {
+            let mut inside = false;
+            for ring in rings {
+                for edge in ring.0.windows(2) {
+                    let (a, b) = (edge[0], edge[1]);
+                    if (a.y > point.y) != (b.y > point.y)
+                        && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+                    {
+                        inside = !inside;
+                    }
+                }
+            }
+            inside
+        })
+}
+
+fn segments_cross(a0: Coord, a1: Coord, b0: Coord, b1: Coord) -> bool {
+    let side = |p: Coord, q0: Coord, q1: Coord| cross(q1 - q0, p - q0);
+    let (d0, d1) = (side(b0, a0, a1), side(b1, a0, a1));
+    let (d2, d3) = (side(a0, b0, b1), side(a1, b0, b1));
+    d0 * d1 < 0. && d2 * d3 < 0.
+}
+
+fn closest_on_segment(point: Coord, start: Coord, end: Coord) -> Coord {
+    let direction = end - start;
+    let length_squared = direction.x * direction.x + direction.y * direction.y;
+    if length_squared == 0. {
+        return start;
+    }
+    let t = (((point.x - start.x) * direction.x + (point.y - start.y) * direction.y)
+        / length_squared)
+        .clamp(0., 1.);
+    start + direction * t
+}
+
+fn farthest(line: &LineString) -> f64 {
+    line.0.iter().map(|coord| length(*coord)).fold(0., f64::max)
+}
+
+fn line_rect(line: &LineString) -> Option<Rect> {
+    let first = *line.0.first()?;
+    Some(line.0.iter().fold(Rect::new(first, first), |rect, coord| {
+        Rect::new(
+            Coord {
+                x: rect.min().x.min(coord.x),
+                y: rect.min().y.min(coord.y),
+            },
+            Coord {
+                x: rect.max().x.max(coord.x),
+                y: rect.max().y.max(coord.y),
+            },
+        )
+    }))
+}
+
+fn rects_within(a: Rect, b: Rect, distance: f64) -> bool {
+    a.min().x <= b.max().x + distance
+        && b.min().x <= a.max().x + distance
+        && a.min().y <= b.max().y + distance
+        && b.min().y <= a.max().y + distance
+}
+
+fn around(point: Coord, distance: f64) -> Rect {
+    expand(Rect::new(point, point), distance)
+}
+
+fn expand(rect: Rect, distance: f64) -> Rect {
+    let offset = Coord {
+        x: distance,
+        y: distance,
+    };
+    Rect::new(rect.min() - offset, rect.max() + offset)
+}
+
+fn finding(indexed: IndexedObject<'_>, location: Coord, issue: LintIssue) -> Finding {
+    Finding {
+        part_index: indexed.part_index,
+        object_index: indexed.object_index,
+        location,
+        issue,
+    }
+}
+
+fn key(indexed: IndexedObject<'_>) -> (usize, usize) {
+    (indexed.part_index, indexed.object_index)
+}
+
+fn cross(a: Coord, b: Coord) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn length(vector: Coord) -> f64 {
+    vector.x.hypot(vector.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use geo_types::{LineString, Point, Polygon};
+
+    use super::{LintIssue, LintOptions};
+    use crate::{
+        Code, NonNegativeF64, Omap, Result,
+        colors::{Cmyk, SpotColor, SymbolColor},
+        objects::{AreaObject, LineObject, PointObject},
+        symbols::{AreaSymbol, LineSymbol, PointSymbol, Symbol},
+    };
+
+    #[test]
+    fn reports_small_objects_and_narrow_gaps() -> Result<()> {
+        let mut map = Omap::new(NonZeroU32::MIN);
+        let black = SymbolColor::Color(map.colors.push(SpotColor::new(
+            "Black",
+            "Black",
+            Cmyk::new(0., 0., 0., 1.)?,
+        )));
+        let area = map.symbols.add_symbol(
+            AreaSymbol::new(Code::new(212, 0, 0), "Impassable cliff area")
+                .with_color(black.clone())
+                .with_minimum_area(NonNegativeF64::try_from(1.)?),
+        );
+        let mut line_symbol = LineSymbol::new(Code::new(201, 0, 0), "Cliff")
+            .with_color(black.clone())
+            .with_line_width(NonNegativeF64::try_from(0.14)?);
+        line_symbol.minimum_length = NonNegativeF64::try_from(2.)?;
+        let line = map.symbols.add_symbol(line_symbol);
+        let point = map.symbols.add_symbol(
+            PointSymbol::new(Code::new(206, 0, 0), "Boulder")
+                .with_inner_color(black)
+                .with_inner_radius(NonNegativeF64::try_from(0.2)?),
+        );
+        let (Some(Symbol::Area(area)), Some(Symbol::Line(line)), Some(Symbol::Point(point))) =
+            (area.upgrade(), line.upgrade(), point.upgrade())
+        else {
+            panic!("the symbols are in the symbol set with their types");
+        };
+        let Some(part) = map.parts.get_mut(0) else {
+            panic!("a new map has a part");
+        };
+        part.add_object(AreaObject::new(
+            std::rc::Rc::downgrade(&area),
+            Polygon::new(
+                LineString::from(vec![(0., 0.), (0.5, 0.), (0.5, 0.5), (0., 0.5), (0., 0.)]),
+                Vec::new(),
+            ),
+        ));
+        part.add_object(LineObject::new(
+            std::rc::Rc::downgrade(&line),
+            LineString::from(vec![(10., 0.), (11., 0.)]),
+        ));
+        part.add_object(LineObject::new(
+            std::rc::Rc::downgrade(&line),
+            LineString::from(vec![(0., 10.), (20., 10.)]),
+        ));
+        part.add_object(LineObject::new(
+            std::rc::Rc::downgrade(&line),
+            LineString::from(vec![(0., 10.2), (20., 10.2)]),
+        ));
+        for x in [30., 30.5, 40.] {
+            part.add_object(PointObject::new(
+                std::rc::Rc::downgrade(&point),
+                Point::new(x, 0.),
+            ));
+        }
+
+        let findings = map.lint(&LintOptions::default())?;
+
+        let issues = findings
+            .iter()
+            .map(|finding| (finding.object_index, finding.issue))
+            .collect::<Vec<_>>();
+        let [
+            (0, LintIssue::AreaTooSmall { area, minimum: 1. }),
+            (
+                1,
+                LintIssue::LineTooShort {
+                    length,
+                    minimum: 2.,
+                },
+            ),
+            (2, LintIssue::GapTooNarrow { other: (0, 3), gap }),
+            (
+                4,
+                LintIssue::PointsTooClose {
+                    other: (0, 5),
+                    gap: point_gap,
+                },
+            ),
+        ] = issues.as_slice()
+        else {
+            panic!("unexpected findings {findings:?}");
+        };
+        assert!((area - 0.25).abs() < 1e-9);
+        assert!((length - 1.).abs() < 1e-9);
+        assert!((gap - 0.06).abs() < 1e-9);
+        assert!((point_gap - 0.1).abs() < 1e-9);
+        assert!((findings[2].location.y - 10.1).abs() < 1e-9);
+        Ok(())
+    }
+}
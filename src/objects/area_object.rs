@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use geo_types::{Coord, Polygon};
+use geo_types::{Coord, Polygon, Rect};
 use quick_xml::{
     Reader, Writer,
     events::{BytesEnd, BytesStart, Event},
@@ -8,7 +8,7 @@ use quick_xml::{
 
 use super::{
     BezierPath, COORD_FLAG_CLOSE_POINT, COORD_FLAGS_RING_END, FileCoord, FlattenedPath,
-    bezier_from_file_coords, file_coords_from_bezier,
+    bezier_from_file_coords, file_coords_from_bezier, union_rects,
 };
 use crate::{
     Error, NonNegativeF64, OmapSection, Result,
@@ -113,6 +113,15 @@ impl BezierPolygon {
         self.exterior.is_empty()
     }
 
+    /// The exact bounding box of all rings, or `None` for an empty polygon.
+    /// See [`BezierPath::bounding_rect`].
+    pub fn bounding_rect(&self) -> Option<Rect> {
+        std::iter::once(&self.exterior)
+            .chain(&self.interiors)
+            .filter_map(BezierPath::bounding_rect)
+            .reduce(union_rects)
+    }
+
     /// Flatten every ring while retaining dash-point metadata.
     ///
     /// # Errors
@@ -473,6 +482,7 @@ impl AreaObject {
         let mut tags = HashMap::new();
         let mut pattern_rotation = PatternRotation::default();
         let mut file_coords = Vec::new();
+        let mut in_coords = false;
         let mut buf = Vec::new();
 
         loop {
@@ -484,7 +494,9 @@ impl AreaObject {
                             .flatten()
                             .unwrap_or(0);
                         file_coords.reserve(count);
+                        in_coords = true;
                     }
+                    b"coord" if in_coords => file_coords.push(super::parse_file_coord(&start)?),
                     b"pattern" => {
                         pattern_rotation.rotation = try_get_attr_raw(&start, "rotation")
                             .ok()
@@ -499,8 +511,14 @@ impl AreaObject {
                     }
                     _ => (),
                 },
-                Event::End(end) if end.local_name().as_ref() == b"object" => break,
-                Event::Text(text) => super::parse_file_coords(text.as_ref(), &mut file_coords)?,
+                Event::End(end) => match end.local_name().as_ref() {
+                    b"object" => break,
+                    b"coords" => in_coords = false,
+                    _ => (),
+                },
+                Event::Text(text) if in_coords => {
+                    super::parse_file_coords(text.as_ref(), &mut file_coords)?;
+                }
                 Event::Eof => return Err(Error::UnexpectedEof(OmapSection::AreaObject)),
                 _ => (),
             }
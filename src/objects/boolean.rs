@@ -0,0 +1,1117 @@
+use std::collections::HashMap;
+
+use geo_types::{Coord, Rect};
+
+use super::{
+    AreaObject, BezierPath, BezierPolygon, BezierSegment, BezierString, cubic_extrema,
+    measure::{cubic_derivative, cubic_point, split_cubic},
+};
+use crate::{Error, Result};
+
+/// The distance in mm of paper below which points are merged, a thousandth of
+/// the precision of the file format.
+const TOLERANCE: f64 = 1e-6;
+/// The distance in mm of paper to either side of an edge at which it is tested
+/// whether the result lies on its left or right.
+const OFFSET: f64 = 1e-4;
+/// The deepest subdivision of two curves when intersecting them.
+const MAX_DEPTH: u32 = 32;
+
+/// A boolean operation on two polygons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl Operation {
+    /// Whether a point in or outside the operands is in the result.
+    fn contains(self, in_first: bool, in_second: bool) -> bool {
+        match self {
+            Self::Union => in_first || in_second,
+            Self::Intersection => in_first && in_second,
+            Self::Difference => in_first && !in_second,
+            Self::Xor => in_first != in_second,
+        }
+    }
+}
+
+impl BezierPolygon {
+    /// The union of two polygons as polygons with holes.
+    ///
+    /// Cubic segments are kept where the result follows a curve of an operand,
+    /// and are only split at intersections. Dash points are kept. Rings are
+    /// filled by the even-odd rule, so self-intersecting operands are allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operand is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn union(&self, other: &Self) -> Result<Vec<Self>> {
+        boolean(self, other, Operation::Union)
+    }
+
+    /// The intersection of two polygons as polygons with holes.
+    /// See [`Self::union`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operand is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn intersection(&self, other: &Self) -> Result<Vec<Self>> {
+        boolean(self, other, Operation::Intersection)
+    }
+
+    /// The polygon with `other` cut out as polygons with holes.
+    /// See [`Self::union`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operand is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn difference(&self, other: &Self) -> Result<Vec<Self>> {
+        boolean(self, other, Operation::Difference)
+    }
+
+    /// The parts of two polygons not covered by the other, as polygons with
+    /// holes. See [`Self::union`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operand is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn xor(&self, other: &Self) -> Result<Vec<Self>> {
+        boolean(self, other, Operation::Xor)
+    }
+}
+
+impl AreaObject {
+    /// The union with another area as objects with the symbol, tags and pattern
+    /// rotation of this object. See [`BezierPolygon::union`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a geometry is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn union(&self, other: &Self) -> Result<Vec<Self>> {
+        Ok(self.with_geometries(self.geometry().union(other.geometry())?))
+    }
+
+    /// The intersection with another area as objects with the symbol, tags and
+    /// pattern rotation of this object. See [`BezierPolygon::union`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a geometry is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn intersection(&self, other: &Self) -> Result<Vec<Self>> {
+        Ok(self.with_geometries(self.geometry().intersection(other.geometry())?))
+    }
+
+    /// This area with another area cut out, as objects with the symbol, tags
+    /// and pattern rotation of this object. See [`BezierPolygon::union`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a geometry is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn difference(&self, other: &Self) -> Result<Vec<Self>> {
+        Ok(self.with_geometries(self.geometry().difference(other.geometry())?))
+    }
+
+    /// The parts of this and another area not covered by the other, as objects
+    /// with the symbol, tags and pattern rotation of this object.
+    /// See [`BezierPolygon::union`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a geometry is invalid or the boundary of the result
+    /// cannot be joined into rings.
+    pub fn xor(&self, other: &Self) -> Result<Vec<Self>> {
+        Ok(self.with_geometries(self.geometry().xor(other.geometry())?))
+    }
+
+    pub(super) fn with_geometries(&self, geometries: Vec<BezierPolygon>) -> Vec<Self> {
+        geometries
+            .into_iter()
+            .map(|geometry| {
+                let mut object = Self::new(self.symbol.clone(), geometry);
+                object.tags.clone_from(&self.tags);
+                object.pattern_rotation = self.pattern_rotation.clone();
+                object
+            })
+            .collect()
+    }
+}
+
+/// A straight or cubic segment, with the handles of a straight segment at a
+/// third and two thirds of its length.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    points: [Coord; 4],
+    is_curve: bool,
+    /// Whether the start and the end are dash points.
+    dash_points: [bool; 2],
+}
+
+impl Edge {
+    fn line(start: Coord, end: Coord) -> Self {
+        Self {
+            points: [
+                start,
+                start + (end - start) / 3.,
+                end + (start - end) / 3.,
+                end,
+            ],
+            is_curve: false,
+            dash_points: [false; 2],
+        }
+    }
+
+    fn from_segment(segment: &BezierSegment) -> Self {
+        match segment.handles() {
+            Some((handle1, handle2)) => Self {
+                points: [segment.start(), handle1, handle2, segment.end()],
+                is_curve: true,
+                dash_points: [false; 2],
+            },
+            None => Self::line(segment.start(), segment.end()),
+        }
+    }
+
+    /// Whether the edge is shorter than the tolerance.
+    fn is_degenerate(self) -> bool {
+        self.points[1..]
+            .iter()
+            .all(|point| length(*point - self.start()) <= TOLERANCE)
+    }
+
+    /// Points just left and right of the middle of the edge.
+    fn sides(self) -> (Coord, Coord) {
+        let middle = self.point(0.5);
+        let tangent = cubic_derivative(self.points, 0.5);
+        let normal = Coord {
+            x: -tangent.y,
+            y: tangent.x,
+        } * (OFFSET / length(tangent));
+        (middle + normal, middle - normal)
+    }
+
+    fn to_segment(self) -> BezierSegment {
+        let [start, handle1, handle2, end] = self.points;
+        BezierSegment::new(start, self.is_curve.then_some((handle1, handle2)), end)
+    }
+
+    fn start(self) -> Coord {
+        self.points[0]
+    }
+
+    fn end(self) -> Coord {
+        self.points[3]
+    }
+
+    fn point(self, t: f64) -> Coord {
+        cubic_point(self.points, t)
+    }
+
+    fn reversed(self) -> Self {
+        let [p0, p1, p2, p3] = self.points;
+        Self {
+            points: [p3, p2, p1, p0],
+            is_curve: self.is_curve,
+            dash_points: [self.dash_points[1], self.dash_points[0]],
+        }
+    }
+
+    /// Split the edge at parameter `t`, ending the first part and starting the
+    /// second at `point`.
+    fn split(self, t: f64, point: Coord) -> (Self, Self) {
+        let (first, second) = if self.is_curve {
+            let (mut first, mut second) = split_cubic(self.points, t);
+            // move the handles at the split along with the snapped end points
+            let shift = point - first[3];
+            first[2] = first[2] + shift;
+            first[3] = point;
+            second[0] = point;
+            second[1] = second[1] + shift;
+            (
+                Self {
+                    points: first,
+                    is_curve: true,
+                    dash_points: [false; 2],
+                },
+                Self {
+                    points: second,
+                    is_curve: true,
+                    dash_points: [false; 2],
+                },
+            )
+        } else {
+            (
+                Self::line(self.start(), point),
+                Self::line(point, self.end()),
+            )
+        };
+        (
+            Self {
+                dash_points: [self.dash_points[0], false],
+                ..first
+            },
+            Self {
+                dash_points: [false, self.dash_points[1]],
+                ..second
+            },
+        )
+    }
+
+    /// The bounding box of the control points, which contains the edge.
+    fn hull(self) -> Rect {
+        let [p0, p1, p2, p3] = self.points;
+        let min = |a: f64, b: f64, c: f64, d: f64| a.min(b).min(c.min(d));
+        let max = |a: f64, b: f64, c: f64, d: f64| a.max(b).max(c.max(d));
+        Rect::new(
+            Coord {
+                x: min(p0.x, p1.x, p2.x, p3.x),
+                y: min(p0.y, p1.y, p2.y, p3.y),
+            },
+            Coord {
+                x: max(p0.x, p1.x, p2.x, p3.x),
+                y: max(p0.y, p1.y, p2.y, p3.y),
+            },
+        )
+    }
+
+    /// Whether the handles are close enough to the chord for the edge to be
+    /// intersected as a straight segment.
+    fn is_flat(self) -> bool {
+        let [p0, p1, p2, p3] = self.points;
+        !self.is_curve
+            || (point_line_distance(p1, p0, p3) <= TOLERANCE
+                && point_line_distance(p2, p0, p3) <= TOLERANCE)
+    }
+
+    /// The direction in which the edge leaves its start.
+    fn start_direction(self) -> Coord {
+        let [p0, p1, p2, p3] = self.points;
+        [p1, p2, p3]
+            .into_iter()
+            .map(|point| point - p0)
+            .find(|direction| length(*direction) > TOLERANCE)
+            .unwrap_or(p3 - p0)
+    }
+
+    /// The direction in which the edge arrives at its end.
+    fn end_direction(self) -> Coord {
+        let [p0, p1, p2, p3] = self.points;
+        [p2, p1, p0]
+            .into_iter()
+            .map(|point| p3 - point)
+            .find(|direction| length(*direction) > TOLERANCE)
+            .unwrap_or(p3 - p0)
+    }
+
+    /// The signed number of times the edge crosses the ray from `point` in the
+    /// positive x direction, upwards crossings counting one and downwards ones
+    /// minus one, and ends counted by the half-open rule of polygons.
+    fn ray_crossings(self, point: Coord) -> i32 {
+        let hull = self.hull();
+        if hull.max().x <= point.x || hull.min().y > point.y || hull.max().y <= point.y {
+            return 0;
+        }
+        if !self.is_curve {
+            let (a, b) = (self.start(), self.end());
+            let crosses = (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            return match (crosses, b.y > point.y) {
+                (false, _) => 0,
+                (true, true) => 1,
+                (true, false) => -1,
+            };
+        }
+        // the curve crosses the ray at most once between its extrema in y
+        let mut breaks = cubic_extrema(self.points.map(|point| point.y))
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        breaks.sort_by(f64::total_cmp);
+        let mut crossings = 0;
+        let mut start = 0.;
+        for end in breaks.into_iter().chain([1.]) {
+            // the ends are taken as they are, like the ends of neighbouring edges
+            let y = |t: f64| {
+                if t <= 0. {
+                    self.start().y
+                } else if t >= 1. {
+                    self.end().y
+                } else {
+                    self.point(t).y
+                }
+            };
+            let above = y(start) > point.y;
+            if above != (y(end) > point.y) {
+                let (mut low, mut high) = (start, end);
+                for _ in 0..60 {
+                    let middle = f64::midpoint(low, high);
+                    if (y(middle) > point.y) == above {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+                if self.point(f64::midpoint(low, high)).x > point.x {
+                    crossings += if above { -1 } else { 1 };
+                }
+            }
+            start = end;
+        }
+        crossings
+    }
+}
+
+/// Whether `point` is inside edges by the even-odd rule.
+fn contains(edges: &[Edge], point: Coord) -> bool {
+    winding_number(edges, point) % 2 != 0
+}
+
+/// The number of times closed edges wind counter-clockwise around `point`.
+fn winding_number(edges: &[Edge], point: Coord) -> i32 {
+    edges.iter().map(|edge| edge.ray_crossings(point)).sum()
+}
+
+fn boolean(
+    first: &BezierPolygon,
+    second: &BezierPolygon,
+    operation: Operation,
+) -> Result<Vec<BezierPolygon>> {
+    first.validate()?;
+    second.validate()?;
+    let first_edges = polygon_edges(first);
+    let second_edges = polygon_edges(second);
+
+    // split all edges where they meet any other edge, also of the same polygon
+    let mut edges = first_edges
+        .iter()
+        .chain(&second_edges)
+        .copied()
+        .collect::<Vec<_>>();
+    let num_first = first_edges.len();
+    let sources = split_at_intersections(&mut edges, num_first);
+    merge_vertices(&mut edges);
+
+    // keep the edges with the result on exactly one side, with that side on the left
+    let mut boundary = Vec::new();
+    let mut seen = HashMap::<(u64, u64, u64, u64), Vec<Coord>>::new();
+    for (edge, from_first) in edges.into_iter().zip(sources) {
+        if edge.is_degenerate() {
+            continue;
+        }
+        let (left, right) = edge.sides();
+        let in_result = |point: Coord| {
+            operation.contains(
+                contains(&first_edges, point),
+                contains(&second_edges, point),
+            )
+        };
+        let edge = match (in_result(left), in_result(right)) {
+            (true, false) => edge,
+            (false, true) => edge.reversed(),
+            _ => continue,
+        };
+        // an edge shared by both polygons is kept once
+        let key = (
+            edge.start().x.to_bits(),
+            edge.start().y.to_bits(),
+            edge.end().x.to_bits(),
+            edge.end().y.to_bits(),
+        );
+        let middle = edge.point(0.5);
+        let middles = seen.entry(key).or_default();
+        if !from_first
+            && middles
+                .iter()
+                .any(|other| length(*other - middle) <= OFFSET)
+        {
+            continue;
+        }
+        middles.push(middle);
+        boundary.push(edge);
+    }
+
+    let rings = join_rings(&boundary)?;
+    Ok(nest_rings(rings))
+}
+
+/// Split a polygon into the pieces between the parts of a path that cross it
+/// from outline to outline.
+pub(super) fn cut(polygon: &BezierPolygon, path: &BezierPath) -> Result<Vec<BezierPolygon>> {
+    polygon.validate()?;
+    path.validate()?;
+    let polygon_edges = polygon_edges(polygon);
+    let mut edges = polygon_edges.clone();
+    edges.extend(path.geometry().segments().map(Edge::from_segment));
+    let sources = split_at_intersections(&mut edges, polygon_edges.len());
+    merge_vertices(&mut edges);
+
+    // the outline with the polygon on the left and the path where it is inside
+    let mut outline = Vec::new();
+    let mut cuts = Vec::new();
+    for (edge, from_polygon) in edges.into_iter().zip(sources) {
+        if edge.is_degenerate() {
+            continue;
+        }
+        let (left, right) = edge.sides();
+        match (
+            from_polygon,
+            contains(&polygon_edges, left),
+            contains(&polygon_edges, right),
+        ) {
+            (true, true, false) => outline.push(edge),
+            (true, false, true) => outline.push(edge.reversed()),
+            (false, true, true) => cuts.push(edge),
+            _ => {}
+        }
+    }
+
+    // drop the ends of the path that do not reach the outline or itself
+    let key = |point: Coord| (point.x.to_bits(), point.y.to_bits());
+    loop {
+        let mut degrees = HashMap::<_, usize>::new();
+        for edge in outline.iter().chain(&cuts) {
+            *degrees.entry(key(edge.start())).or_default() += 1;
+            *degrees.entry(key(edge.end())).or_default() += 1;
+        }
+        let num_cuts = cuts.len();
+        cuts.retain(|edge| degrees[&key(edge.start())] > 1 && degrees[&key(edge.end())] > 1);
+        if cuts.len() == num_cuts {
+            break;
+        }
+    }
+
+    // the pieces on both sides of the cuts are traced with their own copy
+    for edge in cuts {
+        outline.push(edge);
+        outline.push(edge.reversed());
+    }
+    Ok(nest_rings(join_rings(&outline)?))
+}
+
+/// The polygons covering the points around which closed rings wind
+/// counter-clockwise, which resolves rings that cross themselves or each other.
+pub(super) fn fill_positive(rings: &[BezierPath]) -> Result<Vec<BezierPolygon>> {
+    let ring_edges = rings_edges(rings.iter());
+    let mut edges = ring_edges.clone();
+    split_at_intersections(&mut edges, ring_edges.len());
+    merge_vertices(&mut edges);
+
+    let mut boundary = Vec::new();
+    let mut seen = HashMap::<(u64, u64, u64, u64), Vec<Coord>>::new();
+    for edge in edges {
+        if edge.is_degenerate() {
+            continue;
+        }
+        let (left, right) = edge.sides();
+        let edge = match (
+            winding_number(&ring_edges, left) > 0,
+            winding_number(&ring_edges, right) > 0,
+        ) {
+            (true, false) => edge,
+            (false, true) => edge.reversed(),
+            _ => continue,
+        };
+        // rings running along each other give the boundary once
+        let key = (
+            edge.start().x.to_bits(),
+            edge.start().y.to_bits(),
+            edge.end().x.to_bits(),
+            edge.end().y.to_bits(),
+        );
+        let middle = edge.point(0.5);
+        let middles = seen.entry(key).or_default();
+        if middles
+            .iter()
+            .any(|other| length(*other - middle) <= OFFSET)
+        {
+            continue;
+        }
+        middles.push(middle);
+        boundary.push(edge);
+    }
+    Ok(nest_rings(join_rings(&boundary)?))
+}
+
+/// The parameters at which two segments intersect.
+pub(super) fn segment_intersections(a: &BezierSegment, b: &BezierSegment) -> Vec<(f64, f64)> {
+    let (a, b) = (Edge::from_segment(a), Edge::from_segment(b));
+    let mut hits = Vec::new();
+    intersect(a, (0., 1.), b, (0., 1.), 0, &mut hits);
+    hits.into_iter().map(|(s, t)| refine(a, b, s, t)).collect()
+}
+
+/// The points where a path crosses or touches itself, apart from where
+/// consecutive segments meet, or where it crosses or touches `other`.
+pub(super) fn crossings(path: &BezierPath, other: Option<&BezierPath>) -> Vec<Coord> {
+    let mut edges = path
+        .geometry()
+        .segments()
+        .map(Edge::from_segment)
+        .collect::<Vec<_>>();
+    let num_first = edges.len();
+    let is_closed = path.is_closed();
+    if let Some(other) = other {
+        edges.extend(other.geometry().segments().map(Edge::from_segment));
+    }
+    let hulls = edges.iter().map(|edge| edge.hull()).collect::<Vec<_>>();
+    let mut order = (0..edges.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| hulls[*a].min().x.total_cmp(&hulls[*b].min().x));
+
+    let mut points = Vec::<Coord>::new();
+    for (position, &i) in order.iter().enumerate() {
+        for &j in &order[position + 1..] {
+            if hulls[j].min().x > hulls[i].max().x + TOLERANCE {
+                break;
+            }
+            let (i, j) = (i.min(j), i.max(j));
+            let is_pair = match other {
+                Some(_) => i < num_first && j >= num_first,
+                None => true,
+            };
+            if !is_pair
+                || edges[i].is_degenerate()
+                || edges[j].is_degenerate()
+                || !overlaps(hulls[i], hulls[j])
+            {
+                continue;
+            }
+            // the vertex shared by consecutive segments is no crossing
+            let mut shared = Vec::new();
+            if other.is_none() && j == i + 1 {
+                shared.push(edges[i].end());
+            }
+            if other.is_none() && is_closed && i == 0 && j == num_first - 1 {
+                shared.push(edges[i].start());
+            }
+            let mut hits = Vec::new();
+            intersect(edges[i], (0., 1.), edges[j], (0., 1.), 0, &mut hits);
+            for (s, t) in hits {
+                let (s, t) = refine(edges[i], edges[j], s, t);
+                let point = (edges[i].point(s) + edges[j].point(t)) / 2.;
+                if shared
+                    .iter()
+                    .any(|shared| length(point - *shared) <= OFFSET)
+                    || points.iter().any(|other| length(*other - point) <= OFFSET)
+                {
+                    continue;
+                }
+                points.push(point);
+            }
+        }
+    }
+    points
+}
+
+/// Whether `point` is inside a closed path by the even-odd rule.
+pub(super) fn ring_contains(ring: &BezierPath, point: Coord) -> bool {
+    contains(
+        &ring
+            .geometry()
+            .segments()
+            .map(Edge::from_segment)
+            .collect::<Vec<_>>(),
+        point,
+    )
+}
+
+/// Whether `point` is inside a polygon by the even-odd rule.
+pub(super) fn polygon_contains(polygon: &BezierPolygon, point: Coord) -> bool {
+    contains(&polygon_edges(polygon), point)
+}
+
+/// The edges of all rings of a polygon with their dash points, closing open
+/// rings.
+fn polygon_edges(polygon: &BezierPolygon) -> Vec<Edge> {
+    rings_edges(std::iter::once(polygon.exterior()).chain(polygon.interiors()))
+}
+
+/// The edges of rings with their dash points, closing open rings.
+fn rings_edges<'a>(rings: impl Iterator<Item = &'a BezierPath>) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for ring in rings {
+        let start = edges.len();
+        let dash_points = ring.vertex_is_dash_point();
+        edges.extend(ring.geometry().segments().zip(dash_points.windows(2)).map(
+            |(segment, dash_points)| Edge {
+                dash_points: [dash_points[0], dash_points[1]],
+                ..Edge::from_segment(segment)
+            },
+        ));
+        if let (Some(first), Some(last)) = (edges.get(start), edges.last())
+            && first.start() != last.end()
+        {
+            edges.push(Edge::line(last.end(), first.start()));
+        }
+    }
+    edges
+}
+
+/// Split the edges at their intersections and return whether each of the new
+/// edges comes from the first `num_first` edges.
+fn split_at_intersections(edges: &mut Vec<Edge>, num_first: usize) -> Vec<bool> {
+    let mut splits = vec![Vec::new(); edges.len()];
+    let hulls = edges.iter().map(|edge| edge.hull()).collect::<Vec<_>>();
+    let mut order = (0..edges.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| hulls[*a].min().x.total_cmp(&hulls[*b].min().x));
+    for (position, &i) in order.iter().enumerate() {
+        for &j in &order[position + 1..] {
+            if hulls[j].min().x > hulls[i].max().x + TOLERANCE {
+                break;
+            }
+            if !overlaps(hulls[i], hulls[j]) || is_same_curve(edges[i], edges[j]) {
+                continue;
+            }
+            let mut hits = Vec::new();
+            intersect(edges[i], (0., 1.), edges[j], (0., 1.), 0, &mut hits);
+            for (ti, tj) in hits {
+                let (ti, tj) = refine(edges[i], edges[j], ti, tj);
+                let point = (edges[i].point(ti) + edges[j].point(tj)) / 2.;
+                splits[i].push((ti, point));
+                splits[j].push((tj, point));
+            }
+        }
+    }
+
+    let mut split_edges = Vec::new();
+    let mut sources = Vec::new();
+    for (index, (edge, mut edge_splits)) in edges.drain(..).zip(splits).enumerate() {
+        edge_splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut rest = edge;
+        let mut rest_start = 0.;
+        for (t, point) in edge_splits {
+            if length(point - rest.start()) <= TOLERANCE || length(point - rest.end()) <= TOLERANCE
+            {
+                continue;
+            }
+            let (first, second) = rest.split((t - rest_start) / (1. - rest_start), point);
+            split_edges.push(first);
+            sources.push(index < num_first);
+            rest = second;
+            rest_start = t;
+        }
+        split_edges.push(rest);
+        sources.push(index < num_first);
+    }
+    *edges = split_edges;
+    sources
+}
+
+/// Find the parameters at which two pieces of edges intersect, subdividing
+/// curves until they are flat.
+fn intersect(
+    a: Edge,
+    a_range: (f64, f64),
+    b: Edge,
+    b_range: (f64, f64),
+    depth: u32,
+    hits: &mut Vec<(f64, f64)>,
+) {
+    if !overlaps(a.hull(), b.hull()) {
+        return;
+    }
+    let (a_flat, b_flat) = (a.is_flat(), b.is_flat());
+    if (a_flat && b_flat) || depth >= MAX_DEPTH {
+        let at = |range: (f64, f64), t: f64| range.0 + (range.1 - range.0) * t;
+        for (s, t) in line_intersections(a.start(), a.end(), b.start(), b.end()) {
+            hits.push((at(a_range, s), at(b_range, t)));
+        }
+        return;
+    }
+    let split = |edge: Edge, (start, end): (f64, f64)| {
+        let (first, second) = edge.split(0.5, edge.point(0.5));
+        let middle = f64::midpoint(start, end);
+        [(first, (start, middle)), (second, (middle, end))]
+    };
+    let a_size = length(a.end() - a.start()) + length(a.points[2] - a.points[1]);
+    let b_size = length(b.end() - b.start()) + length(b.points[2] - b.points[1]);
+    if b_flat || (!a_flat && a_size >= b_size) {
+        for (piece, range) in split(a, a_range) {
+            intersect(piece, range, b, b_range, depth + 1, hits);
+        }
+    } else {
+        for (piece, range) in split(b, b_range) {
+            intersect(a, a_range, piece, range, depth + 1, hits);
+        }
+    }
+}
+
+/// Improve the parameters of an intersection of two edges found on their
+/// flattened pieces with Newton's method.
+fn refine(a: Edge, b: Edge, mut s: f64, mut t: f64) -> (f64, f64) {
+    let gap = |s: f64, t: f64| length(a.point(s) - b.point(t));
+    for _ in 0..8 {
+        let difference = a.point(s) - b.point(t);
+        let (da, db) = (cubic_derivative(a.points, s), cubic_derivative(b.points, t));
+        let determinant = cross(db, da);
+        if determinant.abs() <= f64::EPSILON * dot(da, da).max(dot(db, db)) {
+            break;
+        }
+        // solve da * ds - db * dt = -difference
+        let next_s = (s + cross(difference, db) / determinant).clamp(0., 1.);
+        let next_t = (t + cross(difference, da) / determinant).clamp(0., 1.);
+        if gap(next_s, next_t) >= gap(s, t) {
+            break;
+        }
+        (s, t) = (next_s, next_t);
+    }
+    (s, t)
+}
+
+/// The parameters of the intersections of two straight segments, the ends of
+/// their overlap if they are collinear.
+fn line_intersections(p0: Coord, p1: Coord, q0: Coord, q1: Coord) -> Vec<(f64, f64)> {
+    let (d1, d2) = (p1 - p0, q1 - q0);
+    let (length1, length2) = (length(d1), length(d2));
+    if length1 <= TOLERANCE || length2 <= TOLERANCE {
+        return Vec::new();
+    }
+    let r = q0 - p0;
+    let denominator = cross(d1, d2);
+    if denominator.abs() > 1e-12 * length1 * length2 {
+        let s = cross(r, d2) / denominator;
+        let t = cross(r, d1) / denominator;
+        let (margin1, margin2) = (TOLERANCE / length1, TOLERANCE / length2);
+        if (-margin1..=1. + margin1).contains(&s) && (-margin2..=1. + margin2).contains(&t) {
+            return vec![(s.clamp(0., 1.), t.clamp(0., 1.))];
+        }
+        return Vec::new();
+    }
+    if point_line_distance(q0, p0, p1) > TOLERANCE {
+        return Vec::new();
+    }
+    // collinear, so the overlap is found by projecting onto the first segment
+    let project = |point: Coord, origin: Coord, direction: Coord| {
+        dot(point - origin, direction) / dot(direction, direction)
+    };
+    let (s0, s1) = (project(q0, p0, d1), project(q1, p0, d1));
+    let start = s0.min(s1).max(0.);
+    let end = s0.max(s1).min(1.);
+    if start > end + TOLERANCE / length1 {
+        return Vec::new();
+    }
+    [start, end]
+        .into_iter()
+        .map(|s| (s, project(p0 + d1 * s, q0, d2).clamp(0., 1.)))
+        .collect()
+}
+
+/// Snap edge ends that are within the tolerance of each other to one point.
+fn merge_vertices(edges: &mut [Edge]) {
+    let mut ends = edges
+        .iter()
+        .enumerate()
+        .flat_map(|(index, edge)| [(edge.start(), index, 0), (edge.end(), index, 3)])
+        .collect::<Vec<_>>();
+    ends.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+    let mut targets = ends.iter().map(|end| end.0).collect::<Vec<_>>();
+    for i in 0..ends.len() {
+        for j in i + 1..ends.len() {
+            if ends[j].0.x > ends[i].0.x + TOLERANCE {
+                break;
+            }
+            if length(ends[j].0 - ends[i].0) <= TOLERANCE {
+                targets[j] = targets[i];
+            }
+        }
+    }
+    for ((point, index, position), target) in ends.into_iter().zip(targets) {
+        let edge = &mut edges[index];
+        let shift = target - point;
+        edge.points[position] = target;
+        let handle = if position == 0 { 1 } else { 2 };
+        if edge.is_curve {
+            edge.points[handle] = edge.points[handle] + shift;
+        } else {
+            *edge = Edge {
+                dash_points: edge.dash_points,
+                ..Edge::line(edge.start(), edge.end())
+            };
+        }
+    }
+}
+
+/// Join oriented boundary edges into closed rings, turning as far left as
+/// possible where several edges leave a point and only turning back along an
+/// edge if there is no other way. A vertex is a dash point if it ends or
+/// starts one at a dash point.
+fn join_rings(edges: &[Edge]) -> Result<Vec<BezierPath>> {
+    let key = |point: Coord| (point.x.to_bits(), point.y.to_bits());
+    let mut outgoing = HashMap::<_, Vec<usize>>::new();
+    for (index, edge) in edges.iter().enumerate() {
+        outgoing.entry(key(edge.start())).or_default().push(index);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut ring = vec![edges[first]];
+        let mut last = edges[first];
+        while key(last.end()) != key(edges[first].start()) {
+            let incoming = last.end_direction();
+            let Some(&next) = outgoing
+                .get(&key(last.end()))
+                .into_iter()
+                .flatten()
+                .filter(|index| !used[**index])
+                .max_by(|a, b| {
+                    let turn = |index: usize| {
+                        let outgoing = edges[index].start_direction();
+                        let turn = cross(incoming, outgoing).atan2(dot(incoming, outgoing));
+                        if turn.abs() >= std::f64::consts::PI {
+                            -turn.abs()
+                        } else {
+                            turn
+                        }
+                    };
+                    turn(**a).total_cmp(&turn(**b))
+                })
+            else {
+                return Err(Error::BooleanOperationError);
+            };
+            used[next] = true;
+            ring.push(edges[next]);
+            last = edges[next];
+        }
+        let mut dash_points = vec![ring[0].dash_points[0] || last.dash_points[1]];
+        dash_points.extend(
+            ring.windows(2)
+                .map(|pair| pair[0].dash_points[1] || pair[1].dash_points[0]),
+        );
+        dash_points.push(dash_points[0]);
+        rings.push(BezierPath::new(
+            BezierString::new(ring.into_iter().map(Edge::to_segment).collect()),
+            dash_points,
+        )?);
+    }
+    Ok(rings)
+}
+
+/// Group counter-clockwise exteriors with the clockwise holes inside them,
+/// dropping rings without area.
+fn nest_rings(rings: Vec<BezierPath>) -> Vec<BezierPolygon> {
+    let mut exteriors = Vec::new();
+    let mut holes = Vec::new();
+    for ring in rings {
+        let area = ring.signed_area();
+        if area > TOLERANCE {
+            exteriors.push((area, ring, Vec::new()));
+        } else if area < -TOLERANCE {
+            holes.push(ring);
+        }
+    }
+    for hole in holes {
+        // the filled side of a hole, which is inside its exterior and outside an
+        // island with the same outline
+        let Some(point) = hole
+            .geometry()
+            .segments()
+            .next()
+            .map(|segment| Edge::from_segment(segment).sides().0)
+        else {
+            continue;
+        };
+        let parent = exteriors
+            .iter_mut()
+            .filter(|(_, exterior, _)| {
+                contains(
+                    &exterior
+                        .geometry()
+                        .segments()
+                        .map(Edge::from_segment)
+                        .collect::<Vec<_>>(),
+                    point,
+                )
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some((_, _, interiors)) = parent {
+            interiors.push(hole);
+        }
+    }
+    exteriors
+        .into_iter()
+        .filter_map(|(_, exterior, interiors)| BezierPolygon::new(exterior, interiors).ok())
+        .collect()
+}
+
+/// Whether two edges are the same curve, possibly reversed, which overlap
+/// without crossing.
+fn is_same_curve(a: Edge, b: Edge) -> bool {
+    a.is_curve && b.is_curve && (a.points == b.points || a.points == b.reversed().points)
+}
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.min().x <= b.max().x + TOLERANCE
+        && b.min().x <= a.max().x + TOLERANCE
+        && a.min().y <= b.max().y + TOLERANCE
+        && b.min().y <= a.max().y + TOLERANCE
+}
+
+fn point_line_distance(point: Coord, start: Coord, end: Coord) -> f64 {
+    let direction = end - start;
+    let chord = length(direction);
+    if chord <= TOLERANCE {
+        return length(point - start);
+    }
+    cross(direction, point - start).abs() / chord
+}
+
+fn cross(a: Coord, b: Coord) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn dot(a: Coord, b: Coord) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+fn length(vector: Coord) -> f64 {
+    vector.x.hypot(vector.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Weak;
+
+    use geo_types::{Coord, LineString, Polygon};
+
+    use crate::{
+        Result,
+        objects::{AreaObject, BezierPath, BezierPolygon, BezierSegment, BezierString},
+        symbols::WeakAreaPathSymbol,
+    };
+
+    fn square(min: (f64, f64), max: (f64, f64)) -> BezierPolygon {
+        Polygon::new(
+            LineString::from(vec![min, (max.0, min.1), max, (min.0, max.1)]),
+            Vec::new(),
+        )
+        .into()
+    }
+
+    /// A counter-clockwise circle around the origin of four cubic curves.
+    fn circle(radius: f64) -> Result<BezierPath> {
+        let handle = radius * 0.552_284_749_831;
+        let segments = [(1., 0.), (0., 1.), (-1., 0.), (0., -1.)]
+            .into_iter()
+            .map(|(x, y)| {
+                BezierSegment::new(
+                    Coord { x, y } * radius,
+                    Some((
+                        Coord {
+                            x: x * radius - y * handle,
+                            y: y * radius + x * handle,
+                        },
+                        Coord {
+                            x: -y * radius + x * handle,
+                            y: x * radius + y * handle,
+                        },
+                    )),
+                    Coord { x: -y, y: x } * radius,
+                )
+            })
+            .collect();
+        BezierPath::new(BezierString::new(segments), vec![false; 5])
+    }
+
+    fn total_area(polygons: &[BezierPolygon]) -> f64 {
+        polygons.iter().map(BezierPolygon::area).sum()
+    }
+
+    #[test]
+    fn combines_overlapping_squares() -> Result<()> {
+        let first = square((0., 0.), (2., 2.));
+        let second = square((1., 1.), (3., 3.));
+
+        let union = first.union(&second)?;
+        assert_eq!(union.len(), 1);
+        assert!(union[0].interiors().is_empty());
+        assert!((total_area(&union) - 7.).abs() < 1e-9);
+        assert!((total_area(&first.intersection(&second)?) - 1.).abs() < 1e-9);
+        assert!((total_area(&first.difference(&second)?) - 3.).abs() < 1e-9);
+        let xor = first.xor(&second)?;
+        assert_eq!(xor.len(), 2);
+        assert!((total_area(&xor) - 6.).abs() < 1e-9);
+
+        // a shared edge disappears in the union
+        let union = first.union(&square((2., 0.), (4., 2.)))?;
+        assert_eq!(union.len(), 1);
+        assert!((total_area(&union) - 8.).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_curves_of_the_operands() -> Result<()> {
+        let disc = BezierPolygon::new(circle(10.)?, Vec::new())?;
+
+        // cutting a lake out of a forest gives a hole of the original curves
+        let forest = square((-20., -20.), (20., 20.));
+        let cut = forest.difference(&disc)?;
+        let [cut] = &cut[..] else {
+            panic!("the forest stays in one piece");
+        };
+        let [hole] = cut.interiors() else {
+            panic!("the lake is a hole");
+        };
+        assert_eq!(
+            hole.geometry()
+                .segments()
+                .filter(|segment| segment.handles().is_some())
+                .count(),
+            4
+        );
+        assert!((cut.area() - (1600. - disc.area())).abs() < 1e-9);
+        assert!(forest.union(&disc)?[0].interiors().is_empty());
+
+        // a straight cut splits two of the curves
+        let east = square((5., -20.), (20., 20.));
+        let west = disc.difference(&east)?;
+        let [west] = &west[..] else {
+            panic!("the cut leaves one piece");
+        };
+        let segments = west.exterior().geometry().segments().collect::<Vec<_>>();
+        assert_eq!(
+            segments
+                .iter()
+                .filter(|segment| segment.handles().is_some())
+                .count(),
+            4
+        );
+        assert_eq!(segments.len(), 5);
+        let cap = disc.intersection(&east)?;
+        assert!((west.area() + total_area(&cap) - disc.area()).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn area_objects_keep_the_first_operands_properties() -> Result<()> {
+        let mut forest = AreaObject::new(
+            WeakAreaPathSymbol::Area(Weak::new()),
+            square((0., 0.), (10., 10.)),
+        );
+        forest.tags.insert("name".to_owned(), "forest".to_owned());
+        forest.pattern_rotation.rotation = 0.5;
+        let lake = AreaObject::new(
+            WeakAreaPathSymbol::Area(Weak::new()),
+            square((8., 8.), (12., 12.)),
+        );
+
+        let [cut] = &forest.difference(&lake)?[..] else {
+            panic!("the difference is one area");
+        };
+        assert_eq!(cut.tags.get("name").map(String::as_str), Some("forest"));
+        assert!((cut.pattern_rotation.rotation - 0.5).abs() < f64::EPSILON);
+        assert!((cut.geometry().area() - 96.).abs() < 1e-9);
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use geo_types::Coord;
+
+use super::{
+    AreaObject, BezierPath, BezierPolygon, LineObject, MapObject, boolean,
+    measure::{cubic_point, cubic_points},
+};
+use crate::{NonNegativeF64, Result};
+
+/// Path parameters closer than this are taken as one.
+const TOLERANCE: f64 = 1e-9;
+
+/// What cropping does with the lines and areas that cross the boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CropMode {
+    /// Cut lines and areas at the boundary and keep the parts inside.
+    #[default]
+    Clip,
+    /// Keep lines and areas that are at least partly inside as they are.
+    KeepIntersecting,
+}
+
+impl BezierPath {
+    /// The parts of the path inside a polygon, filled by the even-odd rule.
+    ///
+    /// The path is split where it crosses the outline of the polygon, see
+    /// [`Self::split_at`], so curves and dash points are kept. A closed path
+    /// that leaves the polygon gives open parts, with the parts before and
+    /// after its start joined.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path or polygon is invalid.
+    pub fn clip(&self, polygon: &BezierPolygon) -> Result<Vec<Self>> {
+        self.validate()?;
+        polygon.validate()?;
+        let num_segments = self.num_segments();
+        if num_segments == 0 {
+            return Ok(Vec::new());
+        }
+
+        let rings = std::iter::once(polygon.exterior())
+            .chain(polygon.interiors())
+            .collect::<Vec<_>>();
+        let mut parameters = vec![0., num_segments as f64];
+        for (index, segment) in self.geometry.segments().enumerate() {
+            for ring in &rings {
+                for other in ring.geometry().segments() {
+                    parameters.extend(
+                        boolean::segment_intersections(segment, other)
+                            .into_iter()
+                            .map(|(s, _)| index as f64 + s.clamp(0., 1.)),
+                    );
+                }
+            }
+        }
+        parameters.sort_by(f64::total_cmp);
+        parameters.dedup_by(|a, b| *a - *b <= TOLERANCE);
+        if let Some(last) = parameters.last_mut() {
+            *last = num_segments as f64;
+        }
+
+        // the runs of pieces between crossings that lie inside the polygon
+        let mut runs = Vec::<(f64, f64)>::new();
+        let mut previous_inside = false;
+        for range in parameters.windows(2) {
+            let inside = boolean::polygon_contains(
+                polygon,
+                self.point_at(f64::midpoint(range[0], range[1])),
+            );
+            match runs.last_mut() {
+                Some(run) if inside && previous_inside => run.1 = range[1],
+                _ if inside => runs.push((range[0], range[1])),
+                _ => {}
+            }
+            previous_inside = inside;
+        }
+
+        if let [(start, end)] = runs.as_slice()
+            && *start == 0.
+            && *end == num_segments as f64
+        {
+            return Ok(vec![self.clone()]);
+        }
+        let mut parts = runs
+            .iter()
+            .map(|&(start, end)| self.sub_path(start, end))
+            .collect::<Vec<_>>();
+        if self.is_closed()
+            && parts.len() > 1
+            && runs.first().is_some_and(|run| run.0 == 0.)
+            && runs.last().is_some_and(|run| run.1 == num_segments as f64)
+        {
+            let first = parts.remove(0);
+            if let Some(last) = parts.pop() {
+                parts.push(last.join(&first, NonNegativeF64::zero()).unwrap_or(last));
+            }
+        }
+        Ok(parts)
+    }
+
+    /// The point at a path parameter, see [`Self::closest_parameter`].
+    pub(super) fn point_at(&self, parameter: f64) -> Coord {
+        let segments = &self.geometry.0;
+        let index = (parameter.floor() as usize).min(segments.len() - 1);
+        cubic_point(cubic_points(&segments[index]), parameter - index as f64)
+    }
+
+    /// The part of the path between two path parameters.
+    fn sub_path(&self, start: f64, end: f64) -> Self {
+        let num_segments = self.num_segments();
+        let after_start = match self.split_at(start) {
+            Some((_, after)) => after,
+            None => self.clone(),
+        };
+        if end >= num_segments as f64 {
+            return after_start;
+        }
+        // the parameter of `end` on the part after `start`
+        let start_index = (start.floor() as usize).min(num_segments - 1);
+        let start_t = start - start_index as f64;
+        let end_index = (end.floor() as usize).min(num_segments - 1);
+        let end_t = end - end_index as f64;
+        let end = if end_index == start_index {
+            (end_t - start_t) / (1. - start_t)
+        } else {
+            (end_index - start_index) as f64 + end_t
+        };
+        match after_start.split_at(end) {
+            Some((before, _)) => before,
+            None => after_start,
+        }
+    }
+}
+
+impl LineObject {
+    /// Crop the line to a polygon, see [`CropMode`] and [`BezierPath::clip`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the geometry or boundary is invalid.
+    pub fn crop_to(&self, boundary: &BezierPolygon, mode: CropMode) -> Result<Vec<Self>> {
+        let parts = self.geometry().clip(boundary)?;
+        Ok(match mode {
+            CropMode::KeepIntersecting if !parts.is_empty() => vec![self.clone()],
+            CropMode::KeepIntersecting => Vec::new(),
+            CropMode::Clip => parts
+                .into_iter()
+                .map(|part| self.with_geometry(part))
+                .collect(),
+        })
+    }
+}
+
+impl AreaObject {
+    /// Crop the area to a polygon, see [`CropMode`] and
+    /// [`BezierPolygon::intersection`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the geometry or boundary is invalid or the boundary
+    /// of the result cannot be joined into rings.
+    pub fn crop_to(&self, boundary: &BezierPolygon, mode: CropMode) -> Result<Vec<Self>> {
+        let parts = self.with_geometries(self.geometry().intersection(boundary)?);
+        Ok(match mode {
+            CropMode::KeepIntersecting if !parts.is_empty() => vec![self.clone()],
+            CropMode::KeepIntersecting => Vec::new(),
+            CropMode::Clip => parts,
+        })
+    }
+}
+
+impl MapObject {
+    /// Crop the object to a polygon, filled by the even-odd rule. Lines and
+    /// areas are handled by `mode`, while points and texts are kept if their
+    /// anchor is inside the polygon.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the geometry or boundary is invalid or the boundary
+    /// of a cropped area cannot be joined into rings.
+    pub fn crop_to(&self, boundary: &BezierPolygon, mode: CropMode) -> Result<Vec<Self>> {
+        Ok(match self {
+            Self::Line(object) => object
+                .crop_to(boundary, mode)?
+                .into_iter()
+                .map(Self::Line)
+                .collect(),
+            Self::Area(object) => object
+                .crop_to(boundary, mode)?
+                .into_iter()
+                .map(Self::Area)
+                .collect(),
+            Self::Point(object) => {
+                if boolean::polygon_contains(boundary, object.geometry().0) {
+                    vec![self.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+            Self::Text(object) => {
+                if boolean::polygon_contains(boundary, *object.geometry().anchor_coord()) {
+                    vec![self.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{Coord, LineString, Polygon};
+
+    use crate::{
+        Result,
+        objects::{BezierPath, BezierPolygon, BezierSegment, BezierString},
+    };
+
+    fn square(min: f64, max: f64) -> BezierPolygon {
+        BezierPolygon::from(Polygon::new(
+            LineString::from(vec![
+                (min, min),
+                (max, min),
+                (max, max),
+                (min, max),
+                (min, min),
+            ]),
+            Vec::new(),
+        ))
+    }
+
+    #[test]
+    fn clips_curves_at_the_boundary() -> Result<()> {
+        let point = |x, y| Coord { x, y };
+        // a straight segment into an arch leaving and re-entering the square
+        let path = BezierPath::new(
+            BezierString::new(vec![
+                BezierSegment::new(point(2., 2.), None, point(4., 2.)),
+                BezierSegment::new(
+                    point(4., 2.),
+                    Some((point(4., 20.), point(8., 20.))),
+                    point(8., 2.),
+                ),
+            ]),
+            vec![false, true, false],
+        )?;
+
+        let parts = path.clip(&square(0., 10.))?;
+
+        let [first, second] = parts.as_slice() else {
+            panic!("expected two parts, got {parts:?}");
+        };
+        assert_eq!(first.num_segments(), 2);
+        assert_eq!(first.vertex_is_dash_point(), [false, true, false]);
+        let Some(exit) = first.geometry().segments().last() else {
+            panic!("expected a segment");
+        };
+        assert!(exit.handles().is_some());
+        assert!((exit.end().y - 10.).abs() < 1e-6);
+        assert_eq!(second.num_segments(), 1);
+        assert!((second.geometry().0[0].start().y - 10.).abs() < 1e-6);
+        assert_eq!(second.geometry().0[0].end(), point(8., 2.));
+        Ok(())
+    }
+
+    #[test]
+    fn joins_the_parts_of_closed_paths_at_their_start() -> Result<()> {
+        let ring = BezierPath::from(LineString::from(vec![
+            (0., 0.),
+            (20., 0.),
+            (20., 20.),
+            (0., 20.),
+            (0., 0.),
+        ]));
+
+        let parts = ring.clip(&square(-5., 10.))?;
+
+        let [part] = parts.as_slice() else {
+            panic!("expected one part, got {parts:?}");
+        };
+        assert!(!part.is_closed());
+        assert_eq!(part.num_segments(), 2);
+        let Some(start) = part.geometry().segments().next() else {
+            panic!("expected a segment");
+        };
+        assert!((start.start().x).abs() < 1e-6 && (start.start().y - 10.).abs() < 1e-6);
+        assert_eq!(start.end(), Coord { x: 0., y: 0. });
+
+        assert!(ring.clip(&square(30., 40.))?.is_empty());
+        let [whole] = ring
+            .clip(&square(-5., 25.))?
+            .try_into()
+            .unwrap_or_else(|parts| {
+                panic!("expected the ring itself, got {parts:?}");
+            });
+        assert!(whole.is_closed());
+        assert_eq!(whole.num_segments(), 4);
+        Ok(())
+    }
+}
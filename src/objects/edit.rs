@@ -0,0 +1,458 @@
+use geo_types::Coord;
+
+use super::{
+    AreaObject, BezierPath, BezierPolygon, BezierSegment, BezierString, LineObject, boolean,
+    measure::{cubic_point, cubic_points, split_cubic},
+};
+use crate::{NonNegativeF64, Result};
+
+/// The number of points sampled on a curve before searching for the point
+/// nearest to a coordinate.
+const CURVE_SAMPLES: u32 = 32;
+
+impl BezierPath {
+    /// The path parameter of the point on the path nearest to `coord`, or
+    /// `None` for an empty path.
+    ///
+    /// A path parameter counts segments: its integer part is the index of a
+    /// segment and its fraction the curve parameter on that segment, so `0.` is
+    /// the start of the path and [`Self::num_segments`] its end.
+    pub fn closest_parameter(&self, coord: Coord) -> Option<f64> {
+        self.geometry
+            .segments()
+            .enumerate()
+            .map(|(index, segment)| {
+                let t = closest_segment_parameter(segment, coord);
+                let point = cubic_point(cubic_points(segment), t);
+                (
+                    index as f64 + t,
+                    (point.x - coord.x).hypot(point.y - coord.y),
+                )
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(parameter, _)| parameter)
+    }
+
+    /// Split the path at a path parameter into the part before and the part
+    /// after it, or `None` if the parameter is not strictly inside the path.
+    /// See [`Self::closest_parameter`] for path parameters.
+    ///
+    /// A curve is split into two curves that follow it exactly. A new vertex is
+    /// not a dash point, while a split at a vertex keeps its dash flag on both
+    /// parts. A closed path is split into two open parts at the parameter and its
+    /// start.
+    pub fn split_at(&self, parameter: f64) -> Option<(Self, Self)> {
+        let num_segments = self.num_segments();
+        if !(parameter > 0. && parameter < num_segments as f64) {
+            return None;
+        }
+        let index = (parameter.floor() as usize).min(num_segments - 1);
+        let t = parameter - index as f64;
+        let segments = &self.geometry.0;
+        let dash_points = &self.vertex_is_dash_point;
+
+        let (first, second) = if t > 0. {
+            let points = cubic_points(&segments[index]);
+            let (before, after) = split_cubic(points, t);
+            let is_curve = segments[index].handles().is_some();
+            let to_segment = |[start, handle1, handle2, end]: [Coord; 4]| {
+                BezierSegment::new(start, is_curve.then_some((handle1, handle2)), end)
+            };
+            let mut first = Self {
+                geometry: BezierString::new(segments[..index].to_vec()),
+                vertex_is_dash_point: dash_points[..=index].to_vec(),
+            };
+            first.geometry.0.push(to_segment(before));
+            first.vertex_is_dash_point.push(false);
+            let mut second = Self {
+                geometry: BezierString::new(vec![to_segment(after)]),
+                vertex_is_dash_point: vec![false],
+            };
+            second.geometry.0.extend_from_slice(&segments[index + 1..]);
+            second
+                .vertex_is_dash_point
+                .extend_from_slice(&dash_points[index + 1..]);
+            (first, second)
+        } else {
+            (
+                Self {
+                    geometry: BezierString::new(segments[..index].to_vec()),
+                    vertex_is_dash_point: dash_points[..=index].to_vec(),
+                },
+                Self {
+                    geometry: BezierString::new(segments[index..].to_vec()),
+                    vertex_is_dash_point: dash_points[index..].to_vec(),
+                },
+            )
+        };
+        Some((first, second))
+    }
+
+    /// Join two open paths whose ends are at most `max_gap` mm apart into one
+    /// path, or `None` if no ends meet or a path is closed.
+    ///
+    /// The path is the continuation of `self`, and `other` is reversed if it
+    /// meets `self` with its end. A gap is closed by moving the meeting end of
+    /// `other`. The joining vertex is a dash point if either end is one.
+    pub fn join(&self, other: &Self, max_gap: NonNegativeF64) -> Option<Self> {
+        let (Some(start), Some(end), Some(other_start), Some(other_end)) = (
+            self.geometry.segments().next().map(BezierSegment::start),
+            self.geometry.segments().last().map(BezierSegment::end),
+            other.geometry.segments().next().map(BezierSegment::start),
+            other.geometry.segments().last().map(BezierSegment::end),
+        ) else {
+            return None;
+        };
+        if self.is_closed() || other.is_closed() {
+            return None;
+        }
+        let meets = |a: Coord, b: Coord| (a.x - b.x).hypot(a.y - b.y) <= max_gap.get();
+        let reversed = |path: &Self| {
+            let mut path = path.clone();
+            path.reverse();
+            path
+        };
+        // the joining end of `other` moves onto `self`
+        let (mut first, second) = if meets(end, other_start) || meets(end, other_end) {
+            let mut second = if meets(end, other_start) {
+                other.clone()
+            } else {
+                reversed(other)
+            };
+            let segment = second.geometry.0.first_mut()?;
+            *segment = move_start(segment, end);
+            (self.clone(), second)
+        } else if meets(start, other_end) || meets(start, other_start) {
+            let mut first = if meets(start, other_end) {
+                other.clone()
+            } else {
+                reversed(other)
+            };
+            let segment = first.geometry.0.last_mut()?;
+            *segment = move_end(segment, start);
+            (first, self.clone())
+        } else {
+            return None;
+        };
+
+        let joint = first.vertex_is_dash_point.pop().unwrap_or(false)
+            || second
+                .vertex_is_dash_point
+                .first()
+                .copied()
+                .unwrap_or(false);
+        first.vertex_is_dash_point.push(joint);
+        first
+            .vertex_is_dash_point
+            .extend_from_slice(&second.vertex_is_dash_point[1..]);
+        first.geometry.0.extend(second.geometry.0);
+        Some(first)
+    }
+}
+
+impl BezierPolygon {
+    /// Cut the polygon along a path into the pieces between the parts of the
+    /// path that cross it from outline to outline.
+    ///
+    /// Parts of the path that end inside the polygon are ignored, so a path that
+    /// does not cross the polygon gives the polygon itself, and a closed path
+    /// inside it cuts out an island. Curves and dash points of the outline are
+    /// kept, see [`Self::union`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the polygon or path is invalid or the pieces cannot
+    /// be joined into rings.
+    pub fn cut(&self, path: &BezierPath) -> Result<Vec<Self>> {
+        boolean::cut(self, path)
+    }
+}
+
+impl LineObject {
+    /// Split the line at a path parameter into two lines with the symbol and tags
+    /// of this line. See [`BezierPath::split_at`] for more docs
+    pub fn split_at(&self, parameter: f64) -> Option<(Self, Self)> {
+        let (first, second) = self.geometry().split_at(parameter)?;
+        Some((self.with_geometry(first), self.with_geometry(second)))
+    }
+
+    /// Split the line at the point nearest to `coord` into two lines with the
+    /// symbol and tags of this line, or `None` if that point is an end of the
+    /// line.
+    pub fn split_at_coord(&self, coord: Coord) -> Option<(Self, Self)> {
+        self.split_at(self.geometry().closest_parameter(coord)?)
+    }
+
+    /// Join a line of the same symbol that touches an end of this line within
+    /// `max_gap` mm into one line, or `None` if the lines do not meet or have
+    /// different symbols.
+    ///
+    /// The tags of both lines are kept, with the values of this line for keys
+    /// both have. See [`BezierPath::join`] for more docs
+    pub fn join(&self, other: &Self, max_gap: NonNegativeF64) -> Option<Self> {
+        if self.symbol != other.symbol {
+            return None;
+        }
+        let mut joined = self.with_geometry(self.geometry().join(other.geometry(), max_gap)?);
+        for (key, value) in &other.tags {
+            joined
+                .tags
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        Some(joined)
+    }
+
+    pub(super) fn with_geometry(&self, geometry: BezierPath) -> Self {
+        let mut object = Self::new(self.symbol.clone(), geometry);
+        object.tags.clone_from(&self.tags);
+        object
+    }
+}
+
+impl AreaObject {
+    /// Cut the area along a path into areas with the symbol, tags and pattern
+    /// rotation of this area. See [`BezierPolygon::cut`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the geometry or path is invalid or the pieces cannot
+    /// be joined into rings.
+    pub fn cut(&self, path: &BezierPath) -> Result<Vec<Self>> {
+        Ok(self.with_geometries(self.geometry().cut(path)?))
+    }
+}
+
+/// The curve parameter of the point of a segment nearest to `coord`.
+pub(super) fn closest_segment_parameter(segment: &BezierSegment, coord: Coord) -> f64 {
+    let points = cubic_points(segment);
+    let distance = |t: f64| {
+        let point = cubic_point(points, t);
+        (point.x - coord.x).powi(2) + (point.y - coord.y).powi(2)
+    };
+    if segment.handles().is_none() {
+        let (start, end) = (points[0], points[3]);
+        let direction = end - start;
+        let length_squared = direction.x * direction.x + direction.y * direction.y;
+        if length_squared == 0. {
+            return 0.;
+        }
+        return (((coord.x - start.x) * direction.x + (coord.y - start.y) * direction.y)
+            / length_squared)
+            .clamp(0., 1.);
+    }
+    // the nearest sample brackets the nearest point, which a ternary search finds
+    let step = 1. / f64::from(CURVE_SAMPLES);
+    let nearest = (0..=CURVE_SAMPLES)
+        .map(|sample| f64::from(sample) * step)
+        .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+        .unwrap_or(0.);
+    let (mut low, mut high) = ((nearest - step).max(0.), (nearest + step).min(1.));
+    for _ in 0..60 {
+        let (a, b) = (low + (high - low) / 3., high - (high - low) / 3.);
+        if distance(a) < distance(b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    f64::midpoint(low, high)
+}
+
+pub(super) fn move_start(segment: &BezierSegment, start: Coord) -> BezierSegment {
+    let shift = start - segment.start();
+    BezierSegment::new(
+        start,
+        segment
+            .handles()
+            .map(|(handle1, handle2)| (handle1 + shift, handle2)),
+        segment.end(),
+    )
+}
+
+pub(super) fn move_end(segment: &BezierSegment, end: Coord) -> BezierSegment {
+    let shift = end - segment.end();
+    BezierSegment::new(
+        segment.start(),
+        segment
+            .handles()
+            .map(|(handle1, handle2)| (handle1, handle2 + shift)),
+        end,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use geo_types::{Coord, LineString, Polygon};
+
+    use crate::{
+        Code, NonNegativeF64, Result,
+        objects::{AreaObject, BezierPath, BezierPolygon, BezierSegment, BezierString, LineObject},
+        symbols::{AreaSymbol, LineSymbol, WeakAreaPathSymbol, WeakLinePathSymbol},
+    };
+
+    /// A straight segment followed by a quarter-circle-like curve, with a dash
+    /// point between them.
+    fn hook() -> Result<BezierPath> {
+        BezierPath::new(
+            BezierString::new(vec![
+                BezierSegment::new(Coord { x: 0., y: 0. }, None, Coord { x: 10., y: 0. }),
+                BezierSegment::new(
+                    Coord { x: 10., y: 0. },
+                    Some((Coord { x: 15., y: 0. }, Coord { x: 20., y: 5. })),
+                    Coord { x: 20., y: 10. },
+                ),
+            ]),
+            vec![false, true, false],
+        )
+    }
+
+    #[test]
+    fn splits_lines_at_coordinates() -> Result<()> {
+        let symbol = Rc::new(RefCell::new(LineSymbol::new(Code::new(505, 0, 0), "Path")));
+        let mut line = LineObject::new(WeakLinePathSymbol::Line(Rc::downgrade(&symbol)), hook()?);
+        line.tags.insert("name".to_owned(), "trail".to_owned());
+
+        let Some((first, second)) = line.split_at_coord(Coord { x: 4., y: 3. }) else {
+            panic!("the point is inside the line");
+        };
+        assert_eq!(first.geometry().num_segments(), 1);
+        assert_eq!(
+            second.geometry().vertex_is_dash_point(),
+            [false, true, false]
+        );
+        assert_eq!(second.tags.get("name").map(String::as_str), Some("trail"));
+        let start = second
+            .geometry()
+            .geometry()
+            .segments()
+            .next()
+            .map(BezierSegment::start);
+        assert!(start.is_some_and(|start| (start.x - 4.).abs() < 1e-9 && start.y.abs() < 1e-9));
+
+        // a split on the curve follows it exactly
+        let Some((first, second)) = line.split_at(1.5) else {
+            panic!("the parameter is inside the line");
+        };
+        assert_eq!(
+            first.geometry().vertex_is_dash_point(),
+            [false, true, false]
+        );
+        let tolerance = NonNegativeF64::clamped_from(1e-6);
+        let whole = line.geometry().length(tolerance).length;
+        let parts =
+            first.geometry().length(tolerance).length + second.geometry().length(tolerance).length;
+        assert!((whole - parts).abs() < 1e-5);
+
+        // a split at a vertex keeps its dash point on both parts
+        let Some((first, second)) = line.split_at(1.) else {
+            panic!("the vertex is inside the line");
+        };
+        assert_eq!(first.geometry().vertex_is_dash_point(), [false, true]);
+        assert_eq!(second.geometry().vertex_is_dash_point(), [true, false]);
+        assert!(line.split_at(2.).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn joins_touching_lines_of_one_symbol() -> Result<()> {
+        let path = Rc::new(RefCell::new(LineSymbol::new(Code::new(505, 0, 0), "Path")));
+        let fence = Rc::new(RefCell::new(LineSymbol::new(Code::new(516, 0, 0), "Fence")));
+        let mut first = LineObject::new(WeakLinePathSymbol::Line(Rc::downgrade(&path)), hook()?);
+        first.tags.insert("name".to_owned(), "trail".to_owned());
+        // meets the first line with its end, 0.01 mm off
+        let mut second = LineObject::new(
+            WeakLinePathSymbol::Line(Rc::downgrade(&path)),
+            LineString::from(vec![(20., 30.), (20.01, 10.)]),
+        );
+        second.geometry_mut().set_vertex_is_dash_point(1, true);
+        second.tags.insert("name".to_owned(), "road".to_owned());
+        second
+            .tags
+            .insert("surface".to_owned(), "gravel".to_owned());
+
+        let max_gap = NonNegativeF64::clamped_from(0.1);
+        let Some(joined) = first.join(&second, max_gap) else {
+            panic!("the lines touch");
+        };
+        assert_eq!(joined.geometry().num_segments(), 3);
+        assert_eq!(
+            joined.geometry().vertex_is_dash_point(),
+            [false, true, true, false]
+        );
+        assert_eq!(
+            joined
+                .geometry()
+                .geometry()
+                .segments()
+                .last()
+                .map(BezierSegment::end),
+            Some(Coord { x: 20., y: 30. })
+        );
+        assert_eq!(joined.tags.get("name").map(String::as_str), Some("trail"));
+        assert_eq!(
+            joined.tags.get("surface").map(String::as_str),
+            Some("gravel")
+        );
+        joined.geometry().validate()?;
+
+        assert!(
+            first
+                .join(&second, NonNegativeF64::clamped_from(0.001))
+                .is_none()
+        );
+        second.symbol = WeakLinePathSymbol::Line(Rc::downgrade(&fence));
+        assert!(first.join(&second, max_gap).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn cuts_areas_along_paths() -> Result<()> {
+        let symbol = Rc::new(RefCell::new(AreaSymbol::new(
+            Code::new(406, 0, 0),
+            "Forest",
+        )));
+        let mut square = BezierPolygon::from(Polygon::new(
+            LineString::from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.)]),
+            Vec::new(),
+        ));
+        square.exterior_mut().set_vertex_is_dash_point(2, true);
+        let mut forest = AreaObject::new(WeakAreaPathSymbol::Area(Rc::downgrade(&symbol)), square);
+        forest.pattern_rotation.rotation = 1.;
+
+        let across = BezierPath::from(LineString::from(vec![(5., -1.), (5., 11.)]));
+        let pieces = forest.cut(&across)?;
+        assert_eq!(pieces.len(), 2);
+        for piece in &pieces {
+            assert!((piece.geometry().area() - 50.).abs() < 1e-9);
+            assert!((piece.pattern_rotation.rotation - 1.).abs() < f64::EPSILON);
+        }
+        assert_eq!(
+            pieces
+                .iter()
+                .flat_map(|piece| piece.geometry().exterior().vertex_is_dash_point())
+                .filter(|is_dash_point| **is_dash_point)
+                .count(),
+            1
+        );
+
+        // a path ending inside does not cut, a closed path cuts out an island
+        let into = BezierPath::from(LineString::from(vec![(5., -1.), (5., 5.)]));
+        assert_eq!(forest.cut(&into)?.len(), 1);
+        let mut island = BezierPath::from(LineString::from(vec![
+            (2., 2.),
+            (4., 2.),
+            (4., 4.),
+            (2., 4.),
+        ]));
+        island.close();
+        let pieces = forest.geometry().cut(&island)?;
+        let mut areas = pieces.iter().map(BezierPolygon::area).collect::<Vec<_>>();
+        areas.sort_by(f64::total_cmp);
+        assert_eq!(areas.len(), 2);
+        assert!((areas[0] - 4.).abs() < 1e-9 && (areas[1] - 96.).abs() < 1e-9);
+        Ok(())
+    }
+}
@@ -178,6 +178,7 @@ impl LineObject {
     ) -> Result<Self> {
         let mut tags = HashMap::new();
         let mut file_coords = Vec::new();
+        let mut in_coords = false;
         let mut buf = Vec::new();
 
         loop {
@@ -189,12 +190,20 @@ impl LineObject {
                             .flatten()
                             .unwrap_or(0);
                         file_coords.reserve(count);
+                        in_coords = true;
                     }
+                    b"coord" if in_coords => file_coords.push(super::parse_file_coord(&start)?),
                     b"tags" => tags = super::parse_tags(reader)?,
                     _ => (),
                 },
-                Event::End(end) if end.local_name().as_ref() == b"object" => break,
-                Event::Text(text) => super::parse_file_coords(text.as_ref(), &mut file_coords)?,
+                Event::End(end) => match end.local_name().as_ref() {
+                    b"object" => break,
+                    b"coords" => in_coords = false,
+                    _ => (),
+                },
+                Event::Text(text) if in_coords => {
+                    super::parse_file_coords(text.as_ref(), &mut file_coords)?;
+                }
                 Event::Eof => return Err(Error::UnexpectedEof(OmapSection::LineObject)),
                 _ => (),
             }
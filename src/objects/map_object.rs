@@ -1,5 +1,6 @@
 use std::{collections::HashMap, rc::Weak};
 
+use geo_types::Rect;
 use quick_xml::{Reader, Writer, events::BytesStart};
 
 use super::{AreaObject, LineObject, PointObject, TextObject};
@@ -72,6 +73,23 @@ impl MapObject {
         }
     }
 
+    /// The exact bounding box of the object's geometry, `None` for an empty line
+    /// or area.
+    ///
+    /// Points and texts give the box of their anchor; the extent of the symbol or
+    /// the text is not included.
+    pub fn bounding_rect(&self) -> Option<Rect> {
+        match self {
+            Self::Point(object) => Some(Rect::new(object.geometry().0, object.geometry().0)),
+            Self::Text(object) => {
+                let anchor = *object.geometry().anchor_coord();
+                Some(Rect::new(anchor, anchor))
+            }
+            Self::Line(object) => object.geometry().bounding_rect(),
+            Self::Area(object) => object.geometry().bounding_rect(),
+        }
+    }
+
     pub(crate) fn write<W: std::io::Write>(
         &self,
         writer: &mut Writer<W>,
@@ -0,0 +1,348 @@
+use geo_types::Coord;
+
+use super::{BezierPath, BezierPolygon, BezierSegment};
+use crate::{NonNegativeF64, geo_referencing::GeoRef};
+
+/// The deepest subdivision of a curve when measuring its length, which limits
+/// a curve to 65536 pieces.
+const MAX_DEPTH: u32 = 16;
+
+/// The nodes and weights of 5-point Gauss-Legendre quadrature on `[0, 1]`,
+/// which integrates the polynomials of degree up to 9 of Green's theorem over
+/// a cubic segment exactly.
+const GAUSS_LEGENDRE: [(f64, f64); 5] = [
+    (0.046_910_077_030_668_004, 0.118_463_442_528_094_54),
+    (0.230_765_344_947_158_45, 0.239_314_335_249_683_23),
+    (0.5, 0.284_444_444_444_444_44),
+    (0.769_234_655_052_841_6, 0.239_314_335_249_683_23),
+    (0.953_089_922_969_332, 0.118_463_442_528_094_54),
+];
+
+/// A measured arc length with a bound on its error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArcLength {
+    /// The measured length.
+    pub length: f64,
+    /// The largest difference between `length` and the exact length.
+    pub error_bound: f64,
+}
+
+impl ArcLength {
+    fn scaled(self, factor: f64) -> Self {
+        Self {
+            length: self.length * factor,
+            error_bound: self.error_bound * factor,
+        }
+    }
+}
+
+impl BezierPath {
+    /// The length of the path in mm of paper, within `allowed_error` of the
+    /// exact length.
+    ///
+    /// Straight segments are measured exactly. Curves are subdivided until the
+    /// length of their control polygon and their chord, which bound the length
+    /// of a cubic curve from above and below, are close enough. The returned
+    /// [`ArcLength::error_bound`] is the sum of the remaining gaps. It only
+    /// exceeds `allowed_error` for tolerances below about a billionth of the
+    /// size of a curve, where the subdivision stops at 65536 pieces.
+    pub fn length(&self, allowed_error: NonNegativeF64) -> ArcLength {
+        let num_curves = self
+            .geometry
+            .segments()
+            .filter(|segment| segment.handles().is_some())
+            .count();
+        let curve_error = allowed_error.get() / num_curves.max(1) as f64;
+        self.geometry
+            .segments()
+            .map(|segment| match segment.handles() {
+                Some((handle1, handle2)) => cubic_length(
+                    [segment.start(), handle1, handle2, segment.end()],
+                    curve_error,
+                    0,
+                ),
+                None => ArcLength {
+                    length: distance(segment.start(), segment.end()),
+                    error_bound: 0.,
+                },
+            })
+            .fold(
+                ArcLength {
+                    length: 0.,
+                    error_bound: 0.,
+                },
+                |total, length| ArcLength {
+                    length: total.length + length.length,
+                    error_bound: total.error_bound + length.error_bound,
+                },
+            )
+    }
+
+    /// The length of the path on the ground in the units of the projection,
+    /// usually metres, with the scale and scale factors of `geo_ref`.
+    /// See [`Self::length`] for more docs
+    pub fn ground_length(&self, geo_ref: &GeoRef, allowed_error: NonNegativeF64) -> ArcLength {
+        self.length(allowed_error)
+            .scaled(geo_ref.map_to_ground_scale())
+    }
+
+    /// The exact signed area enclosed by the path in mm² of paper, positive for
+    /// counter-clockwise paths. An open path is closed by a straight segment
+    /// from its end to its start.
+    pub fn signed_area(&self) -> f64 {
+        ring_moments(self).area
+    }
+}
+
+impl BezierPolygon {
+    /// The exact signed area of the polygon in mm² of paper, the area of the
+    /// exterior minus the areas of the interiors, whatever their orientation.
+    ///
+    /// The sign is that of the exterior, positive when it is counter-clockwise.
+    pub fn signed_area(&self) -> f64 {
+        self.moments().area
+    }
+
+    /// The exact area of the polygon in mm² of paper, the area of the exterior
+    /// minus the areas of the interiors.
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    /// The area of the polygon on the ground in the squared units of the
+    /// projection, usually m², with the scale and scale factors of `geo_ref`.
+    pub fn ground_area(&self, geo_ref: &GeoRef) -> f64 {
+        self.area() * geo_ref.map_to_ground_scale().powi(2)
+    }
+
+    /// The exact centroid of the polygon with its holes, or `None` if it has
+    /// no area.
+    pub fn centroid(&self) -> Option<Coord> {
+        let moments = self.moments();
+        (moments.area != 0.).then(|| Coord {
+            x: moments.x / moments.area,
+            y: moments.y / moments.area,
+        })
+    }
+
+    /// The area and first moments of the polygon, with the interiors
+    /// subtracted and the sign of the exterior.
+    fn moments(&self) -> Moments {
+        let exterior = ring_moments(self.exterior());
+        let sign = exterior.area.signum();
+        self.interiors()
+            .iter()
+            .map(ring_moments)
+            .fold(exterior, |total, interior| {
+                // an interior counts against the exterior whatever its orientation
+                let factor = -sign * interior.area.signum();
+                Moments {
+                    area: total.area + factor * interior.area,
+                    x: total.x + factor * interior.x,
+                    y: total.y + factor * interior.y,
+                }
+            })
+    }
+}
+
+/// The signed area enclosed by a ring and its first moments about the axes.
+#[derive(Debug, Clone, Copy)]
+struct Moments {
+    area: f64,
+    /// The integral of x over the area.
+    x: f64,
+    /// The integral of y over the area.
+    y: f64,
+}
+
+/// The moments of the area enclosed by a path and the straight segment from
+/// its end back to its start, by Green's theorem.
+fn ring_moments(path: &BezierPath) -> Moments {
+    let closing = match (
+        path.geometry.segments().next(),
+        path.geometry.segments().last(),
+    ) {
+        (Some(first), Some(last)) if first.start() != last.end() => {
+            Some(BezierSegment::new(last.end(), None, first.start()))
+        }
+        _ => None,
+    };
+    let mut moments = Moments {
+        area: 0.,
+        x: 0.,
+        y: 0.,
+    };
+    for segment in path.geometry.segments().chain(&closing) {
+        let points = cubic_points(segment);
+        for (t, weight) in GAUSS_LEGENDRE {
+            let point = cubic_point(points, t);
+            let tangent = cubic_derivative(points, t);
+            moments.area += weight * (point.x * tangent.y - point.y * tangent.x) / 2.;
+            moments.x += weight * point.x * point.x * tangent.y / 2.;
+            moments.y -= weight * point.y * point.y * tangent.x / 2.;
+        }
+    }
+    moments
+}
+
+/// The length of a cubic curve within `allowed_error`, halving the curve and
+/// its share of the error until the bounds of the length meet.
+fn cubic_length(points: [Coord; 4], allowed_error: f64, depth: u32) -> ArcLength {
+    let [p0, p1, p2, p3] = points;
+    let chord = distance(p0, p3);
+    let polygon = distance(p0, p1) + distance(p1, p2) + distance(p2, p3);
+    let error_bound = (polygon - chord) / 2.;
+    if error_bound <= allowed_error || depth >= MAX_DEPTH || !error_bound.is_finite() {
+        return ArcLength {
+            length: f64::midpoint(polygon, chord),
+            error_bound,
+        };
+    }
+    let (first, second) = split_cubic(points, 0.5);
+    let first = cubic_length(first, allowed_error / 2., depth + 1);
+    let second = cubic_length(second, allowed_error / 2., depth + 1);
+    ArcLength {
+        length: first.length + second.length,
+        error_bound: first.error_bound + second.error_bound,
+    }
+}
+
+/// The control points of a segment, with the handles of a straight segment at
+/// a third and two thirds of its length.
+pub(super) fn cubic_points(segment: &BezierSegment) -> [Coord; 4] {
+    let (start, end) = (segment.start(), segment.end());
+    let (handle1, handle2) = segment
+        .handles()
+        .unwrap_or_else(|| (start + (end - start) / 3., end + (start - end) / 3.));
+    [start, handle1, handle2, end]
+}
+
+/// The point at parameter `t` of a cubic Bézier curve.
+pub(super) fn cubic_point([p0, p1, p2, p3]: [Coord; 4], t: f64) -> Coord {
+    let s = 1. - t;
+    p0 * (s * s * s) + p1 * (3. * s * s * t) + p2 * (3. * s * t * t) + p3 * (t * t * t)
+}
+
+/// The derivative at parameter `t` of a cubic Bézier curve.
+pub(super) fn cubic_derivative([p0, p1, p2, p3]: [Coord; 4], t: f64) -> Coord {
+    let s = 1. - t;
+    (p1 - p0) * (3. * s * s) + (p2 - p1) * (6. * s * t) + (p3 - p2) * (3. * t * t)
+}
+
+/// Split a cubic Bézier curve at parameter `t` by de Casteljau's algorithm.
+pub(super) fn split_cubic(points: [Coord; 4], t: f64) -> ([Coord; 4], [Coord; 4]) {
+    let lerp = |a: Coord, b: Coord| a + (b - a) * t;
+    let [p0, p1, p2, p3] = points;
+    let (a, b, c) = (lerp(p0, p1), lerp(p1, p2), lerp(p2, p3));
+    let (d, e) = (lerp(a, b), lerp(b, c));
+    let middle = lerp(d, e);
+    ([p0, a, d, middle], [middle, e, c, p3])
+}
+
+fn distance(a: Coord, b: Coord) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, num::NonZeroU32};
+
+    use geo_types::{Coord, LineString, Polygon};
+
+    use crate::{
+        NonNegativeF64, Result,
+        geo_referencing::GeoRef,
+        objects::{BezierPath, BezierPolygon, BezierSegment, BezierString},
+    };
+
+    /// A circle of four cubic curves, counter-clockwise unless `reversed`.
+    fn circle(center: Coord, radius: f64, reversed: bool) -> Result<BezierPath> {
+        // the handle length of the usual four-curve circle
+        let handle = radius * 0.552_284_749_831;
+        let at = |x: f64, y: f64| Coord {
+            x: center.x + x,
+            y: center.y + y,
+        };
+        let segments = [(1., 0.), (0., 1.), (-1., 0.), (0., -1.)]
+            .into_iter()
+            .map(|(x, y)| {
+                BezierSegment::new(
+                    at(x * radius, y * radius),
+                    Some((
+                        at(x * radius - y * handle, y * radius + x * handle),
+                        at(-y * radius + x * handle, x * radius + y * handle),
+                    )),
+                    at(-y * radius, x * radius),
+                )
+            })
+            .collect();
+        let mut path = BezierPath::new(BezierString::new(segments), vec![false; 5])?;
+        if reversed {
+            path.reverse();
+        }
+        Ok(path)
+    }
+
+    #[test]
+    fn measures_curves_within_their_error_bound() -> Result<()> {
+        let path = circle(Coord { x: 3., y: 4. }, 10., false)?;
+        let allowed_error = NonNegativeF64::clamped_from(0.001);
+        let length = path.length(allowed_error);
+        assert!(length.error_bound <= 0.001);
+
+        let flattened = path.flatten(NonNegativeF64::clamped_from(1e-7))?;
+        let flattened_length = flattened
+            .geometry()
+            .lines()
+            .map(|line| line.dx().hypot(line.dy()))
+            .sum::<f64>();
+        assert!((length.length - flattened_length).abs() <= length.error_bound + 1e-5);
+        // the four-curve circle is within 0.03 % of a true circle
+        assert!((length.length / (2. * PI * 10.) - 1.).abs() < 3e-4);
+
+        let line = BezierPath::from(LineString::from(vec![(0., 0.), (3., 4.), (3., 10.)]));
+        assert_eq!(line.length(allowed_error).length, 11.);
+        assert_eq!(line.length(allowed_error).error_bound, 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn measures_areas_and_centroids_with_holes() -> Result<()> {
+        let square = Polygon::new(
+            LineString::from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.)]),
+            vec![LineString::from(vec![
+                (6., 6.),
+                (8., 6.),
+                (8., 8.),
+                (6., 8.),
+            ])],
+        );
+        let mut polygon = BezierPolygon::from(square);
+        assert!((polygon.signed_area() - 96.).abs() < 1e-12);
+        let Some(centroid) = polygon.centroid() else {
+            panic!("the polygon has an area");
+        };
+        // the hole of area 4 at (7, 7) moves the centroid from (5, 5)
+        let expected = (500. - 28.) / 96.;
+        assert!((centroid.x - expected).abs() < 1e-12 && (centroid.y - expected).abs() < 1e-12);
+
+        polygon.reverse();
+        assert!((polygon.signed_area() + 96.).abs() < 1e-12);
+        assert!((polygon.area() - 96.).abs() < 1e-12);
+
+        // a curved exterior with a hole of the same orientation
+        let disc = BezierPolygon::new(
+            circle(Coord { x: 0., y: 0. }, 10., false)?,
+            vec![circle(Coord { x: 0., y: 0. }, 5., false)?],
+        )?;
+        assert!((disc.area() / (PI * 75.) - 1.).abs() < 3e-4);
+        let Some(centroid) = disc.centroid() else {
+            panic!("the disc has an area");
+        };
+        assert!(centroid.x.abs() < 1e-9 && centroid.y.abs() < 1e-9);
+
+        let geo_ref = GeoRef::new(NonZeroU32::new(15_000).unwrap_or(NonZeroU32::MIN));
+        assert!((polygon.ground_area(&geo_ref) - 96. * 225.).abs() < 1e-9);
+        Ok(())
+    }
+}
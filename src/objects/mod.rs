@@ -1,11 +1,21 @@
 mod area_object;
+mod boolean;
+mod crop;
+mod edit;
 mod line_object;
 mod point_object;
 mod text_object;
 
 mod map_object;
+mod measure;
+mod network;
+mod offset;
+mod simplify;
+mod topology;
 
-use geo_types::{Coord, LineString};
+pub(crate) use network::clean_lines;
+
+use geo_types::{Coord, LineString, Rect};
 pub use linestring2bezier::{BezierSegment, BezierString};
 use quick_xml::{
     Reader, Writer,
@@ -19,11 +29,18 @@ pub use line_object::LineObject;
 pub use point_object::PointObject;
 pub use text_object::{HorizontalAlign, TextGeometry, TextObject, VerticalAlign, WrapBox};
 
+pub use crop::CropMode;
 pub use map_object::MapObject;
+pub use measure::ArcLength;
+pub use network::{NetworkFix, ObjectFix};
+pub use simplify::{SimplifiedObject, VertexCounts};
+pub use topology::{GeometryIssue, ObjectIssue};
+
+use measure::cubic_point;
 
 use crate::{
     CoordinateComponent, notes,
-    utils::{from_file_coords, to_file_coords, try_get_attr},
+    utils::{from_file_coords, to_file_coords, try_get_attr, try_get_attr_raw},
 };
 
 use super::{Error, OmapSection, Result};
@@ -285,6 +302,17 @@ impl BezierPath {
             .zip(self.vertex_is_dash_point.iter().copied())
     }
 
+    /// The exact bounding box of the path, or `None` for an empty path.
+    ///
+    /// Curves extend the box only as far as they reach at their extrema, not to
+    /// their handles.
+    pub fn bounding_rect(&self) -> Option<Rect> {
+        self.geometry
+            .segments()
+            .map(segment_bounds)
+            .reduce(union_rects)
+    }
+
     /// Validate segment continuity and dash-point metadata.
     ///
     /// # Errors
@@ -348,6 +376,60 @@ impl From<LineString> for BezierPath {
     }
 }
 
+/// The exact bounding box of a segment, including the extrema of a curve.
+fn segment_bounds(segment: &BezierSegment) -> Rect {
+    let mut bounds = Rect::new(segment.start(), segment.end());
+    if let Some((handle1, handle2)) = segment.handles() {
+        let points = [segment.start(), handle1, handle2, segment.end()];
+        let [x, y] = [|coord: Coord| coord.x, |coord: Coord| coord.y]
+            .map(|axis| cubic_extrema(points.map(axis)));
+        for t in x.into_iter().chain(y).flatten() {
+            let point = cubic_point(points, t);
+            bounds = union_rects(bounds, Rect::new(point, point));
+        }
+    }
+    bounds
+}
+
+/// The parameters strictly between 0 and 1 where one coordinate of a cubic
+/// Bézier curve has a zero derivative.
+fn cubic_extrema([p0, p1, p2, p3]: [f64; 4]) -> [Option<f64>; 2] {
+    // a third of the derivative is a t² + b t + c
+    let a = -p0 + 3. * p1 - 3. * p2 + p3;
+    let b = 2. * (p0 - 2. * p1 + p2);
+    let c = p1 - p0;
+    let inside = |t: f64| (t > 0. && t < 1.).then_some(t);
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return [None, None];
+        }
+        return [inside(-c / b), None];
+    }
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return [None, None];
+    }
+    let root = discriminant.sqrt();
+    [
+        inside((-b + root) / (2. * a)),
+        inside((-b - root) / (2. * a)),
+    ]
+}
+
+/// The smallest rectangle containing both rectangles.
+pub(crate) fn union_rects(a: Rect, b: Rect) -> Rect {
+    Rect::new(
+        Coord {
+            x: a.min().x.min(b.min().x),
+            y: a.min().y.min(b.min().y),
+        },
+        Coord {
+            x: a.max().x.max(b.max().x),
+            y: a.max().y.max(b.max().y),
+        },
+    )
+}
+
 /// An owned flattened path with one dash flag for every coordinate.
 ///
 /// Coordinates are implicit straight-segment starts except for the last,
@@ -544,7 +626,10 @@ fn file_coords_from_bezier(path: &BezierPath, final_vertex_flags: u8) -> Result<
 
 fn parse_file_coords(text: &[u8], coords: &mut Vec<FileCoord>) -> Result<()> {
     let raw_xml = str::from_utf8(text)?;
-    for vertex in raw_xml.split_terminator(';') {
+    for vertex in raw_xml
+        .split_terminator(';')
+        .filter(|vertex| !vertex.trim().is_empty())
+    {
         let mut parts = vertex.split_whitespace();
         let x = parts
             .next()
@@ -560,6 +645,17 @@ fn parse_file_coords(text: &[u8], coords: &mut Vec<FileCoord>) -> Result<()> {
     Ok(())
 }
 
+/// Parse a `<coord x=".." y=".." flags=".."/>` element, which the pretty-printed
+/// layout writes for every vertex instead of the text content of `<coords>`.
+fn parse_file_coord(element: &BytesStart<'_>) -> Result<FileCoord> {
+    let x = try_get_attr_raw(element, "x")?
+        .ok_or(Error::MissingCoordinateComponent(CoordinateComponent::X))?;
+    let y = try_get_attr_raw(element, "y")?
+        .ok_or(Error::MissingCoordinateComponent(CoordinateComponent::Y))?;
+    let flags = try_get_attr_raw(element, "flags")?.unwrap_or(0);
+    Ok((Coord { x, y }, flags))
+}
+
 fn parse_tags<R: std::io::BufRead>(reader: &mut Reader<R>) -> Result<HashMap<String, String>> {
     let mut buf = Vec::new();
 
@@ -892,4 +988,26 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn bounds_reach_curve_extrema_not_handles() -> Result<()> {
+        let path = BezierPath::new(
+            BezierString::new(vec![
+                BezierSegment::new(
+                    Coord { x: 0.0, y: 0.0 },
+                    Some((Coord { x: 0.0, y: 10.0 }, Coord { x: 10.0, y: 10.0 })),
+                    Coord { x: 10.0, y: 0.0 },
+                ),
+                BezierSegment::new(Coord { x: 10.0, y: 0.0 }, None, Coord { x: 12.0, y: -1.0 }),
+            ]),
+            vec![false, false, false],
+        )?;
+        let Some(bounds) = path.bounding_rect() else {
+            panic!("the path is not empty");
+        };
+        assert_eq!(bounds.min(), Coord { x: 0.0, y: -1.0 });
+        assert_eq!(bounds.max(), Coord { x: 12.0, y: 7.5 });
+        assert!(BezierPath::empty().bounding_rect().is_none());
+        Ok(())
+    }
 }
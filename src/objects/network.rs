@@ -0,0 +1,386 @@
+use geo_types::{Coord, Rect};
+
+use super::{
+    BezierPath, LineObject, MapObject,
+    edit::{move_end, move_start},
+    measure::{cubic_point, cubic_points},
+};
+use crate::symbols::WeakSymbol;
+
+/// Points closer than this, in mm, are taken as one.
+const TOLERANCE: f64 = 1e-9;
+/// The number of points compared on every segment when looking for duplicates.
+const DUPLICATE_SAMPLES: u32 = 4;
+
+/// A change made by [`crate::Omap::clean_line_network`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NetworkFix {
+    /// A line whose ends were within the tolerance was closed with a straight
+    /// segment, see [`BezierPath::close`].
+    ClosedRing {
+        /// The end of the line, where the closing segment starts.
+        location: Coord,
+    },
+    /// A line was removed because it runs along another line of the same
+    /// symbol within the tolerance.
+    RemovedDuplicate {
+        /// The index of the line that was kept, in the same part.
+        of: usize,
+    },
+    /// An end of a line was moved onto an end of another line.
+    SnappedToEndpoint {
+        /// Where the end was.
+        from: Coord,
+        /// Where the end is now.
+        to: Coord,
+    },
+    /// A dangling end of a line was moved onto the nearest point of another
+    /// line.
+    SnappedOntoLine {
+        /// Where the end was.
+        from: Coord,
+        /// Where the end is now.
+        to: Coord,
+        /// The index of the other line, in the same part.
+        line: usize,
+    },
+}
+
+/// A change to an object of a map, made by
+/// [`crate::Omap::clean_line_network`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectFix {
+    /// The index of the object's part in [`crate::Omap::parts`].
+    pub part_index: usize,
+    /// The index of the object in its part before cleaning, see
+    /// [`crate::parts::MapPart::get`].
+    pub object_index: usize,
+    /// The change.
+    pub fix: NetworkFix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum End {
+    Start,
+    End,
+}
+
+/// Clean the network of the lines among `objects` whose symbol passes `filter`
+/// and return the changes with the indices of the objects, see
+/// [`crate::Omap::clean_line_network`]. Removed duplicates are dropped from
+/// `objects`.
+pub(crate) fn clean_lines<F>(
+    objects: &mut Vec<MapObject>,
+    filter: F,
+    tolerance: f64,
+) -> Vec<(usize, NetworkFix)>
+where
+    F: Fn(&WeakSymbol) -> bool,
+{
+    let mut lines = objects
+        .iter_mut()
+        .enumerate()
+        .filter(|(_, object)| filter(&object.symbol()))
+        .filter_map(|(index, object)| match object {
+            MapObject::Line(line) if !line.geometry().is_empty() => Some((index, line)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut fixes = Vec::new();
+
+    for (index, line) in &mut lines {
+        if let Some(location) = close_ring(line.geometry_mut(), tolerance) {
+            fixes.push((*index, NetworkFix::ClosedRing { location }));
+        }
+    }
+
+    let is_removed = remove_duplicates(&lines, tolerance, &mut fixes);
+    let open = (0..lines.len())
+        .filter(|&k| !is_removed[k] && !lines[k].1.geometry().is_closed())
+        .collect::<Vec<_>>();
+    snap_to_endpoints(&mut lines, &open, tolerance, &mut fixes);
+    snap_onto_lines(&mut lines, &open, &is_removed, tolerance, &mut fixes);
+
+    let removed = lines
+        .iter()
+        .zip(&is_removed)
+        .filter(|(_, is_removed)| **is_removed)
+        .map(|((index, _), _)| *index)
+        .collect::<Vec<_>>();
+    let mut index = 0;
+    objects.retain(|_| {
+        index += 1;
+        !removed.contains(&(index - 1))
+    });
+    fixes
+}
+
+/// Mark the lines that run along an earlier line of the same symbol as removed.
+fn remove_duplicates(
+    lines: &[(usize, &mut LineObject)],
+    tolerance: f64,
+    fixes: &mut Vec<(usize, NetworkFix)>,
+) -> Vec<bool> {
+    let mut is_removed = vec![false; lines.len()];
+    let rects = lines
+        .iter()
+        .map(|(_, line)| line.geometry().bounding_rect())
+        .collect::<Vec<_>>();
+    for b in 0..lines.len() {
+        let duplicate_of = (0..b).find(|&a| {
+            !is_removed[a]
+                && lines[a].1.symbol == lines[b].1.symbol
+                && rects_overlap(rects[a], rects[b], tolerance)
+                && runs_along(lines[b].1.geometry(), lines[a].1.geometry(), tolerance)
+                && runs_along(lines[a].1.geometry(), lines[b].1.geometry(), tolerance)
+        });
+        if let Some(a) = duplicate_of {
+            is_removed[b] = true;
+            fixes.push((lines[b].0, NetworkFix::RemovedDuplicate { of: lines[a].0 }));
+        }
+    }
+    is_removed
+}
+
+/// Snap every end of the `open` lines to the first end of another line near it.
+fn snap_to_endpoints(
+    lines: &mut [(usize, &mut LineObject)],
+    open: &[usize],
+    tolerance: f64,
+    fixes: &mut Vec<(usize, NetworkFix)>,
+) {
+    let mut anchors = Vec::<(usize, Coord)>::new();
+    for &k in open {
+        for end in [End::Start, End::End] {
+            let Some(from) = endpoint(lines[k].1.geometry(), end) else {
+                continue;
+            };
+            let nearest = anchors
+                .iter()
+                .filter(|(other, _)| *other != k)
+                .map(|(_, anchor)| (*anchor, distance(*anchor, from)))
+                .filter(|(_, distance)| *distance <= tolerance)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match nearest {
+                Some((to, distance)) if distance > TOLERANCE => {
+                    move_endpoint(lines[k].1.geometry_mut(), end, to);
+                    fixes.push((lines[k].0, NetworkFix::SnappedToEndpoint { from, to }));
+                }
+                Some(_) => {}
+                None => anchors.push((k, from)),
+            }
+        }
+    }
+}
+
+/// Snap the ends of the `open` lines that meet no end of another line onto the
+/// nearest point of another line.
+fn snap_onto_lines(
+    lines: &mut [(usize, &mut LineObject)],
+    open: &[usize],
+    is_removed: &[bool],
+    tolerance: f64,
+    fixes: &mut Vec<(usize, NetworkFix)>,
+) {
+    let rects = lines
+        .iter()
+        .map(|(_, line)| line.geometry().bounding_rect())
+        .collect::<Vec<_>>();
+    for &k in open {
+        for end in [End::Start, End::End] {
+            let Some(from) = endpoint(lines[k].1.geometry(), end) else {
+                continue;
+            };
+            let others = (0..lines.len())
+                .filter(|&other| other != k && !is_removed[other])
+                .collect::<Vec<_>>();
+            let is_connected = others
+                .iter()
+                .flat_map(|&other| {
+                    [End::Start, End::End].map(|end| endpoint(lines[other].1.geometry(), end))
+                })
+                .flatten()
+                .any(|other| distance(other, from) <= TOLERANCE);
+            if is_connected {
+                continue;
+            }
+            let nearest = others
+                .into_iter()
+                .filter(|&other| {
+                    rects_overlap(rects[other], Some(Rect::new(from, from)), tolerance)
+                })
+                .filter_map(|other| {
+                    let point = closest_point(lines[other].1.geometry(), from)?;
+                    Some((other, point, distance(point, from)))
+                })
+                .filter(|(_, _, distance)| *distance <= tolerance)
+                .min_by(|a, b| a.2.total_cmp(&b.2));
+            if let Some((other, to, distance)) = nearest
+                && distance > TOLERANCE
+            {
+                move_endpoint(lines[k].1.geometry_mut(), end, to);
+                fixes.push((
+                    lines[k].0,
+                    NetworkFix::SnappedOntoLine {
+                        from,
+                        to,
+                        line: lines[other].0,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Close an open path whose ends are within `tolerance` and which is larger
+/// than the gap, and return its end.
+fn close_ring(path: &mut BezierPath, tolerance: f64) -> Option<Coord> {
+    if path.is_closed() {
+        return None;
+    }
+    let start = endpoint(path, End::Start)?;
+    let end = endpoint(path, End::End)?;
+    let rect = path.bounding_rect()?;
+    if distance(start, end) > tolerance || rect.width().hypot(rect.height()) <= 2. * tolerance {
+        return None;
+    }
+    path.close();
+    Some(end)
+}
+
+/// Whether every part of `path` is within `tolerance` of `other`.
+fn runs_along(path: &BezierPath, other: &BezierPath, tolerance: f64) -> bool {
+    path.geometry.segments().all(|segment| {
+        let points = cubic_points(segment);
+        (0..=DUPLICATE_SAMPLES).all(|sample| {
+            let point = cubic_point(points, f64::from(sample) / f64::from(DUPLICATE_SAMPLES));
+            closest_point(other, point).is_some_and(|closest| distance(closest, point) <= tolerance)
+        })
+    })
+}
+
+fn closest_point(path: &BezierPath, coord: Coord) -> Option<Coord> {
+    Some(path.point_at(path.closest_parameter(coord)?))
+}
+
+fn endpoint(path: &BezierPath, end: End) -> Option<Coord> {
+    match end {
+        End::Start => path
+            .geometry
+            .segments()
+            .next()
+            .map(|segment| segment.start()),
+        End::End => path.geometry.segments().last().map(|segment| segment.end()),
+    }
+}
+
+fn move_endpoint(path: &mut BezierPath, end: End, to: Coord) {
+    let segments = &mut path.geometry.0;
+    match end {
+        End::Start => {
+            if let Some(first) = segments.first_mut() {
+                *first = move_start(first, to);
+            }
+        }
+        End::End => {
+            if let Some(last) = segments.last_mut() {
+                *last = move_end(last, to);
+            }
+        }
+    }
+}
+
+fn rects_overlap(a: Option<Rect>, b: Option<Rect>, tolerance: f64) -> bool {
+    let (Some(a), Some(b)) = (a, b) else {
+        return false;
+    };
+    a.min().x <= b.max().x + tolerance
+        && b.min().x <= a.max().x + tolerance
+        && a.min().y <= b.max().y + tolerance
+        && b.min().y <= a.max().y + tolerance
+}
+
+fn distance(a: Coord, b: Coord) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use geo_types::{Coord, LineString};
+
+    use super::{NetworkFix, clean_lines};
+    use crate::{
+        Code,
+        objects::{LineObject, MapObject},
+        symbols::{LineSymbol, WeakLinePathSymbol, WeakSymbol},
+    };
+
+    #[test]
+    fn snaps_closes_and_removes_duplicates() {
+        let path = Rc::new(RefCell::new(LineSymbol::new(Code::new(505, 0, 0), "Path")));
+        let stream = Rc::new(RefCell::new(LineSymbol::new(
+            Code::new(305, 0, 0),
+            "Stream",
+        )));
+        let line = |symbol: &Rc<RefCell<LineSymbol>>, points: Vec<(f64, f64)>| {
+            MapObject::Line(LineObject::new(
+                WeakLinePathSymbol::Line(Rc::downgrade(symbol)),
+                LineString::from(points),
+            ))
+        };
+        let mut objects = vec![
+            line(&path, vec![(0., 0.), (10., 0.)]),
+            line(&path, vec![(10.2, 0.1), (20., 0.)]),
+            line(&path, vec![(5., 5.), (5., 0.3)]),
+            line(&path, vec![(10., 0.1), (0., 0.1)]),
+            line(&path, vec![(0., 10.), (10., 10.), (10., 20.), (0., 10.2)]),
+            line(&stream, vec![(0., 0.1), (10., 0.1)]),
+        ];
+
+        let fixes = clean_lines(
+            &mut objects,
+            |symbol| *symbol == WeakSymbol::Line(Rc::downgrade(&path)),
+            0.5,
+        );
+
+        let point = |x, y| Coord { x, y };
+        assert_eq!(
+            fixes,
+            [
+                (
+                    4,
+                    NetworkFix::ClosedRing {
+                        location: point(0., 10.2)
+                    }
+                ),
+                (3, NetworkFix::RemovedDuplicate { of: 0 }),
+                (
+                    1,
+                    NetworkFix::SnappedToEndpoint {
+                        from: point(10.2, 0.1),
+                        to: point(10., 0.),
+                    }
+                ),
+                (
+                    2,
+                    NetworkFix::SnappedOntoLine {
+                        from: point(5., 0.3),
+                        to: point(5., 0.),
+                        line: 0,
+                    }
+                ),
+            ]
+        );
+        assert_eq!(objects.len(), 5);
+        let MapObject::Line(ring) = &objects[3] else {
+            panic!("expected the ring");
+        };
+        assert!(ring.geometry().is_closed());
+        let MapObject::Line(stream) = &objects[4] else {
+            panic!("expected the stream");
+        };
+        assert_eq!(stream.geometry().geometry().0[0].start(), point(0., 0.1));
+    }
+}
@@ -0,0 +1,673 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use geo_types::Coord;
+
+use super::{
+    BezierPath, BezierPolygon, BezierSegment, BezierString, boolean,
+    edit::{closest_segment_parameter, move_end, move_start},
+    measure::{cubic_derivative, cubic_point, cubic_points, split_cubic},
+};
+use crate::{NonNegativeF64, Result, symbols::JoinStyle};
+
+/// The longest miter, in offset distances, before a miter join is bevelled.
+const MITER_LIMIT: f64 = 4.;
+
+/// Segments and gaps shorter than this are taken as points.
+const TOLERANCE: f64 = 1e-6;
+
+/// The deepest subdivision of a curve, where its offset is taken as it is even
+/// if it is not within the allowed error.
+const MAX_DEPTH: u32 = 10;
+
+/// The number of intervals of a curve at whose ends the exact offset is fitted
+/// and compared to its approximation.
+const SAMPLES: u32 = 16;
+
+/// The most cubic arcs in a round join.
+const MAX_ARCS: u32 = 64;
+
+impl BezierPath {
+    /// The path offset by `distance` mm to its left, or to its right for a
+    /// negative distance, e.g. for the side lines of a road.
+    ///
+    /// Straight segments are offset exactly. The offset of a curve is
+    /// approximated by cubic curves within `allowed_error` of the exact offset,
+    /// subdividing the curve up to 1024 times where one cubic does not fit.
+    /// Corners on the outer side are joined by `join` like a stroked line, with
+    /// miters longer than four times the distance bevelled, and the offsets
+    /// meeting in a corner on the inner side are cut back to where they cross.
+    /// A dash point at a corner becomes the start of the offset of the segment
+    /// after it.
+    pub fn offset(&self, distance: f64, join: JoinStyle, allowed_error: NonNegativeF64) -> Self {
+        let corners = Corners {
+            distance,
+            join,
+            allowed_error: allowed_error.get(),
+            trim_inner: true,
+        };
+        corners.offset(self)
+    }
+}
+
+impl BezierPolygon {
+    /// The polygon grown by `distance` mm, or shrunk for a negative distance,
+    /// with the corners on the grown side joined by `join`. See
+    /// [`BezierPath::offset`] for how curves are offset within
+    /// `allowed_error`.
+    ///
+    /// Holes shrink while the polygon grows and vanish once they are filled,
+    /// and a shrinking polygon may fall apart into several polygons or vanish.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the polygon is invalid or the offset outlines cannot
+    /// be joined into rings.
+    pub fn buffer(
+        &self,
+        distance: f64,
+        join: JoinStyle,
+        allowed_error: NonNegativeF64,
+    ) -> Result<Vec<Self>> {
+        self.validate()?;
+        let corners = Corners {
+            distance: -distance,
+            join,
+            allowed_error: allowed_error.get(),
+            trim_inner: false,
+        };
+        let outline = || std::iter::once(self.exterior()).chain(self.interiors());
+        let mut rings = Vec::new();
+        for (index, ring) in outline().enumerate() {
+            // with the polygon on the left, growing it offsets to the right
+            let mut ring = ring.clone();
+            if (ring.signed_area() > 0.) != (index == 0) {
+                ring.reverse();
+            }
+            rings.push(corners.offset(&ring));
+        }
+
+        // the offset of a curve turning tighter than the distance runs backwards
+        // and can leave a ring that comes closer to the outline than the distance
+        let reach = distance.abs() - 2. * allowed_error.get() - TOLERANCE;
+        let keeps_distance = |ring: &BezierPath| {
+            ring.geometry().segments().any(|segment| {
+                let middle = cubic_point(cubic_points(segment), 0.5);
+                outline()
+                    .flat_map(|ring| ring.geometry().segments())
+                    .all(|boundary| {
+                        let t = closest_segment_parameter(boundary, middle);
+                        length(cubic_point(cubic_points(boundary), t) - middle) >= reach
+                    })
+            })
+        };
+        let mut polygons = Vec::new();
+        for polygon in boolean::fill_positive(&rings)? {
+            if keeps_distance(polygon.exterior()) {
+                let interiors = polygon
+                    .interiors()
+                    .iter()
+                    .filter(|hole| keeps_distance(hole))
+                    .cloned()
+                    .collect();
+                polygons.push(Self::new(polygon.exterior().clone(), interiors)?);
+            }
+        }
+        Ok(polygons)
+    }
+}
+
+/// How the offsets of segments are joined.
+struct Corners {
+    /// The distance to the left.
+    distance: f64,
+    join: JoinStyle,
+    allowed_error: f64,
+    /// Whether the offsets meeting in an inner corner are cut back to where they
+    /// cross, rather than joined through the corner and left crossing.
+    trim_inner: bool,
+}
+
+/// The offset of a segment of a path.
+struct SegmentOffset {
+    pieces: Vec<Piece>,
+    /// The indices of the start and end vertex of the segment.
+    vertices: (usize, usize),
+    start: Coord,
+    /// The unit directions leaving the start and arriving at the end.
+    directions: (Coord, Coord),
+}
+
+/// A segment of an offset path and whether its end is a dash point.
+struct Piece {
+    segment: BezierSegment,
+    dash_point: bool,
+}
+
+impl Piece {
+    fn new(segment: BezierSegment) -> Self {
+        Self {
+            segment,
+            dash_point: false,
+        }
+    }
+}
+
+impl Corners {
+    fn offset(&self, path: &BezierPath) -> BezierPath {
+        let dash_points = &path.vertex_is_dash_point;
+        let offsets = path
+            .geometry
+            .segments()
+            .enumerate()
+            .filter_map(|(index, segment)| {
+                let points = cubic_points(segment);
+                let directions = directions(points)?;
+                let mut pieces = Vec::new();
+                if segment.handles().is_some() {
+                    self.offset_curve(points, 0, &mut pieces);
+                } else {
+                    let shift = left_normal(directions.0) * self.distance;
+                    pieces.push(Piece::new(BezierSegment::new(
+                        points[0] + shift,
+                        None,
+                        points[3] + shift,
+                    )));
+                }
+                Some(SegmentOffset {
+                    pieces,
+                    vertices: (index, index + 1),
+                    start: points[0],
+                    directions,
+                })
+            })
+            .collect::<Vec<_>>();
+        let (Some(first), Some(last)) = (offsets.first(), offsets.last()) else {
+            return BezierPath::empty();
+        };
+        let closing = (
+            last.vertices.1,
+            first.vertices.0,
+            first.start,
+            (last.directions.1, first.directions.0),
+        );
+
+        // the pieces of the first segment, and those of the last after its start
+        let mut num_first = 0;
+        let mut last_start = 0;
+        let mut output = Vec::<Piece>::new();
+        let mut previous = None;
+        for offset in offsets {
+            let mut pieces = offset.pieces;
+            if let Some((vertex, direction)) = previous {
+                self.join(
+                    &mut output,
+                    last_start,
+                    &mut pieces,
+                    offset.start,
+                    (direction, offset.directions.0),
+                    dash_points[vertex..=offset.vertices.0].contains(&true),
+                );
+            }
+            last_start = output.len();
+            output.extend(pieces);
+            if num_first == 0 {
+                num_first = output.len();
+            }
+            previous = Some((offset.vertices.1, offset.directions.1));
+        }
+
+        let start_is_dash_point = if path.is_closed() {
+            let (end_vertex, start_vertex, corner, directions) = closing;
+            if let [piece] = output.as_mut_slice() {
+                let (first, second) = split_segment(&piece.segment, 0.5);
+                piece.segment = first;
+                output.push(Piece::new(second));
+            }
+            let count = num_first.clamp(1, output.len() - 1);
+            let mut outgoing = output.drain(..count).collect::<Vec<_>>();
+            let dash_point = dash_points[end_vertex..].contains(&true)
+                || dash_points[..=start_vertex].contains(&true);
+            self.join(
+                &mut output,
+                last_start.saturating_sub(count),
+                &mut outgoing,
+                corner,
+                directions,
+                dash_point,
+            );
+            outgoing.append(&mut output);
+            output = outgoing;
+            let start = output[0].segment.start();
+            if let Some(piece) = output.last_mut() {
+                piece.segment = move_end(&piece.segment, start);
+            }
+            dash_point
+        } else {
+            dash_points[closing.1]
+        };
+
+        let mut vertex_is_dash_point = vec![start_is_dash_point];
+        vertex_is_dash_point.extend(output.iter().map(|piece| piece.dash_point));
+        BezierPath {
+            geometry: BezierString::new(output.into_iter().map(|piece| piece.segment).collect()),
+            vertex_is_dash_point,
+        }
+    }
+
+    /// Approximate the offset of a cubic curve by cubic curves within the
+    /// allowed error, fitting the lengths of the handles to the exact offset
+    /// and subdividing the curve where that is not enough.
+    fn offset_curve(&self, points: [Coord; 4], depth: u32, pieces: &mut Vec<Piece>) {
+        let Some((start_direction, end_direction)) = directions(points) else {
+            return;
+        };
+        let exact = |t: f64| {
+            let tangent = cubic_derivative(points, t);
+            let norm = length(tangent);
+            (norm > TOLERANCE)
+                .then(|| cubic_point(points, t) + left_normal(tangent / norm) * self.distance)
+        };
+        let start = points[0] + left_normal(start_direction) * self.distance;
+        let end = points[3] + left_normal(end_direction) * self.distance;
+
+        // least squares fit of the handle lengths along the end directions
+        let (mut aa, mut ab, mut bb, mut ar, mut br) = (0., 0., 0., 0., 0.);
+        for index in 1..SAMPLES {
+            let t = f64::from(index) / f64::from(SAMPLES);
+            let Some(target) = exact(t) else {
+                continue;
+            };
+            let s = 1. - t;
+            let a = start_direction * (3. * s * s * t);
+            let b = end_direction * (-3. * s * t * t);
+            let rest =
+                target - start * (s * s * s + 3. * s * s * t) - end * (3. * s * t * t + t * t * t);
+            aa += dot(a, a);
+            ab += dot(a, b);
+            bb += dot(b, b);
+            ar += dot(a, rest);
+            br += dot(b, rest);
+        }
+        let determinant = aa * bb - ab * ab;
+        let (length1, length2) = if determinant > f64::EPSILON * aa * bb {
+            (
+                (ar * bb - ab * br) / determinant,
+                (aa * br - ab * ar) / determinant,
+            )
+        } else {
+            (length(points[1] - points[0]), length(points[3] - points[2]))
+        };
+        let fitted = [
+            start,
+            start + start_direction * length1,
+            end - end_direction * length2,
+            end,
+        ];
+
+        let error = (1..2 * SAMPLES)
+            .filter_map(|index| {
+                let t = f64::from(index) / f64::from(2 * SAMPLES);
+                exact(t).map(|target| length(cubic_point(fitted, t) - target))
+            })
+            .fold(0., f64::max);
+        if error > self.allowed_error && depth < MAX_DEPTH {
+            let (first, second) = split_cubic(points, 0.5);
+            self.offset_curve(first, depth + 1, pieces);
+            self.offset_curve(second, depth + 1, pieces);
+            return;
+        }
+        let segment = BezierSegment::new(start, Some((fitted[1], fitted[2])), end);
+        pieces.push(Piece::new(match pieces.last() {
+            Some(last) => move_start(&segment, last.segment.end()),
+            None => segment,
+        }));
+    }
+
+    /// Join the offsets before and after a corner of the path, the pieces of
+    /// the segment before it starting at `incoming_start`, and mark the start
+    /// of the offset after it as a dash point.
+    fn join(
+        &self,
+        incoming: &mut Vec<Piece>,
+        incoming_start: usize,
+        outgoing: &mut Vec<Piece>,
+        corner: Coord,
+        (incoming_direction, outgoing_direction): (Coord, Coord),
+        dash_point: bool,
+    ) {
+        let (Some(last), Some(next)) = (incoming.last(), outgoing.first()) else {
+            return;
+        };
+        let (start, end) = (last.segment.end(), next.segment.start());
+        if length(end - start) <= TOLERANCE {
+            outgoing[0].segment = move_start(&outgoing[0].segment, start);
+        } else if cross(incoming_direction, outgoing_direction) * self.distance <= 0. {
+            let pieces = self.outer_corner(corner, start, end);
+            incoming.extend(pieces.into_iter().map(Piece::new));
+        } else if !self.trim_inner {
+            incoming.push(Piece::new(BezierSegment::new(start, None, corner)));
+            incoming.push(Piece::new(BezierSegment::new(corner, None, end)));
+        } else if !trim(incoming, incoming_start, outgoing) {
+            incoming.push(Piece::new(BezierSegment::new(start, None, end)));
+        }
+        if let Some(last) = incoming.last_mut() {
+            last.dash_point = dash_point;
+        }
+    }
+
+    /// The segments joining the offsets from `start` to `end` around the outer
+    /// side of a corner.
+    fn outer_corner(&self, corner: Coord, start: Coord, end: Coord) -> Vec<BezierSegment> {
+        let radius = self.distance.abs();
+        match self.join {
+            JoinStyle::Miter => {
+                let sum = (start - corner) + (end - corner);
+                let bisector = sum / length(sum);
+                let cosine = dot(bisector, end - corner) / radius;
+                if length(sum) > TOLERANCE && cosine > 1. / MITER_LIMIT {
+                    let tip = corner + bisector * (radius / cosine);
+                    return vec![
+                        BezierSegment::new(start, None, tip),
+                        BezierSegment::new(tip, None, end),
+                    ];
+                }
+            }
+            JoinStyle::Round => return self.arc(corner, start, end),
+            JoinStyle::Bevel => {}
+        }
+        vec![BezierSegment::new(start, None, end)]
+    }
+
+    /// Cubic arcs around `center` from `start` to `end`, turning right for a
+    /// left offset and left for a right offset.
+    fn arc(&self, center: Coord, start: Coord, end: Coord) -> Vec<BezierSegment> {
+        let (from, to) = (start - center, end - center);
+        let radius = length(from);
+        let mut sweep = cross(from, to).atan2(dot(from, to));
+        if sweep * self.distance > 0. {
+            sweep -= TAU.copysign(sweep);
+        }
+        // the radial error of a cubic arc grows with the sixth power of its angle
+        let error = |angle: f64| {
+            let quarter = angle / 4.;
+            radius * 2. * quarter.sin().powi(6) / (27. * quarter.cos().powi(2))
+        };
+        let mut count = 1;
+        while count < MAX_ARCS {
+            let angle = sweep.abs() / f64::from(count);
+            if angle <= FRAC_PI_2 && error(angle) <= self.allowed_error {
+                break;
+            }
+            count += 1;
+        }
+        let angle = sweep / f64::from(count);
+        let handle = 4. / 3. * (angle / 4.).tan();
+        let rotate = |angle: f64| Coord {
+            x: from.x * angle.cos() - from.y * angle.sin(),
+            y: from.x * angle.sin() + from.y * angle.cos(),
+        };
+        let mut arcs = (0..count)
+            .map(|index| {
+                let a = rotate(angle * f64::from(index));
+                let b = rotate(angle * f64::from(index + 1));
+                BezierSegment::new(
+                    center + a,
+                    Some((
+                        center + a + left_normal(a) * handle,
+                        center + b - left_normal(b) * handle,
+                    )),
+                    center + b,
+                )
+            })
+            .collect::<Vec<_>>();
+        if let Some(last) = arcs.last_mut() {
+            *last = move_end(last, end);
+        }
+        arcs
+    }
+}
+
+/// Cut the offsets meeting in an inner corner back to where they cross,
+/// searching the incoming pieces from `incoming_start` on, and return whether
+/// they cross.
+fn trim(incoming: &mut Vec<Piece>, incoming_start: usize, outgoing: &mut Vec<Piece>) -> bool {
+    for i in (incoming_start..incoming.len()).rev() {
+        for j in 0..outgoing.len() {
+            // the crossing closest to the corner along the incoming offset
+            let Some((s, t)) =
+                boolean::segment_intersections(&incoming[i].segment, &outgoing[j].segment)
+                    .into_iter()
+                    .max_by(|a, b| a.0.total_cmp(&b.0))
+            else {
+                continue;
+            };
+            let point = (cubic_point(cubic_points(&incoming[i].segment), s)
+                + cubic_point(cubic_points(&outgoing[j].segment), t))
+                / 2.;
+            incoming.truncate(i + 1);
+            outgoing.drain(..j);
+            let (before, _) = split_segment(&incoming[i].segment, s);
+            incoming[i].segment = move_end(&before, point);
+            let (_, after) = split_segment(&outgoing[0].segment, t);
+            outgoing[0].segment = move_start(&after, point);
+            return true;
+        }
+    }
+    false
+}
+
+/// The unit directions in which a cubic curve leaves its start and arrives at
+/// its end, or `None` if it is a point.
+pub(super) fn directions([p0, p1, p2, p3]: [Coord; 4]) -> Option<(Coord, Coord)> {
+    let start = [p1, p2, p3]
+        .into_iter()
+        .map(|point| point - p0)
+        .find(|direction| length(*direction) > TOLERANCE)?;
+    let end = [p2, p1, p0]
+        .into_iter()
+        .map(|point| p3 - point)
+        .find(|direction| length(*direction) > TOLERANCE)?;
+    Some((start / length(start), end / length(end)))
+}
+
+fn split_segment(segment: &BezierSegment, t: f64) -> (BezierSegment, BezierSegment) {
+    let (before, after) = split_cubic(cubic_points(segment), t);
+    let is_curve = segment.handles().is_some();
+    let to_segment = |[start, handle1, handle2, end]: [Coord; 4]| {
+        BezierSegment::new(start, is_curve.then_some((handle1, handle2)), end)
+    };
+    (to_segment(before), to_segment(after))
+}
+
+fn left_normal(vector: Coord) -> Coord {
+    Coord {
+        x: -vector.y,
+        y: vector.x,
+    }
+}
+
+fn cross(a: Coord, b: Coord) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn dot(a: Coord, b: Coord) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+fn length(vector: Coord) -> f64 {
+    vector.x.hypot(vector.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{Coord, LineString, Polygon};
+
+    use crate::{
+        NonNegativeF64, Result,
+        objects::{BezierPath, BezierPolygon, BezierSegment, BezierString},
+        symbols::JoinStyle,
+    };
+
+    fn vertices(path: &BezierPath) -> Vec<(f64, f64)> {
+        let segments = &path.geometry().0;
+        segments
+            .first()
+            .map(BezierSegment::start)
+            .into_iter()
+            .chain(segments.iter().map(BezierSegment::end))
+            .map(|point| (point.x, point.y))
+            .collect()
+    }
+
+    fn assert_vertices(path: &BezierPath, expected: &[(f64, f64)]) {
+        let vertices = vertices(path);
+        assert_eq!(vertices.len(), expected.len(), "{vertices:?}");
+        for (vertex, expected) in vertices.iter().zip(expected) {
+            assert!(
+                (vertex.0 - expected.0).hypot(vertex.1 - expected.1) < 1e-9,
+                "{vertices:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn offsets_corners_by_join_style() -> Result<()> {
+        let mut path = BezierPath::from(LineString::from(vec![(0., 0.), (10., 0.), (10., 10.)]));
+        path.set_vertex_is_dash_point(1, true);
+        let tolerance = NonNegativeF64::clamped_from(1e-3);
+
+        // the inner side of the left turn is cut back to the crossing
+        let inner = path.offset(1., JoinStyle::Miter, tolerance);
+        assert_vertices(&inner, &[(0., 1.), (9., 1.), (9., 10.)]);
+        assert_eq!(inner.vertex_is_dash_point(), [false, true, false]);
+
+        let miter = path.offset(-1., JoinStyle::Miter, tolerance);
+        assert_vertices(
+            &miter,
+            &[(0., -1.), (10., -1.), (11., -1.), (11., 0.), (11., 10.)],
+        );
+        assert_eq!(
+            miter.vertex_is_dash_point(),
+            [false, false, false, true, false]
+        );
+        let bevel = path.offset(-1., JoinStyle::Bevel, tolerance);
+        assert_vertices(&bevel, &[(0., -1.), (10., -1.), (11., 0.), (11., 10.)]);
+
+        let round = path.offset(-1., JoinStyle::Round, tolerance);
+        let segments = &round.geometry().0;
+        assert_eq!(segments.len(), 3);
+        let Some((handle1, handle2)) = segments[1].handles() else {
+            panic!("a round join is curved");
+        };
+        let middle =
+            (segments[1].start() + segments[1].end()) * 0.125 + (handle1 + handle2) * 0.375;
+        assert!(((middle.x - 10.).hypot(middle.y) - 1.).abs() < 1e-3);
+        round.validate()
+    }
+
+    #[test]
+    fn offsets_curves_within_the_tolerance() -> Result<()> {
+        // a circle of radius 10 from four cubic arcs
+        let k = 10. * 0.552_284_749_830_793_4;
+        let point = |x: f64, y: f64| Coord { x, y };
+        let circle = BezierPath::new(
+            BezierString::new(vec![
+                BezierSegment::new(
+                    point(10., 0.),
+                    Some((point(10., k), point(k, 10.))),
+                    point(0., 10.),
+                ),
+                BezierSegment::new(
+                    point(0., 10.),
+                    Some((point(-k, 10.), point(-10., k))),
+                    point(-10., 0.),
+                ),
+                BezierSegment::new(
+                    point(-10., 0.),
+                    Some((point(-10., -k), point(-k, -10.))),
+                    point(0., -10.),
+                ),
+                BezierSegment::new(
+                    point(0., -10.),
+                    Some((point(k, -10.), point(10., -k))),
+                    point(10., 0.),
+                ),
+            ]),
+            vec![true, false, false, false, true],
+        )?;
+        let tolerance = NonNegativeF64::clamped_from(0.01);
+        for (distance, radius) in [(-2., 12.), (2., 8.), (6., 4.)] {
+            let offset = circle.offset(distance, JoinStyle::Round, tolerance);
+            offset.validate()?;
+            assert!(offset.is_closed());
+            assert_eq!(offset.vertex_is_dash_point().first(), Some(&true));
+            let buffer = BezierPolygon::new(circle.clone(), vec![])?.buffer(
+                -distance,
+                JoinStyle::Round,
+                tolerance,
+            )?;
+            let [disk] = buffer.as_slice() else {
+                panic!("a disk is buffered into a disk");
+            };
+            assert!((disk.area() - radius * radius * std::f64::consts::PI).abs() < radius * 0.1);
+            for segment in offset.geometry().segments() {
+                let Some((handle1, handle2)) = segment.handles() else {
+                    panic!("the offset of a curve is curved");
+                };
+                for index in 0..=8 {
+                    let t = f64::from(index) / 8.;
+                    let s = 1. - t;
+                    let point = segment.start() * (s * s * s)
+                        + handle1 * (3. * s * s * t)
+                        + handle2 * (3. * s * t * t)
+                        + segment.end() * (t * t * t);
+                    // the circle itself is 0.003 mm off
+                    assert!((point.x.hypot(point.y) - radius).abs() < 0.015);
+                }
+            }
+        }
+        // shrinking by more than the radius leaves nothing
+        let disk = BezierPolygon::new(circle, vec![])?;
+        assert!(disk.buffer(-11., JoinStyle::Round, tolerance)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn buffers_polygons_with_holes() -> Result<()> {
+        let square = |min: f64, max: f64| {
+            LineString::from(vec![
+                (min, min),
+                (max, min),
+                (max, max),
+                (min, max),
+                (min, min),
+            ])
+        };
+        let polygon = BezierPolygon::from(Polygon::new(square(0., 10.), vec![]));
+        let tolerance = NonNegativeF64::clamped_from(1e-3);
+        let area = |distance: f64, join: JoinStyle| -> Result<f64> {
+            Ok(polygon
+                .buffer(distance, join, tolerance)?
+                .iter()
+                .map(BezierPolygon::area)
+                .sum())
+        };
+        assert!((area(2., JoinStyle::Miter)? - 196.).abs() < 1e-6);
+        assert!((area(2., JoinStyle::Bevel)? - 188.).abs() < 1e-6);
+        assert!((area(2., JoinStyle::Round)? - (180. + 4. * std::f64::consts::PI)).abs() < 0.01);
+        assert!((area(-2., JoinStyle::Round)? - 36.).abs() < 1e-6);
+        assert!(polygon.buffer(-6., JoinStyle::Miter, tolerance)?.is_empty());
+
+        let frame = BezierPolygon::from(Polygon::new(square(0., 10.), vec![square(3., 7.)]));
+        let grown = frame.buffer(1., JoinStyle::Miter, tolerance)?;
+        assert_eq!(grown.len(), 1);
+        assert_eq!(grown[0].interiors().len(), 1);
+        assert!((grown[0].area() - 140.).abs() < 1e-6);
+        // the hole is filled
+        let grown = frame.buffer(3., JoinStyle::Miter, tolerance)?;
+        assert_eq!(grown.len(), 1);
+        assert!(grown[0].interiors().is_empty());
+        assert!((grown[0].area() - 256.).abs() < 1e-6);
+        Ok(())
+    }
+}
@@ -169,15 +169,21 @@ impl PointObject {
                         break;
                     }
                 }
-                Event::Start(bytes_start) => {
-                    if matches!(bytes_start.local_name().as_ref(), b"tags") {
-                        tags = super::parse_tags(reader)?;
+                Event::Start(bytes_start) => match bytes_start.local_name().as_ref() {
+                    b"tags" => tags = super::parse_tags(reader)?,
+                    b"coord" => {
+                        let (coord, _) = super::parse_file_coord(&bytes_start)?;
+                        point = Some(Point::from(from_file_coords(coord)));
                     }
-                }
+                    _ => (),
+                },
                 Event::Text(bytes_text) => {
                     let raw_xml = str::from_utf8(bytes_text.as_ref())?;
 
-                    for vertex in raw_xml.split_terminator(';') {
+                    for vertex in raw_xml
+                        .split_terminator(';')
+                        .filter(|vertex| !vertex.trim().is_empty())
+                    {
                         let mut split = vertex.split_whitespace();
 
                         let x: i32 = split
@@ -0,0 +1,357 @@
+use std::f64::consts::FRAC_PI_4;
+
+use geo_types::{Coord, LineString};
+
+use super::{
+    AreaObject, BezierPath, BezierPolygon, BezierSegment, BezierString, LineObject, MapObject,
+    measure::{cubic_point, cubic_points},
+    offset::directions,
+};
+use crate::{NonNegativeF64, Result};
+
+/// Vertices where a path turns by more than this angle, in radians, are kept
+/// as corners.
+const CORNER_ANGLE: f64 = FRAC_PI_4;
+
+/// The fewest and most points a curve is flattened into before it is refitted.
+const CURVE_POINTS: (usize, usize) = (8, 64);
+
+/// The number of vertices of an object before and after it was simplified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexCounts {
+    /// The number of vertices before.
+    pub before: usize,
+    /// The number of vertices after.
+    pub after: usize,
+}
+
+/// An object of a map that was simplified by [`crate::Omap::simplify_objects`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimplifiedObject {
+    /// The index of the object's part in [`crate::Omap::parts`].
+    pub part_index: usize,
+    /// The index of the object in its part, see [`crate::parts::MapPart::get`].
+    pub object_index: usize,
+    /// The number of vertices of the object before and after.
+    pub vertices: VertexCounts,
+}
+
+impl BezierPath {
+    /// Simplify the path to fewer vertices within `allowed_error` mm, e.g. to
+    /// clean up noisy lines traced from laser scans.
+    ///
+    /// Dash points and corners, where the path turns by more than 45° between
+    /// segments longer than `allowed_error`, are kept as they are. The parts of
+    /// the path between them are flattened and refitted with cubic curves, or
+    /// replaced by a straight segment where they are within `allowed_error` of
+    /// one. A part keeps its segments if refitting does not make it shorter. A
+    /// closed path starts at its first kept vertex.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `allowed_error` is too small for fitting.
+    pub fn simplify(&self, allowed_error: NonNegativeF64) -> Result<Self> {
+        let segments = &self.geometry.0;
+        let num_segments = segments.len();
+        if num_segments == 0 {
+            return Ok(self.clone());
+        }
+        let tolerance = allowed_error.get();
+        let is_closed = self.is_closed();
+        let is_corner = |before: &BezierSegment, after: &BezierSegment| {
+            let (points_before, points_after) = (cubic_points(before), cubic_points(after));
+            match (directions(points_before), directions(points_after)) {
+                (Some((_, incoming)), Some((outgoing, _))) => {
+                    dot(incoming, outgoing) < CORNER_ANGLE.cos()
+                        && polygon_length(points_before) > tolerance
+                        && polygon_length(points_after) > tolerance
+                }
+                _ => false,
+            }
+        };
+        let is_kept = (0..num_segments)
+            .map(|index| {
+                self.vertex_is_dash_point[index]
+                    || match index {
+                        0 => !is_closed || is_corner(&segments[num_segments - 1], &segments[0]),
+                        _ => is_corner(&segments[index - 1], &segments[index]),
+                    }
+            })
+            .collect::<Vec<_>>();
+
+        let start = is_kept.iter().position(|is_kept| *is_kept).unwrap_or(0);
+        let mut geometry = Vec::new();
+        let mut vertex_is_dash_point = vec![self.vertex_is_dash_point[start]];
+        let mut part = Vec::new();
+        for offset in 0..num_segments {
+            let index = (start + offset) % num_segments;
+            part.push(&segments[index]);
+            let end = index + 1;
+            if offset == num_segments - 1 || is_kept[end % num_segments] {
+                let fitted = refit(&part, tolerance)?;
+                vertex_is_dash_point.extend(std::iter::repeat_n(false, fitted.len() - 1));
+                vertex_is_dash_point.push(self.vertex_is_dash_point[end]);
+                geometry.extend(fitted);
+                part.clear();
+            }
+        }
+        Self::new(BezierString::new(geometry), vertex_is_dash_point)
+    }
+}
+
+impl BezierPolygon {
+    /// Simplify every ring of the polygon within `allowed_error` mm, see
+    /// [`BezierPath::simplify`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `allowed_error` is too small for fitting.
+    pub fn simplify(&self, allowed_error: NonNegativeF64) -> Result<Self> {
+        Self::new(
+            self.exterior().simplify(allowed_error)?,
+            self.interiors()
+                .iter()
+                .map(|ring| ring.simplify(allowed_error))
+                .collect::<Result<_>>()?,
+        )
+    }
+
+    fn num_vertices(&self) -> usize {
+        std::iter::once(self.exterior())
+            .chain(self.interiors())
+            .map(BezierPath::num_vertices)
+            .sum()
+    }
+}
+
+impl LineObject {
+    /// Simplify the line within `allowed_error` mm and return its vertex counts
+    /// before and after. See [`BezierPath::simplify`] for more docs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `allowed_error` is too small for fitting. The line
+    /// is unchanged on failure.
+    pub fn simplify_in_place(&mut self, allowed_error: NonNegativeF64) -> Result<VertexCounts> {
+        let before = self.geometry().num_vertices();
+        *self.geometry_mut() = self.geometry().simplify(allowed_error)?;
+        Ok(VertexCounts {
+            before,
+            after: self.geometry().num_vertices(),
+        })
+    }
+}
+
+impl AreaObject {
+    /// Simplify the area within `allowed_error` mm and return the vertex counts
+    /// of all its rings before and after. See [`BezierPath::simplify`] for more
+    /// docs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `allowed_error` is too small for fitting. The area
+    /// is unchanged on failure.
+    pub fn simplify_in_place(&mut self, allowed_error: NonNegativeF64) -> Result<VertexCounts> {
+        let before = self.geometry().num_vertices();
+        *self.geometry_mut() = self.geometry().simplify(allowed_error)?;
+        Ok(VertexCounts {
+            before,
+            after: self.geometry().num_vertices(),
+        })
+    }
+}
+
+impl MapObject {
+    /// Simplify a line or area within `allowed_error` mm and return its vertex
+    /// counts before and after, or `None` for points and texts. See
+    /// [`BezierPath::simplify`] for more docs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `allowed_error` is too small for fitting. The
+    /// object is unchanged on failure.
+    pub fn simplify_in_place(
+        &mut self,
+        allowed_error: NonNegativeF64,
+    ) -> Result<Option<VertexCounts>> {
+        match self {
+            Self::Line(object) => object.simplify_in_place(allowed_error).map(Some),
+            Self::Area(object) => object.simplify_in_place(allowed_error).map(Some),
+            Self::Point(_) | Self::Text(_) => Ok(None),
+        }
+    }
+}
+
+/// Refit consecutive segments between kept vertices with fewer segments, or
+/// return them as they are.
+fn refit(segments: &[&BezierSegment], tolerance: f64) -> Result<Vec<BezierSegment>> {
+    let unchanged = || segments.iter().map(|segment| (*segment).clone()).collect();
+    let mut points = segments
+        .first()
+        .map(|segment| segment.start())
+        .into_iter()
+        .collect::<Vec<_>>();
+    for segment in segments {
+        if segment.handles().is_none() {
+            points.push(segment.end());
+            continue;
+        }
+        let cubic = cubic_points(segment);
+        let count = ((polygon_length(cubic) / tolerance).ceil() as usize)
+            .clamp(CURVE_POINTS.0, CURVE_POINTS.1);
+        points.extend((1..=count).map(|index| cubic_point(cubic, index as f64 / count as f64)));
+    }
+    points.dedup();
+    let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+        return Ok(unchanged());
+    };
+    if points.len() < 2 {
+        return Ok(unchanged());
+    }
+    if first != last
+        && points
+            .iter()
+            .all(|point| segment_distance(*point, first, last) <= tolerance)
+    {
+        return Ok(vec![BezierSegment::new(first, None, last)]);
+    }
+    let fitted = BezierString::from_line_string(LineString::new(points), tolerance)?;
+    Ok(if fitted.num_segments() < segments.len() {
+        fitted.0
+    } else {
+        unchanged()
+    })
+}
+
+/// The length of the control polygon of a cubic curve.
+fn polygon_length(points: [Coord; 4]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| length(pair[1] - pair[0]))
+        .sum()
+}
+
+/// The distance from a point to a straight segment.
+fn segment_distance(point: Coord, start: Coord, end: Coord) -> f64 {
+    let direction = end - start;
+    let t = (dot(point - start, direction) / dot(direction, direction)).clamp(0., 1.);
+    length(start + direction * t - point)
+}
+
+fn dot(a: Coord, b: Coord) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+fn length(vector: Coord) -> f64 {
+    vector.x.hypot(vector.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use geo_types::{Coord, LineString, Polygon};
+
+    use crate::{
+        Code, NonNegativeF64, Result,
+        objects::{
+            AreaObject, BezierPath, BezierPolygon, LineObject,
+            edit::closest_segment_parameter,
+            measure::{cubic_point, cubic_points},
+        },
+        symbols::{AreaSymbol, LineSymbol, WeakAreaPathSymbol, WeakLinePathSymbol},
+    };
+
+    /// A wavy line traced with 0.01 mm of noise, ending in a sharp corner.
+    fn traced_line() -> LineString {
+        let mut points = (0..=100)
+            .map(|index| {
+                let x = f64::from(index) * 0.5;
+                let noise = if index % 2 == 0 { 0.01 } else { -0.01 };
+                (x, 5. * (x / 8.).sin() + noise)
+            })
+            .collect::<Vec<_>>();
+        points.push((50., -20.));
+        LineString::from(points)
+    }
+
+    fn distance(path: &BezierPath, coord: Coord) -> f64 {
+        path.geometry()
+            .segments()
+            .map(|segment| {
+                let point = cubic_point(
+                    cubic_points(segment),
+                    closest_segment_parameter(segment, coord),
+                );
+                (point.x - coord.x).hypot(point.y - coord.y)
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn simplifies_lines_keeping_corners_and_dash_points() -> Result<()> {
+        let traced = traced_line();
+        let symbol = Rc::new(RefCell::new(LineSymbol::new(Code::new(506, 0, 0), "Path")));
+        let mut line = LineObject::new(
+            WeakLinePathSymbol::Line(Rc::downgrade(&symbol)),
+            traced.clone(),
+        );
+        line.geometry_mut().set_vertex_is_dash_point(40, true);
+
+        let counts = line.simplify_in_place(NonNegativeF64::clamped_from(0.1))?;
+        assert_eq!(counts.before, 102);
+        assert!(counts.after < 15, "{counts:?}");
+        for coord in &traced {
+            assert!(distance(line.geometry(), *coord) < 0.1 + 1e-9);
+        }
+
+        // the dash point and the corner stay vertices
+        let segments = &line.geometry().geometry().0;
+        let vertices = segments
+            .iter()
+            .map(|segment| segment.start())
+            .chain(segments.last().map(|segment| segment.end()))
+            .collect::<Vec<_>>();
+        for (index, is_dash_point) in [(40, true), (100, false)] {
+            let Some(position) = vertices.iter().position(|vertex| *vertex == traced[index]) else {
+                panic!("vertex {index} is kept");
+            };
+            assert_eq!(
+                line.geometry().vertex_is_dash_point()[position],
+                is_dash_point
+            );
+        }
+        assert_eq!(segments.last().map(|segment| segment.handles()), Some(None));
+        Ok(())
+    }
+
+    #[test]
+    fn simplifies_areas_without_adding_vertices() -> Result<()> {
+        let symbol = Rc::new(RefCell::new(AreaSymbol::new(
+            Code::new(406, 0, 0),
+            "Forest",
+        )));
+        // a noisy circle and a square hole, which is already as simple as it gets
+        let circle = (0..=72)
+            .map(|index| {
+                let angle = f64::from(index % 72).to_radians() * 5.;
+                let radius = if index % 2 == 0 { 20. } else { 19.98 };
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect::<Vec<_>>();
+        let hole = LineString::from(vec![(-5., -5.), (-5., 5.), (5., 5.), (5., -5.), (-5., -5.)]);
+        let mut area = AreaObject::new(
+            WeakAreaPathSymbol::Area(Rc::downgrade(&symbol)),
+            BezierPolygon::from(Polygon::new(LineString::from(circle), vec![hole])),
+        );
+        let area_before = area.geometry().area();
+
+        let counts = area.simplify_in_place(NonNegativeF64::clamped_from(0.05))?;
+        assert_eq!(counts.before, 78);
+        assert!(counts.after < 20, "{counts:?}");
+        assert_eq!(area.geometry().interiors()[0].num_vertices(), 5);
+        assert!(area.geometry().exterior().is_closed());
+        assert!((area.geometry().area() - area_before).abs() < 0.05 * 2. * 20. * 3.2);
+        Ok(())
+    }
+}
@@ -289,6 +289,7 @@ impl TextObject {
         let mut text_geo = TextGeometry::SingleAnchor(Coord::default());
         let mut tags = HashMap::new();
         let mut text = String::new();
+        let mut coord_count = 0;
         let mut buf = Vec::new();
         loop {
             match reader.read_event_into(&mut buf)? {
@@ -309,6 +310,11 @@ impl TextObject {
                             Some(2) => text_geo = TextGeometry::WrapBox(WrapBox::default()),
                             _ => return Err(Error::ObjectError),
                         },
+                        b"coord" => {
+                            let (coord, _) = super::parse_file_coord(&bytes_start)?;
+                            set_file_coord(&mut text_geo, coord_count, coord);
+                            coord_count += 1;
+                        }
                         b"text" => text = notes::parse(reader)?,
                         _ => (),
                     }
@@ -321,6 +327,10 @@ impl TextObject {
                 Event::Text(bytes_text) => {
                     // parse the text location
                     let raw_xml = str::from_utf8(bytes_text.as_ref())?;
+                    if raw_xml.trim().is_empty() {
+                        // indentation between the child elements
+                        continue;
+                    }
 
                     if let Some((coords_str, opt_wh)) = raw_xml.split_once(';') {
                         let mut split = coords_str.split_whitespace();
@@ -385,3 +395,19 @@ impl TextObject {
         })
     }
 }
+
+/// Set the coordinate at `index` of the `<coord>` elements of a text object,
+/// the anchor followed by the box size for wrapped text.
+fn set_file_coord(geometry: &mut TextGeometry, index: usize, coord: Coord<i32>) {
+    match geometry {
+        TextGeometry::SingleAnchor(point) => *point = from_file_coords(coord),
+        TextGeometry::WrapBox(wrap_box) if index == 0 => {
+            wrap_box.anchor = from_file_coords(coord);
+        }
+        TextGeometry::WrapBox(wrap_box) => {
+            let size = |value| u32::try_from(value).unwrap_or(0);
+            wrap_box.width = NonNegativeF64::from_file_value(size(coord.x));
+            wrap_box.height = NonNegativeF64::from_file_value(size(coord.y));
+        }
+    }
+}
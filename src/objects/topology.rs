@@ -0,0 +1,473 @@
+use geo_types::Coord;
+
+use super::{
+    BezierPath, BezierPolygon, BezierSegment, BezierString, MapObject, boolean,
+    edit::{move_end, move_start},
+    measure::cubic_points,
+};
+
+/// Vertices closer than this, in mm, are taken as one.
+const TOLERANCE: f64 = 1e-6;
+
+/// A problem with the geometry of a line or area.
+///
+/// Rings are numbered from 0 for the path of a line or the exterior of an
+/// area, followed by the holes of an area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeometryIssue {
+    /// A vertex repeats the vertex before it, joined by a straight segment of
+    /// zero length.
+    DuplicateVertex {
+        /// The ring of the segment.
+        ring: usize,
+        /// The index of the segment in its ring.
+        segment: usize,
+        /// The repeated vertex.
+        location: Coord,
+    },
+    /// A curve whose control points all coincide, so that it has no length.
+    DegenerateCurve {
+        /// The ring of the curve.
+        ring: usize,
+        /// The index of the curve in its ring.
+        segment: usize,
+        /// The point the curve collapses to.
+        location: Coord,
+    },
+    /// A ring runs the wrong way round. Exteriors run counter-clockwise and
+    /// holes clockwise, like the results of [`BezierPolygon::union`].
+    ///
+    /// Only reported by [`BezierPolygon::orientation_issues`], as Mapper
+    /// itself has no convention for the orientation of rings.
+    WrongOrientation {
+        /// The ring.
+        ring: usize,
+        /// The start of the ring.
+        location: Coord,
+    },
+    /// A ring crosses or touches itself.
+    SelfIntersection {
+        /// The ring.
+        ring: usize,
+        /// Where the ring meets itself.
+        location: Coord,
+    },
+    /// A hole crosses or touches the exterior.
+    HoleCrossesExterior {
+        /// The ring of the hole.
+        ring: usize,
+        /// Where the hole meets the exterior.
+        location: Coord,
+    },
+    /// Two holes cross or touch each other.
+    HolesCross {
+        /// The rings of the holes.
+        rings: (usize, usize),
+        /// Where the holes meet.
+        location: Coord,
+    },
+    /// A hole lies outside the exterior.
+    HoleOutsideExterior {
+        /// The ring of the hole.
+        ring: usize,
+        /// The start of the hole.
+        location: Coord,
+    },
+}
+
+impl GeometryIssue {
+    /// Where the issue is, in map coordinates.
+    pub fn location(&self) -> Coord {
+        match self {
+            Self::DuplicateVertex { location, .. }
+            | Self::DegenerateCurve { location, .. }
+            | Self::WrongOrientation { location, .. }
+            | Self::SelfIntersection { location, .. }
+            | Self::HoleCrossesExterior { location, .. }
+            | Self::HolesCross { location, .. }
+            | Self::HoleOutsideExterior { location, .. } => *location,
+        }
+    }
+
+    /// Whether the issue is fixed by [`MapObject::repair_geometry`], or
+    /// [`MapObject::repair_orientation`] for [`Self::WrongOrientation`],
+    /// without changing the shape of the object.
+    pub fn is_repairable(&self) -> bool {
+        matches!(
+            self,
+            Self::DuplicateVertex { .. }
+                | Self::DegenerateCurve { .. }
+                | Self::WrongOrientation { .. }
+        )
+    }
+}
+
+/// A geometry issue of an object of a map, found by
+/// [`crate::Omap::geometry_issues`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectIssue {
+    /// The index of the object's part in [`crate::Omap::parts`].
+    pub part_index: usize,
+    /// The index of the object in its part, see [`crate::parts::MapPart::get`].
+    pub object_index: usize,
+    /// The issue.
+    pub issue: GeometryIssue,
+}
+
+impl BezierPath {
+    /// The duplicate vertices and degenerate curves of the path as a line. See
+    /// [`BezierPolygon::geometry_issues`] for the issues of rings.
+    pub fn geometry_issues(&self) -> Vec<GeometryIssue> {
+        segment_issues(self, 0)
+    }
+
+    /// Remove the duplicate vertices and degenerate curves of the path, keeping
+    /// their dash points on the vertices that remain, and return the issues that
+    /// remain. A path of only such segments is left as it is.
+    pub fn repair(&mut self) -> Vec<GeometryIssue> {
+        remove_zero_length_segments(self);
+        self.geometry_issues()
+    }
+}
+
+impl BezierPolygon {
+    /// Check the topology of the polygon: duplicate vertices, degenerate
+    /// curves, rings crossing themselves, holes crossing the
+    /// exterior or each other and holes outside the exterior.
+    pub fn geometry_issues(&self) -> Vec<GeometryIssue> {
+        let exterior = self.exterior();
+        let rings = std::iter::once(exterior)
+            .chain(self.interiors())
+            .collect::<Vec<_>>();
+        let mut issues = Vec::new();
+        for (ring, path) in rings.iter().enumerate() {
+            issues.extend(segment_issues(path, ring));
+            let Some(start) = path.geometry().segments().next().map(BezierSegment::start) else {
+                continue;
+            };
+            issues.extend(
+                boolean::crossings(path, None)
+                    .into_iter()
+                    .map(|location| GeometryIssue::SelfIntersection { ring, location }),
+            );
+            if ring == 0 || exterior.is_empty() {
+                continue;
+            }
+            let crossings = boolean::crossings(path, Some(exterior));
+            if crossings.is_empty() && !boolean::ring_contains(exterior, start) {
+                issues.push(GeometryIssue::HoleOutsideExterior {
+                    ring,
+                    location: start,
+                });
+            }
+            issues.extend(
+                crossings
+                    .into_iter()
+                    .map(|location| GeometryIssue::HoleCrossesExterior { ring, location }),
+            );
+            for (other, other_path) in rings.iter().enumerate().take(ring).skip(1) {
+                issues.extend(boolean::crossings(other_path, Some(path)).into_iter().map(
+                    |location| GeometryIssue::HolesCross {
+                        rings: (other, ring),
+                        location,
+                    },
+                ));
+            }
+        }
+        issues
+    }
+
+    /// Fix the issues that do not change the shape of the polygon: remove
+    /// duplicate vertices and degenerate curves like [`BezierPath::repair`].
+    /// Returns the issues that remain.
+    pub fn repair(&mut self) -> Vec<GeometryIssue> {
+        remove_zero_length_segments(self.exterior_mut());
+        for hole in self.interiors_mut() {
+            remove_zero_length_segments(hole);
+        }
+        self.geometry_issues()
+    }
+
+    /// The rings running the wrong way round: exteriors that are not
+    /// counter-clockwise and holes that are not clockwise.
+    ///
+    /// This check is not part of [`Self::geometry_issues`] as Mapper accepts
+    /// rings of either orientation and writes them as drawn, but some
+    /// consumers of exported geometry expect the orientation of
+    /// [`BezierPolygon::union`].
+    pub fn orientation_issues(&self) -> Vec<GeometryIssue> {
+        std::iter::once(self.exterior())
+            .chain(self.interiors())
+            .enumerate()
+            .filter_map(|(ring, path)| {
+                let location = path.geometry().segments().next()?.start();
+                let area = path.signed_area();
+                ((ring == 0 && area < -TOLERANCE) || (ring > 0 && area > TOLERANCE))
+                    .then_some(GeometryIssue::WrongOrientation { ring, location })
+            })
+            .collect()
+    }
+
+    /// Reverse the rings with the wrong orientation, see
+    /// [`Self::orientation_issues`].
+    pub fn repair_orientation(&mut self) {
+        let exterior = self.exterior_mut();
+        if exterior.signed_area() < -TOLERANCE {
+            exterior.reverse();
+        }
+        for hole in self.interiors_mut() {
+            if hole.signed_area() > TOLERANCE {
+                hole.reverse();
+            }
+        }
+    }
+}
+
+impl MapObject {
+    /// The geometry issues of a line or area, none for points and texts. See
+    /// [`BezierPolygon::geometry_issues`] for more docs.
+    pub fn geometry_issues(&self) -> Vec<GeometryIssue> {
+        match self {
+            Self::Line(object) => object.geometry().geometry_issues(),
+            Self::Area(object) => object.geometry().geometry_issues(),
+            Self::Point(_) | Self::Text(_) => Vec::new(),
+        }
+    }
+
+    /// Fix the geometry issues of a line or area that do not change its shape
+    /// and return the issues that remain. See [`BezierPolygon::repair`] for
+    /// more docs.
+    pub fn repair_geometry(&mut self) -> Vec<GeometryIssue> {
+        match self {
+            Self::Line(object) => object.geometry_mut().repair(),
+            Self::Area(object) => object.geometry_mut().repair(),
+            Self::Point(_) | Self::Text(_) => Vec::new(),
+        }
+    }
+
+    /// The rings of an area with the wrong orientation, none for other
+    /// objects. See [`BezierPolygon::orientation_issues`] for more docs.
+    pub fn orientation_issues(&self) -> Vec<GeometryIssue> {
+        match self {
+            Self::Area(object) => object.geometry().orientation_issues(),
+            Self::Point(_) | Self::Line(_) | Self::Text(_) => Vec::new(),
+        }
+    }
+
+    /// Reverse the rings of an area with the wrong orientation. See
+    /// [`BezierPolygon::repair_orientation`] for more docs.
+    pub fn repair_orientation(&mut self) {
+        if let Self::Area(object) = self {
+            object.geometry_mut().repair_orientation();
+        }
+    }
+}
+
+fn segment_issues(path: &BezierPath, ring: usize) -> Vec<GeometryIssue> {
+    path.geometry()
+        .segments()
+        .enumerate()
+        .filter(|(_, segment)| is_zero_length(segment))
+        .map(|(segment, bezier)| match bezier.handles() {
+            Some(_) => GeometryIssue::DegenerateCurve {
+                ring,
+                segment,
+                location: bezier.start(),
+            },
+            None => GeometryIssue::DuplicateVertex {
+                ring,
+                segment,
+                location: bezier.start(),
+            },
+        })
+        .collect()
+}
+
+fn is_zero_length(segment: &BezierSegment) -> bool {
+    let points = cubic_points(segment);
+    points[1..].iter().all(|point| {
+        let offset = *point - points[0];
+        offset.x.hypot(offset.y) <= TOLERANCE
+    })
+}
+
+/// Remove the segments of zero length from a path, moving their dash points to
+/// the vertex before them, unless no other segment remains.
+fn remove_zero_length_segments(path: &mut BezierPath) {
+    let is_closed = path.is_closed();
+    let mut segments = Vec::<BezierSegment>::new();
+    let mut vertex_is_dash_point = path
+        .vertex_is_dash_point
+        .first()
+        .copied()
+        .into_iter()
+        .collect::<Vec<_>>();
+    for (segment, is_dash_point) in path
+        .geometry
+        .segments()
+        .zip(path.vertex_is_dash_point.iter().skip(1))
+    {
+        if is_zero_length(segment) {
+            if let Some(last) = vertex_is_dash_point.last_mut() {
+                *last |= is_dash_point;
+            }
+            continue;
+        }
+        // the removed segments may have been up to the tolerance long
+        let segment = match segments.last() {
+            Some(last) if last.end() != segment.start() => move_start(segment, last.end()),
+            _ => segment.clone(),
+        };
+        segments.push(segment);
+        vertex_is_dash_point.push(*is_dash_point);
+    }
+    if segments.is_empty() || segments.len() == path.num_segments() {
+        return;
+    }
+    if is_closed {
+        let start = segments[0].start();
+        let last = segments.len() - 1;
+        segments[last] = move_end(&segments[last], start);
+        let seam = vertex_is_dash_point[0] || vertex_is_dash_point[last + 1];
+        vertex_is_dash_point[0] = seam;
+        vertex_is_dash_point[last + 1] = seam;
+    }
+    path.geometry = BezierString::new(segments);
+    path.vertex_is_dash_point = vertex_is_dash_point;
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{Coord, LineString, Polygon, coord};
+
+    use super::GeometryIssue;
+    use crate::{
+        Result,
+        objects::{BezierPath, BezierPolygon, BezierSegment, BezierString},
+    };
+
+    fn square(min: f64, max: f64) -> LineString {
+        LineString::from(vec![
+            (min, min),
+            (max, min),
+            (max, max),
+            (min, max),
+            (min, min),
+        ])
+    }
+
+    #[test]
+    fn finds_self_intersections() {
+        let bow_tie = BezierPolygon::from(Polygon::new(
+            LineString::from(vec![(0., 0.), (10., 10.), (10., 0.), (0., 10.), (0., 0.)]),
+            Vec::new(),
+        ));
+
+        let issues = bow_tie.geometry_issues();
+
+        let [GeometryIssue::SelfIntersection { ring: 0, location }] = issues.as_slice() else {
+            panic!("expected one self intersection, got {issues:?}");
+        };
+        assert!((location.x - 5.).abs() < 1e-9 && (location.y - 5.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repairs_zero_length_segments_keeping_dash_points() -> Result<()> {
+        let point = |x, y| Coord { x, y };
+        let geometry = BezierString::new(vec![
+            BezierSegment::new(point(0., 0.), None, point(5., 0.)),
+            BezierSegment::new(point(5., 0.), None, point(5., 0.)),
+            BezierSegment::new(
+                point(5., 0.),
+                Some((point(5., 0.), point(5., 0.))),
+                point(5., 0.),
+            ),
+            BezierSegment::new(point(5., 0.), None, point(10., 0.)),
+        ]);
+        let mut path = BezierPath::new(geometry, vec![false, false, true, false, false])?;
+
+        let issues = path.geometry_issues();
+        assert!(matches!(
+            issues.as_slice(),
+            [
+                GeometryIssue::DuplicateVertex { segment: 1, .. },
+                GeometryIssue::DegenerateCurve { segment: 2, .. },
+            ]
+        ));
+        assert!(issues.iter().all(GeometryIssue::is_repairable));
+
+        assert_eq!(path.repair(), Vec::new());
+        assert_eq!(path.num_segments(), 2);
+        assert_eq!(path.vertex_is_dash_point, vec![false, true, false]);
+        path.validate()
+    }
+
+    #[test]
+    fn accepts_rings_of_either_orientation() {
+        let exterior = square(0., 10.);
+        let mut hole = square(2., 4.);
+        hole.0.reverse();
+        let polygon = BezierPolygon::from(Polygon::new(exterior.clone(), vec![hole.clone()]));
+        assert_eq!(polygon.geometry_issues(), Vec::new());
+
+        let reversed = BezierPolygon::from(Polygon::new(
+            exterior.into_iter().rev().collect(),
+            vec![hole.into_iter().rev().collect()],
+        ));
+        assert_eq!(reversed.geometry_issues(), Vec::new());
+    }
+
+    #[test]
+    fn reverses_rings_with_the_wrong_orientation() {
+        let mut exterior = square(0., 10.);
+        exterior.0.reverse();
+        let mut hole = square(2., 4.);
+        hole.0.reverse();
+        let mut polygon = BezierPolygon::from(Polygon::new(exterior, vec![hole]));
+
+        assert_eq!(
+            polygon.orientation_issues(),
+            vec![GeometryIssue::WrongOrientation {
+                ring: 0,
+                location: coord! { x: 0., y: 0. },
+            }]
+        );
+        polygon.repair_orientation();
+        assert_eq!(polygon.orientation_issues(), Vec::new());
+        assert!(polygon.exterior().signed_area() > 0.);
+        assert!(polygon.interiors()[0].signed_area() < 0.);
+    }
+
+    #[test]
+    fn finds_holes_outside_or_crossing_the_exterior() {
+        let mut outside = square(20., 22.);
+        outside.0.reverse();
+        let mut crossing = square(8., 12.);
+        crossing.0.reverse();
+        let mut polygon =
+            BezierPolygon::from(Polygon::new(square(0., 10.), vec![outside, crossing]));
+
+        let issues = polygon.repair();
+
+        assert_eq!(issues.len(), 3);
+        assert_eq!(
+            issues[0],
+            GeometryIssue::HoleOutsideExterior {
+                ring: 1,
+                location: coord! { x: 20., y: 20. },
+            }
+        );
+        let mut crossings = issues[1..]
+            .iter()
+            .map(|issue| match issue {
+                GeometryIssue::HoleCrossesExterior { ring: 2, location } => {
+                    (location.x.round(), location.y.round())
+                }
+                _ => panic!("expected the hole to cross the exterior, got {issue:?}"),
+            })
+            .collect::<Vec<_>>();
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        assert_eq!(crossings, vec![(8., 10.), (10., 8.)]);
+        assert!(issues.iter().all(|issue| !issue.is_repairable()));
+    }
+}
@@ -0,0 +1,1808 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    rc::Rc,
+};
+
+use geo_types::Coord;
+
+use super::{
+    OcdSymbolFeature, OcdWarning,
+    format::{
+        COORD_SIZE, DESCRIPTION_UTF16_CAPACITY, ELEMENT_HEADER_SIZE, FILE_HEADER_SIZE,
+        INDEX_BLOCK_ENTRIES, OCAD_MARK, OcdFormat, OcdPoint, OcdRecord, SYMBOL_ENTRY_SIZE,
+        X_FLAG_CURVE_FIRST_HANDLE, X_FLAG_CURVE_SECOND_HANDLE, Y_FLAG_DASH_POINT,
+        Y_FLAG_HOLE_START, angle_to_ocd, area_symbol, base_symbol, element, header, length_to_ocd,
+        line_symbol, object, object_entry, object_type, point_symbol, string_entry, string_type,
+        symbol_flags, symbol_type, text_symbol,
+    },
+};
+use crate::{
+    Code, Omap, Result,
+    colors::{Color, SpotColor, SymbolColor},
+    objects::{
+        BezierPath, BezierPolygon, HorizontalAlign, MapObject, TextGeometry, TextObject,
+        VerticalAlign,
+    },
+    symbols::{
+        AreaOrLineSymbol, AreaSymbol, BorderStyle, CapStyle, DashStyle, Element, FillPattern,
+        FramingMode, GroupDashes, JoinStyle, LineSymbol, PointSymbol, PublicOrPrivateSymbol,
+        Symbol, SymbolCommon, TextSymbol, WeakSymbol,
+    },
+};
+
+/// The OCAD version of written files.
+const VERSION: u16 = 12;
+/// The layout of [`VERSION`].
+const FORMAT: OcdFormat = OcdFormat::V12;
+/// The name of the color added for symbols drawn in registration black.
+const REGISTRATION_BLACK: &str = "Registration black";
+
+/// Write the map as an OCAD 12 file.
+pub(crate) fn write<W: Write>(omap: &Omap, writer: &mut W) -> Result<Vec<OcdWarning>> {
+    omap.validate()?;
+
+    let mut exporter = Exporter {
+        omap,
+        targets: HashMap::new(),
+        used_numbers: HashSet::new(),
+        text_alignments: text_alignments(omap),
+        symbol_colors: Vec::new(),
+        uses_registration_black: false,
+        warnings: Vec::new(),
+    };
+    if omap.parts.len() > 1 {
+        exporter.warnings.push(OcdWarning::PartsMerged {
+            num_parts: omap.parts.len(),
+        });
+    }
+    let symbols = exporter.write_symbols()?;
+    let objects = exporter.write_objects()?;
+    let strings = exporter.parameter_strings()?;
+
+    writer.write_all(&build_file(&strings, &symbols, &objects)?)?;
+    writer.flush()?;
+    Ok(exporter.warnings)
+}
+
+/// The kind of objects an OCAD symbol is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetKind {
+    Point,
+    Line,
+    Area,
+    Text,
+}
+
+/// An OCAD symbol written for an OMAP symbol.
+#[derive(Debug, Clone, Copy)]
+struct Target {
+    number: i32,
+    kind: TargetKind,
+}
+
+/// An object ready to be written with its index entry.
+struct OcdObject {
+    number: i32,
+    object_type: u8,
+    angle: i16,
+    points: Vec<OcdPoint>,
+    /// Nul-terminated UTF-16 text padded to whole coordinates.
+    text: Vec<u8>,
+}
+
+struct Exporter<'a> {
+    omap: &'a Omap,
+    /// The OCAD symbols written for every OMAP symbol, several for combined symbols.
+    targets: HashMap<WeakSymbol, Vec<Target>>,
+    /// Symbol numbers already taken.
+    used_numbers: HashSet<i32>,
+    /// The alignment of every text symbol, which OMAP stores on the objects.
+    text_alignments: HashMap<WeakSymbol, u16>,
+    /// The color numbers referenced by the symbol being written.
+    symbol_colors: Vec<u16>,
+    uses_registration_black: bool,
+    warnings: Vec<OcdWarning>,
+}
+
+/// Get the OCAD symbol number of a code, if it has one.
+fn symbol_number(code: Code) -> Option<i32> {
+    let factor = FORMAT.symbol_number_factor();
+    (code.patch == 0 && i32::from(code.minor) < factor)
+        .then(|| i32::from(code.major) * factor + i32::from(code.minor))
+}
+
+/// Get the OCAD alignment code of a text.
+fn alignment_code(h_align: HorizontalAlign, v_align: VerticalAlign) -> u16 {
+    let horizontal = match h_align {
+        HorizontalAlign::Left => 0,
+        HorizontalAlign::HCenter => 1,
+        HorizontalAlign::Right => 2,
+    };
+    let vertical = match v_align {
+        VerticalAlign::Baseline | VerticalAlign::Bottom => 0,
+        VerticalAlign::VCenter => 1,
+        VerticalAlign::Top => 2,
+    };
+    horizontal | (vertical << text_symbol::ALIGN_VERTICAL_SHIFT)
+}
+
+/// Take the alignment of every text symbol from its first object, preferring
+/// objects with a single anchor as text boxes are always aligned to the top.
+fn text_alignments(omap: &Omap) -> HashMap<WeakSymbol, u16> {
+    let mut alignments = HashMap::new();
+    for is_box in [false, true] {
+        for object in omap.iter_all_objects() {
+            if let MapObject::Text(text) = object
+                && matches!(text.geometry(), TextGeometry::WrapBox(_)) == is_box
+            {
+                let v_align = if is_box {
+                    VerticalAlign::Baseline
+                } else {
+                    text.v_align
+                };
+                alignments
+                    .entry(object.symbol())
+                    .or_insert_with(|| alignment_code(text.h_align, v_align));
+            }
+        }
+    }
+    alignments
+}
+
+/// Get the name of the separation printing a spot color.
+fn spot_name(spot: &SpotColor) -> &str {
+    if spot.spotcolor_name.is_empty() {
+        &spot.color_name
+    } else {
+        &spot.spotcolor_name
+    }
+}
+
+/// Format a fraction as a percentage with one decimal.
+fn percentage(value: f64) -> f64 {
+    (value * 1000.).round() / 10.
+}
+
+impl Exporter<'_> {
+    /// Get the OCAD color number of a symbol color and record its use by the current symbol.
+    fn color_number(&mut self, color: &SymbolColor) -> Option<u16> {
+        let number = match color {
+            SymbolColor::Color(weak) => {
+                u16::try_from(self.omap.colors.priority_of_weak_color(weak)?).ok()?
+            }
+            SymbolColor::RegistrationBlack => {
+                self.uses_registration_black = true;
+                self.registration_black_number()
+            }
+            SymbolColor::NoColor => return None,
+        };
+        if !self.symbol_colors.contains(&number) {
+            self.symbol_colors.push(number);
+        }
+        Some(number)
+    }
+
+    /// Registration black is written after all colors of the color set.
+    fn registration_black_number(&self) -> u16 {
+        u16::try_from(self.omap.colors.len()).unwrap_or(u16::MAX)
+    }
+
+    fn drop_feature(&mut self, code: Code, feature: OcdSymbolFeature) {
+        self.warnings
+            .push(OcdWarning::SymbolFeatureDropped { code, feature });
+    }
+
+    /// Take the next free symbol number after the one closest to `code`.
+    fn free_number(&mut self, code: Code) -> i32 {
+        let factor = FORMAT.symbol_number_factor();
+        let mut number = i32::from(code.major) * factor + i32::from(code.minor).min(factor - 1);
+        while !self.used_numbers.insert(number) {
+            number += 1;
+        }
+        number
+    }
+
+    fn parameter_strings(&self) -> Result<Vec<(i32, String)>> {
+        let colors = &self.omap.colors;
+        let spot_colors = colors
+            .iter()
+            .filter_map(|color| match color {
+                Color::SpotColor(spot) => Some(spot),
+                Color::MixedColor(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let cmyk_fields = |c: f64, m: f64, y: f64, k: f64| {
+            format!(
+                "\tc{}\tm{}\ty{}\tk{}",
+                percentage(c),
+                percentage(m),
+                percentage(y),
+                percentage(k)
+            )
+        };
+
+        let mut strings = Vec::new();
+        for (number, color) in colors.iter().enumerate() {
+            let cmyk = color.cmyk()?;
+            let (name, separations) = match color {
+                Color::SpotColor(spot) => {
+                    let spot = spot.try_borrow()?;
+                    (
+                        spot.color_name.clone(),
+                        format!("\ts{}\tp100", spot_name(&spot)),
+                    )
+                }
+                Color::MixedColor(mixed) => {
+                    let mixed = mixed.try_borrow()?;
+                    let mut separations = String::new();
+                    for component in &mixed.components {
+                        let Some(spot) = spot_colors
+                            .iter()
+                            .find(|spot| Rc::as_ptr(spot) == component.color.as_ptr())
+                        else {
+                            continue;
+                        };
+                        let spot = spot.try_borrow()?;
+                        separations.push_str(&format!(
+                            "\ts{}\tp{}",
+                            spot_name(&spot),
+                            percentage(component.factor.get())
+                        ));
+                    }
+                    (mixed.color_name.clone(), separations)
+                }
+            };
+            strings.push((
+                string_type::COLOR,
+                format!(
+                    "{name}\tn{number}{}{separations}",
+                    cmyk_fields(cmyk.c.get(), cmyk.m.get(), cmyk.y.get(), cmyk.k.get())
+                ),
+            ));
+        }
+        if self.uses_registration_black {
+            let mut separations = String::new();
+            for spot in &spot_colors {
+                let spot = spot.try_borrow()?;
+                separations.push_str(&format!("\ts{}\tp100", spot_name(&spot)));
+            }
+            strings.push((
+                string_type::COLOR,
+                format!(
+                    "{REGISTRATION_BLACK}\tn{}{}{separations}",
+                    self.registration_black_number(),
+                    cmyk_fields(1., 1., 1., 1.)
+                ),
+            ));
+        }
+        for (number, spot) in spot_colors.iter().enumerate() {
+            let spot = spot.try_borrow()?;
+            let cmyk = spot.cmyk()?;
+            strings.push((
+                string_type::SPOT_COLOR,
+                format!(
+                    "{}\tv1\tn{number}{}\tf{}\ta{}",
+                    spot_name(&spot),
+                    cmyk_fields(cmyk.c.get(), cmyk.m.get(), cmyk.y.get(), cmyk.k.get()),
+                    spot.screen_frequency.get(),
+                    spot.screen_angle_deg
+                ),
+            ));
+        }
+
+        // OCAD stores the real world coordinates of the paper origin
+        let geo_ref = &self.omap.geo_referencing;
+        let origin = geo_ref.create_transform().to_projected(Coord::zero());
+        strings.push((
+            string_type::SCALE,
+            format!(
+                "\tm{}\tx{}\ty{}\ta{}\tr1",
+                geo_ref.scale_denominator.get(),
+                origin.x,
+                origin.y,
+                geo_ref.grivation_deg()
+            ),
+        ));
+        Ok(strings)
+    }
+
+    /// Write every symbol, splitting combined symbols into their components.
+    fn write_symbols(&mut self) -> Result<Vec<OcdRecord>> {
+        let symbols = &self.omap.symbols;
+
+        // symbols keep the number of their code unless an earlier symbol took it
+        let mut numbers = HashMap::new();
+        for symbol in symbols.iter() {
+            if let Some(number) = symbol_number(symbol.common()?.code)
+                && self.used_numbers.insert(number)
+            {
+                let _previous = numbers.insert(symbol.downgrade(), number);
+            }
+        }
+
+        let mut records = Vec::with_capacity(symbols.len());
+        for symbol in symbols.iter() {
+            let weak = symbol.downgrade();
+            let (symbol_type, kind) = match symbol {
+                Symbol::Point(_) => (symbol_type::POINT, TargetKind::Point),
+                Symbol::Line(_) => (symbol_type::LINE, TargetKind::Line),
+                Symbol::Area(_) => (symbol_type::AREA, TargetKind::Area),
+                Symbol::Text(_) => (symbol_type::TEXT, TargetKind::Text),
+                Symbol::CombinedArea(_) | Symbol::CombinedLine(_) => continue,
+            };
+            let code = symbol.common()?.code;
+            let number = if let Some(number) = numbers.remove(&weak) {
+                number
+            } else {
+                let number = self.free_number(code);
+                self.warnings.push(OcdWarning::SymbolRenumbered {
+                    code,
+                    symbol_number: number,
+                });
+                number
+            };
+            let (is_rotatable, data) = match symbol {
+                Symbol::Point(point) => {
+                    let point = point.try_borrow()?;
+                    (point.is_rotatable, self.point_data(code, &point)?)
+                }
+                Symbol::Line(line) => {
+                    let line = line.try_borrow()?;
+                    (false, self.line_data(code, &line)?)
+                }
+                Symbol::Area(area) => {
+                    let area = area.try_borrow()?;
+                    (area.is_rotatable, self.area_data(code, &area)?)
+                }
+                Symbol::Text(text) => {
+                    let alignment = self.text_alignments.get(&weak).copied().unwrap_or(0);
+                    let text = text.try_borrow()?;
+                    (text.is_rotatable, self.text_data(code, &text, alignment))
+                }
+                Symbol::CombinedArea(_) | Symbol::CombinedLine(_) => continue,
+            };
+            let common = symbol.common()?.clone();
+            records.push(self.symbol_record(number, symbol_type, &common, is_rotatable, &data)?);
+            let _previous = self.targets.insert(weak, vec![Target { number, kind }]);
+        }
+
+        for symbol in symbols.iter() {
+            if matches!(symbol, Symbol::CombinedArea(_) | Symbol::CombinedLine(_)) {
+                let _targets =
+                    self.combined_targets(symbol, &mut numbers, &mut records, &mut HashSet::new())?;
+            }
+        }
+        Ok(records)
+    }
+
+    /// Resolve the OCAD symbols of a combined symbol, writing its private parts as symbols of
+    /// their own. The first private part takes the number of the combined symbol.
+    fn combined_targets(
+        &mut self,
+        symbol: &Symbol,
+        numbers: &mut HashMap<WeakSymbol, i32>,
+        records: &mut Vec<OcdRecord>,
+        visiting: &mut HashSet<WeakSymbol>,
+    ) -> Result<Vec<Target>> {
+        let weak = symbol.downgrade();
+        if let Some(targets) = self.targets.get(&weak) {
+            return Ok(targets.clone());
+        }
+        // a combined symbol containing itself draws nothing the second time
+        if !visiting.insert(weak.clone()) {
+            return Ok(Vec::new());
+        }
+        let components: Vec<PublicOrPrivateSymbol<WeakSymbol, AreaOrLineSymbol>> = match symbol {
+            Symbol::CombinedArea(combined) => combined
+                .try_borrow()?
+                .components()
+                .map(|component| match component {
+                    PublicOrPrivateSymbol::Public(weak) => {
+                        PublicOrPrivateSymbol::Public(weak.clone().into())
+                    }
+                    PublicOrPrivateSymbol::Private(private) => {
+                        PublicOrPrivateSymbol::Private(private.clone())
+                    }
+                })
+                .collect(),
+            Symbol::CombinedLine(combined) => combined
+                .try_borrow()?
+                .components()
+                .map(|component| match component {
+                    PublicOrPrivateSymbol::Public(weak) => {
+                        PublicOrPrivateSymbol::Public(weak.clone().into())
+                    }
+                    PublicOrPrivateSymbol::Private(line) => {
+                        PublicOrPrivateSymbol::Private(line.clone().into())
+                    }
+                })
+                .collect(),
+            _ => return Ok(self.targets.get(&weak).cloned().unwrap_or_default()),
+        };
+        let common = symbol.common()?.clone();
+        let mut reserved = numbers.remove(&weak);
+
+        let mut targets = Vec::new();
+        let mut num_private = 0;
+        for component in components {
+            match component {
+                PublicOrPrivateSymbol::Public(component) => {
+                    if let Some(component_targets) = self.targets.get(&component) {
+                        targets.extend(component_targets.iter().copied());
+                    } else if let Some(component) = component.upgrade() {
+                        targets
+                            .extend(self.combined_targets(&component, numbers, records, visiting)?);
+                    }
+                }
+                PublicOrPrivateSymbol::Private(private) => {
+                    num_private += 1;
+                    let number = reserved
+                        .take()
+                        .unwrap_or_else(|| self.free_number(common.code));
+                    let mut part_common = common.clone();
+                    if num_private > 1 {
+                        part_common.name = format!("{} ({num_private})", common.name);
+                    }
+                    let (symbol_type, kind, is_rotatable, data) = match &private {
+                        AreaOrLineSymbol::Area(area) => (
+                            symbol_type::AREA,
+                            TargetKind::Area,
+                            area.is_rotatable,
+                            self.area_data(common.code, area)?,
+                        ),
+                        AreaOrLineSymbol::Line(line) => (
+                            symbol_type::LINE,
+                            TargetKind::Line,
+                            false,
+                            self.line_data(common.code, line)?,
+                        ),
+                    };
+                    records.push(self.symbol_record(
+                        number,
+                        symbol_type,
+                        &part_common,
+                        is_rotatable,
+                        &data,
+                    )?);
+                    targets.push(Target { number, kind });
+                }
+            }
+        }
+
+        self.warnings.push(OcdWarning::CombinedSymbolSplit {
+            code: common.code,
+            symbol_numbers: targets.iter().map(|target| target.number).collect(),
+        });
+        let _previous = self.targets.insert(weak, targets.clone());
+        Ok(targets)
+    }
+
+    /// Prepend the base symbol to the type specific `data`.
+    fn symbol_record(
+        &mut self,
+        number: i32,
+        symbol_type: u8,
+        common: &SymbolCommon,
+        is_rotatable: bool,
+        data: &OcdRecord,
+    ) -> Result<OcdRecord> {
+        let base_size = FORMAT.base_symbol_size();
+        let mut record = OcdRecord::new(base_size);
+        record.set_offset(base_symbol::SIZE, base_size + data.len())?;
+        record.set_i32(base_symbol::NUMBER, number);
+        record.set_u8(base_symbol::TYPE, symbol_type);
+        if is_rotatable {
+            record.set_u8(base_symbol::FLAGS, symbol_flags::ROTATABLE);
+        }
+        let mut status = 0;
+        if common.is_protected {
+            status |= symbol_flags::STATUS_PROTECTED;
+        }
+        if common.is_hidden {
+            status |= symbol_flags::STATUS_HIDDEN;
+        }
+        record.set_u8(base_symbol::STATUS, status);
+
+        let colors = std::mem::take(&mut self.symbol_colors);
+        let colors = &colors[..colors.len().min(base_symbol::MAX_COLORS)];
+        record.set_u16(base_symbol::NUM_COLORS, u16::try_from(colors.len())?);
+        for (index, &color) in colors.iter().enumerate() {
+            record.set_u16(base_symbol::COLORS + index * 2, color);
+        }
+        record.set_utf16_string(
+            base_symbol::DESCRIPTION,
+            DESCRIPTION_UTF16_CAPACITY,
+            &common.name,
+        );
+        record.extend(data.as_slice());
+        Ok(record)
+    }
+
+    fn point_data(&mut self, code: Code, symbol: &PointSymbol) -> Result<OcdRecord> {
+        let mut elements = OcdRecord::default();
+        self.point_elements(code, symbol, Coord::zero(), &mut elements)?;
+        let mut data = OcdRecord::new(point_symbol::ELEMENTS);
+        data.set_u16(
+            point_symbol::DATA_SIZE,
+            u16::try_from(elements.len() / COORD_SIZE)?,
+        );
+        data.extend(elements.as_slice());
+        Ok(data)
+    }
+
+    /// Append the elements of a point symbol, moved by `offset` when the symbol is itself an element.
+    fn point_elements(
+        &mut self,
+        code: Code,
+        symbol: &PointSymbol,
+        offset: Coord,
+        elements: &mut OcdRecord,
+    ) -> Result<()> {
+        let inner_radius = symbol.inner_radius.get();
+        let outer_width = symbol.outer_width.get();
+        let center = [OcdPoint::new(offset, 0, 0)?];
+        if inner_radius > 0.
+            && let Some(color) = self.color_number(&symbol.inner_color)
+        {
+            push_element(
+                elements,
+                (element::TYPE_DOT, 0, color),
+                0.,
+                2. * inner_radius,
+                &center,
+            )?;
+        }
+        if outer_width > 0.
+            && let Some(color) = self.color_number(&symbol.outer_color)
+        {
+            push_element(
+                elements,
+                (element::TYPE_CIRCLE, 0, color),
+                outer_width,
+                2. * (inner_radius + outer_width),
+                &center,
+            )?;
+        }
+
+        for part in &symbol.elements {
+            match part {
+                Element::Point { symbol, object } => {
+                    self.point_elements(code, symbol, offset + object.geometry().0, elements)?;
+                }
+                Element::Line { symbol, object } => {
+                    let Some(color) = self.color_number(&symbol.color) else {
+                        continue;
+                    };
+                    let flags = if matches!(symbol.join_style, JoinStyle::Round)
+                        || matches!(symbol.cap_style, CapStyle::Round)
+                    {
+                        element::FLAG_ROUND
+                    } else if matches!(symbol.join_style, JoinStyle::Miter) {
+                        element::FLAG_MITER
+                    } else {
+                        0
+                    };
+                    let mut points = Vec::new();
+                    path_points(object.geometry(), offset, 0, &mut points)?;
+                    push_element(
+                        elements,
+                        (element::TYPE_LINE, flags, color),
+                        symbol.line_width.get(),
+                        0.,
+                        &points,
+                    )?;
+                }
+                Element::Area { symbol, object } => {
+                    if !symbol.patterns.is_empty() {
+                        self.drop_feature(code, OcdSymbolFeature::FillPattern);
+                    }
+                    let Some(color) = self.color_number(&symbol.color) else {
+                        continue;
+                    };
+                    let points = polygon_points(object.geometry(), offset)?;
+                    push_element(elements, (element::TYPE_AREA, 0, color), 0., 0., &points)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn line_data(&mut self, code: Code, symbol: &LineSymbol) -> Result<OcdRecord> {
+        let mut data = OcdRecord::new(line_symbol::ELEMENTS);
+        if symbol.line_width.get() > 0.
+            && let Some(color) = self.color_number(&symbol.color)
+        {
+            data.set_u16(line_symbol::LINE_COLOR, color);
+            data.set_u16(
+                line_symbol::LINE_WIDTH,
+                length_to_ocd(symbol.line_width.get()) as u16,
+            );
+        }
+        data.set_u16(
+            line_symbol::LINE_STYLE,
+            line_style(symbol.cap_style, symbol.join_style),
+        );
+        data.set_i16(
+            line_symbol::DIST_FROM_START,
+            length_to_ocd(symbol.start_offset.get()) as i16,
+        );
+        data.set_i16(
+            line_symbol::DIST_TO_END,
+            length_to_ocd(symbol.end_offset.get()) as i16,
+        );
+        self.write_dashes(code, &symbol.dash_style, &mut data);
+        self.write_double_line(code, symbol, &mut data);
+
+        if let Some(mid_symbol) = &symbol.mid_symbol {
+            data.set_i16(
+                line_symbol::MIN_SYM,
+                i16::try_from(mid_symbol.minimum_mid_symbol_count).unwrap_or(i16::MAX),
+            );
+            data.set_i16(
+                line_symbol::NUM_PRIM_SYM,
+                i16::try_from(mid_symbol.mid_symbols_per_spot).unwrap_or(i16::MAX),
+            );
+            data.set_i16(
+                line_symbol::PRIM_SYM_DIST,
+                length_to_ocd(mid_symbol.mid_symbol_distance.get()) as i16,
+            );
+        }
+        let sections = [
+            (
+                line_symbol::PRIMARY_DATA_SIZE,
+                symbol.mid_symbol.as_ref().map(|mid| &mid.mid_symbol),
+            ),
+            (
+                line_symbol::CORNER_DATA_SIZE,
+                symbol.dash_symbol.as_ref().map(|dash| &dash.dash_symbol),
+            ),
+            (line_symbol::START_DATA_SIZE, symbol.start_symbol.as_ref()),
+            (line_symbol::END_DATA_SIZE, symbol.end_symbol.as_ref()),
+        ];
+        for (size_offset, point) in sections {
+            if let Some(point) = point {
+                let mut elements = OcdRecord::default();
+                self.point_elements(code, point, Coord::zero(), &mut elements)?;
+                data.set_u16(size_offset, u16::try_from(elements.len() / COORD_SIZE)?);
+                data.extend(elements.as_slice());
+            }
+        }
+        Ok(data)
+    }
+
+    /// Write the dash settings of a line symbol, the counterpart of the reader's `dash_style`.
+    fn write_dashes(&mut self, code: Code, dash_style: &DashStyle, data: &mut OcdRecord) {
+        let (main_length, end_length, main_gap, sec_gap) = match dash_style {
+            DashStyle::NotDashed {
+                segment_length,
+                end_length,
+            } => (segment_length.get(), end_length.get(), 0., 0.),
+            DashStyle::Dashed {
+                dash_length,
+                break_length,
+                dash_group: GroupDashes::UnGrouped { half_outer_dashes },
+            } => {
+                let end_length = if *half_outer_dashes {
+                    dash_length.get() / 2.
+                } else {
+                    dash_length.get()
+                };
+                (dash_length.get(), end_length, break_length.get(), 0.)
+            }
+            DashStyle::Dashed {
+                dash_length,
+                break_length,
+                dash_group:
+                    GroupDashes::Grouped {
+                        dashes_in_group,
+                        in_group_break_length,
+                    },
+            } => {
+                if *dashes_in_group > 2 {
+                    self.drop_feature(code, OcdSymbolFeature::DashGroup);
+                }
+                // the main length spans both dashes of a pair and the gap between them
+                let main_length = 2. * dash_length.get() + in_group_break_length.get();
+                (
+                    main_length,
+                    main_length,
+                    break_length.get(),
+                    in_group_break_length.get(),
+                )
+            }
+        };
+        data.set_i16(line_symbol::MAIN_LENGTH, length_to_ocd(main_length) as i16);
+        data.set_i16(line_symbol::END_LENGTH, length_to_ocd(end_length) as i16);
+        data.set_i16(line_symbol::MAIN_GAP, length_to_ocd(main_gap) as i16);
+        data.set_i16(line_symbol::SEC_GAP, length_to_ocd(sec_gap) as i16);
+    }
+
+    /// Write the borders of a line symbol as the two lines of an OCAD double line.
+    fn write_double_line(&mut self, code: Code, symbol: &LineSymbol, data: &mut OcdRecord) {
+        let (left, right) = match &symbol.border {
+            Some(BorderStyle::SymmetricBorder { both }) => (both, both),
+            Some(BorderStyle::AsymmetricBorder { left, right }) => (left, right),
+            None => return,
+        };
+        let line_width = symbol.line_width.get();
+        let mut double_widths = Vec::new();
+        let mut dashes = Vec::new();
+        for (border, color_offset, width_offset) in [
+            (
+                left,
+                line_symbol::DOUBLE_LEFT_COLOR,
+                line_symbol::DOUBLE_LEFT_WIDTH,
+            ),
+            (
+                right,
+                line_symbol::DOUBLE_RIGHT_COLOR,
+                line_symbol::DOUBLE_RIGHT_WIDTH,
+            ),
+        ] {
+            let width = border.width.get();
+            let color = if width > 0. {
+                self.color_number(&border.color)
+            } else {
+                None
+            };
+            let Some(color) = color else {
+                dashes.push(None);
+                continue;
+            };
+            data.set_u16(color_offset, color);
+            data.set_i16(width_offset, length_to_ocd(width) as i16);
+            // Mapper measures the shift from the edge of the main line
+            double_widths.push(2. * border.shift.get() + line_width - width);
+            dashes.push(border.dashed.as_ref());
+        }
+        let Some(&double_width) = double_widths.first() else {
+            return;
+        };
+        if double_widths
+            .iter()
+            .any(|width| (width - double_width).abs() >= 0.005)
+        {
+            self.drop_feature(code, OcdSymbolFeature::AsymmetricBorderShift);
+        }
+        data.set_i16(
+            line_symbol::DOUBLE_WIDTH,
+            length_to_ocd(double_width) as i16,
+        );
+
+        let mode = match (dashes[0], dashes[1]) {
+            (Some(_), Some(_)) => line_symbol::DOUBLE_DASHED,
+            (Some(_), None) => line_symbol::DOUBLE_LEFT_DASHED,
+            (None, Some(_)) => line_symbol::DOUBLE_RIGHT_DASHED,
+            (None, None) => line_symbol::DOUBLE_CONTINUOUS,
+        };
+        data.set_u16(line_symbol::DOUBLE_MODE, mode);
+        if let Some(dash) = dashes.iter().flatten().next() {
+            data.set_i16(
+                line_symbol::DOUBLE_LENGTH,
+                length_to_ocd(dash.dash_length.get()) as i16,
+            );
+            data.set_i16(
+                line_symbol::DOUBLE_GAP,
+                length_to_ocd(dash.break_length.get()) as i16,
+            );
+        }
+    }
+
+    /// Write the fill of an area symbol. OCAD has room for one hatch, which may be crossed,
+    /// and one point structure, which may shift every other row.
+    fn area_data(&mut self, code: Code, symbol: &AreaSymbol) -> Result<OcdRecord> {
+        let mut data = OcdRecord::new(area_symbol::ELEMENTS);
+        if let Some(color) = self.color_number(&symbol.color) {
+            data.set_u16(area_symbol::FILL_COLOR, color);
+            data.set_u8(area_symbol::FILL_ON, 1);
+        }
+
+        let line_patterns = symbol
+            .patterns
+            .iter()
+            .filter(|pattern| matches!(pattern, FillPattern::LinePattern { .. }))
+            .collect::<Vec<_>>();
+        let point_patterns = symbol
+            .patterns
+            .iter()
+            .filter(|pattern| matches!(pattern, FillPattern::PointPattern { .. }))
+            .collect::<Vec<_>>();
+        let num_written = self.write_hatch(&line_patterns, &mut data)
+            + self.write_structure(code, &point_patterns, &mut data)?;
+        if num_written < symbol.patterns.len() {
+            self.drop_feature(code, OcdSymbolFeature::FillPattern);
+        }
+        Ok(data)
+    }
+
+    /// Write the first line pattern, or the first two if they form a cross hatch, and
+    /// return the number of written patterns.
+    fn write_hatch(&mut self, patterns: &[&FillPattern], data: &mut OcdRecord) -> usize {
+        let hatch = |pattern: &FillPattern| match pattern {
+            FillPattern::LinePattern {
+                angle,
+                line_spacing,
+                line_color,
+                line_width,
+                ..
+            } => Some((
+                *angle,
+                line_spacing.get(),
+                line_color.clone(),
+                line_width.get(),
+            )),
+            FillPattern::PointPattern { .. } => None,
+        };
+        let mut hatches = patterns.iter().filter_map(|pattern| hatch(pattern));
+        let Some((angle, spacing, color, width)) = hatches.next() else {
+            return 0;
+        };
+        let Some(color_number) = self.color_number(&color) else {
+            return 0;
+        };
+        let cross = hatches
+            .next()
+            .filter(|(_, other_spacing, other_color, other_width)| {
+                *other_color == color
+                    && (other_spacing - spacing).abs() < 0.005
+                    && (other_width - width).abs() < 0.005
+            });
+
+        data.set_u16(area_symbol::HATCH_COLOR, color_number);
+        data.set_u16(area_symbol::HATCH_LINE_WIDTH, length_to_ocd(width) as u16);
+        // OCAD measures the distance between the lines
+        data.set_u16(
+            area_symbol::HATCH_DIST,
+            length_to_ocd(spacing - width) as u16,
+        );
+        data.set_i16(area_symbol::HATCH_ANGLE_1, angle_to_ocd(angle));
+        if let Some((cross_angle, ..)) = cross {
+            data.set_u16(area_symbol::HATCH_MODE, area_symbol::HATCH_CROSS);
+            data.set_i16(area_symbol::HATCH_ANGLE_2, angle_to_ocd(cross_angle));
+            2
+        } else {
+            data.set_u16(area_symbol::HATCH_MODE, area_symbol::HATCH_SINGLE);
+            1
+        }
+    }
+
+    /// Write the first point pattern, or the first two if the second shifts every other
+    /// row, and return the number of written patterns.
+    fn write_structure(
+        &mut self,
+        code: Code,
+        patterns: &[&FillPattern],
+        data: &mut OcdRecord,
+    ) -> Result<usize> {
+        fn structure(pattern: &FillPattern) -> Option<(f64, f64, f64, f64, f64, &PointSymbol)> {
+            match pattern {
+                FillPattern::PointPattern {
+                    angle,
+                    line_spacing,
+                    line_offset,
+                    offset_along_line,
+                    point_distance,
+                    point,
+                    ..
+                } => Some((
+                    *angle,
+                    line_spacing.get(),
+                    line_offset.get(),
+                    offset_along_line.get(),
+                    point_distance.get(),
+                    point,
+                )),
+                FillPattern::LinePattern { .. } => None,
+            }
+        }
+        let mut structures = patterns.iter().filter_map(|pattern| structure(pattern));
+        let Some((angle, spacing, _, _, distance, point)) = structures.next() else {
+            return Ok(0);
+        };
+        let is_shifted = structures.next().is_some_and(
+            |(other_angle, other_spacing, other_offset, other_along, other_distance, _)| {
+                (other_angle - angle).abs() < 1e-6
+                    && (other_spacing - spacing).abs() < 0.005
+                    && (other_distance - distance).abs() < 0.005
+                    && (other_offset - spacing / 2.).abs() < 0.005
+                    && (other_along - distance / 2.).abs() < 0.005
+            },
+        );
+
+        let mut elements = OcdRecord::default();
+        self.point_elements(code, point, Coord::zero(), &mut elements)?;
+        let (mode, height, num_written) = if is_shifted {
+            (area_symbol::STRUCTURE_SHIFTED, spacing / 2., 2)
+        } else {
+            (area_symbol::STRUCTURE_ALIGNED, spacing, 1)
+        };
+        data.set_u16(area_symbol::STRUCTURE_MODE, mode);
+        data.set_u16(area_symbol::STRUCTURE_WIDTH, length_to_ocd(distance) as u16);
+        data.set_u16(area_symbol::STRUCTURE_HEIGHT, length_to_ocd(height) as u16);
+        data.set_i16(area_symbol::STRUCTURE_ANGLE, angle_to_ocd(angle));
+        data.set_u16(
+            area_symbol::DATA_SIZE,
+            u16::try_from(elements.len() / COORD_SIZE)?,
+        );
+        data.extend(elements.as_slice());
+        Ok(num_written)
+    }
+
+    fn text_data(&mut self, code: Code, symbol: &TextSymbol, alignment: u16) -> OcdRecord {
+        let mut data = OcdRecord::new(text_symbol::SIZE);
+        data.set_pascal_string(
+            text_symbol::FONT_NAME,
+            text_symbol::FONT_NAME_CAPACITY,
+            &symbol.font_family,
+        );
+        if let Some(color) = self.color_number(&symbol.color) {
+            data.set_u16(text_symbol::FONT_COLOR, color);
+        }
+        // the font size is stored in tenths of a point
+        data.set_u16(
+            text_symbol::FONT_SIZE,
+            (symbol.font_size.get() / 25.4 * 72. * 10.).round() as u16,
+        );
+        data.set_u16(
+            text_symbol::FONT_WEIGHT,
+            if symbol.bold {
+                text_symbol::BOLD_WEIGHT
+            } else {
+                text_symbol::NORMAL_WEIGHT
+            },
+        );
+        data.set_u8(text_symbol::ITALIC, u8::from(symbol.italic));
+        data.set_i16(
+            text_symbol::CHAR_SPACING,
+            (symbol.character_spacing * 100.).round() as i16,
+        );
+        data.set_u16(text_symbol::ALIGNMENT, alignment);
+        data.set_u16(
+            text_symbol::LINE_SPACING,
+            (symbol.line_spacing.get() * 100.).round() as u16,
+        );
+        data.set_i16(
+            text_symbol::PARA_SPACING,
+            length_to_ocd(symbol.paragraph_spacing) as i16,
+        );
+
+        let tabs = &symbol.custom_tabs[..symbol.custom_tabs.len().min(text_symbol::MAX_TABS)];
+        data.set_u16(text_symbol::NUM_TABS, tabs.len() as u16);
+        for (index, tab) in tabs.iter().enumerate() {
+            data.set_u32(
+                text_symbol::TABS + index * 4,
+                length_to_ocd(tab.get()) as u32,
+            );
+        }
+
+        if let Some(line_below) = &symbol.line_below
+            && let Some(color) = self.color_number(&line_below.color)
+        {
+            data.set_u16(text_symbol::LINE_BELOW_ON, 1);
+            data.set_u16(text_symbol::LINE_BELOW_COLOR, color);
+            data.set_u16(
+                text_symbol::LINE_BELOW_WIDTH,
+                length_to_ocd(line_below.width.get()) as u16,
+            );
+            data.set_u16(
+                text_symbol::LINE_BELOW_OFFSET,
+                length_to_ocd(line_below.distance.get()) as u16,
+            );
+        }
+        if symbol.underline {
+            self.drop_feature(code, OcdSymbolFeature::Underline);
+        }
+
+        match &symbol.framing_mode {
+            Some(FramingMode::LineFraming(framing)) => {
+                if let Some(color) = self.color_number(&framing.color) {
+                    data.set_u8(text_symbol::FRAMING_MODE, text_symbol::FRAMING_LINE);
+                    data.set_u16(text_symbol::FRAMING_COLOR, color);
+                    data.set_u16(
+                        text_symbol::FRAMING_WIDTH,
+                        length_to_ocd(2. * framing.framing_line_half_width.get()) as u16,
+                    );
+                }
+            }
+            Some(FramingMode::ShadowFraming(framing)) => {
+                if let Some(color) = self.color_number(&framing.color) {
+                    data.set_u8(text_symbol::FRAMING_MODE, text_symbol::FRAMING_SHADOW);
+                    data.set_u16(text_symbol::FRAMING_COLOR, color);
+                    data.set_i16(
+                        text_symbol::FRAMING_OFFSET_X,
+                        length_to_ocd(framing.shadow_offset.x) as i16,
+                    );
+                    data.set_i16(
+                        text_symbol::FRAMING_OFFSET_Y,
+                        length_to_ocd(framing.shadow_offset.y) as i16,
+                    );
+                }
+            }
+            Some(FramingMode::NoFraming) | None => (),
+        }
+        data
+    }
+
+    /// Write every object once per OCAD symbol of its symbol.
+    fn write_objects(&mut self) -> Result<Vec<OcdObject>> {
+        let mut objects = Vec::new();
+        for (object_index, object) in self.omap.iter_all_objects().enumerate() {
+            if !object.tags().is_empty() {
+                self.warnings
+                    .push(OcdWarning::ObjectTagsDropped { object_index });
+            }
+            let targets = self
+                .targets
+                .get(&object.symbol())
+                .cloned()
+                .unwrap_or_default();
+            let written = match object {
+                MapObject::Point(point) => {
+                    let points = vec![OcdPoint::new(point.geometry().0, 0, 0)?];
+                    let angle = angle_to_ocd(point.rotation);
+                    targets
+                        .iter()
+                        .map(|target| OcdObject {
+                            number: target.number,
+                            object_type: object_type::POINT,
+                            angle,
+                            points: points.clone(),
+                            text: Vec::new(),
+                        })
+                        .collect()
+                }
+                MapObject::Line(line) => {
+                    let mut points = Vec::new();
+                    path_points(line.geometry(), Coord::zero(), 0, &mut points)?;
+                    if points.len() < 2 {
+                        self.warnings
+                            .push(OcdWarning::InvalidObjectGeometry { object_index });
+                        continue;
+                    }
+                    path_objects(&targets, &points)
+                }
+                MapObject::Area(area) => {
+                    let polygon = area.geometry();
+                    if polygon.exterior().num_segments() < 2 {
+                        self.warnings
+                            .push(OcdWarning::InvalidObjectGeometry { object_index });
+                        continue;
+                    }
+                    area_objects(
+                        &targets,
+                        polygon,
+                        angle_to_ocd(area.pattern_rotation.rotation),
+                    )?
+                }
+                MapObject::Text(text) => {
+                    let alignment = self
+                        .text_alignments
+                        .get(&object.symbol())
+                        .copied()
+                        .unwrap_or(0);
+                    if !keeps_alignment(text, alignment) {
+                        self.warnings
+                            .push(OcdWarning::TextAlignmentChanged { object_index });
+                    }
+                    text_objects(&targets, text)?
+                }
+            };
+            if written.is_empty() {
+                // no OCAD symbol of the symbol fits the object
+                let code = match object.symbol().upgrade() {
+                    Some(symbol) => symbol.code()?,
+                    None => Code::default(),
+                };
+                self.warnings
+                    .push(OcdWarning::ObjectWithoutOcdSymbol { object_index, code });
+            }
+            objects.extend(written);
+        }
+        Ok(objects)
+    }
+}
+
+/// Get the OCAD line style closest to a cap and join style.
+fn line_style(cap_style: CapStyle, join_style: JoinStyle) -> u16 {
+    match (cap_style, join_style) {
+        (CapStyle::Pointed, JoinStyle::Bevel) => line_symbol::STYLE_BEVEL_POINTED,
+        (CapStyle::Pointed, JoinStyle::Round) => line_symbol::STYLE_ROUND_POINTED,
+        (CapStyle::Pointed, JoinStyle::Miter) => line_symbol::STYLE_MITER_POINTED,
+        (_, JoinStyle::Miter) => line_symbol::STYLE_MITER_FLAT,
+        (CapStyle::Round, _) | (_, JoinStyle::Round) => line_symbol::STYLE_ROUND_ROUND,
+        _ => line_symbol::STYLE_BEVEL_FLAT,
+    }
+}
+
+/// Append a point symbol element given its type, flags and color.
+fn push_element(
+    elements: &mut OcdRecord,
+    (element_type, flags, color): (u16, u16, u16),
+    line_width: f64,
+    diameter: f64,
+    points: &[OcdPoint],
+) -> Result<()> {
+    let mut header = OcdRecord::new(ELEMENT_HEADER_SIZE);
+    header.set_u16(element::TYPE, element_type);
+    header.set_u16(element::FLAGS, flags);
+    header.set_u16(element::COLOR, color);
+    header.set_u16(element::LINE_WIDTH, length_to_ocd(line_width) as u16);
+    header.set_u16(element::DIAMETER, length_to_ocd(diameter) as u16);
+    header.set_u16(element::NUM_COORDS, u16::try_from(points.len())?);
+    elements.extend(header.as_slice());
+    for &point in points {
+        elements.push_point(point);
+    }
+    Ok(())
+}
+
+/// Append the coordinates of a path moved by `offset`, where a curve is stored as its
+/// two handles followed by its end and `first_y_flags` marks the first coordinate.
+fn path_points(
+    path: &BezierPath,
+    offset: Coord,
+    first_y_flags: i32,
+    points: &mut Vec<OcdPoint>,
+) -> Result<()> {
+    let is_dash_point = path.vertex_is_dash_point();
+    let y_flags = |vertex: usize| {
+        if is_dash_point.get(vertex).copied().unwrap_or(false) {
+            Y_FLAG_DASH_POINT
+        } else {
+            0
+        }
+    };
+    let mut segments = path.geometry().segments().peekable();
+    let Some(first) = segments.peek() else {
+        return Ok(());
+    };
+    points.push(OcdPoint::new(
+        first.start() + offset,
+        0,
+        first_y_flags | y_flags(0),
+    )?);
+    for (index, segment) in segments.enumerate() {
+        if let Some((handle1, handle2)) = segment.handles() {
+            points.push(OcdPoint::new(
+                handle1 + offset,
+                X_FLAG_CURVE_FIRST_HANDLE,
+                0,
+            )?);
+            points.push(OcdPoint::new(
+                handle2 + offset,
+                X_FLAG_CURVE_SECOND_HANDLE,
+                0,
+            )?);
+        }
+        points.push(OcdPoint::new(
+            segment.end() + offset,
+            0,
+            y_flags(index + 1),
+        )?);
+    }
+    Ok(())
+}
+
+/// Get the coordinates of a polygon, where every hole starts with a flagged coordinate.
+fn polygon_points(polygon: &BezierPolygon, offset: Coord) -> Result<Vec<OcdPoint>> {
+    let mut points = Vec::new();
+    path_points(polygon.exterior(), offset, 0, &mut points)?;
+    for interior in polygon.interiors() {
+        path_points(interior, offset, Y_FLAG_HOLE_START, &mut points)?;
+    }
+    Ok(points)
+}
+
+/// Write a line for every line symbol among `targets`.
+fn path_objects(targets: &[Target], points: &[OcdPoint]) -> Vec<OcdObject> {
+    targets
+        .iter()
+        .filter(|target| target.kind == TargetKind::Line)
+        .map(|target| OcdObject {
+            number: target.number,
+            object_type: object_type::LINE,
+            angle: 0,
+            points: points.to_vec(),
+            text: Vec::new(),
+        })
+        .collect()
+}
+
+/// Write an area for every area symbol among `targets` and the outline of every ring
+/// for every line symbol, as combined area symbols may contain lines.
+fn area_objects(targets: &[Target], polygon: &BezierPolygon, angle: i16) -> Result<Vec<OcdObject>> {
+    let mut objects = Vec::new();
+    for target in targets {
+        match target.kind {
+            TargetKind::Area => objects.push(OcdObject {
+                number: target.number,
+                object_type: object_type::AREA,
+                angle,
+                points: polygon_points(polygon, Coord::zero())?,
+                text: Vec::new(),
+            }),
+            TargetKind::Line => {
+                for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+                    let mut points = Vec::new();
+                    path_points(ring, Coord::zero(), 0, &mut points)?;
+                    objects.extend(path_objects(std::slice::from_ref(target), &points));
+                }
+            }
+            TargetKind::Point | TargetKind::Text => (),
+        }
+    }
+    Ok(objects)
+}
+
+/// Whether a text object is drawn with its own alignment when the symbol has `alignment`.
+fn keeps_alignment(text: &TextObject, alignment: u16) -> bool {
+    match text.geometry() {
+        TextGeometry::SingleAnchor(_) => {
+            text.v_align != VerticalAlign::Bottom
+                && alignment_code(text.h_align, text.v_align) == alignment
+        }
+        // formatted text always starts at the top of its box
+        TextGeometry::WrapBox(_) => {
+            text.v_align == VerticalAlign::Top
+                && alignment_code(text.h_align, VerticalAlign::Baseline)
+                    == alignment & text_symbol::ALIGN_HORIZONTAL_MASK
+        }
+    }
+}
+
+/// Write unformatted text at its anchor, or formatted text with the corners of its
+/// box starting at the lower left corner and going counter-clockwise.
+fn text_objects(targets: &[Target], text: &TextObject) -> Result<Vec<OcdObject>> {
+    let (object_type, points) = match text.geometry() {
+        TextGeometry::SingleAnchor(anchor) => (
+            object_type::UNFORMATTED_TEXT,
+            vec![OcdPoint::new(*anchor, 0, 0)?],
+        ),
+        TextGeometry::WrapBox(wrap_box) => {
+            let (sin, cos) = text.rotation.sin_cos();
+            let (half_width, half_height) = (wrap_box.width.get() / 2., wrap_box.height.get() / 2.);
+            let points = [
+                (-half_width, -half_height),
+                (half_width, -half_height),
+                (half_width, half_height),
+                (-half_width, half_height),
+            ]
+            .into_iter()
+            .map(|(x, y)| {
+                OcdPoint::new(
+                    wrap_box.anchor
+                        + Coord {
+                            x: x * cos - y * sin,
+                            y: x * sin + y * cos,
+                        },
+                    0,
+                    0,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+            (object_type::FORMATTED_TEXT, points)
+        }
+    };
+
+    let mut bytes = text
+        .text
+        .replace('\n', "\r\n")
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    // at least one nul character, padded to whole coordinates
+    bytes.resize((bytes.len() + 2).div_ceil(COORD_SIZE) * COORD_SIZE, 0);
+
+    Ok(targets
+        .iter()
+        .filter(|target| target.kind == TargetKind::Text)
+        .map(|target| OcdObject {
+            number: target.number,
+            object_type,
+            angle: angle_to_ocd(text.rotation),
+            points: points.clone(),
+            text: bytes.clone(),
+        })
+        .collect())
+}
+
+/// Append chained index blocks for `count` entries, link the first block from
+/// `header_field` and return the position of every entry.
+fn index_blocks(
+    file: &mut OcdRecord,
+    header_field: usize,
+    entry_size: usize,
+    count: usize,
+) -> Result<Vec<usize>> {
+    let block_size = 4 + INDEX_BLOCK_ENTRIES * entry_size;
+    let num_blocks = count.div_ceil(INDEX_BLOCK_ENTRIES).max(1);
+    file.set_offset(header_field, file.len())?;
+    let mut entries = Vec::with_capacity(num_blocks * INDEX_BLOCK_ENTRIES);
+    for block in 0..num_blocks {
+        let pos = file.len();
+        file.extend(&vec![0; block_size]);
+        if block + 1 < num_blocks {
+            file.set_offset(pos, pos + block_size)?;
+        }
+        entries.extend((0..INDEX_BLOCK_ENTRIES).map(|index| pos + 4 + index * entry_size));
+    }
+    entries.truncate(count);
+    Ok(entries)
+}
+
+/// Lay out the file header, the index blocks and the data they point to.
+fn build_file(
+    strings: &[(i32, String)],
+    symbols: &[OcdRecord],
+    objects: &[OcdObject],
+) -> Result<Vec<u8>> {
+    let mut file = OcdRecord::new(FILE_HEADER_SIZE);
+    file.set_u16(header::MARK, OCAD_MARK);
+    file.set_u16(header::VERSION, VERSION);
+    let string_entries = index_blocks(
+        &mut file,
+        header::FIRST_STRING_BLOCK,
+        string_entry::SIZE,
+        strings.len(),
+    )?;
+    let symbol_entries = index_blocks(
+        &mut file,
+        header::FIRST_SYMBOL_BLOCK,
+        SYMBOL_ENTRY_SIZE,
+        symbols.len(),
+    )?;
+    let object_entries = index_blocks(
+        &mut file,
+        header::FIRST_OBJECT_BLOCK,
+        FORMAT.object_entry_size(),
+        objects.len(),
+    )?;
+
+    for (entry, (string_type, value)) in string_entries.into_iter().zip(strings) {
+        let pos = file.len();
+        file.extend(value.as_bytes());
+        file.extend(&[0]);
+        file.set_offset(entry + string_entry::POS, pos)?;
+        file.set_offset(entry + string_entry::LEN, value.len() + 1)?;
+        file.set_i32(entry + string_entry::TYPE, *string_type);
+    }
+    for (entry, symbol) in symbol_entries.into_iter().zip(symbols) {
+        let pos = file.len();
+        file.extend(symbol.as_slice());
+        file.set_offset(entry, pos)?;
+    }
+    for (entry, object) in object_entries.into_iter().zip(objects) {
+        object.write(&mut file, entry)?;
+    }
+    Ok(file.into_bytes())
+}
+
+impl OcdObject {
+    /// Append the object and fill in its index entry at `entry`.
+    fn write(&self, file: &mut OcdRecord, entry: usize) -> Result<()> {
+        let num_text = self.text.len() / COORD_SIZE;
+        let mut header = OcdRecord::new(FORMAT.object_header_size());
+        header.set_i32(object::SYMBOL, self.number);
+        header.set_u8(object::TYPE, self.object_type);
+        header.set_i16(object::ANGLE, self.angle);
+        header.set_offset(object::NUM_ITEMS, self.points.len())?;
+        header.set_u16(object::NUM_TEXT, u16::try_from(num_text)?);
+
+        let pos = file.len();
+        file.extend(header.as_slice());
+        for &point in &self.points {
+            file.push_point(point);
+        }
+        file.extend(&self.text);
+
+        let (mut lower_left, mut upper_right) = (
+            Coord {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Coord {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        );
+        for point in &self.points {
+            let coord = point.coord();
+            lower_left = Coord {
+                x: lower_left.x.min(coord.x),
+                y: lower_left.y.min(coord.y),
+            };
+            upper_right = Coord {
+                x: upper_right.x.max(coord.x),
+                y: upper_right.y.max(coord.y),
+            };
+        }
+        if !self.points.is_empty() {
+            file.set_point(
+                entry + object_entry::LOWER_LEFT,
+                OcdPoint::new(lower_left, 0, 0)?,
+            );
+            file.set_point(
+                entry + object_entry::UPPER_RIGHT,
+                OcdPoint::new(upper_right, 0, 0)?,
+            );
+        }
+        file.set_offset(entry + object_entry::POS, pos)?;
+        file.set_offset(entry + object_entry::LEN, self.points.len() + num_text)?;
+        file.set_i32(entry + object_entry::SYMBOL, self.number);
+        file.set_u8(entry + object_entry::TYPE, self.object_type);
+        file.set_u8(entry + object_entry::STATUS, object_entry::STATUS_NORMAL);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroU32, rc::Rc};
+
+    use geo_types::{Coord, LineString, Polygon, coord};
+    use linestring2bezier::{BezierSegment, BezierString};
+
+    use super::write;
+    use crate::{
+        Code, NonNegativeF64, Omap, Result, UnitF64,
+        colors::{Cmyk, CmykMode, Color, ColorComponent, MixedColor, SpotColor, SymbolColor},
+        objects::{
+            AreaObject, BezierPath, HorizontalAlign, LineObject, MapObject, TextGeometry,
+            TextObject, VerticalAlign, WrapBox,
+        },
+        ocad::{OcdWarning, read},
+        symbols::{
+            AreaOrLineSymbol, AreaSymbol, CombinedAreaSymbol, CombinedLineSymbol, DashStyle,
+            FillPattern, GroupDashes, LineSymbol, PublicOrPrivateSymbol, Symbol, TextSymbol,
+            WeakLinePathSymbol,
+        },
+    };
+
+    fn round_trip(omap: &Omap) -> Result<(Omap, Vec<OcdWarning>)> {
+        let mut bytes = Vec::new();
+        let warnings = write(omap, &mut bytes)?;
+        let (read_back, read_warnings) = read(&bytes)?;
+        assert_eq!(read_warnings, [], "the reader understands the writer");
+        Ok((read_back, warnings))
+    }
+
+    /// A map with a spot color, a line, an area and a text symbol with one object each.
+    fn test_map() -> Result<Omap> {
+        let mut omap = Omap::new(NonZeroU32::new(10_000).unwrap_or(NonZeroU32::MIN));
+        omap.geo_referencing.projected_ref_point = Coord {
+            x: 500_000.,
+            y: 6_600_000.,
+        };
+
+        let black = {
+            let mut black = MixedColor::new("Black", Vec::new());
+            black.set_cmyk_mode(CmykMode::Cmyk(Cmyk::new(0., 0., 0., 1.)?))?;
+            SymbolColor::Color(omap.colors.push(black))
+        };
+        let spot = Rc::new(std::cell::RefCell::new(SpotColor::new(
+            "Brown spot",
+            "Brown spot",
+            Cmyk::new(0., 0.56, 1., 0.18)?,
+        )));
+        let brown = SymbolColor::Color(omap.colors.push(MixedColor::new(
+            "Brown",
+            vec![ColorComponent {
+                factor: UnitF64::clamped_from(0.5),
+                color: Rc::downgrade(&spot),
+            }],
+        )));
+        let _spot = omap.colors.push(Color::SpotColor(spot));
+
+        let line = omap.symbols.add_symbol(
+            LineSymbol::new(Code::new(101, 0, 0), "Contour")
+                .with_color(brown.clone())
+                .with_line_width(NonNegativeF64::clamped_from(0.35))
+                .with_dash_style(DashStyle::Dashed {
+                    dash_length: NonNegativeF64::clamped_from(2.),
+                    break_length: NonNegativeF64::clamped_from(0.5),
+                    dash_group: GroupDashes::Grouped {
+                        dashes_in_group: 2,
+                        in_group_break_length: NonNegativeF64::clamped_from(0.25),
+                    },
+                }),
+        );
+        let mut area = AreaSymbol::new(Code::new(408, 0, 0), "Vegetation").with_color(brown);
+        area.patterns.push(FillPattern::LinePattern {
+            angle: std::f64::consts::FRAC_PI_4,
+            line_spacing: NonNegativeF64::clamped_from(0.5),
+            line_offset: NonNegativeF64::zero(),
+            line_color: black.clone(),
+            line_width: NonNegativeF64::clamped_from(0.1),
+            rotatable: false,
+        });
+        let area = omap.symbols.add_symbol(area);
+        let text = omap.symbols.add_symbol(
+            TextSymbol::new(Code::new(520, 0, 0), "Label")
+                .with_font_family("Arial")
+                .with_font_size(NonNegativeF64::clamped_from(2.54))
+                .with_color(black)
+                .with_bold(true),
+        );
+
+        let (Some(line), Some(area), Some(text)) = (line.upgrade(), area.upgrade(), text.upgrade())
+        else {
+            panic!("the symbols are in the symbol set");
+        };
+        let (Symbol::Line(line), Symbol::Area(area), Symbol::Text(text)) = (line, area, text)
+        else {
+            panic!("the symbols have their types");
+        };
+        let path = BezierPath::new(
+            BezierString::new(vec![
+                BezierSegment::new(
+                    coord! { x: 0., y: 0. },
+                    Some((coord! { x: 1., y: 0. }, coord! { x: 2., y: 1. })),
+                    coord! { x: 2., y: 2. },
+                ),
+                BezierSegment::new(coord! { x: 2., y: 2. }, None, coord! { x: 4., y: 2. }),
+            ]),
+            vec![false, true, false],
+        )?;
+        let polygon = Polygon::new(
+            LineString::from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 0.)]),
+            vec![LineString::from(vec![
+                (2., 2.),
+                (4., 2.),
+                (4., 4.),
+                (2., 2.),
+            ])],
+        );
+        let mut label = TextObject::new(
+            Rc::downgrade(&text),
+            TextGeometry::WrapBox(WrapBox {
+                anchor: coord! { x: 10., y: 5. },
+                width: NonNegativeF64::clamped_from(20.),
+                height: NonNegativeF64::clamped_from(10.),
+            }),
+            "Lake\nshore".to_owned(),
+        );
+        label.v_align = VerticalAlign::Top;
+        label.h_align = HorizontalAlign::Left;
+
+        let part = omap
+            .parts
+            .get_mut(0)
+            .unwrap_or_else(|| panic!("default part"));
+        part.add_object(LineObject::new(Rc::downgrade(&line), path));
+        part.add_object(AreaObject::new(Rc::downgrade(&area), polygon));
+        part.add_object(label);
+        Ok(omap)
+    }
+
+    #[test]
+    fn round_trips_colors_and_symbols() -> Result<()> {
+        let (omap, warnings) = round_trip(&test_map()?)?;
+        assert_eq!(warnings, [], "everything is written exactly");
+
+        assert_eq!(
+            omap.geo_referencing.scale_denominator.get(),
+            10_000,
+            "scale"
+        );
+        assert_eq!(
+            omap.geo_referencing.projected_ref_point,
+            Coord {
+                x: 500_000.,
+                y: 6_600_000.
+            },
+            "reference point"
+        );
+
+        // the spot color is read back as a process color printed on its separation
+        assert_eq!(omap.colors.len(), 4, "three colors and one separation");
+        let Some(Color::MixedColor(brown)) = omap.colors.color_by_priority(1) else {
+            panic!("expected the brown color");
+        };
+        let brown = brown.borrow();
+        assert_eq!(brown.name(), "Brown", "color name");
+        assert_eq!(brown.components[0].factor.get(), 0.5, "spot factor");
+        assert_eq!(brown.cmyk()?.m.get(), 0.28, "magenta from the separation");
+
+        let Some(Symbol::Line(line)) = omap.symbols.symbol_by_code(Code::new(101, 0, 0))? else {
+            panic!("expected the contour line symbol");
+        };
+        let line = line.borrow();
+        assert_eq!(line.common.name, "Contour", "symbol name");
+        assert_eq!(line.line_width.get(), 0.35, "line width");
+        let DashStyle::Dashed {
+            dash_length,
+            dash_group:
+                GroupDashes::Grouped {
+                    in_group_break_length,
+                    ..
+                },
+            ..
+        } = &line.dash_style
+        else {
+            panic!("expected grouped dashes");
+        };
+        assert_eq!(
+            (dash_length.get(), in_group_break_length.get()),
+            (2., 0.25),
+            "dash pairs"
+        );
+
+        let Some(Symbol::Area(area)) = omap.symbols.symbol_by_code(Code::new(408, 0, 0))? else {
+            panic!("expected the vegetation area symbol");
+        };
+        let area = area.borrow();
+        let [FillPattern::LinePattern { line_spacing, .. }] = area.patterns.as_slice() else {
+            panic!("expected a single hatch pattern");
+        };
+        assert!((line_spacing.get() - 0.5).abs() < 1e-9, "hatch spacing");
+
+        let Some(Symbol::Text(text)) = omap.symbols.symbol_by_code(Code::new(520, 0, 0))? else {
+            panic!("expected the label text symbol");
+        };
+        let text = text.borrow();
+        assert_eq!(
+            (text.font_family.as_str(), text.bold),
+            ("Arial", true),
+            "font"
+        );
+        assert!((text.font_size.get() - 2.54).abs() < 1e-12, "font size");
+        Ok(())
+    }
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn round_trips_objects() -> Result<()> {
+        let (omap, _) = round_trip(&test_map()?)?;
+        let objects = omap.iter_all_objects().collect::<Vec<_>>();
+        assert_eq!(objects.len(), 3, "every object is written");
+
+        let line = objects
+            .iter()
+            .find_map(|object| match object {
+                MapObject::Line(line) => Some(line.geometry()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(line.num_segments(), 2, "a curve and a straight segment");
+        assert_eq!(
+            line.geometry().0[0].handles(),
+            Some((coord! { x: 1., y: 0. }, coord! { x: 2., y: 1. })),
+            "Bézier handles"
+        );
+        assert_eq!(
+            line.vertex_is_dash_point(),
+            [false, true, false],
+            "dash point"
+        );
+
+        let area = objects
+            .iter()
+            .find_map(|object| match object {
+                MapObject::Area(area) => Some(area.geometry()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(area.interiors().len(), 1, "one hole");
+
+        let text = objects
+            .iter()
+            .find_map(|object| match object {
+                MapObject::Text(text) => Some(text),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(text.text, "Lake\nshore", "line breaks");
+        assert_eq!(text.h_align, HorizontalAlign::Left, "alignment");
+        let TextGeometry::WrapBox(wrap_box) = text.geometry() else {
+            panic!("the text keeps its box");
+        };
+        assert_eq!(wrap_box.anchor, coord! { x: 10., y: 5. }, "box centre");
+        assert_eq!(
+            (wrap_box.width.get(), wrap_box.height.get()),
+            (20., 10.),
+            "box size"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_renumbered_and_split_symbols() -> Result<()> {
+        let mut omap = test_map()?;
+        let _patch = omap
+            .symbols
+            .add_symbol(LineSymbol::new(Code::new(101, 0, 1), "Form line"));
+        let mut combined = CombinedAreaSymbol::new(Code::new(409, 0, 0), "Marsh");
+        combined.add_component(PublicOrPrivateSymbol::Private(AreaOrLineSymbol::from(
+            AreaSymbol::new(Code::default(), String::new()),
+        )))?;
+        combined.add_component(PublicOrPrivateSymbol::Private(AreaOrLineSymbol::from(
+            LineSymbol::new(Code::default(), String::new()),
+        )))?;
+        let combined = omap.symbols.add_symbol(combined);
+        let Some(Symbol::CombinedArea(combined)) = combined.upgrade() else {
+            panic!("expected the combined symbol");
+        };
+        omap.parts
+            .get_mut(0)
+            .unwrap_or_else(|| panic!("default part"))
+            .add_object(AreaObject::new(
+                Rc::downgrade(&combined),
+                Polygon::new(
+                    LineString::from(vec![(0., 0.), (5., 0.), (5., 5.), (0., 0.)]),
+                    Vec::new(),
+                ),
+            ));
+
+        let (read_back, warnings) = round_trip(&omap)?;
+        assert!(
+            warnings.contains(&OcdWarning::SymbolRenumbered {
+                code: Code::new(101, 0, 1),
+                symbol_number: 101_001
+            }),
+            "{warnings:?}"
+        );
+        assert!(
+            warnings.contains(&OcdWarning::CombinedSymbolSplit {
+                code: Code::new(409, 0, 0),
+                symbol_numbers: vec![409_000, 409_001]
+            }),
+            "{warnings:?}"
+        );
+
+        let Some(Symbol::Line(part)) = read_back.symbols.symbol_by_code(Code::new(409, 1, 0))?
+        else {
+            panic!("expected the private line as a symbol of its own");
+        };
+        assert_eq!(part.borrow().common.name, "Marsh (2)", "part name");
+        assert_eq!(
+            read_back.iter_all_objects().count(),
+            5,
+            "the combined object is written as an area and a line"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_objects_without_an_ocad_symbol() -> Result<()> {
+        let mut omap = test_map()?;
+        let num_objects = omap.iter_all_objects().count();
+        let empty = omap
+            .symbols
+            .add_symbol(CombinedLineSymbol::new(Code::new(507, 0, 0), "Empty"));
+        let Some(Symbol::CombinedLine(empty)) = empty.upgrade() else {
+            panic!("expected the combined symbol");
+        };
+        omap.parts
+            .get_mut(0)
+            .unwrap_or_else(|| panic!("default part"))
+            .add_object(LineObject::new(
+                WeakLinePathSymbol::CombinedLine(Rc::downgrade(&empty)),
+                LineString::from(vec![(0., 0.), (5., 0.)]),
+            ));
+
+        let (read_back, warnings) = round_trip(&omap)?;
+        assert!(
+            warnings.contains(&OcdWarning::ObjectWithoutOcdSymbol {
+                object_index: num_objects,
+                code: Code::new(507, 0, 0)
+            }),
+            "the object of a symbol without parts is skipped, {warnings:?}"
+        );
+        assert_eq!(read_back.iter_all_objects().count(), num_objects);
+        Ok(())
+    }
+
+    #[test]
+    fn exports_example_map() -> Result<()> {
+        let omap = Omap::from_path("./example_data/from_path.omap")?;
+        let mut bytes = Vec::new();
+        let _warnings = omap.to_ocd_writer(&mut bytes)?;
+        let (read_back, _) = read(&bytes)?;
+        assert!(
+            read_back.iter_all_objects().count() >= omap.iter_all_objects().count(),
+            "no object is lost"
+        );
+        Ok(())
+    }
+}
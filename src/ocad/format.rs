@@ -0,0 +1,663 @@
+//! Binary layout of OCAD files.
+//!
+//! All values are little endian. Offsets of symbol and object fields are
+//! relative to the start of the structure they belong to.
+
+use geo_types::Coord;
+
+use crate::{Code, Error, Result};
+
+/// The first two bytes of every OCAD file.
+pub(super) const OCAD_MARK: u16 = 0x0cad;
+/// Size of the file header shared by all versions.
+pub(super) const FILE_HEADER_SIZE: usize = 48;
+
+/// Offsets into the file header.
+pub(super) mod header {
+    pub(in crate::ocad) const MARK: usize = 0;
+    pub(in crate::ocad) const VERSION: usize = 4;
+    pub(in crate::ocad) const FIRST_SYMBOL_BLOCK: usize = 8;
+    pub(in crate::ocad) const FIRST_OBJECT_BLOCK: usize = 12;
+    /// Version 8 only, the position of the setup block holding the scale.
+    pub(in crate::ocad) const SETUP_POS: usize = 16;
+    /// Version 9 and later only, the first block of parameter strings.
+    pub(in crate::ocad) const FIRST_STRING_BLOCK: usize = 32;
+}
+
+/// Number of entries in every symbol, object and string index block.
+pub(super) const INDEX_BLOCK_ENTRIES: usize = 256;
+/// Size of an entry in a symbol index block, which holds the symbol position.
+pub(super) const SYMBOL_ENTRY_SIZE: usize = 4;
+
+/// Offsets into a parameter string index entry.
+pub(super) mod string_entry {
+    pub(in crate::ocad) const SIZE: usize = 16;
+    pub(in crate::ocad) const POS: usize = 0;
+    pub(in crate::ocad) const LEN: usize = 4;
+    pub(in crate::ocad) const TYPE: usize = 8;
+}
+
+/// Layout of the version 8 color table following the file header.
+pub(super) mod colors_v8 {
+    pub(in crate::ocad) const NUM_COLORS: usize = 48;
+    pub(in crate::ocad) const NUM_SEPARATIONS: usize = 50;
+    pub(in crate::ocad) const COLOR_INFO: usize = 72;
+    pub(in crate::ocad) const COLOR_INFO_SIZE: usize = 72;
+    pub(in crate::ocad) const MAX_COLORS: usize = 256;
+    pub(in crate::ocad) const SEPARATION_INFO: usize = COLOR_INFO + MAX_COLORS * COLOR_INFO_SIZE;
+    pub(in crate::ocad) const SEPARATION_INFO_SIZE: usize = 24;
+    pub(in crate::ocad) const MAX_SEPARATIONS: usize = 32;
+    /// A separation percentage which is not used by a color.
+    pub(in crate::ocad) const UNUSED_SEPARATION: u8 = 255;
+}
+
+/// Offsets into the version 8 setup block.
+pub(super) mod setup_v8 {
+    pub(in crate::ocad) const MAP_SCALE: usize = 24;
+    pub(in crate::ocad) const REAL_OFFSET_X: usize = 32;
+    pub(in crate::ocad) const REAL_OFFSET_Y: usize = 40;
+    pub(in crate::ocad) const REAL_ANGLE: usize = 48;
+}
+
+/// Parameter string types.
+pub(super) mod string_type {
+    pub(in crate::ocad) const COLOR: i32 = 9;
+    pub(in crate::ocad) const SPOT_COLOR: i32 = 10;
+    pub(in crate::ocad) const SCALE: i32 = 1039;
+}
+
+/// Symbol types of version 9 and later. Version 8 uses the same numbers
+/// except for line text symbols, which are line symbols with a subtype.
+pub(super) mod symbol_type {
+    pub(in crate::ocad) const POINT: u8 = 1;
+    pub(in crate::ocad) const LINE: u8 = 2;
+    pub(in crate::ocad) const AREA: u8 = 3;
+    pub(in crate::ocad) const TEXT: u8 = 4;
+    pub(in crate::ocad) const LINE_TEXT: u8 = 6;
+    pub(in crate::ocad) const RECTANGLE: u8 = 7;
+    /// The version 8 rectangle symbol type.
+    pub(in crate::ocad) const RECTANGLE_V8: u8 = 5;
+}
+
+/// Symbol status and flag bits.
+pub(super) mod symbol_flags {
+    pub(in crate::ocad) const ROTATABLE: u8 = 1;
+    pub(in crate::ocad) const STATUS_PROTECTED: u8 = 1;
+    pub(in crate::ocad) const STATUS_HIDDEN: u8 = 2;
+}
+
+/// Offsets into the version 8 base symbol.
+pub(super) mod base_symbol_v8 {
+    pub(in crate::ocad) const SIZE: usize = 0;
+    pub(in crate::ocad) const NUMBER: usize = 2;
+    pub(in crate::ocad) const TYPE: usize = 4;
+    pub(in crate::ocad) const SUBTYPE: usize = 6;
+    pub(in crate::ocad) const FLAGS: usize = 7;
+    pub(in crate::ocad) const STATUS: usize = 11;
+    pub(in crate::ocad) const DESCRIPTION: usize = 52;
+    /// The version 8 subtype of a line text symbol.
+    pub(in crate::ocad) const SUBTYPE_LINE_TEXT: u8 = 1;
+}
+
+/// Offsets into the base symbol of version 9 and later.
+pub(super) mod base_symbol {
+    pub(in crate::ocad) const SIZE: usize = 0;
+    pub(in crate::ocad) const NUMBER: usize = 4;
+    pub(in crate::ocad) const TYPE: usize = 8;
+    pub(in crate::ocad) const FLAGS: usize = 9;
+    pub(in crate::ocad) const STATUS: usize = 11;
+    /// The number of used colors followed by up to `MAX_COLORS` color numbers.
+    pub(in crate::ocad) const NUM_COLORS: usize = 26;
+    pub(in crate::ocad) const COLORS: usize = 28;
+    pub(in crate::ocad) const MAX_COLORS: usize = 14;
+    /// A Latin-1 string before version 11 and a UTF-16 string later.
+    pub(in crate::ocad) const DESCRIPTION: usize = 56;
+}
+
+/// Capacity of the symbol description, which holds the symbol name.
+pub(super) const DESCRIPTION_CAPACITY: usize = 31;
+/// Capacity of the UTF-16 symbol description of version 11 and later.
+pub(super) const DESCRIPTION_UTF16_CAPACITY: usize = 64;
+
+/// Size of a point symbol element header preceding its coordinates.
+pub(super) const ELEMENT_HEADER_SIZE: usize = 16;
+
+/// Offsets into a point symbol element header.
+pub(super) mod element {
+    pub(in crate::ocad) const TYPE: usize = 0;
+    pub(in crate::ocad) const FLAGS: usize = 2;
+    pub(in crate::ocad) const COLOR: usize = 4;
+    pub(in crate::ocad) const LINE_WIDTH: usize = 6;
+    pub(in crate::ocad) const DIAMETER: usize = 8;
+    pub(in crate::ocad) const NUM_COORDS: usize = 10;
+
+    pub(in crate::ocad) const TYPE_LINE: u16 = 1;
+    pub(in crate::ocad) const TYPE_AREA: u16 = 2;
+    pub(in crate::ocad) const TYPE_CIRCLE: u16 = 3;
+    pub(in crate::ocad) const TYPE_DOT: u16 = 4;
+
+    pub(in crate::ocad) const FLAG_ROUND: u16 = 1;
+    pub(in crate::ocad) const FLAG_MITER: u16 = 4;
+}
+
+/// Offsets into the point symbol part following the base symbol.
+pub(super) mod point_symbol {
+    pub(in crate::ocad) const DATA_SIZE: usize = 0;
+    pub(in crate::ocad) const ELEMENTS: usize = 4;
+}
+
+/// Offsets into the line symbol part following the base symbol.
+pub(super) mod line_symbol {
+    pub(in crate::ocad) const LINE_COLOR: usize = 0;
+    pub(in crate::ocad) const LINE_WIDTH: usize = 2;
+    pub(in crate::ocad) const LINE_STYLE: usize = 4;
+    pub(in crate::ocad) const DIST_FROM_START: usize = 6;
+    pub(in crate::ocad) const DIST_TO_END: usize = 8;
+    pub(in crate::ocad) const MAIN_LENGTH: usize = 10;
+    pub(in crate::ocad) const END_LENGTH: usize = 12;
+    pub(in crate::ocad) const MAIN_GAP: usize = 14;
+    pub(in crate::ocad) const SEC_GAP: usize = 16;
+    pub(in crate::ocad) const MIN_SYM: usize = 20;
+    pub(in crate::ocad) const NUM_PRIM_SYM: usize = 22;
+    pub(in crate::ocad) const PRIM_SYM_DIST: usize = 24;
+    pub(in crate::ocad) const DOUBLE_MODE: usize = 26;
+    pub(in crate::ocad) const DOUBLE_FLAGS: usize = 28;
+    pub(in crate::ocad) const DOUBLE_COLOR: usize = 30;
+    pub(in crate::ocad) const DOUBLE_LEFT_COLOR: usize = 32;
+    pub(in crate::ocad) const DOUBLE_RIGHT_COLOR: usize = 34;
+    pub(in crate::ocad) const DOUBLE_WIDTH: usize = 36;
+    pub(in crate::ocad) const DOUBLE_LEFT_WIDTH: usize = 38;
+    pub(in crate::ocad) const DOUBLE_RIGHT_WIDTH: usize = 40;
+    pub(in crate::ocad) const DOUBLE_LENGTH: usize = 42;
+    pub(in crate::ocad) const DOUBLE_GAP: usize = 44;
+    pub(in crate::ocad) const DEC_MODE: usize = 52;
+    pub(in crate::ocad) const FRAME_WIDTH: usize = 60;
+    pub(in crate::ocad) const PRIMARY_DATA_SIZE: usize = 64;
+    pub(in crate::ocad) const SECONDARY_DATA_SIZE: usize = 66;
+    pub(in crate::ocad) const CORNER_DATA_SIZE: usize = 68;
+    pub(in crate::ocad) const START_DATA_SIZE: usize = 70;
+    pub(in crate::ocad) const END_DATA_SIZE: usize = 72;
+    pub(in crate::ocad) const ELEMENTS: usize = 76;
+
+    pub(in crate::ocad) const STYLE_BEVEL_FLAT: u16 = 0;
+    pub(in crate::ocad) const STYLE_ROUND_ROUND: u16 = 1;
+    pub(in crate::ocad) const STYLE_BEVEL_POINTED: u16 = 2;
+    pub(in crate::ocad) const STYLE_ROUND_POINTED: u16 = 3;
+    pub(in crate::ocad) const STYLE_MITER_FLAT: u16 = 4;
+    pub(in crate::ocad) const STYLE_MITER_POINTED: u16 = 6;
+
+    pub(in crate::ocad) const DOUBLE_CONTINUOUS: u16 = 1;
+    pub(in crate::ocad) const DOUBLE_DASHED: u16 = 2;
+    pub(in crate::ocad) const DOUBLE_LEFT_DASHED: u16 = 3;
+    pub(in crate::ocad) const DOUBLE_RIGHT_DASHED: u16 = 4;
+    pub(in crate::ocad) const DOUBLE_FLAG_FILL: u16 = 1;
+}
+
+/// Offsets into the area symbol part following the base symbol of version 9
+/// and later. Version 8 lacks the leading border symbol number.
+pub(super) mod area_symbol {
+    pub(in crate::ocad) const BORDER_SYMBOL: usize = 0;
+    pub(in crate::ocad) const FILL_COLOR: usize = 4;
+    pub(in crate::ocad) const HATCH_MODE: usize = 6;
+    pub(in crate::ocad) const HATCH_COLOR: usize = 8;
+    pub(in crate::ocad) const HATCH_LINE_WIDTH: usize = 10;
+    pub(in crate::ocad) const HATCH_DIST: usize = 12;
+    pub(in crate::ocad) const HATCH_ANGLE_1: usize = 14;
+    pub(in crate::ocad) const HATCH_ANGLE_2: usize = 16;
+    pub(in crate::ocad) const FILL_ON: usize = 18;
+    pub(in crate::ocad) const BORDER_ON: usize = 19;
+    pub(in crate::ocad) const STRUCTURE_MODE: usize = 20;
+    pub(in crate::ocad) const STRUCTURE_WIDTH: usize = 22;
+    pub(in crate::ocad) const STRUCTURE_HEIGHT: usize = 24;
+    pub(in crate::ocad) const STRUCTURE_ANGLE: usize = 26;
+    pub(in crate::ocad) const DATA_SIZE: usize = 30;
+    pub(in crate::ocad) const ELEMENTS: usize = 32;
+    /// The size of the border symbol number missing from version 8.
+    pub(in crate::ocad) const V8_SHIFT: usize = 4;
+
+    pub(in crate::ocad) const HATCH_SINGLE: u16 = 1;
+    pub(in crate::ocad) const HATCH_CROSS: u16 = 2;
+    pub(in crate::ocad) const STRUCTURE_ALIGNED: u16 = 1;
+    pub(in crate::ocad) const STRUCTURE_SHIFTED: u16 = 2;
+}
+
+/// Offsets into the text symbol part following the base symbol.
+pub(super) mod text_symbol {
+    pub(in crate::ocad) const FONT_NAME: usize = 0;
+    pub(in crate::ocad) const FONT_NAME_CAPACITY: usize = 31;
+    pub(in crate::ocad) const FONT_COLOR: usize = 32;
+    pub(in crate::ocad) const FONT_SIZE: usize = 34;
+    pub(in crate::ocad) const FONT_WEIGHT: usize = 36;
+    pub(in crate::ocad) const ITALIC: usize = 38;
+    pub(in crate::ocad) const CHAR_SPACING: usize = 40;
+    pub(in crate::ocad) const ALIGNMENT: usize = 44;
+    pub(in crate::ocad) const LINE_SPACING: usize = 46;
+    pub(in crate::ocad) const PARA_SPACING: usize = 48;
+    pub(in crate::ocad) const NUM_TABS: usize = 54;
+    pub(in crate::ocad) const TABS: usize = 56;
+    pub(in crate::ocad) const MAX_TABS: usize = 32;
+    pub(in crate::ocad) const LINE_BELOW_ON: usize = 184;
+    pub(in crate::ocad) const LINE_BELOW_COLOR: usize = 186;
+    pub(in crate::ocad) const LINE_BELOW_WIDTH: usize = 188;
+    pub(in crate::ocad) const LINE_BELOW_OFFSET: usize = 190;
+    pub(in crate::ocad) const FRAMING_MODE: usize = 194;
+    pub(in crate::ocad) const FRAMING_COLOR: usize = 196;
+    pub(in crate::ocad) const FRAMING_WIDTH: usize = 198;
+    pub(in crate::ocad) const FRAMING_OFFSET_X: usize = 204;
+    pub(in crate::ocad) const FRAMING_OFFSET_Y: usize = 206;
+
+    pub(in crate::ocad) const SIZE: usize = 208;
+
+    pub(in crate::ocad) const BOLD_WEIGHT: u16 = 700;
+    pub(in crate::ocad) const NORMAL_WEIGHT: u16 = 400;
+    pub(in crate::ocad) const FRAMING_SHADOW: u8 = 1;
+    pub(in crate::ocad) const FRAMING_LINE: u8 = 2;
+    pub(in crate::ocad) const FRAMING_RECTANGLE: u8 = 3;
+    pub(in crate::ocad) const ALIGN_HORIZONTAL_MASK: u16 = 3;
+    pub(in crate::ocad) const ALIGN_VERTICAL_SHIFT: u16 = 2;
+}
+
+/// Object types, shared by all versions.
+pub(super) mod object_type {
+    pub(in crate::ocad) const POINT: u8 = 1;
+    pub(in crate::ocad) const LINE: u8 = 2;
+    pub(in crate::ocad) const AREA: u8 = 3;
+    pub(in crate::ocad) const UNFORMATTED_TEXT: u8 = 4;
+    pub(in crate::ocad) const FORMATTED_TEXT: u8 = 5;
+}
+
+/// Offsets into an object index entry.
+pub(super) mod object_entry {
+    /// The lower left and upper right corner of the bounding box.
+    pub(in crate::ocad) const LOWER_LEFT: usize = 0;
+    pub(in crate::ocad) const UPPER_RIGHT: usize = 8;
+    pub(in crate::ocad) const POS: usize = 16;
+    /// The number of coordinates reserved for the object, including its text.
+    pub(in crate::ocad) const LEN: usize = 20;
+    pub(in crate::ocad) const SYMBOL: usize = 24;
+    /// The version 8 symbol number, which is 0 for deleted objects.
+    pub(in crate::ocad) const SYMBOL_V8: usize = 22;
+    pub(in crate::ocad) const TYPE: usize = 28;
+    pub(in crate::ocad) const STATUS: usize = 30;
+
+    pub(in crate::ocad) const STATUS_NORMAL: u8 = 1;
+    pub(in crate::ocad) const STATUS_HIDDEN: u8 = 2;
+}
+
+/// Offsets into the header of an object of version 9 and later.
+pub(super) mod object {
+    pub(in crate::ocad) const SYMBOL: usize = 0;
+    pub(in crate::ocad) const TYPE: usize = 4;
+    pub(in crate::ocad) const ANGLE: usize = 6;
+    pub(in crate::ocad) const NUM_ITEMS: usize = 8;
+    pub(in crate::ocad) const NUM_TEXT: usize = 12;
+}
+
+/// Offsets into the header of a version 8 object.
+pub(super) mod object_v8 {
+    pub(in crate::ocad) const SYMBOL: usize = 0;
+    pub(in crate::ocad) const TYPE: usize = 2;
+    pub(in crate::ocad) const UNICODE: usize = 3;
+    pub(in crate::ocad) const NUM_ITEMS: usize = 4;
+    pub(in crate::ocad) const NUM_TEXT: usize = 6;
+    pub(in crate::ocad) const ANGLE: usize = 8;
+}
+
+/// Size of a coordinate, and the unit of element and text sizes.
+pub(super) const COORD_SIZE: usize = 8;
+
+/// Flags stored in the low byte of a coordinate's x value.
+pub(super) const X_FLAG_CURVE_FIRST_HANDLE: i32 = 1;
+pub(super) const X_FLAG_CURVE_SECOND_HANDLE: i32 = 2;
+/// Flags stored in the low byte of a coordinate's y value.
+pub(super) const Y_FLAG_CORNER: i32 = 1;
+pub(super) const Y_FLAG_HOLE_START: i32 = 2;
+pub(super) const Y_FLAG_DASH_POINT: i32 = 8;
+
+/// The OCAD file format generations with distinct layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum OcdFormat {
+    /// OCAD 8.
+    V8,
+    /// OCAD 9 and 10.
+    V9,
+    /// OCAD 11.
+    V11,
+    /// OCAD 12 and 2018.
+    V12,
+}
+
+impl OcdFormat {
+    /// Get the layout generation of a file version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnsupportedOcadVersion`] for versions other than 8 to 12 and 2018.
+    pub(super) fn from_version(version: u16) -> Result<Self> {
+        match version {
+            8 => Ok(Self::V8),
+            9 | 10 => Ok(Self::V9),
+            11 => Ok(Self::V11),
+            12 | 2018 => Ok(Self::V12),
+            _ => Err(Error::UnsupportedOcadVersion(version)),
+        }
+    }
+
+    /// The size of the common part of every symbol.
+    pub(super) fn base_symbol_size(self) -> usize {
+        match self {
+            Self::V8 => 348,
+            Self::V9 => 572,
+            Self::V11 | Self::V12 => 700,
+        }
+    }
+
+    /// The size of an entry in an object index block.
+    pub(super) fn object_entry_size(self) -> usize {
+        match self {
+            Self::V8 => 24,
+            Self::V9 | Self::V11 | Self::V12 => 40,
+        }
+    }
+
+    /// The size of an object header preceding its coordinates.
+    pub(super) fn object_header_size(self) -> usize {
+        match self {
+            Self::V8 => 16,
+            Self::V9 | Self::V11 => 32,
+            Self::V12 => 52,
+        }
+    }
+
+    /// Symbol numbers are stored as the code times this factor.
+    pub(super) fn symbol_number_factor(self) -> i32 {
+        match self {
+            Self::V8 => 10,
+            Self::V9 | Self::V11 | Self::V12 => 1000,
+        }
+    }
+
+    /// Get the [`Code`] of an OCAD symbol number.
+    pub(super) fn code_from_symbol_number(self, number: i32) -> Code {
+        let factor = self.symbol_number_factor();
+        let major = u16::try_from(number.max(0) / factor).unwrap_or(u16::MAX);
+        let minor = u16::try_from(number.max(0) % factor).unwrap_or(0);
+        Code::new(major, minor, 0)
+    }
+}
+
+/// Bounds checked little endian access to the bytes of an OCAD file.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct OcdBytes<'a>(&'a [u8]);
+
+impl<'a> OcdBytes<'a> {
+    pub(super) fn new(bytes: &'a [u8]) -> Self {
+        Self(bytes)
+    }
+
+    pub(super) fn len(self) -> usize {
+        self.0.len()
+    }
+
+    /// Get the bytes from `pos` to `pos + len`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TruncatedOcadFile`] if the range is out of bounds.
+    pub(super) fn slice(self, pos: usize, len: usize) -> Result<Self> {
+        pos.checked_add(len)
+            .and_then(|end| self.0.get(pos..end))
+            .map(Self)
+            .ok_or(Error::TruncatedOcadFile)
+    }
+
+    /// Get the bytes from `pos` to the end.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TruncatedOcadFile`] if `pos` is out of bounds.
+    pub(super) fn tail(self, pos: usize) -> Result<Self> {
+        self.0.get(pos..).map(Self).ok_or(Error::TruncatedOcadFile)
+    }
+
+    fn array<const N: usize>(self, pos: usize) -> Result<[u8; N]> {
+        let bytes = self.slice(pos, N)?.0;
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        Ok(array)
+    }
+
+    pub(super) fn u8(self, pos: usize) -> Result<u8> {
+        self.0.get(pos).copied().ok_or(Error::TruncatedOcadFile)
+    }
+
+    pub(super) fn u16(self, pos: usize) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.array(pos)?))
+    }
+
+    pub(super) fn i16(self, pos: usize) -> Result<i16> {
+        Ok(i16::from_le_bytes(self.array(pos)?))
+    }
+
+    pub(super) fn u32(self, pos: usize) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array(pos)?))
+    }
+
+    pub(super) fn i32(self, pos: usize) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.array(pos)?))
+    }
+
+    pub(super) fn f64(self, pos: usize) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.array(pos)?))
+    }
+
+    /// Read a position stored as `u32`.
+    pub(super) fn offset(self, pos: usize) -> Result<usize> {
+        Ok(usize::try_from(self.u32(pos)?)?)
+    }
+
+    /// Read a length-prefixed Latin-1 string with room for `capacity` characters.
+    pub(super) fn pascal_string(self, pos: usize, capacity: usize) -> Result<String> {
+        let len = usize::from(self.u8(pos)?).min(capacity);
+        Ok(latin1(self.slice(pos + 1, len)?.0))
+    }
+
+    /// Read a UTF-16 string with room for `capacity` characters.
+    pub(super) fn utf16_string(self, pos: usize, capacity: usize) -> Result<String> {
+        Ok(utf16(self.slice(pos, capacity * 2)?.0))
+    }
+
+    /// Read a coordinate with its flags.
+    pub(super) fn point(self, pos: usize) -> Result<OcdPoint> {
+        Ok(OcdPoint {
+            x: self.i32(pos)?,
+            y: self.i32(pos + 4)?,
+        })
+    }
+
+    pub(super) fn as_slice(self) -> &'a [u8] {
+        self.0
+    }
+}
+
+/// Decode Latin-1 text up to the first nul character.
+pub(super) fn latin1(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| char::from(b))
+        .collect()
+}
+
+/// Decode UTF-16LE text up to the first nul character.
+pub(super) fn utf16(bytes: &[u8]) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0);
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// A raw OCAD coordinate in 1/100 mm in the upper 24 bits with flags in the low byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct OcdPoint {
+    pub(super) x: i32,
+    pub(super) y: i32,
+}
+
+impl OcdPoint {
+    /// Encode a map coordinate in mm with its flags.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MapCoordOutOfBounds`] if the coordinate does not fit into 24 bits.
+    pub(super) fn new(coord: Coord, x_flags: i32, y_flags: i32) -> Result<Self> {
+        const LIMIT: f64 = (1 << 23) as f64;
+        let raw = |value: f64, flags: i32| {
+            let value = (value * 100.).round();
+            if value.is_finite() && (-LIMIT..LIMIT).contains(&value) {
+                Ok(((value as i32) << 8) | flags)
+            } else {
+                Err(Error::MapCoordOutOfBounds)
+            }
+        };
+        Ok(Self {
+            x: raw(coord.x, x_flags)?,
+            y: raw(coord.y, y_flags)?,
+        })
+    }
+
+    /// Get the map coordinate in mm.
+    pub(super) fn coord(self) -> Coord {
+        Coord {
+            x: f64::from(self.x >> 8) / 100.,
+            y: f64::from(self.y >> 8) / 100.,
+        }
+    }
+
+    pub(super) fn x_flags(self) -> i32 {
+        self.x & 0xff
+    }
+
+    pub(super) fn y_flags(self) -> i32 {
+        self.y & 0xff
+    }
+}
+
+/// Convert a length in 1/100 mm to mm.
+pub(super) fn length_from_ocd(value: impl Into<f64>) -> f64 {
+    value.into() / 100.
+}
+
+/// Convert a length in mm to 1/100 mm, to be saturated by an `as` cast to the field type.
+pub(super) fn length_to_ocd(value: f64) -> f64 {
+    (value * 100.).round()
+}
+
+/// Convert an angle in radians to 1/10 degrees in `0..3600`.
+pub(super) fn angle_to_ocd(value: f64) -> i16 {
+    ((value.to_degrees() * 10.).round().rem_euclid(3600.) as i16) % 3600
+}
+
+/// Convert an angle in 1/10 degrees to radians.
+pub(super) fn angle_from_ocd(value: i16) -> f64 {
+    (f64::from(value) / 10.).to_radians()
+}
+
+/// A little endian record under construction, the counterpart of [`OcdBytes`].
+#[derive(Debug, Clone, Default)]
+pub(super) struct OcdRecord(Vec<u8>);
+
+impl OcdRecord {
+    /// Create a zeroed record of `size` bytes.
+    pub(super) fn new(size: usize) -> Self {
+        Self(vec![0; size])
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn set(&mut self, pos: usize, bytes: &[u8]) {
+        let end = pos + bytes.len();
+        if self.0.len() < end {
+            self.0.resize(end, 0);
+        }
+        self.0[pos..end].copy_from_slice(bytes);
+    }
+
+    pub(super) fn set_u8(&mut self, pos: usize, value: u8) {
+        self.set(pos, &[value]);
+    }
+
+    pub(super) fn set_u16(&mut self, pos: usize, value: u16) {
+        self.set(pos, &value.to_le_bytes());
+    }
+
+    pub(super) fn set_i16(&mut self, pos: usize, value: i16) {
+        self.set(pos, &value.to_le_bytes());
+    }
+
+    pub(super) fn set_u32(&mut self, pos: usize, value: u32) {
+        self.set(pos, &value.to_le_bytes());
+    }
+
+    pub(super) fn set_i32(&mut self, pos: usize, value: i32) {
+        self.set(pos, &value.to_le_bytes());
+    }
+
+    /// Store a position as `u32`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TryFromIntError`] if the position does not fit into 32 bits.
+    pub(super) fn set_offset(&mut self, pos: usize, value: usize) -> Result<()> {
+        self.set_u32(pos, u32::try_from(value)?);
+        Ok(())
+    }
+
+    /// Store a length-prefixed Latin-1 string, truncated to `capacity` characters.
+    pub(super) fn set_pascal_string(&mut self, pos: usize, capacity: usize, value: &str) {
+        let bytes = value
+            .chars()
+            .take(capacity)
+            .map(|c| u8::try_from(c).unwrap_or(b'?'))
+            .collect::<Vec<_>>();
+        self.set_u8(pos, u8::try_from(bytes.len()).unwrap_or(u8::MAX));
+        self.set(pos + 1, &bytes);
+    }
+
+    /// Store a nul-terminated UTF-16 string, truncated to leave room for the nul.
+    pub(super) fn set_utf16_string(&mut self, pos: usize, capacity: usize, value: &str) {
+        let bytes = value
+            .encode_utf16()
+            .take(capacity - 1)
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        self.set(pos, &bytes);
+    }
+
+    pub(super) fn set_point(&mut self, pos: usize, point: OcdPoint) {
+        self.set_i32(pos, point.x);
+        self.set_i32(pos + 4, point.y);
+    }
+
+    pub(super) fn extend(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    pub(super) fn push_point(&mut self, point: OcdPoint) {
+        let pos = self.len();
+        self.set_point(pos, point);
+    }
+
+    pub(super) fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub(super) fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
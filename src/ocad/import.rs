@@ -0,0 +1,1713 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    num::NonZeroU32,
+    rc::{Rc, Weak},
+};
+
+use geo_types::{Coord, Point};
+use linestring2bezier::{BezierSegment, BezierString};
+
+use super::{
+    OcdSymbolFeature, OcdWarning,
+    format::{
+        COORD_SIZE, DESCRIPTION_CAPACITY, DESCRIPTION_UTF16_CAPACITY, ELEMENT_HEADER_SIZE,
+        FILE_HEADER_SIZE, INDEX_BLOCK_ENTRIES, OCAD_MARK, OcdBytes, OcdFormat, OcdPoint,
+        SYMBOL_ENTRY_SIZE, X_FLAG_CURVE_FIRST_HANDLE, Y_FLAG_CORNER, Y_FLAG_DASH_POINT,
+        Y_FLAG_HOLE_START, angle_from_ocd, area_symbol, base_symbol, base_symbol_v8, colors_v8,
+        element, header, latin1, length_from_ocd, line_symbol, object, object_entry, object_type,
+        object_v8, point_symbol, setup_v8, string_entry, string_type, symbol_flags, symbol_type,
+        text_symbol, utf16,
+    },
+};
+use crate::{
+    Code, Error, NonNegativeF64, Omap, Result, UnitF64,
+    colors::{
+        Cmyk, CmykMode, Color, ColorComponent, ColorSet, MixedColor, RgbMode, SpotColor,
+        SymbolColor, WeakColor,
+    },
+    geo_referencing::GeoRef,
+    objects::{
+        AreaObject, BezierPath, BezierPolygon, HorizontalAlign, LineObject, MapObject, PointObject,
+        TextGeometry, TextObject, VerticalAlign, WrapBox,
+    },
+    parts::{MapPart, MapParts},
+    symbols::{
+        AreaSymbol, BorderDash, BorderStyle, CapStyle, DashStyle, DashSymbol, Element, FillPattern,
+        FramingMode, GroupDashes, JoinStyle, LineBelow, LineFraming, LineSymbol, LineSymbolBorder,
+        MidSymbol, MidSymbolPlacement, PointSymbol, ShadowFraming, Symbol, SymbolCommon, SymbolSet,
+        TextSymbol,
+    },
+};
+
+/// The map scale used when a file does not store one.
+const FALLBACK_SCALE: u32 = 15_000;
+
+/// Read an OCAD file of version 8 to 2018.
+pub(crate) fn read(bytes: &[u8]) -> Result<(Omap, Vec<OcdWarning>)> {
+    let bytes = OcdBytes::new(bytes);
+    if bytes.len() < FILE_HEADER_SIZE || bytes.u16(header::MARK)? != OCAD_MARK {
+        return Err(Error::NotAnOcadFile);
+    }
+    let format = OcdFormat::from_version(bytes.u16(header::VERSION)?)?;
+
+    let mut importer = Importer {
+        bytes,
+        format,
+        colors: HashMap::new(),
+        symbols: HashMap::new(),
+        warnings: Vec::new(),
+    };
+
+    let (colors, geo_referencing) = if format == OcdFormat::V8 {
+        (importer.read_colors_v8()?, importer.read_geo_ref_v8()?)
+    } else {
+        let strings = importer.parameter_strings()?;
+        (
+            importer.colors_from_strings(&strings)?,
+            geo_ref_from_strings(&strings),
+        )
+    };
+    let symbols = importer.read_symbols()?;
+    let part = importer.read_objects()?;
+
+    let mut parts = MapParts::new();
+    parts.push(part);
+    let omap = Omap {
+        notes: String::new(),
+        geo_referencing,
+        colors,
+        symbols,
+        parts,
+        templates: Default::default(),
+        view: Default::default(),
+    };
+    Ok((omap, importer.warnings))
+}
+
+/// An imported symbol as referenced by objects.
+enum ImportedSymbol {
+    Point(Weak<RefCell<PointSymbol>>),
+    Line(Weak<RefCell<LineSymbol>>),
+    Area(Weak<RefCell<AreaSymbol>>),
+    Text {
+        symbol: Weak<RefCell<TextSymbol>>,
+        h_align: HorizontalAlign,
+        v_align: VerticalAlign,
+    },
+}
+
+struct Importer<'a> {
+    bytes: OcdBytes<'a>,
+    format: OcdFormat,
+    /// Imported colors by OCAD color number.
+    colors: HashMap<u16, WeakColor>,
+    /// Imported symbols by OCAD symbol number.
+    symbols: HashMap<i32, ImportedSymbol>,
+    warnings: Vec<OcdWarning>,
+}
+
+/// A color of the OCAD color table before it is added to the color set.
+struct ColorDefinition {
+    number: u16,
+    name: String,
+    cmyk: Cmyk,
+    /// Spot separation indices and their factors.
+    separations: Vec<(usize, f64)>,
+}
+
+/// A spot color separation before it is added to the color set.
+struct SeparationDefinition {
+    name: String,
+    cmyk: Cmyk,
+    frequency: f64,
+    angle: f64,
+}
+
+/// A parameter string split into its first unnamed field and its coded fields.
+struct ParameterString {
+    string_type: i32,
+    name: String,
+    fields: Vec<(char, String)>,
+}
+
+impl ParameterString {
+    fn parse(string_type: i32, value: &str) -> Self {
+        let mut parts = value.split('\t');
+        let name = parts.next().unwrap_or_default().to_owned();
+        let fields = parts
+            .filter_map(|part| {
+                let mut chars = part.chars();
+                chars.next().map(|code| (code, chars.as_str().to_owned()))
+            })
+            .collect();
+        Self {
+            string_type,
+            name,
+            fields,
+        }
+    }
+
+    /// Get the first value of a field as a number.
+    fn number(&self, code: char) -> Option<f64> {
+        self.fields
+            .iter()
+            .find(|(c, _)| *c == code)
+            .and_then(|(_, value)| value.trim().parse().ok())
+    }
+}
+
+fn cmyk_from_percentages(c: f64, m: f64, y: f64, k: f64) -> Cmyk {
+    Cmyk {
+        c: UnitF64::clamped_from(c / 100.),
+        m: UnitF64::clamped_from(m / 100.),
+        y: UnitF64::clamped_from(y / 100.),
+        k: UnitF64::clamped_from(k / 100.),
+    }
+}
+
+fn geo_ref_from_strings(strings: &[ParameterString]) -> GeoRef {
+    let scale = strings.iter().find(|s| s.string_type == string_type::SCALE);
+    let Some(scale) = scale else {
+        return GeoRef::new(NonZeroU32::new(FALLBACK_SCALE).unwrap_or(NonZeroU32::MIN));
+    };
+    geo_ref_from_setup(
+        scale
+            .number('m')
+            .unwrap_or_else(|| f64::from(FALLBACK_SCALE)),
+        Coord {
+            x: scale.number('x').unwrap_or(0.),
+            y: scale.number('y').unwrap_or(0.),
+        },
+        scale.number('a').unwrap_or(0.),
+    )
+}
+
+/// Build the georeferencing from the scale, the real world coordinates of
+/// the paper origin and the grid angle in degrees.
+fn geo_ref_from_setup(scale: f64, offset: Coord, angle_deg: f64) -> GeoRef {
+    let scale = if scale.is_finite() && scale >= 1. && scale <= f64::from(u32::MAX) {
+        scale.round() as u32
+    } else {
+        FALLBACK_SCALE
+    };
+    let mut geo_ref = GeoRef::new(NonZeroU32::new(scale).unwrap_or(NonZeroU32::MIN));
+    if offset.x.is_finite() && offset.y.is_finite() {
+        geo_ref.projected_ref_point = offset;
+    }
+    if angle_deg.is_finite() {
+        geo_ref.declination_deg = angle_deg;
+    }
+    geo_ref
+}
+
+/// Build the color set in OCAD's color order, followed by the spot color separations.
+fn build_colors(
+    definitions: Vec<ColorDefinition>,
+    separations: Vec<SeparationDefinition>,
+) -> Result<(ColorSet, HashMap<u16, WeakColor>)> {
+    let spot_colors = separations
+        .into_iter()
+        .map(|separation| {
+            let mut spot =
+                SpotColor::new(separation.name.clone(), separation.name, separation.cmyk);
+            spot.screen_frequency = NonNegativeF64::clamped_from(separation.frequency);
+            spot.screen_angle_deg = separation.angle;
+            Rc::new(RefCell::new(spot))
+        })
+        .collect::<Vec<_>>();
+
+    let mut color_set = ColorSet::new();
+    let mut by_number = HashMap::new();
+    for definition in definitions {
+        let components = definition
+            .separations
+            .iter()
+            .filter_map(|&(index, factor)| {
+                spot_colors.get(index).map(|spot| ColorComponent {
+                    factor: UnitF64::clamped_from(factor),
+                    color: Rc::downgrade(spot),
+                })
+            })
+            .collect();
+        let mut color = MixedColor::new(definition.name, components);
+        color.set_cmyk_mode(CmykMode::Cmyk(definition.cmyk))?;
+        color.set_rgb_mode(RgbMode::FromCmyk)?;
+        let weak = color_set.push(color);
+        let _previous = by_number.insert(definition.number, weak);
+    }
+    for spot in spot_colors {
+        let _weak = color_set.push(Color::SpotColor(spot));
+    }
+    Ok((color_set, by_number))
+}
+
+impl<'a> Importer<'a> {
+    /// Iterate over the entries of a chain of index blocks.
+    fn index_entries(&self, first_block: usize, entry_size: usize) -> Result<Vec<OcdBytes<'a>>> {
+        let mut entries = Vec::new();
+        let mut visited = HashSet::new();
+        let mut block = first_block;
+        while block != 0 && visited.insert(block) {
+            let data = self
+                .bytes
+                .slice(block, 4 + INDEX_BLOCK_ENTRIES * entry_size)?;
+            for index in 0..INDEX_BLOCK_ENTRIES {
+                entries.push(data.slice(4 + index * entry_size, entry_size)?);
+            }
+            block = data.offset(0)?;
+        }
+        Ok(entries)
+    }
+
+    fn symbol_color(&mut self, code: Code, number: u16) -> SymbolColor {
+        if let Some(color) = self.colors.get(&number) {
+            SymbolColor::Color(color.clone())
+        } else {
+            self.warnings.push(OcdWarning::UnknownColor {
+                code,
+                color_number: number,
+            });
+            SymbolColor::NoColor
+        }
+    }
+
+    fn read_colors_v8(&mut self) -> Result<ColorSet> {
+        let num_colors =
+            usize::from(self.bytes.u16(colors_v8::NUM_COLORS)?).min(colors_v8::MAX_COLORS);
+        let num_separations = usize::from(self.bytes.u16(colors_v8::NUM_SEPARATIONS)?)
+            .min(colors_v8::MAX_SEPARATIONS);
+
+        let mut separations = Vec::with_capacity(num_separations);
+        for index in 0..num_separations {
+            let info = self.bytes.slice(
+                colors_v8::SEPARATION_INFO + index * colors_v8::SEPARATION_INFO_SIZE,
+                colors_v8::SEPARATION_INFO_SIZE,
+            )?;
+            separations.push(SeparationDefinition {
+                name: info.pascal_string(0, 15)?,
+                cmyk: cmyk_from_half_percentages(info.slice(16, 4)?.as_slice()),
+                frequency: f64::from(info.u16(20)?) / 10.,
+                angle: f64::from(info.u16(22)?) / 10.,
+            });
+        }
+
+        let mut definitions = Vec::with_capacity(num_colors);
+        for index in 0..num_colors {
+            let info = self.bytes.slice(
+                colors_v8::COLOR_INFO + index * colors_v8::COLOR_INFO_SIZE,
+                colors_v8::COLOR_INFO_SIZE,
+            )?;
+            let separation_factors = info.slice(40, colors_v8::MAX_SEPARATIONS)?;
+            definitions.push(ColorDefinition {
+                number: info.u16(0)?,
+                name: info.pascal_string(8, 31)?,
+                cmyk: cmyk_from_half_percentages(info.slice(4, 4)?.as_slice()),
+                separations: separation_factors.as_slice()[..num_separations]
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &value)| value != colors_v8::UNUSED_SEPARATION)
+                    .map(|(index, &value)| (index, f64::from(value) / 200.))
+                    .collect(),
+            });
+        }
+
+        let (color_set, by_number) = build_colors(definitions, separations)?;
+        self.colors = by_number;
+        Ok(color_set)
+    }
+
+    fn read_geo_ref_v8(&self) -> Result<GeoRef> {
+        let setup_pos = self.bytes.offset(header::SETUP_POS)?;
+        if setup_pos == 0 {
+            return Ok(GeoRef::new(
+                NonZeroU32::new(FALLBACK_SCALE).unwrap_or(NonZeroU32::MIN),
+            ));
+        }
+        let setup = self.bytes.tail(setup_pos)?;
+        Ok(geo_ref_from_setup(
+            setup.f64(setup_v8::MAP_SCALE)?,
+            Coord {
+                x: setup.f64(setup_v8::REAL_OFFSET_X)?,
+                y: setup.f64(setup_v8::REAL_OFFSET_Y)?,
+            },
+            setup.f64(setup_v8::REAL_ANGLE)?,
+        ))
+    }
+
+    /// Read all parameter strings in file order.
+    fn parameter_strings(&self) -> Result<Vec<ParameterString>> {
+        let first_block = self.bytes.offset(header::FIRST_STRING_BLOCK)?;
+        let mut strings = Vec::new();
+        for entry in self.index_entries(first_block, string_entry::SIZE)? {
+            let pos = entry.offset(string_entry::POS)?;
+            let len = entry.offset(string_entry::LEN)?;
+            if pos == 0 || len == 0 {
+                continue;
+            }
+            let raw = self.bytes.slice(pos, len)?.as_slice();
+            let value = if self.format >= OcdFormat::V11 {
+                let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+                String::from_utf8_lossy(&raw[..end]).into_owned()
+            } else {
+                latin1(raw)
+            };
+            strings.push(ParameterString::parse(
+                entry.i32(string_entry::TYPE)?,
+                &value,
+            ));
+        }
+        Ok(strings)
+    }
+
+    fn colors_from_strings(&mut self, strings: &[ParameterString]) -> Result<ColorSet> {
+        let spot_strings = strings
+            .iter()
+            .filter(|s| s.string_type == string_type::SPOT_COLOR)
+            .collect::<Vec<_>>();
+        let separations = spot_strings
+            .iter()
+            .map(|s| SeparationDefinition {
+                name: s.name.clone(),
+                cmyk: cmyk_from_percentages(
+                    s.number('c').unwrap_or(0.),
+                    s.number('m').unwrap_or(0.),
+                    s.number('y').unwrap_or(0.),
+                    s.number('k').unwrap_or(0.),
+                ),
+                frequency: s.number('f').unwrap_or(0.),
+                angle: s.number('a').unwrap_or(0.),
+            })
+            .collect();
+
+        let definitions = strings
+            .iter()
+            .filter(|s| s.string_type == string_type::COLOR)
+            .map(|s| {
+                // every `s` field names a separation whose factor follows in a `p` field
+                let mut separations = Vec::new();
+                let mut current = None;
+                for (code, value) in &s.fields {
+                    match code {
+                        's' => current = spot_strings.iter().position(|spot| spot.name == *value),
+                        'p' => {
+                            if let (Some(index), Ok(percentage)) =
+                                (current.take(), value.trim().parse::<f64>())
+                            {
+                                separations.push((index, percentage / 100.));
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                ColorDefinition {
+                    number: s.number('n').unwrap_or(0.) as u16,
+                    name: s.name.clone(),
+                    cmyk: cmyk_from_percentages(
+                        s.number('c').unwrap_or(0.),
+                        s.number('m').unwrap_or(0.),
+                        s.number('y').unwrap_or(0.),
+                        s.number('k').unwrap_or(0.),
+                    ),
+                    separations,
+                }
+            })
+            .collect();
+
+        let (color_set, by_number) = build_colors(definitions, separations)?;
+        self.colors = by_number;
+        Ok(color_set)
+    }
+
+    fn read_symbols(&mut self) -> Result<SymbolSet> {
+        let mut symbol_set = SymbolSet::new("OCAD");
+        let first_block = self.bytes.offset(header::FIRST_SYMBOL_BLOCK)?;
+        let positions = self
+            .index_entries(first_block, SYMBOL_ENTRY_SIZE)?
+            .into_iter()
+            .map(|entry| entry.offset(0))
+            .collect::<Result<Vec<_>>>()?;
+        for pos in positions.into_iter().filter(|&pos| pos != 0) {
+            if let Some((number, symbol)) = self.read_symbol(pos)? {
+                let imported = match &symbol {
+                    Symbol::Point(s) => ImportedSymbol::Point(Rc::downgrade(s)),
+                    Symbol::Line(s) => ImportedSymbol::Line(Rc::downgrade(s)),
+                    Symbol::Area(s) => ImportedSymbol::Area(Rc::downgrade(s)),
+                    Symbol::Text(s) => {
+                        let (h_align, v_align) = self.text_alignment(pos)?;
+                        ImportedSymbol::Text {
+                            symbol: Rc::downgrade(s),
+                            h_align,
+                            v_align,
+                        }
+                    }
+                    Symbol::CombinedArea(_) | Symbol::CombinedLine(_) => continue,
+                };
+                let _weak = symbol_set.add_symbol(symbol);
+                let _previous = self.symbols.insert(number, imported);
+            }
+        }
+        Ok(symbol_set)
+    }
+
+    /// Read the symbol at `pos` and return it with its OCAD symbol number.
+    fn read_symbol(&mut self, pos: usize) -> Result<Option<(i32, Symbol)>> {
+        let base_size = self.format.base_symbol_size();
+        let raw = self.bytes.tail(pos)?;
+        let (size, number, kind, flags, status, description) = if self.format == OcdFormat::V8 {
+            let kind = raw.u16(base_symbol_v8::TYPE)? as u8;
+            let kind = match kind {
+                symbol_type::LINE
+                    if raw.u8(base_symbol_v8::SUBTYPE)? == base_symbol_v8::SUBTYPE_LINE_TEXT =>
+                {
+                    symbol_type::LINE_TEXT
+                }
+                symbol_type::RECTANGLE_V8 => symbol_type::RECTANGLE,
+                kind => kind,
+            };
+            (
+                usize::from(raw.u16(base_symbol_v8::SIZE)?),
+                i32::from(raw.u16(base_symbol_v8::NUMBER)?),
+                kind,
+                raw.u8(base_symbol_v8::FLAGS)?,
+                raw.u8(base_symbol_v8::STATUS)?,
+                base_symbol_v8::DESCRIPTION,
+            )
+        } else {
+            (
+                usize::try_from(raw.i32(base_symbol::SIZE)?)?,
+                raw.i32(base_symbol::NUMBER)?,
+                raw.u8(base_symbol::TYPE)?,
+                raw.u8(base_symbol::FLAGS)?,
+                raw.u8(base_symbol::STATUS)?,
+                base_symbol::DESCRIPTION,
+            )
+        };
+        let raw = self.bytes.slice(pos, size.max(base_size))?;
+        let code = self.format.code_from_symbol_number(number);
+        let name = if self.format >= OcdFormat::V11 {
+            raw.utf16_string(description, DESCRIPTION_UTF16_CAPACITY)?
+        } else {
+            raw.pascal_string(description, DESCRIPTION_CAPACITY)?
+        };
+        let common = SymbolCommon {
+            name,
+            code,
+            is_hidden: status & symbol_flags::STATUS_HIDDEN != 0,
+            is_protected: status & symbol_flags::STATUS_PROTECTED != 0,
+            ..Default::default()
+        };
+        let is_rotatable = flags & symbol_flags::ROTATABLE != 0;
+        let data = raw.tail(base_size)?;
+
+        let symbol = match kind {
+            symbol_type::POINT => {
+                let data_size = usize::from(data.u16(point_symbol::DATA_SIZE)?);
+                let mut symbol =
+                    self.point_symbol(code, data.tail(point_symbol::ELEMENTS)?, data_size)?;
+                symbol.common = common;
+                symbol.is_rotatable = is_rotatable;
+                Symbol::from(symbol)
+            }
+            symbol_type::LINE => Symbol::from(self.line_symbol(common, data)?),
+            symbol_type::AREA => Symbol::from(self.area_symbol(common, is_rotatable, data)?),
+            symbol_type::TEXT => Symbol::from(self.text_symbol(common, data)?),
+            _ => {
+                self.warnings.push(OcdWarning::UnsupportedSymbolType {
+                    code,
+                    symbol_type: kind,
+                });
+                return Ok(None);
+            }
+        };
+        Ok(Some((number, symbol)))
+    }
+
+    /// Build a point symbol from `data_size` coordinate units of elements.
+    fn point_symbol(
+        &mut self,
+        code: Code,
+        data: OcdBytes<'_>,
+        data_size: usize,
+    ) -> Result<PointSymbol> {
+        let mut symbol = PointSymbol::new(code, String::new());
+        let end = data_size * COORD_SIZE;
+        let mut pos = 0;
+        while pos + ELEMENT_HEADER_SIZE <= end {
+            let header = data.slice(pos, ELEMENT_HEADER_SIZE)?;
+            let num_coords = usize::from(header.u16(element::NUM_COORDS)?);
+            let points = (0..num_coords)
+                .map(|index| data.point(pos + ELEMENT_HEADER_SIZE + index * COORD_SIZE))
+                .collect::<Result<Vec<_>>>()?;
+            pos += ELEMENT_HEADER_SIZE + num_coords * COORD_SIZE;
+
+            let color = self.symbol_color(code, header.u16(element::COLOR)?);
+            let line_width = length_from_ocd(header.u16(element::LINE_WIDTH)?);
+            let diameter = length_from_ocd(header.u16(element::DIAMETER)?);
+            let position = points.first().map(|p| p.coord()).unwrap_or_default();
+            let element = match header.u16(element::TYPE)? {
+                element::TYPE_LINE => {
+                    let flags = header.u16(element::FLAGS)?;
+                    let (cap_style, join_style) = if flags & element::FLAG_ROUND != 0 {
+                        (CapStyle::Round, JoinStyle::Round)
+                    } else if flags & element::FLAG_MITER != 0 {
+                        (CapStyle::Flat, JoinStyle::Miter)
+                    } else {
+                        (CapStyle::Flat, JoinStyle::Bevel)
+                    };
+                    let line = LineSymbol::new(Code::default(), String::new())
+                        .with_color(color)
+                        .with_line_width(NonNegativeF64::clamped_from(line_width))
+                        .with_cap_style(cap_style)
+                        .with_join_style(join_style);
+                    Element::Line {
+                        symbol: Box::new(line),
+                        object: Box::new(LineObject::new_element(bezier_path(&points)?)),
+                    }
+                }
+                element::TYPE_AREA => Element::Area {
+                    symbol: Box::new(
+                        AreaSymbol::new(Code::default(), String::new()).with_color(color),
+                    ),
+                    object: Box::new(AreaObject::new_element(bezier_polygon(&points)?)),
+                },
+                element::TYPE_CIRCLE => Element::Point {
+                    symbol: Box::new(
+                        PointSymbol::new(Code::default(), String::new())
+                            .with_outer_color(color)
+                            .with_outer_width(NonNegativeF64::clamped_from(line_width))
+                            .with_inner_radius(NonNegativeF64::clamped_from(
+                                diameter / 2. - line_width,
+                            )),
+                    ),
+                    object: Box::new(PointObject::new(Weak::new(), Point(position))),
+                },
+                element::TYPE_DOT => Element::Point {
+                    symbol: Box::new(
+                        PointSymbol::new(Code::default(), String::new())
+                            .with_inner_color(color)
+                            .with_inner_radius(NonNegativeF64::clamped_from(diameter / 2.)),
+                    ),
+                    object: Box::new(PointObject::new(Weak::new(), Point(position))),
+                },
+                _ => continue,
+            };
+            symbol = symbol.with_element(element);
+        }
+        Ok(symbol)
+    }
+
+    fn line_symbol(&mut self, common: SymbolCommon, data: OcdBytes<'_>) -> Result<LineSymbol> {
+        let code = common.code;
+        let mut symbol = LineSymbol::new(code, String::new());
+        symbol.common = common;
+
+        let mut line_width = length_from_ocd(data.u16(line_symbol::LINE_WIDTH)?);
+        if line_width > 0. {
+            symbol.color = self.symbol_color(code, data.u16(line_symbol::LINE_COLOR)?);
+        }
+        let (cap_style, join_style) = match data.u16(line_symbol::LINE_STYLE)? {
+            line_symbol::STYLE_ROUND_ROUND => (CapStyle::Round, JoinStyle::Round),
+            line_symbol::STYLE_BEVEL_POINTED => (CapStyle::Pointed, JoinStyle::Bevel),
+            line_symbol::STYLE_ROUND_POINTED => (CapStyle::Pointed, JoinStyle::Round),
+            line_symbol::STYLE_MITER_FLAT => (CapStyle::Flat, JoinStyle::Miter),
+            line_symbol::STYLE_MITER_POINTED => (CapStyle::Pointed, JoinStyle::Miter),
+            _ => (CapStyle::Flat, JoinStyle::Bevel),
+        };
+        symbol.cap_style = cap_style;
+        symbol.join_style = join_style;
+        if matches!(cap_style, CapStyle::Pointed) {
+            symbol.start_offset = NonNegativeF64::clamped_from(length_from_ocd(
+                data.i16(line_symbol::DIST_FROM_START)?,
+            ));
+            symbol.end_offset =
+                NonNegativeF64::clamped_from(length_from_ocd(data.i16(line_symbol::DIST_TO_END)?));
+        }
+
+        symbol.dash_style = dash_style(data)?;
+
+        let mut elements_pos = line_symbol::ELEMENTS;
+        let mut next_elements = |size_offset| -> Result<(OcdBytes<'_>, usize)> {
+            let size = usize::from(data.u16(size_offset)?);
+            let elements = data.tail(elements_pos)?;
+            elements_pos += size * COORD_SIZE;
+            Ok((elements, size))
+        };
+        let primary = next_elements(line_symbol::PRIMARY_DATA_SIZE)?;
+        let secondary = next_elements(line_symbol::SECONDARY_DATA_SIZE)?;
+        let corner = next_elements(line_symbol::CORNER_DATA_SIZE)?;
+        let start = next_elements(line_symbol::START_DATA_SIZE)?;
+        let end = next_elements(line_symbol::END_DATA_SIZE)?;
+
+        if primary.1 > 0 {
+            let minimum = data.i16(line_symbol::MIN_SYM)?.max(0) as u16;
+            symbol.mid_symbol = Some(MidSymbol {
+                mid_symbols_per_spot: data.i16(line_symbol::NUM_PRIM_SYM)?.max(1) as u16,
+                mid_symbol_distance: ocd_length(data, line_symbol::PRIM_SYM_DIST)?,
+                minimum_mid_symbol_count: minimum,
+                minimum_mid_symbol_count_when_closed: minimum,
+                show_at_least_one_mid_symbol: false,
+                mid_symbol_placement: MidSymbolPlacement::default(),
+                mid_symbol: self.point_symbol(code, primary.0, primary.1)?,
+            });
+        }
+        if secondary.1 > 0 {
+            self.drop_feature(code, OcdSymbolFeature::SecondarySymbol);
+        }
+        if corner.1 > 0 {
+            symbol.dash_symbol = Some(DashSymbol {
+                suppress_dash_symbol_at_ends: false,
+                scale_dash_symbol: true,
+                dash_symbol: self.point_symbol(code, corner.0, corner.1)?,
+            });
+        }
+        if start.1 > 0 {
+            symbol.start_symbol = Some(self.point_symbol(code, start.0, start.1)?);
+        }
+        if end.1 > 0 {
+            symbol.end_symbol = Some(self.point_symbol(code, end.0, end.1)?);
+        }
+
+        if data.u16(line_symbol::DOUBLE_MODE)? != 0 {
+            let double_width = length_from_ocd(data.i16(line_symbol::DOUBLE_WIDTH)?);
+            if data.u16(line_symbol::DOUBLE_FLAGS)? & line_symbol::DOUBLE_FLAG_FILL != 0 {
+                if line_width > 0. {
+                    self.drop_feature(code, OcdSymbolFeature::DoubleLineFill);
+                } else {
+                    symbol.color = self.symbol_color(code, data.u16(line_symbol::DOUBLE_COLOR)?);
+                    line_width = double_width;
+                }
+            }
+            symbol.border = self.double_line_borders(code, data, double_width, line_width)?;
+        }
+        symbol.line_width = NonNegativeF64::clamped_from(line_width);
+
+        if data.i16(line_symbol::FRAME_WIDTH)? > 0 {
+            self.drop_feature(code, OcdSymbolFeature::LineFrame);
+        }
+        if data.u16(line_symbol::DEC_MODE)? != 0 {
+            self.drop_feature(code, OcdSymbolFeature::DecreasingWidth);
+        }
+        Ok(symbol)
+    }
+
+    /// Translate the two lines of an OCAD double line into borders of the main line.
+    fn double_line_borders(
+        &mut self,
+        code: Code,
+        data: OcdBytes<'_>,
+        double_width: f64,
+        line_width: f64,
+    ) -> Result<Option<BorderStyle>> {
+        let mode = data.u16(line_symbol::DOUBLE_MODE)?;
+        let dash = BorderDash {
+            dash_length: NonNegativeF64::clamped_from(length_from_ocd(
+                data.i16(line_symbol::DOUBLE_LENGTH)?,
+            )),
+            break_length: NonNegativeF64::clamped_from(length_from_ocd(
+                data.i16(line_symbol::DOUBLE_GAP)?,
+            )),
+        };
+        let mut border =
+            |color_offset, width_offset, dashed: bool| -> Result<Option<LineSymbolBorder>> {
+                let width = length_from_ocd(data.i16(width_offset)?);
+                if width <= 0. {
+                    return Ok(None);
+                }
+                Ok(Some(LineSymbolBorder {
+                    color: self.symbol_color(code, data.u16(color_offset)?),
+                    width: NonNegativeF64::clamped_from(width),
+                    // Mapper measures the shift from the edge of the main line
+                    shift: NonNegativeF64::clamped_from((double_width + width - line_width) / 2.),
+                    dashed: dashed.then(|| dash.clone()),
+                }))
+            };
+        let left = border(
+            line_symbol::DOUBLE_LEFT_COLOR,
+            line_symbol::DOUBLE_LEFT_WIDTH,
+            matches!(
+                mode,
+                line_symbol::DOUBLE_DASHED | line_symbol::DOUBLE_LEFT_DASHED
+            ),
+        )?;
+        let right = border(
+            line_symbol::DOUBLE_RIGHT_COLOR,
+            line_symbol::DOUBLE_RIGHT_WIDTH,
+            matches!(
+                mode,
+                line_symbol::DOUBLE_DASHED | line_symbol::DOUBLE_RIGHT_DASHED
+            ),
+        )?;
+        let no_border = || LineSymbolBorder {
+            color: SymbolColor::NoColor,
+            width: NonNegativeF64::zero(),
+            shift: NonNegativeF64::zero(),
+            dashed: None,
+        };
+        Ok(match (left, right) {
+            (None, None) => None,
+            (Some(left), Some(right))
+                if left.color == right.color
+                    && left.width == right.width
+                    && left.dashed.is_some() == right.dashed.is_some() =>
+            {
+                Some(BorderStyle::SymmetricBorder { both: left })
+            }
+            (left, right) => Some(BorderStyle::AsymmetricBorder {
+                left: left.unwrap_or_else(no_border),
+                right: right.unwrap_or_else(no_border),
+            }),
+        })
+    }
+
+    fn area_symbol(
+        &mut self,
+        common: SymbolCommon,
+        is_rotatable: bool,
+        data: OcdBytes<'_>,
+    ) -> Result<AreaSymbol> {
+        let code = common.code;
+        let mut symbol = AreaSymbol::new(code, String::new()).with_rotatable(is_rotatable);
+        symbol.common = common;
+
+        // version 8 lacks the border symbol, which shifts every other field
+        let shift = if self.format == OcdFormat::V8 {
+            area_symbol::V8_SHIFT
+        } else {
+            0
+        };
+        let at = |offset: usize| offset - shift;
+
+        if shift == 0
+            && data.u8(area_symbol::BORDER_ON)? != 0
+            && data.i32(area_symbol::BORDER_SYMBOL)? != 0
+        {
+            self.drop_feature(code, OcdSymbolFeature::AreaBorder);
+        }
+        if data.u8(at(area_symbol::FILL_ON))? != 0 {
+            symbol.color = self.symbol_color(code, data.u16(at(area_symbol::FILL_COLOR))?);
+        }
+
+        let hatch_mode = data.u16(at(area_symbol::HATCH_MODE))?;
+        if matches!(
+            hatch_mode,
+            area_symbol::HATCH_SINGLE | area_symbol::HATCH_CROSS
+        ) {
+            let line_color = self.symbol_color(code, data.u16(at(area_symbol::HATCH_COLOR))?);
+            let line_width = length_from_ocd(data.u16(at(area_symbol::HATCH_LINE_WIDTH))?);
+            let line_spacing = length_from_ocd(data.u16(at(area_symbol::HATCH_DIST))?) + line_width;
+            let mut angles = vec![data.i16(at(area_symbol::HATCH_ANGLE_1))?];
+            if hatch_mode == area_symbol::HATCH_CROSS {
+                angles.push(data.i16(at(area_symbol::HATCH_ANGLE_2))?);
+            }
+            for angle in angles {
+                symbol.patterns.push(FillPattern::LinePattern {
+                    angle: angle_from_ocd(angle),
+                    line_spacing: NonNegativeF64::clamped_from(line_spacing),
+                    line_offset: NonNegativeF64::zero(),
+                    line_color: line_color.clone(),
+                    line_width: NonNegativeF64::clamped_from(line_width),
+                    rotatable: is_rotatable,
+                });
+            }
+        }
+
+        let structure_mode = data.u16(at(area_symbol::STRUCTURE_MODE))?;
+        if matches!(
+            structure_mode,
+            area_symbol::STRUCTURE_ALIGNED | area_symbol::STRUCTURE_SHIFTED
+        ) {
+            let width = length_from_ocd(data.u16(at(area_symbol::STRUCTURE_WIDTH))?);
+            let height = length_from_ocd(data.u16(at(area_symbol::STRUCTURE_HEIGHT))?);
+            let angle = angle_from_ocd(data.i16(at(area_symbol::STRUCTURE_ANGLE))?);
+            let data_size = usize::from(data.u16(at(area_symbol::DATA_SIZE))?);
+            let point =
+                self.point_symbol(code, data.tail(at(area_symbol::ELEMENTS))?, data_size)?;
+            let pattern = |line_spacing: f64, line_offset: f64, offset_along_line: f64| {
+                FillPattern::PointPattern {
+                    clip_options: Default::default(),
+                    angle,
+                    line_spacing: NonNegativeF64::clamped_from(line_spacing),
+                    line_offset: NonNegativeF64::clamped_from(line_offset),
+                    offset_along_line: NonNegativeF64::clamped_from(offset_along_line),
+                    point_distance: NonNegativeF64::clamped_from(width),
+                    point: point.clone(),
+                    rotatable: is_rotatable,
+                }
+            };
+            if structure_mode == area_symbol::STRUCTURE_SHIFTED {
+                // every other row is shifted by half the point distance
+                symbol.patterns.push(pattern(2. * height, 0., 0.));
+                symbol
+                    .patterns
+                    .push(pattern(2. * height, height, width / 2.));
+            } else {
+                symbol.patterns.push(pattern(height, 0., 0.));
+            }
+        }
+        Ok(symbol)
+    }
+
+    fn text_symbol(&mut self, common: SymbolCommon, data: OcdBytes<'_>) -> Result<TextSymbol> {
+        let code = common.code;
+        let mut symbol = TextSymbol::new(code, String::new())
+            .with_font_family(
+                data.pascal_string(text_symbol::FONT_NAME, text_symbol::FONT_NAME_CAPACITY)?,
+            )
+            .with_rotatable(true);
+        symbol.common = common;
+        symbol.color = self.symbol_color(code, data.u16(text_symbol::FONT_COLOR)?);
+        // the font size is stored in tenths of a point
+        symbol.font_size = NonNegativeF64::clamped_from(
+            f64::from(data.u16(text_symbol::FONT_SIZE)?) / 10. * 25.4 / 72.,
+        );
+        symbol.bold = data.u16(text_symbol::FONT_WEIGHT)? >= text_symbol::BOLD_WEIGHT;
+        symbol.italic = data.u8(text_symbol::ITALIC)? != 0;
+        symbol.character_spacing = f64::from(data.i16(text_symbol::CHAR_SPACING)?) / 100.;
+        symbol.line_spacing =
+            NonNegativeF64::clamped_from(f64::from(data.u16(text_symbol::LINE_SPACING)?) / 100.);
+        symbol.paragraph_spacing = length_from_ocd(data.i16(text_symbol::PARA_SPACING)?);
+
+        let num_tabs = usize::from(data.u16(text_symbol::NUM_TABS)?).min(text_symbol::MAX_TABS);
+        symbol.custom_tabs = (0..num_tabs)
+            .map(|index| {
+                Ok(NonNegativeF64::clamped_from(length_from_ocd(
+                    data.u32(text_symbol::TABS + index * 4)?,
+                )))
+            })
+            .collect::<Result<_>>()?;
+
+        if data.u16(text_symbol::LINE_BELOW_ON)? != 0 {
+            symbol.line_below = Some(LineBelow {
+                color: self.symbol_color(code, data.u16(text_symbol::LINE_BELOW_COLOR)?),
+                width: NonNegativeF64::clamped_from(length_from_ocd(
+                    data.u16(text_symbol::LINE_BELOW_WIDTH)?,
+                )),
+                distance: NonNegativeF64::clamped_from(length_from_ocd(
+                    data.u16(text_symbol::LINE_BELOW_OFFSET)?,
+                )),
+            });
+        }
+
+        symbol.framing_mode = match data.u8(text_symbol::FRAMING_MODE)? {
+            text_symbol::FRAMING_SHADOW => Some(FramingMode::ShadowFraming(ShadowFraming {
+                color: self.symbol_color(code, data.u16(text_symbol::FRAMING_COLOR)?),
+                shadow_offset: Coord {
+                    x: length_from_ocd(data.i16(text_symbol::FRAMING_OFFSET_X)?),
+                    y: length_from_ocd(data.i16(text_symbol::FRAMING_OFFSET_Y)?),
+                },
+            })),
+            text_symbol::FRAMING_LINE => Some(FramingMode::LineFraming(LineFraming {
+                color: self.symbol_color(code, data.u16(text_symbol::FRAMING_COLOR)?),
+                framing_line_half_width: NonNegativeF64::clamped_from(
+                    length_from_ocd(data.u16(text_symbol::FRAMING_WIDTH)?) / 2.,
+                ),
+            })),
+            text_symbol::FRAMING_RECTANGLE => {
+                self.drop_feature(code, OcdSymbolFeature::RectangleFraming);
+                None
+            }
+            _ => None,
+        };
+        Ok(symbol)
+    }
+
+    /// Read the alignment of the text symbol at `pos`, which OMAP stores on the objects.
+    fn text_alignment(&self, pos: usize) -> Result<(HorizontalAlign, VerticalAlign)> {
+        let alignment = self
+            .bytes
+            .tail(pos + self.format.base_symbol_size())?
+            .u16(text_symbol::ALIGNMENT)?;
+        let h_align = match alignment & text_symbol::ALIGN_HORIZONTAL_MASK {
+            1 => HorizontalAlign::HCenter,
+            2 => HorizontalAlign::Right,
+            _ => HorizontalAlign::Left,
+        };
+        let v_align = match (alignment >> text_symbol::ALIGN_VERTICAL_SHIFT) & 3 {
+            1 => VerticalAlign::VCenter,
+            2 => VerticalAlign::Top,
+            _ => VerticalAlign::Baseline,
+        };
+        Ok((h_align, v_align))
+    }
+
+    fn drop_feature(&mut self, code: Code, feature: OcdSymbolFeature) {
+        self.warnings
+            .push(OcdWarning::SymbolFeatureDropped { code, feature });
+    }
+
+    fn read_objects(&mut self) -> Result<MapPart> {
+        let mut part = MapPart::new("Map");
+        let first_block = self.bytes.offset(header::FIRST_OBJECT_BLOCK)?;
+        let entries = self.index_entries(first_block, self.format.object_entry_size())?;
+        let mut object_index = 0;
+        for entry in entries {
+            let pos = entry.offset(object_entry::POS)?;
+            if pos == 0 {
+                continue;
+            }
+            let is_deleted = if self.format == OcdFormat::V8 {
+                entry.i16(object_entry::SYMBOL_V8)? == 0
+            } else {
+                !matches!(
+                    entry.u8(object_entry::STATUS)?,
+                    object_entry::STATUS_NORMAL | object_entry::STATUS_HIDDEN
+                )
+            };
+            if !is_deleted && let Some(object) = self.read_object(pos, object_index)? {
+                part.add_object(object);
+            }
+            object_index += 1;
+        }
+        Ok(part)
+    }
+
+    fn read_object(&mut self, pos: usize, object_index: usize) -> Result<Option<MapObject>> {
+        let raw = self.bytes.tail(pos)?;
+        let (number, kind, angle, num_items, num_text, text_is_utf16) =
+            if self.format == OcdFormat::V8 {
+                (
+                    i32::from(raw.i16(object_v8::SYMBOL)?),
+                    raw.u8(object_v8::TYPE)?,
+                    raw.i16(object_v8::ANGLE)?,
+                    usize::from(raw.u16(object_v8::NUM_ITEMS)?),
+                    usize::from(raw.u16(object_v8::NUM_TEXT)?),
+                    raw.u8(object_v8::UNICODE)? != 0,
+                )
+            } else {
+                (
+                    raw.i32(object::SYMBOL)?,
+                    raw.u8(object::TYPE)?,
+                    raw.i16(object::ANGLE)?,
+                    raw.offset(object::NUM_ITEMS)?,
+                    usize::from(raw.u16(object::NUM_TEXT)?),
+                    true,
+                )
+            };
+        let header_size = self.format.object_header_size();
+        let points = (0..num_items)
+            .map(|index| raw.point(header_size + index * COORD_SIZE))
+            .collect::<Result<Vec<_>>>()?;
+        let text_bytes = raw
+            .slice(header_size + num_items * COORD_SIZE, num_text * COORD_SIZE)?
+            .as_slice();
+        let rotation = angle_from_ocd(angle);
+
+        if !(object_type::POINT..=object_type::FORMATTED_TEXT).contains(&kind) {
+            self.warnings.push(OcdWarning::UnsupportedObjectType {
+                object_index,
+                object_type: kind,
+            });
+            return Ok(None);
+        }
+        // `None` if the symbol does not fit the object, `Some(None)` if the geometry is invalid
+        let object = match (kind, self.symbols.get(&number)) {
+            (object_type::POINT, Some(ImportedSymbol::Point(symbol))) => {
+                Some(points.first().map(|point| {
+                    let mut object = PointObject::new(Weak::clone(symbol), Point(point.coord()));
+                    object.rotation = rotation;
+                    MapObject::Point(object)
+                }))
+            }
+            (object_type::LINE, Some(ImportedSymbol::Line(symbol))) => Some(
+                (points.len() > 1)
+                    .then(|| bezier_path(&points))
+                    .transpose()?
+                    .map(|path| MapObject::Line(LineObject::new(Weak::clone(symbol), path))),
+            ),
+            (object_type::AREA, Some(ImportedSymbol::Area(symbol))) => Some(
+                (points.len() > 2)
+                    .then(|| bezier_polygon(&points))
+                    .transpose()?
+                    .map(|polygon| {
+                        let mut object = AreaObject::new(Weak::clone(symbol), polygon);
+                        object.pattern_rotation.rotation = rotation;
+                        MapObject::Area(object)
+                    }),
+            ),
+            (
+                object_type::UNFORMATTED_TEXT | object_type::FORMATTED_TEXT,
+                Some(ImportedSymbol::Text {
+                    symbol,
+                    h_align,
+                    v_align,
+                }),
+            ) => {
+                let text = if text_is_utf16 {
+                    utf16(text_bytes)
+                } else {
+                    latin1(text_bytes)
+                }
+                .replace("\r\n", "\n");
+                Some(text_geometry(kind, &points).map(|(geometry, is_box)| {
+                    let mut object = TextObject::new(Weak::clone(symbol), geometry, text);
+                    object.h_align = *h_align;
+                    object.v_align = if is_box { VerticalAlign::Top } else { *v_align };
+                    object.rotation = rotation;
+                    MapObject::Text(object)
+                }))
+            }
+            _ => None,
+        };
+        let Some(object) = object else {
+            self.warnings.push(OcdWarning::UnknownObjectSymbol {
+                object_index,
+                code: self.format.code_from_symbol_number(number),
+            });
+            return Ok(None);
+        };
+        if object.is_none() {
+            self.warnings
+                .push(OcdWarning::InvalidObjectGeometry { object_index });
+        }
+        Ok(object)
+    }
+}
+
+/// Read a length in 1/100 mm, clamping negative lengths to 0.
+fn ocd_length(data: OcdBytes<'_>, offset: usize) -> Result<NonNegativeF64> {
+    Ok(NonNegativeF64::clamped_from(length_from_ocd(
+        data.i16(offset)?,
+    )))
+}
+
+/// Translate the dash settings of a line symbol.
+///
+/// OCAD groups dashes in pairs separated by the secondary gap, where the main
+/// length spans the whole pair.
+fn dash_style(data: OcdBytes<'_>) -> Result<DashStyle> {
+    let main_length = ocd_length(data, line_symbol::MAIN_LENGTH)?;
+    let end_length = ocd_length(data, line_symbol::END_LENGTH)?;
+    let main_gap = ocd_length(data, line_symbol::MAIN_GAP)?;
+    let sec_gap = ocd_length(data, line_symbol::SEC_GAP)?;
+    if main_gap.get() <= 0. && sec_gap.get() <= 0. {
+        return Ok(DashStyle::NotDashed {
+            segment_length: main_length,
+            end_length,
+        });
+    }
+    let (dash_length, dash_group) = if sec_gap.get() > 0. {
+        (
+            NonNegativeF64::clamped_from((main_length.get() - sec_gap.get()) / 2.),
+            GroupDashes::Grouped {
+                dashes_in_group: 2,
+                in_group_break_length: sec_gap,
+            },
+        )
+    } else {
+        (
+            main_length,
+            GroupDashes::UnGrouped {
+                half_outer_dashes: (end_length.get() * 2. - main_length.get()).abs() < 0.005,
+            },
+        )
+    };
+    Ok(DashStyle::Dashed {
+        dash_length,
+        break_length: main_gap,
+        dash_group,
+    })
+}
+
+fn cmyk_from_half_percentages(values: &[u8]) -> Cmyk {
+    let value = |index: usize| {
+        UnitF64::clamped_from(f64::from(values.get(index).copied().unwrap_or(0)) / 200.)
+    };
+    Cmyk {
+        c: value(0),
+        m: value(1),
+        y: value(2),
+        k: value(3),
+    }
+}
+
+/// Get the text geometry and whether it is a box.
+///
+/// Formatted text stores the corners of its box, starting at the lower left
+/// corner and going counter-clockwise.
+fn text_geometry(kind: u8, points: &[OcdPoint]) -> Option<(TextGeometry, bool)> {
+    if kind == object_type::FORMATTED_TEXT {
+        let [lower_left, lower_right, upper_right, ..] = points.get(..4)? else {
+            return None;
+        };
+        let (lower_left, lower_right, upper_right) =
+            (lower_left.coord(), lower_right.coord(), upper_right.coord());
+        let bottom = lower_right - lower_left;
+        let side = upper_right - lower_right;
+        let anchor = (lower_left + upper_right) / 2.;
+        let (width, height) = (bottom.x.hypot(bottom.y), side.x.hypot(side.y));
+        Some((
+            TextGeometry::WrapBox(WrapBox {
+                anchor,
+                width: NonNegativeF64::clamped_from(width),
+                height: NonNegativeF64::clamped_from(height),
+            }),
+            true,
+        ))
+    } else {
+        points
+            .first()
+            .map(|p| (TextGeometry::SingleAnchor(p.coord()), false))
+    }
+}
+
+/// Build a path from OCAD coordinates, where a coordinate flagged as the
+/// first Bézier handle starts a curve ending two coordinates later.
+fn bezier_path(points: &[OcdPoint]) -> Result<BezierPath> {
+    let [first, rest @ ..] = points else {
+        return Ok(BezierPath::empty());
+    };
+    if rest.is_empty() {
+        return Ok(BezierPath::empty());
+    }
+    // Mapper's dash points are OCAD's corner points
+    let is_dash_point =
+        |point: &OcdPoint| point.y_flags() & (Y_FLAG_CORNER | Y_FLAG_DASH_POINT) != 0;
+
+    let mut segments = Vec::with_capacity(rest.len());
+    let mut vertex_is_dash_point = vec![is_dash_point(first)];
+    let mut previous = first.coord();
+    let mut index = 0;
+    while index < rest.len() {
+        let point = rest[index];
+        if point.x_flags() & X_FLAG_CURVE_FIRST_HANDLE != 0 && index + 2 < rest.len() {
+            let end = rest[index + 2];
+            segments.push(BezierSegment::new(
+                previous,
+                Some((point.coord(), rest[index + 1].coord())),
+                end.coord(),
+            ));
+            vertex_is_dash_point.push(is_dash_point(&end));
+            previous = end.coord();
+            index += 3;
+        } else {
+            segments.push(BezierSegment::new(previous, None, point.coord()));
+            vertex_is_dash_point.push(is_dash_point(&point));
+            previous = point.coord();
+            index += 1;
+        }
+    }
+
+    let geometry = BezierString::new(segments);
+    if geometry.is_closed() {
+        let last = vertex_is_dash_point.len() - 1;
+        let seam_is_dash_point = vertex_is_dash_point[0] || vertex_is_dash_point[last];
+        vertex_is_dash_point[0] = seam_is_dash_point;
+        vertex_is_dash_point[last] = seam_is_dash_point;
+    }
+    BezierPath::new(geometry, vertex_is_dash_point)
+}
+
+/// Build a polygon from OCAD coordinates, where every hole starts at a
+/// coordinate flagged as the first point of a hole. Rings need not repeat
+/// their first coordinate at the end in OCAD, so open rings are closed.
+fn bezier_polygon(points: &[OcdPoint]) -> Result<BezierPolygon> {
+    let mut rings = points
+        .chunk_by(|_, next| next.y_flags() & Y_FLAG_HOLE_START == 0)
+        .map(|ring| match (ring.first(), ring.last()) {
+            (Some(first), Some(last)) if first.coord() != last.coord() => {
+                bezier_path(&[ring, &[*first]].concat())
+            }
+            _ => bezier_path(ring),
+        });
+    let exterior = rings.next().transpose()?.unwrap_or_else(BezierPath::empty);
+    let interiors = rings.collect::<Result<Vec<_>>>()?;
+    BezierPolygon::new(exterior, interiors)
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Coord;
+
+    use super::read;
+    use crate::{
+        Code, Error, Result,
+        colors::{Color, SymbolColor},
+        objects::{MapObject, TextGeometry},
+        ocad::{
+            OcdWarning,
+            format::{FILE_HEADER_SIZE, INDEX_BLOCK_ENTRIES, OCAD_MARK},
+        },
+        symbols::{DashStyle, FillPattern, Symbol},
+    };
+
+    /// A minimal OCAD 12 file with one index block of each kind.
+    #[derive(Default)]
+    struct TestFile {
+        strings: Vec<(i32, String)>,
+        symbols: Vec<Vec<u8>>,
+        objects: Vec<Vec<u8>>,
+    }
+
+    fn put(bytes: &mut [u8], pos: usize, value: &[u8]) {
+        bytes[pos..pos + value.len()].copy_from_slice(value);
+    }
+
+    fn put_u32(bytes: &mut [u8], pos: usize, value: usize) {
+        put(
+            bytes,
+            pos,
+            &u32::try_from(value).unwrap_or(u32::MAX).to_le_bytes(),
+        );
+    }
+
+    /// Encode a coordinate in mm with its flags.
+    fn coord(x: f64, y: f64, x_flags: i32, y_flags: i32) -> Vec<u8> {
+        let raw = |value: f64, flags: i32| (((value * 100.).round() as i32) << 8) | flags;
+        [raw(x, x_flags).to_le_bytes(), raw(y, y_flags).to_le_bytes()].concat()
+    }
+
+    impl TestFile {
+        fn build(&self) -> Vec<u8> {
+            let string_block = FILE_HEADER_SIZE;
+            let symbol_block = string_block + 4 + INDEX_BLOCK_ENTRIES * 16;
+            let object_block = symbol_block + 4 + INDEX_BLOCK_ENTRIES * 4;
+            let mut bytes = vec![0; object_block + 4 + INDEX_BLOCK_ENTRIES * 40];
+            put(&mut bytes, 0, &OCAD_MARK.to_le_bytes());
+            put(&mut bytes, 4, &12_u16.to_le_bytes());
+            put_u32(&mut bytes, 8, symbol_block);
+            put_u32(&mut bytes, 12, object_block);
+            put_u32(&mut bytes, 32, string_block);
+
+            for (index, (string_type, value)) in self.strings.iter().enumerate() {
+                let entry = string_block + 4 + index * 16;
+                let pos = bytes.len();
+                bytes.extend(value.as_bytes());
+                bytes.push(0);
+                put_u32(&mut bytes, entry, pos);
+                put_u32(&mut bytes, entry + 4, value.len() + 1);
+                put(&mut bytes, entry + 8, &string_type.to_le_bytes());
+            }
+            for (index, symbol) in self.symbols.iter().enumerate() {
+                let pos = bytes.len();
+                bytes.extend(symbol);
+                put_u32(&mut bytes, symbol_block + 4 + index * 4, pos);
+            }
+            for (index, object) in self.objects.iter().enumerate() {
+                let entry = object_block + 4 + index * 40;
+                let pos = bytes.len();
+                bytes.extend(object);
+                put_u32(&mut bytes, entry + 16, pos);
+                bytes[entry + 30] = 1;
+            }
+            bytes
+        }
+    }
+
+    fn symbol(number: i32, symbol_type: u8, name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0; 700];
+        put_u32(&mut bytes, 0, 700 + data.len());
+        put(&mut bytes, 4, &number.to_le_bytes());
+        bytes[8] = symbol_type;
+        let name = name
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        put(&mut bytes, 56, &name);
+        bytes.extend(data);
+        bytes
+    }
+
+    fn object(number: i32, object_type: u8, coords: &[Vec<u8>], text: &str) -> Vec<u8> {
+        let mut text = text
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        text.resize(text.len().div_ceil(8) * 8 + 8, 0);
+        let mut bytes = vec![0; 52];
+        put(&mut bytes, 0, &number.to_le_bytes());
+        bytes[4] = object_type;
+        put_u32(&mut bytes, 8, coords.len());
+        put(
+            &mut bytes,
+            12,
+            &u16::try_from(text.len() / 8).unwrap_or(0).to_le_bytes(),
+        );
+        bytes.extend(coords.concat());
+        bytes.extend(text);
+        bytes
+    }
+
+    fn test_file() -> TestFile {
+        let mut line_data = vec![0; 76];
+        put(&mut line_data, 0, &1_u16.to_le_bytes()); // color
+        put(&mut line_data, 2, &35_u16.to_le_bytes()); // width
+        put(&mut line_data, 4, &1_u16.to_le_bytes()); // round
+        put(&mut line_data, 10, &600_u16.to_le_bytes()); // main length
+        put(&mut line_data, 12, &300_u16.to_le_bytes()); // end length
+        put(&mut line_data, 14, &100_u16.to_le_bytes()); // main gap
+
+        let mut area_data = vec![0; 32];
+        put(&mut area_data, 4, &2_u16.to_le_bytes()); // fill color
+        put(&mut area_data, 6, &1_u16.to_le_bytes()); // single hatch
+        put(&mut area_data, 8, &1_u16.to_le_bytes()); // hatch color
+        put(&mut area_data, 10, &10_u16.to_le_bytes()); // hatch width
+        put(&mut area_data, 12, &40_u16.to_le_bytes()); // hatch distance
+        put(&mut area_data, 14, &450_i16.to_le_bytes()); // hatch angle
+        area_data[18] = 1;
+
+        let mut text_data = vec![0; 208];
+        text_data[0] = 5;
+        put(&mut text_data, 1, b"Arial");
+        put(&mut text_data, 32, &1_u16.to_le_bytes()); // color
+        put(&mut text_data, 34, &72_u16.to_le_bytes()); // 7.2 pt
+        put(&mut text_data, 36, &700_u16.to_le_bytes()); // bold
+        put(&mut text_data, 44, &1_u16.to_le_bytes()); // centered
+        put(&mut text_data, 46, &100_u16.to_le_bytes()); // line spacing
+
+        TestFile {
+            strings: vec![
+                (1039, "\tm10000\tx500000\ty6600000\ta1.5".to_owned()),
+                (9, "Black\tn1\tc0\tm0\ty0\tk100".to_owned()),
+                (
+                    9,
+                    "Brown\tn2\tc0\tm56\ty100\tk18\tsBrown spot\tp100".to_owned(),
+                ),
+                (10, "Brown spot\tc0\tm56\ty100\tk18\tf60\ta45".to_owned()),
+            ],
+            symbols: vec![
+                symbol(101_000, 2, "Contour", &line_data),
+                symbol(408_000, 3, "Vegetation", &area_data),
+                symbol(520_000, 4, "Label", &text_data),
+                symbol(530_000, 7, "Frame", &[]),
+            ],
+            objects: vec![
+                object(
+                    101_000,
+                    2,
+                    &[
+                        coord(0., 0., 0, 0),
+                        coord(1., 0., 1, 0),
+                        coord(2., 1., 2, 0),
+                        coord(2., 2., 0, 8),
+                        coord(4., 2., 0, 0),
+                    ],
+                    "",
+                ),
+                object(
+                    408_000,
+                    3,
+                    &[
+                        coord(0., 0., 0, 0),
+                        coord(10., 0., 0, 0),
+                        coord(10., 10., 0, 0),
+                        coord(0., 0., 0, 0),
+                        coord(2., 2., 0, 2),
+                        coord(4., 2., 0, 0),
+                        coord(4., 4., 0, 0),
+                        coord(2., 2., 0, 0),
+                    ],
+                    "",
+                ),
+                object(
+                    520_000,
+                    5,
+                    &[
+                        coord(0., 0., 0, 0),
+                        coord(20., 0., 0, 0),
+                        coord(20., 10., 0, 0),
+                        coord(0., 10., 0, 0),
+                    ],
+                    "Lake\r\nshore",
+                ),
+                object(530_000, 7, &[coord(0., 0., 0, 0)], ""),
+                object(999_000, 1, &[coord(0., 0., 0, 0)], ""),
+            ],
+        }
+    }
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn reads_colors_and_georeferencing() -> Result<()> {
+        let (omap, _) = read(&test_file().build())?;
+
+        assert_eq!(
+            omap.geo_referencing.scale_denominator.get(),
+            10_000,
+            "scale"
+        );
+        assert_eq!(
+            omap.geo_referencing.projected_ref_point,
+            Coord {
+                x: 500_000.,
+                y: 6_600_000.
+            },
+            "reference point"
+        );
+        assert_eq!(omap.geo_referencing.declination_deg, 1.5, "declination");
+
+        assert_eq!(omap.colors.len(), 3, "two colors and one separation");
+        let Some(Color::MixedColor(brown)) = omap.colors.color_by_priority(1) else {
+            panic!("expected the brown process color");
+        };
+        let brown = brown.borrow();
+        assert_eq!(brown.name(), "Brown", "color name");
+        assert_eq!(brown.components.len(), 1, "spot color component");
+        assert_eq!(brown.components[0].factor.get(), 1., "spot factor");
+        assert_eq!(brown.cmyk()?.m.get(), 0.56, "magenta");
+        let Some(Color::SpotColor(spot)) = omap.colors.color_by_priority(2) else {
+            panic!("expected the separation after all colors");
+        };
+        assert_eq!(spot.borrow().screen_angle_deg, 45., "screen angle");
+        assert!(
+            brown.components[0].color.upgrade().unwrap().as_ptr() == spot.as_ptr(),
+            "the component references the separation"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_symbols() -> Result<()> {
+        let (omap, warnings) = read(&test_file().build())?;
+
+        assert_eq!(omap.symbols.len(), 3, "the rectangle symbol is skipped");
+        assert!(
+            warnings.contains(&OcdWarning::UnsupportedSymbolType {
+                code: Code::new(530, 0, 0),
+                symbol_type: 7
+            }),
+            "{warnings:?}"
+        );
+
+        let Some(Symbol::Line(line)) = omap.symbols.symbol_by_code(Code::new(101, 0, 0))? else {
+            panic!("expected the contour line symbol");
+        };
+        let line = line.borrow();
+        assert_eq!(line.common.name, "Contour", "symbol name");
+        assert_eq!(line.line_width.get(), 0.35, "line width");
+        let DashStyle::Dashed {
+            dash_length,
+            break_length,
+            ..
+        } = line.dash_style
+        else {
+            panic!("expected a dashed line");
+        };
+        assert_eq!((dash_length.get(), break_length.get()), (6., 1.), "dashes");
+
+        let Some(Symbol::Area(area)) = omap.symbols.symbol_by_code(Code::new(408, 0, 0))? else {
+            panic!("expected the vegetation area symbol");
+        };
+        let area = area.borrow();
+        let [
+            FillPattern::LinePattern {
+                angle,
+                line_spacing,
+                ..
+            },
+        ] = area.patterns.as_slice()
+        else {
+            panic!("expected a single hatch pattern");
+        };
+        assert!((angle - std::f64::consts::FRAC_PI_4).abs() < 1e-12, "angle");
+        assert_eq!(line_spacing.get(), 0.5, "spacing includes the line width");
+
+        let Some(Symbol::Text(text)) = omap.symbols.symbol_by_code(Code::new(520, 0, 0))? else {
+            panic!("expected the label text symbol");
+        };
+        let text = text.borrow();
+        assert_eq!(text.font_family, "Arial", "font");
+        assert!(
+            (text.font_size.get() - 2.54).abs() < 1e-12,
+            "7.2 pt is 2.54 mm"
+        );
+        assert!(text.bold, "bold");
+        assert!(matches!(text.color, SymbolColor::Color(_)), "text color");
+        Ok(())
+    }
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn reads_objects() -> Result<()> {
+        let (omap, warnings) = read(&test_file().build())?;
+        let objects = omap
+            .parts
+            .get(0)
+            .unwrap()
+            .iter_all_objects()
+            .collect::<Vec<_>>();
+        assert_eq!(objects.len(), 3, "{warnings:?}");
+
+        let line = objects
+            .iter()
+            .find_map(|object| match object {
+                MapObject::Line(line) => Some(line.geometry()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(line.num_segments(), 2, "a curve and a straight segment");
+        assert!(
+            line.geometry().0[0].handles().is_some(),
+            "the first segment is a curve"
+        );
+        assert_eq!(
+            line.vertex_is_dash_point(),
+            [false, true, false],
+            "dash point"
+        );
+
+        let area = objects
+            .iter()
+            .find_map(|object| match object {
+                MapObject::Area(area) => Some(area.geometry()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(area.interiors().len(), 1, "one hole");
+
+        let text = objects
+            .iter()
+            .find_map(|object| match object {
+                MapObject::Text(text) => Some(text),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(text.text, "Lake\nshore", "line breaks");
+        let TextGeometry::WrapBox(wrap_box) = text.geometry() else {
+            panic!("formatted text has a box");
+        };
+        assert_eq!(wrap_box.anchor, Coord { x: 10., y: 5. }, "box centre");
+        assert_eq!(
+            (wrap_box.width.get(), wrap_box.height.get()),
+            (20., 10.),
+            "box size"
+        );
+
+        assert!(
+            warnings.contains(&OcdWarning::UnsupportedObjectType {
+                object_index: 3,
+                object_type: 7
+            }),
+            "{warnings:?}"
+        );
+        assert!(
+            warnings.contains(&OcdWarning::UnknownObjectSymbol {
+                object_index: 4,
+                code: Code::new(999, 0, 0)
+            }),
+            "{warnings:?}"
+        );
+        Ok(())
+    }
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn closes_open_area_rings() -> Result<()> {
+        let mut file = test_file();
+        file.objects = vec![object(
+            408_000,
+            3,
+            &[
+                coord(0., 0., 0, 0),
+                coord(10., 0., 0, 0),
+                coord(10., 10., 0, 0),
+                coord(2., 2., 0, 2),
+                coord(4., 2., 0, 0),
+                coord(4., 4., 0, 0),
+            ],
+            "",
+        )];
+        let (omap, warnings) = read(&file.build())?;
+        let objects = omap
+            .parts
+            .get(0)
+            .unwrap()
+            .iter_all_objects()
+            .collect::<Vec<_>>();
+        let [MapObject::Area(area)] = objects.as_slice() else {
+            panic!("expected the open area, {warnings:?}");
+        };
+        assert!(area.geometry().exterior().is_closed(), "exterior");
+        assert_eq!(area.geometry().exterior().num_segments(), 3, "exterior");
+        assert_eq!(area.geometry().interiors().len(), 1, "one hole");
+        assert!(area.geometry().interiors()[0].is_closed(), "hole");
+        assert!(
+            !warnings.contains(&OcdWarning::InvalidObjectGeometry { object_index: 0 }),
+            "{warnings:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn warns_about_areas_with_too_few_coordinates() -> Result<()> {
+        let mut file = test_file();
+        file.objects = vec![object(
+            408_000,
+            3,
+            &[coord(0., 0., 0, 0), coord(10., 0., 0, 0)],
+            "",
+        )];
+        let (omap, warnings) = read(&file.build())?;
+        let Some(part) = omap.parts.get(0) else {
+            panic!("expected a map part");
+        };
+        assert_eq!(part.iter_all_objects().count(), 0, "{warnings:?}");
+        assert!(
+            warnings.contains(&OcdWarning::InvalidObjectGeometry { object_index: 0 }),
+            "{warnings:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_version_8_color_table() -> Result<()> {
+        let separations = 72 + 256 * 72;
+        let mut bytes = vec![0; separations + 32 * 24];
+        put(&mut bytes, 0, &OCAD_MARK.to_le_bytes());
+        put(&mut bytes, 4, &8_u16.to_le_bytes());
+        put(&mut bytes, 48, &1_u16.to_le_bytes());
+        put(&mut bytes, 50, &1_u16.to_le_bytes());
+        put(&mut bytes, 72, &3_u16.to_le_bytes());
+        bytes[79] = 200;
+        bytes[80] = 5;
+        put(&mut bytes, 81, b"Black");
+        bytes[112..144].fill(255);
+        bytes[112] = 100;
+        bytes[separations] = 10;
+        put(&mut bytes, separations + 1, b"Black spot");
+        put(&mut bytes, separations + 22, &450_u16.to_le_bytes());
+
+        let (omap, warnings) = read(&bytes)?;
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(
+            omap.geo_referencing.scale_denominator.get(),
+            15_000,
+            "fallback scale"
+        );
+        assert_eq!(omap.colors.len(), 2, "a color and its separation");
+        let Some(Color::MixedColor(black)) = omap.colors.color_by_priority(0) else {
+            panic!("expected the process color first");
+        };
+        let black = black.borrow();
+        assert_eq!(black.name(), "Black", "color name");
+        assert_eq!(black.cmyk()?.k.get(), 1., "black");
+        assert_eq!(
+            black.components[0].factor.get(),
+            0.5,
+            "half tone separation"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(
+            matches!(read(&[0; 64]), Err(Error::NotAnOcadFile)),
+            "wrong mark"
+        );
+
+        let mut bytes = test_file().build();
+        bytes[4] = 7;
+        assert!(
+            matches!(read(&bytes), Err(Error::UnsupportedOcadVersion(7))),
+            "version 7"
+        );
+
+        let mut bytes = test_file().build();
+        bytes.truncate(bytes.len() - 8);
+        assert!(
+            matches!(read(&bytes), Err(Error::TruncatedOcadFile)),
+            "truncated object"
+        );
+    }
+}
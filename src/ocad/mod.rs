@@ -0,0 +1,178 @@
+//! OCAD `.ocd` files from version 8 to 2018, written as OCAD 12.
+//!
+//! OCAD stores coordinates in 1/100 mm on paper with the positive y-axis
+//! pointing up, so converting to map coordinates is a plain division by 100.
+//! Constructs without an equivalent in the other model are reported as
+//! [`OcdWarning`](crate::ocad::OcdWarning)s instead of failing the conversion.
+
+mod export;
+mod format;
+mod import;
+
+pub(crate) use export::write;
+pub(crate) use import::read;
+
+use crate::Code;
+
+/// A part of a symbol definition that has no equivalent in the other model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcdSymbolFeature {
+    /// The line symbol drawn along the border of an area symbol.
+    AreaBorder,
+    /// The fill between the two lines of a double line.
+    DoubleLineFill,
+    /// The frame line drawn around a line symbol.
+    LineFrame,
+    /// The secondary point symbol placed between dash groups.
+    SecondarySymbol,
+    /// The decreasing width towards the ends of a line.
+    DecreasingWidth,
+    /// The rectangle framing of a text symbol.
+    RectangleFraming,
+    /// Dash groups of more than two dashes, which OCAD writes as pairs.
+    DashGroup,
+    /// Borders of a line symbol at different distances from the main line.
+    AsymmetricBorderShift,
+    /// Fill patterns beyond one hatch and one point structure.
+    FillPattern,
+    /// The underline of a text symbol.
+    Underline,
+}
+
+/// A construct that could not be carried over exactly between OCAD and OMAP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcdWarning {
+    /// A symbol of a type without an OMAP equivalent was skipped, e.g. a line text or rectangle symbol.
+    UnsupportedSymbolType {
+        /// The code of the skipped symbol.
+        code: Code,
+        /// The OCAD symbol type.
+        symbol_type: u8,
+    },
+    /// Part of a symbol definition was dropped.
+    SymbolFeatureDropped {
+        /// The code of the symbol.
+        code: Code,
+        /// The dropped part.
+        feature: OcdSymbolFeature,
+    },
+    /// A symbol referenced a color number that is not in the color table.
+    UnknownColor {
+        /// The code of the symbol.
+        code: Code,
+        /// The OCAD color number.
+        color_number: u16,
+    },
+    /// An object of a type without an OMAP equivalent was skipped, e.g. a line text or rectangle object.
+    UnsupportedObjectType {
+        /// The index of the object in the file.
+        object_index: usize,
+        /// The OCAD object type.
+        object_type: u8,
+    },
+    /// An object referenced a symbol that is undefined, was skipped or has a different type.
+    UnknownObjectSymbol {
+        /// The index of the object in the file.
+        object_index: usize,
+        /// The code derived from the OCAD symbol number.
+        code: Code,
+    },
+    /// An object without enough coordinates for its type was skipped.
+    InvalidObjectGeometry {
+        /// The index of the object in the file or map.
+        object_index: usize,
+    },
+    /// A symbol code without an OCAD symbol number of its own was written under another number.
+    SymbolRenumbered {
+        /// The code of the symbol.
+        code: Code,
+        /// The OCAD symbol number written instead.
+        symbol_number: i32,
+    },
+    /// A combined symbol was written as its component symbols, with every object repeated per component.
+    CombinedSymbolSplit {
+        /// The code of the combined symbol.
+        code: Code,
+        /// The OCAD symbol numbers of the components.
+        symbol_numbers: Vec<i32>,
+    },
+    /// A text object was written with the alignment of its symbol.
+    TextAlignmentChanged {
+        /// The index of the object in the map.
+        object_index: usize,
+    },
+    /// The tags of an object were dropped.
+    ObjectTagsDropped {
+        /// The index of the object in the map.
+        object_index: usize,
+    },
+    /// An object was skipped because no OCAD symbol was written for its symbol,
+    /// e.g. a combined symbol without parts.
+    ObjectWithoutOcdSymbol {
+        /// The index of the object in the map.
+        object_index: usize,
+        /// The code of the object's symbol.
+        code: Code,
+    },
+    /// The objects of several map parts were written into one.
+    PartsMerged {
+        /// The number of merged map parts.
+        num_parts: usize,
+    },
+}
+
+impl std::fmt::Display for OcdWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedSymbolType { code, symbol_type } => {
+                write!(f, "symbol {code} has unsupported OCAD type {symbol_type}")
+            }
+            Self::SymbolFeatureDropped { code, feature } => {
+                write!(f, "symbol {code} lost its {feature:?}")
+            }
+            Self::UnknownColor { code, color_number } => {
+                write!(f, "symbol {code} references unknown color {color_number}")
+            }
+            Self::UnsupportedObjectType {
+                object_index,
+                object_type,
+            } => write!(
+                f,
+                "object {object_index} has unsupported OCAD type {object_type}"
+            ),
+            Self::UnknownObjectSymbol { object_index, code } => {
+                write!(f, "object {object_index} references unusable symbol {code}")
+            }
+            Self::InvalidObjectGeometry { object_index } => {
+                write!(f, "object {object_index} has invalid geometry")
+            }
+            Self::SymbolRenumbered {
+                code,
+                symbol_number,
+            } => write!(
+                f,
+                "symbol {code} was written as OCAD symbol {symbol_number}"
+            ),
+            Self::CombinedSymbolSplit {
+                code,
+                symbol_numbers,
+            } => write!(
+                f,
+                "combined symbol {code} was split into OCAD symbols {symbol_numbers:?}"
+            ),
+            Self::TextAlignmentChanged { object_index } => {
+                write!(f, "object {object_index} lost its text alignment")
+            }
+            Self::ObjectTagsDropped { object_index } => {
+                write!(f, "object {object_index} lost its tags")
+            }
+            Self::ObjectWithoutOcdSymbol { object_index, code } => {
+                write!(
+                    f,
+                    "object {object_index} has no OCAD symbol for symbol {code}"
+                )
+            }
+            Self::PartsMerged { num_parts } => write!(f, "{num_parts} map parts were merged"),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Cursor, Read, Write};
 use std::num::NonZeroU32;
@@ -16,16 +17,21 @@ use quick_xml::{
 
 use crate::{
     colors::ColorSet,
-    format_info::{OmapVersion, XmlDeclaration},
+    format_info::{OmapVersion, OutputStyle, XmlDeclaration, write_pretty},
     geo_referencing::{GeoRef, MapTransform},
     notes,
-    objects::MapObject,
+    objects::{
+        BezierPolygon, CropMode, MapObject, ObjectFix, ObjectIssue, SimplifiedObject, TextGeometry,
+        clean_lines,
+    },
+    ocad::OcdWarning,
     parts::MapPart,
     parts::MapParts,
-    symbols::SymbolSet,
+    parts::SpatialIndex,
+    symbols::{SymbolSet, UnmatchedObject, WeakSymbol},
     templates::Templates,
     view::View,
-    {Error, Result, ValidationError},
+    {Error, NonNegativeF64, PositiveF64, Result, ValidationError},
 };
 
 const DEFAULT_ISOM_15000: &[u8] = include_bytes!("default_maps/isom_15000.omap");
@@ -127,6 +133,49 @@ impl Omap {
         Ok(omap)
     }
 
+    /// Create a new georeferenced map with the bundled symbol set and color
+    /// order of a mapping standard at a scale, see [`Self::default_map`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no map is bundled for the standard and scale,
+    /// georeferencing cannot be initialized or the embedded default map cannot
+    /// be parsed.
+    #[cfg(feature = "geo_ref")]
+    pub fn default_map_geo_referenced(
+        standard: MapStandard,
+        scale_denominator: NonZeroU32,
+        projected_ref_point: Coord,
+        crs: CrsType,
+        meters_above_sea: f64,
+    ) -> Result<Self> {
+        let mut omap = Self::default_map(standard, scale_denominator)?;
+        omap.geo_referencing = GeoRef::initialize(
+            projected_ref_point,
+            crs,
+            meters_above_sea,
+            scale_denominator,
+        )?;
+        Ok(omap)
+    }
+
+    /// Create a new map with the bundled symbol set and color order of a
+    /// mapping standard at a scale, see [`MapStandard::bundled_scales`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoDefaultMap`] if no map is bundled for the standard
+    /// and scale, or an error if the embedded default map cannot be parsed.
+    pub fn default_map(standard: MapStandard, scale_denominator: NonZeroU32) -> Result<Self> {
+        let bytes = match (standard, scale_denominator.get()) {
+            (MapStandard::Isom2017, 15_000) => DEFAULT_ISOM_15000,
+            (MapStandard::Isom2017, 10_000) => DEFAULT_ISOM_10000,
+            (MapStandard::Issprom2019, 4_000) => DEFAULT_ISSPROM_4000,
+            _ => return Err(Error::NoDefaultMap(standard, scale_denominator)),
+        };
+        Self::from_bytes(bytes)
+    }
+
     /// Create a new `1:15_000` map with a complete ISOM symbolset and color order
     ///
     /// # Errors
@@ -187,6 +236,10 @@ impl Omap {
     ///
     /// `barrier`s, `undo` and `redo` sections of the file are ignored
     ///
+    /// Files of version 6 to 9 are accepted. Geographic reference points
+    /// stored in radians by the older versions are translated into the
+    /// current model, and the map is always written as version 9.
+    ///
     /// The core sections `georeferencing`, `colors`, `symbols`, and `parts`
     /// must still parse successfully or else loading fails.
     ///
@@ -246,11 +299,7 @@ impl Omap {
                     }
                     _ => (),
                 },
-                Event::End(bytes_end) => {
-                    if bytes_end.local_name().as_ref() == b"map" {
-                        break;
-                    }
-                }
+                Event::End(bytes_end) if bytes_end.local_name().as_ref() == b"map" => break,
                 Event::Eof => break,
                 _ => (),
             }
@@ -269,8 +318,10 @@ impl Omap {
         })
     }
 
-    /// Create an [`Omap`] from a path to an `.omap` file.
+    /// Create an [`Omap`] from a path to an `.omap` or `.xmap` file.
     ///
+    /// Both the compact and the pretty-printed layout are accepted regardless
+    /// of the file extension, see [`OutputStyle`].
     /// See [`Self::from_reader`] for more docs
     ///
     /// # Errors
@@ -282,7 +333,36 @@ impl Omap {
         Self::from_reader(BufReader::new(file))
     }
 
-    /// Write the map to anything that implements [`Write`]
+    /// Create an [`Omap`] from anything that implements [`Read`] holding an OCAD `.ocd` file
+    /// of version 8 to 12 or 2018.
+    ///
+    /// OCAD colors, symbols and objects are mapped onto their OMAP counterparts and all
+    /// objects are placed in a single map part. Symbol parts and objects without an OMAP
+    /// equivalent are skipped and reported in the returned list of warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotAnOcadFile`] if the data is not an OCAD file,
+    /// [`Error::UnsupportedOcadVersion`] for other versions and
+    /// [`Error::TruncatedOcadFile`] if the file is cut short.
+    pub fn from_ocd_reader<R: Read>(mut reader: R) -> Result<(Self, Vec<OcdWarning>)> {
+        let mut bytes = Vec::new();
+        let _size = reader.read_to_end(&mut bytes)?;
+        crate::ocad::read(&bytes)
+    }
+
+    /// Create an [`Omap`] from a path to an OCAD `.ocd` file.
+    /// See [`Self::from_ocd_reader`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a supported OCAD file.
+    pub fn from_ocd_path(path: impl AsRef<Path>) -> Result<(Self, Vec<OcdWarning>)> {
+        let file = File::open(path)?;
+        Self::from_ocd_reader(BufReader::new(file))
+    }
+
+    /// Write the map to anything that implements [`Write`] in the compact `.omap` layout
     ///
     /// Takes a mutable borrow of self as the symbol set is sorted by [`crate::Code`] before writing
     ///
@@ -290,6 +370,34 @@ impl Omap {
     ///
     /// Returns an error if any of the map data cannot be serialized.
     pub fn to_writer<W: Write>(&mut self, writer: &mut W) -> Result<()> {
+        self.to_writer_with_style(writer, OutputStyle::Compact)
+    }
+
+    /// Write the map to anything that implements [`Write`] in the given [`OutputStyle`]
+    ///
+    /// See [`Self::to_writer`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the map data cannot be serialized.
+    pub fn to_writer_with_style<W: Write>(
+        &mut self,
+        writer: &mut W,
+        style: OutputStyle,
+    ) -> Result<()> {
+        match style {
+            OutputStyle::Compact => self.write_compact(writer),
+            OutputStyle::Pretty => {
+                let mut compact = Vec::new();
+                self.write_compact(&mut compact)?;
+                write_pretty(&compact, writer)?;
+                writer.flush()?;
+                Ok(())
+            }
+        }
+    }
+
+    fn write_compact<W: Write>(&mut self, writer: &mut W) -> Result<()> {
         let mut writer = Writer::new(writer);
 
         XmlDeclaration::write(&mut writer)?;
@@ -327,9 +435,11 @@ impl Omap {
         Ok(())
     }
 
-    /// Write the map to an `.omap` file at the given path.
+    /// Write the map to an `.omap` or `.xmap` file at the given path.
     ///
-    /// See [`Self::to_writer`] for more docs
+    /// The [`OutputStyle`] follows the file extension, a path ending in `.xmap`
+    /// is written pretty-printed and anything else compactly.
+    /// See [`Self::to_writer_with_style`] for more docs
     ///
     /// The replacement is atomic on platforms where [`fs::rename`] atomically
     /// replaces an existing destination. The temporary file is created beside
@@ -341,38 +451,36 @@ impl Omap {
     /// cannot be serialized, or the temporary file cannot replace `path`.
     pub fn to_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
         let path = path.as_ref();
+        let style = OutputStyle::from_path(path);
+        write_atomically(path, |writer| self.to_writer_with_style(writer, style))
+    }
 
-        // create temp file for safe writing
-        let parent = path
-            .parent()
-            .filter(|parent| !parent.as_os_str().is_empty())
-            .unwrap_or_else(|| Path::new("."));
-        let file_name = path.file_name().ok_or_else(|| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "OMAP output path must name a file",
-            )
-        })?;
-
-        let mut temporary_name = file_name.to_os_string();
-        temporary_name.push(format!(".omap-rs-{}.tmp", std::process::id()));
-        let temporary_path = parent.join(temporary_name);
-        let temporary_file = File::create(&temporary_path)?;
-
-        let write_result = {
-            let mut writer = BufWriter::new(temporary_file);
-            self.to_writer(&mut writer)
-        };
-        if let Err(error) = write_result {
-            std::fs::remove_file(temporary_path)?;
-            return Err(error);
-        }
+    /// Write the map as an OCAD 12 `.ocd` file to anything that implements [`Write`]
+    ///
+    /// Colors keep their priority as OCAD color number and symbols are numbered after
+    /// their [`crate::Code`]. Combined symbols are written as their component symbols
+    /// with every object repeated per component, and all map parts are merged. Anything
+    /// that cannot be carried over exactly is reported in the returned list of warnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the map has dangling references, a coordinate is beyond the
+    /// range of OCAD coordinates or the data cannot be written.
+    pub fn to_ocd_writer<W: Write>(&self, writer: &mut W) -> Result<Vec<OcdWarning>> {
+        crate::ocad::write(self, writer)
+    }
 
-        if let Err(error) = std::fs::rename(&temporary_path, path) {
-            std::fs::remove_file(temporary_path)?;
-            return Err(error.into());
-        }
-        Ok(())
+    /// Write the map as an OCAD 12 `.ocd` file at the given path.
+    /// See [`Self::to_ocd_writer`] for more docs
+    ///
+    /// The file is replaced atomically like in [`Self::to_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary file cannot be created, the map cannot be
+    /// written as OCAD file, or the temporary file cannot replace `path`.
+    pub fn to_ocd_file(&self, path: impl AsRef<Path>) -> Result<Vec<OcdWarning>> {
+        write_atomically(path.as_ref(), |writer| self.to_ocd_writer(writer))
     }
 
     /// Iterate through all objects of every map part in a flat iterator.
@@ -393,6 +501,151 @@ impl Omap {
             .flat_map(MapPart::iter_all_objects_mut)
     }
 
+    /// Build a spatial index over the objects of all map parts.
+    ///
+    /// The whole-map counterpart of [`MapPart::spatial_index`]; the results of its
+    /// queries carry the index of the object's part in [`Self::parts`].
+    pub fn spatial_index(&self) -> SpatialIndex<'_> {
+        SpatialIndex::new(self.parts.iter().enumerate())
+    }
+
+    /// Simplify the lines and areas whose symbol passes `filter` within
+    /// `allowed_error` mm and report the vertex counts of every simplified
+    /// object. See [`crate::objects::BezierPath::simplify`] for more docs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `allowed_error` is too small for fitting. The map is
+    /// unchanged on failure.
+    pub fn simplify_objects<F>(
+        &mut self,
+        filter: F,
+        allowed_error: NonNegativeF64,
+    ) -> Result<Vec<SimplifiedObject>>
+    where
+        F: Fn(&WeakSymbol) -> bool,
+    {
+        let mut parts = self.parts.clone();
+        let mut simplified = Vec::new();
+        for (part_index, part) in parts.iter_mut().enumerate() {
+            for (object_index, object) in part.iter_all_objects_mut().enumerate() {
+                if !filter(&object.symbol()) {
+                    continue;
+                }
+                if let Some(vertices) = object.simplify_in_place(allowed_error)? {
+                    simplified.push(SimplifiedObject {
+                        part_index,
+                        object_index,
+                        vertices,
+                    });
+                }
+            }
+        }
+        self.parts = parts;
+        Ok(simplified)
+    }
+
+    /// Crop every map part to a boundary polygon in map coordinates, e.g. to hand
+    /// out a section of the map. Objects outside the boundary are removed and
+    /// lines and areas crossing it are handled by `mode`, see
+    /// [`MapObject::crop_to`]. Symbols, colors, templates and the georeferencing
+    /// are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a geometry or the boundary is invalid or a cropped
+    /// area cannot be joined into rings. The map is unchanged on failure.
+    pub fn crop_to(&mut self, boundary: &BezierPolygon, mode: CropMode) -> Result<()> {
+        let mut parts = MapParts::new();
+        for part in &self.parts {
+            let mut cropped = MapPart::new(part.name.clone());
+            for object in part.iter_all_objects() {
+                for object in object.crop_to(boundary, mode)? {
+                    cropped.add_object(object);
+                }
+            }
+            parts.push(cropped);
+        }
+        self.parts = parts;
+        Ok(())
+    }
+
+    /// Clean the network of the lines whose symbol passes `filter`, e.g. of
+    /// imported paths and streams, within `tolerance` mm, and report the changes.
+    ///
+    /// In every map part, lines whose ends are within the tolerance are closed
+    /// with [`crate::objects::BezierPath::close`], and lines that run along an
+    /// earlier line of the same symbol are removed. Then the ends of the other
+    /// lines are snapped to the first end of another line near them, and the
+    /// ends that meet no other end onto the nearest point of another line. The
+    /// reported object indices refer to the parts before cleaning.
+    pub fn clean_line_network<F>(&mut self, filter: F, tolerance: NonNegativeF64) -> Vec<ObjectFix>
+    where
+        F: Fn(&WeakSymbol) -> bool,
+    {
+        let mut fixes = Vec::new();
+        for (part_index, part) in self.parts.iter_mut().enumerate() {
+            let name = part.name.clone();
+            let mut objects = std::mem::replace(part, MapPart::new(name)).into_objects();
+            fixes.extend(
+                clean_lines(&mut objects, &filter, tolerance.get())
+                    .into_iter()
+                    .map(|(object_index, fix)| ObjectFix {
+                        part_index,
+                        object_index,
+                        fix,
+                    }),
+            );
+            for object in objects {
+                part.add_object(object);
+            }
+        }
+        fixes
+    }
+
+    /// Check the geometry of every line and area in the map, see
+    /// [`crate::objects::BezierPolygon::geometry_issues`].
+    pub fn geometry_issues(&self) -> Vec<ObjectIssue> {
+        let mut issues = Vec::new();
+        for (part_index, part) in self.parts.iter().enumerate() {
+            for (object_index, object) in part.iter_all_objects().enumerate() {
+                issues.extend(
+                    object
+                        .geometry_issues()
+                        .into_iter()
+                        .map(|issue| ObjectIssue {
+                            part_index,
+                            object_index,
+                            issue,
+                        }),
+                );
+            }
+        }
+        issues
+    }
+
+    /// Fix the geometry issues of every line and area in the map that do not
+    /// change their shape and return the issues that remain. See
+    /// [`crate::objects::BezierPolygon::repair`] for more docs.
+    pub fn repair_geometries(&mut self) -> Vec<ObjectIssue> {
+        let mut issues = Vec::new();
+        for (part_index, part) in self.parts.iter_mut().enumerate() {
+            for (object_index, object) in part.iter_all_objects_mut().enumerate() {
+                issues.extend(
+                    object
+                        .repair_geometry()
+                        .into_iter()
+                        .map(|issue| ObjectIssue {
+                            part_index,
+                            object_index,
+                            issue,
+                        }),
+                );
+            }
+        }
+        issues
+    }
+
     /// Transform every object and non-georeferenced template in the map.
     ///
     /// Use this after changing the georeferencing
@@ -449,6 +702,51 @@ impl Omap {
         self.try_transform(transform)
     }
 
+    /// Change the scale of the map, e.g. from a 1:15 000 forest map to a
+    /// 1:10 000 print.
+    ///
+    /// Objects, text boxes and non-georeferenced templates are scaled about the
+    /// map reference point by the ratio of the old to the new scale, so they keep
+    /// their real-world positions, see [`Self::transform`]. The symbols are
+    /// handled by `symbols`. Objects without a counterpart when the symbol set is
+    /// replaced are returned, see [`Self::replace_symbol_set`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol or color is borrowed somewhere else or the
+    /// replacement symbols cannot be copied. The map is unchanged on failure.
+    pub fn change_scale(
+        &mut self,
+        scale_denominator: NonZeroU32,
+        symbols: SymbolScaling<'_>,
+    ) -> Result<Vec<UnmatchedObject>> {
+        let factor = PositiveF64::try_from(
+            f64::from(self.geo_referencing.scale_denominator.get())
+                / f64::from(scale_denominator.get()),
+        )?;
+        let unmatched = match symbols {
+            SymbolScaling::WithObjects => {
+                self.symbols.scale(factor)?;
+                Vec::new()
+            }
+            SymbolScaling::Keep => Vec::new(),
+            SymbolScaling::Replace(source) => self.replace_symbol_set(source, &HashMap::new())?,
+        };
+
+        let center = self.geo_referencing.map_ref_point;
+        self.transform(|coord| center + (coord - center) * factor.get());
+        for object in self.iter_all_objects_mut() {
+            if let MapObject::Text(text) = object
+                && let TextGeometry::WrapBox(wrap_box) = text.geometry_mut()
+            {
+                wrap_box.width = wrap_box.width.scaled(factor);
+                wrap_box.height = wrap_box.height.scaled(factor);
+            }
+        }
+        self.geo_referencing.scale_denominator = scale_denominator;
+        Ok(unmatched)
+    }
+
     /// Validate references between objects, symbols, and colors.
     ///
     /// # Errors
@@ -509,20 +807,109 @@ impl Omap {
     }
 }
 
+/// A mapping standard with bundled default maps, see [`Omap::default_map`].
+///
+/// Only the standards and scales of the maps bundled with this crate are
+/// available. Mapper also ships symbol sets for ISMTBOM at 1:12 500 and
+/// 1:7 500, `ISSkiOM` at 1:15 000 and 1:10 000 and ISOM 2000, which are not
+/// bundled yet and may be added as new variants and scales.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MapStandard {
+    /// International Specification for Orienteering Maps, ISOM 2017-2.
+    Isom2017,
+    /// International Specification for Sprint Orienteering Maps, `ISSprOM` 2019.
+    Issprom2019,
+}
+
+impl MapStandard {
+    /// The scale denominators the standard has a bundled default map for.
+    pub fn bundled_scales(self) -> &'static [u32] {
+        match self {
+            Self::Isom2017 => &[15_000, 10_000],
+            Self::Issprom2019 => &[4_000],
+        }
+    }
+}
+
+/// What [`Omap::change_scale`] does with the symbols of the map.
+#[derive(Debug, Clone, Copy)]
+pub enum SymbolScaling<'a> {
+    /// Scale every symbol dimension by the same factor as the objects, as ISOM
+    /// does between 1:15 000 and 1:10 000.
+    WithObjects,
+    /// Keep the symbols as they are.
+    Keep,
+    /// Replace the symbol set with the symbols of another map of the new scale,
+    /// e.g. [`Omap::default_10_000`], matched by code.
+    Replace(&'a Omap),
+}
+
+/// Write a file through a temporary file beside `path` which then replaces `path`.
+///
+/// The replacement is atomic on platforms where [`std::fs::rename`] atomically
+/// replaces an existing destination.
+pub(crate) fn write_atomically<T>(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<T>,
+) -> Result<T> {
+    // create temp file for safe writing
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "OMAP output path must name a file",
+        )
+    })?;
+
+    let mut temporary_name = file_name.to_os_string();
+    temporary_name.push(format!(".omap-rs-{}.tmp", std::process::id()));
+    let temporary_path = parent.join(temporary_name);
+    let temporary_file = File::create(&temporary_path)?;
+
+    let write_result = {
+        let mut writer = BufWriter::new(temporary_file);
+        write(&mut writer)
+    };
+    let value = match write_result {
+        Ok(value) => value,
+        Err(error) => {
+            std::fs::remove_file(temporary_path)?;
+            return Err(error);
+        }
+    };
+
+    if let Err(error) = std::fs::rename(&temporary_path, path) {
+        std::fs::remove_file(temporary_path)?;
+        return Err(error.into());
+    }
+    Ok(value)
+}
+
 #[expect(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
-    use std::{fs, num::NonZeroU32};
+    use std::{cell::RefCell, fs, num::NonZeroU32, rc::Rc};
 
-    use geo_types::{Coord, Point};
+    use geo_types::{Coord, LineString, Point, Polygon};
 
-    use super::Omap;
-    use crate::{Error, Result, ValidationError, objects::PointObject};
+    use super::{MapStandard, Omap, SymbolScaling};
+    use crate::{
+        Code, Error, NonNegativeF64, OutputStyle, Result, ValidationError,
+        objects::{
+            BezierPolygon, BezierSegment, CropMode, LineObject, MapObject, PointObject,
+            SimplifiedObject, TextGeometry, VertexCounts,
+        },
+        symbols::{LineSymbol, Symbol, WeakLinePathSymbol, WeakSymbol},
+    };
 
     fn point_positions(map: &Omap) -> Vec<Coord> {
         map.iter_all_objects()
             .filter_map(|object| match object {
-                crate::objects::MapObject::Point(point) => Some(point.geometry().0),
+                MapObject::Point(point) => Some(point.geometry().0),
                 _ => None,
             })
             .collect()
@@ -555,6 +942,172 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn simplify_objects_filters_by_symbol_and_is_transactional() -> Result<()> {
+        let path = Rc::new(RefCell::new(LineSymbol::new(Code::new(505, 0, 0), "Path")));
+        let fence = Rc::new(RefCell::new(LineSymbol::new(Code::new(516, 0, 0), "Fence")));
+        // a straight line traced with noise
+        let traced = LineString::from(
+            (0..=20)
+                .map(|index| (f64::from(index), if index % 2 == 0 { 0.01 } else { 0. }))
+                .collect::<Vec<_>>(),
+        );
+        let mut map = Omap::new(NonZeroU32::new(10_000).ok_or(Error::ObjectError)?);
+        let part = map.parts.get_mut(0).ok_or(Error::ObjectError)?;
+        part.add_object(PointObject::new(std::rc::Weak::new(), Point::new(1.0, 2.0)));
+        part.add_object(LineObject::new(
+            WeakLinePathSymbol::Line(Rc::downgrade(&path)),
+            traced.clone(),
+        ));
+        part.add_object(LineObject::new(
+            WeakLinePathSymbol::Line(Rc::downgrade(&fence)),
+            traced,
+        ));
+        let is_path = |symbol: &WeakSymbol| *symbol == WeakSymbol::Line(Rc::downgrade(&path));
+        let num_vertices = |map: &Omap| {
+            map.iter_all_objects()
+                .filter_map(|object| match object {
+                    MapObject::Line(line) => Some(line.geometry().num_vertices()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(
+            map.simplify_objects(is_path, NonNegativeF64::zero())
+                .is_err()
+        );
+        assert_eq!(num_vertices(&map), [21, 21]);
+
+        let simplified = map.simplify_objects(is_path, NonNegativeF64::clamped_from(0.05))?;
+        assert_eq!(
+            simplified,
+            [SimplifiedObject {
+                part_index: 0,
+                object_index: 1,
+                vertices: VertexCounts {
+                    before: 21,
+                    after: 2
+                },
+            }]
+        );
+        assert_eq!(num_vertices(&map), [2, 21]);
+        Ok(())
+    }
+
+    #[test]
+    fn crop_to_removes_objects_outside_and_clips_lines() -> Result<()> {
+        let boundary = BezierPolygon::from(Polygon::new(
+            LineString::from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 0.)]),
+            Vec::new(),
+        ));
+        let mut map = Omap::new(NonZeroU32::new(10_000).ok_or(Error::ObjectError)?);
+        let part = map.parts.get_mut(0).ok_or(Error::ObjectError)?;
+        part.add_object(PointObject::new(std::rc::Weak::new(), Point::new(1.0, 2.0)));
+        part.add_object(PointObject::new(
+            std::rc::Weak::new(),
+            Point::new(11.0, 2.0),
+        ));
+        part.add_object(LineObject::new(
+            WeakLinePathSymbol::Line(std::rc::Weak::new()),
+            LineString::from(vec![(5., 5.), (15., 5.)]),
+        ));
+        part.add_object(LineObject::new(
+            WeakLinePathSymbol::Line(std::rc::Weak::new()),
+            LineString::from(vec![(15., 5.), (20., 5.)]),
+        ));
+        let line_ends = |map: &Omap| {
+            map.iter_all_objects()
+                .filter_map(|object| match object {
+                    MapObject::Line(line) => line.geometry().geometry().segments().last(),
+                    _ => None,
+                })
+                .map(|segment| segment.end().x.round())
+                .collect::<Vec<_>>()
+        };
+
+        let parts = map.parts.clone();
+        map.crop_to(&boundary, CropMode::KeepIntersecting)?;
+        assert_eq!(point_positions(&map), [Coord { x: 1.0, y: 2.0 }]);
+        assert_eq!(line_ends(&map), [15.]);
+
+        map.parts = parts;
+        map.crop_to(&boundary, CropMode::Clip)?;
+        assert_eq!(point_positions(&map), [Coord { x: 1.0, y: 2.0 }]);
+        assert_eq!(line_ends(&map), [10.]);
+        Ok(())
+    }
+
+    #[test]
+    fn change_scale_scales_objects_and_symbols() -> Result<()> {
+        let path_width = |map: &Omap| -> Result<f64> {
+            match map.symbols.symbol_by_code(Code::new(505, 0, 0))? {
+                Some(Symbol::Line(path)) => Ok(path.try_borrow()?.line_width.get()),
+                symbol => panic!("expected the path line symbol, got {symbol:?}"),
+            }
+        };
+        let mut map = Omap::default_15_000()?;
+        map.geo_referencing.map_ref_point = Coord { x: 10., y: 10. };
+        let Some(Symbol::Line(path)) = map.symbols.symbol_by_code(Code::new(505, 0, 0))? else {
+            panic!("expected the path line symbol");
+        };
+        let path = WeakLinePathSymbol::Line(Rc::downgrade(path));
+        let part = map.parts.get_mut(0).ok_or(Error::ObjectError)?;
+        part.add_object(LineObject::new(
+            path,
+            LineString::from(vec![(10., 10.), (20., 30.)]),
+        ));
+        let width = path_width(&map)?;
+        let scale = NonZeroU32::new(10_000).ok_or(Error::ObjectError)?;
+
+        let unmatched = map.change_scale(scale, SymbolScaling::WithObjects)?;
+
+        assert!(unmatched.is_empty());
+        assert_eq!(map.geo_referencing.scale_denominator, scale);
+        assert!((path_width(&map)? - 1.5 * width).abs() < 1e-9);
+        let Some(MapObject::Line(line)) = map.iter_all_objects().next() else {
+            panic!("expected the line");
+        };
+        let Some(end) = line.geometry().geometry().segments().last() else {
+            panic!("expected a segment");
+        };
+        assert_eq!(end.start(), Coord { x: 10., y: 10. });
+        assert_eq!(end.end(), Coord { x: 25., y: 40. });
+
+        let mut map = Omap::default_15_000()?;
+        let isom_10_000 = Omap::default_10_000()?;
+        let unmatched = map.change_scale(scale, SymbolScaling::Replace(&isom_10_000))?;
+        assert!(unmatched.is_empty());
+        assert!((path_width(&map)? - path_width(&isom_10_000)?).abs() < 1e-9);
+        map.validate()
+    }
+
+    #[test]
+    fn default_map_looks_up_the_bundled_maps() -> Result<()> {
+        for standard in [MapStandard::Isom2017, MapStandard::Issprom2019] {
+            for &scale in standard.bundled_scales() {
+                let scale = NonZeroU32::new(scale).ok_or(Error::ObjectError)?;
+                let map = Omap::default_map(standard, scale)?;
+                assert_eq!(map.geo_referencing.scale_denominator, scale);
+                assert!(map.symbols.iter().next().is_some());
+            }
+        }
+
+        let scale = NonZeroU32::new(5_000).ok_or(Error::ObjectError)?;
+        assert!(matches!(
+            Omap::default_map(MapStandard::Isom2017, scale),
+            Err(Error::NoDefaultMap(MapStandard::Isom2017, _))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn example_map_has_no_geometry_issues() -> Result<()> {
+        let map = Omap::from_path("./example_data/from_path.omap")?;
+        assert_eq!(map.geometry_issues(), Vec::new());
+        Ok(())
+    }
+
     #[test]
     fn validate_reports_the_dangling_object_location() -> Result<()> {
         let mut map = Omap::new(NonZeroU32::new(10_000).ok_or(Error::ObjectError)?);
@@ -591,4 +1144,261 @@ mod tests {
         fs::remove_file(path)?;
         Ok(())
     }
+
+    #[test]
+    fn xmap_round_trip_reproduces_the_compact_layout() -> Result<()> {
+        let mut map = Omap::from_path("./example_data/from_path.omap")?;
+        let mut compact = Vec::new();
+        map.to_writer(&mut compact)?;
+
+        let mut pretty = Vec::new();
+        map.to_writer_with_style(&mut pretty, OutputStyle::Pretty)?;
+        assert_ne!(pretty, compact);
+
+        let mut reread = Omap::from_bytes(&pretty)?;
+        let mut rewritten = Vec::new();
+        reread.to_writer(&mut rewritten)?;
+        assert_eq!(
+            String::from_utf8(rewritten).unwrap(),
+            String::from_utf8(compact).unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn xmap_layout_has_one_coord_element_per_coordinate() -> Result<()> {
+        let mut map = Omap::from_path("./example_data/from_path.omap")?;
+        let mut pretty = Vec::new();
+        map.to_writer_with_style(&mut pretty, OutputStyle::Pretty)?;
+        let pretty = String::from_utf8(pretty).unwrap();
+
+        assert!(pretty.contains("\n  <colors count="));
+        let mut in_coords = false;
+        let mut coordinate_lines = 0;
+        for line in pretty.lines().map(str::trim_start) {
+            if in_coords {
+                if line == "</coords>" {
+                    in_coords = false;
+                } else {
+                    assert!(line.starts_with("<coord x=\""), "{line}");
+                    assert!(line.ends_with("\"/>"), "{line}");
+                    coordinate_lines += 1;
+                }
+            } else if line.starts_with("<coords ") {
+                assert!(line.ends_with('>'));
+                in_coords = true;
+            }
+        }
+        assert!(coordinate_lines > 0);
+        Ok(())
+    }
+
+    /// A map in the layout Mapper writes for .xmap files.
+    const MAPPER_XMAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map xmlns="http://openorienteering.org/apps/mapper/xml/v2" version="9">
+    <notes></notes>
+    <georeferencing scale="10000">
+        <projected_crs id="Local"/>
+    </georeferencing>
+    <colors count="1">
+        <color priority="0" name="Black" c="0" m="0" y="0" k="1" opacity="1">
+            <cmyk method="custom"/>
+            <rgb method="cmyk" r="0" g="0" b="0"/>
+        </color>
+    </colors>
+    <barrier version="6" required="0.6.0">
+        <symbols count="4" id="Test">
+            <symbol type="1" id="0" code="115" name="Knoll">
+                <point_symbol inner_radius="375" inner_color="0" outer_width="0" outer_color="-1" elements="0"/>
+            </symbol>
+            <symbol type="2" id="1" code="101" name="Contour">
+                <line_symbol color="0" line_width="140" minimum_length="0" join_style="1" cap_style="0" start_offset="0" end_offset="0" segment_length="4000" end_length="0"/>
+            </symbol>
+            <symbol type="4" id="2" code="403" name="Rough open land">
+                <area_symbol inner_color="0" min_area="0" patterns="0"/>
+            </symbol>
+            <symbol type="8" id="3" code="102.1" name="Contour value">
+                <text_symbol icon_text="225">
+                    <font family="Sans Serif" size="3143"/>
+                    <text color="0" line_spacing="1" paragraph_spacing="0" character_spacing="0" kerning="true"/>
+                </text_symbol>
+            </symbol>
+        </symbols>
+        <parts count="1" current="0">
+            <part name="default part">
+                <objects count="4">
+                    <object type="0" symbol="0">
+                        <coords count="1">
+                            <coord x="1000" y="-2000"/>
+                        </coords>
+                    </object>
+                    <object type="1" symbol="1">
+                        <coords count="5">
+                            <coord x="0" y="0" flags="1"/>
+                            <coord x="3000" y="0"/>
+                            <coord x="7000" y="-1000"/>
+                            <coord x="10000" y="0"/>
+                            <coord x="10000" y="-10000"/>
+                        </coords>
+                    </object>
+                    <object type="1" symbol="2">
+                        <coords count="9">
+                            <coord x="0" y="0"/>
+                            <coord x="6000" y="0"/>
+                            <coord x="6000" y="-6000"/>
+                            <coord x="0" y="-6000"/>
+                            <coord x="0" y="0" flags="18"/>
+                            <coord x="1000" y="-1000"/>
+                            <coord x="2000" y="-1000"/>
+                            <coord x="2000" y="-2000"/>
+                            <coord x="1000" y="-1000" flags="18"/>
+                        </coords>
+                        <pattern rotation="0.5">
+                            <coord x="500" y="-500"/>
+                        </pattern>
+                    </object>
+                    <object type="4" symbol="3" h_align="0" v_align="0">
+                        <coords count="2">
+                            <coord x="4000" y="-4000"/>
+                            <coord x="3000" y="1500"/>
+                        </coords>
+                        <size width="3000" height="1500"/>
+                        <text>120</text>
+                    </object>
+                </objects>
+            </part>
+        </parts>
+    </barrier>
+</map>
+"#;
+
+    #[test]
+    fn reads_coord_elements_of_mapper_xmap_files() -> Result<()> {
+        let map = Omap::from_bytes(MAPPER_XMAP)?;
+        let objects = map.iter_all_objects().collect::<Vec<_>>();
+        assert_eq!(objects.len(), 4);
+
+        let MapObject::Point(point) = objects[0] else {
+            panic!("expected a point object");
+        };
+        assert_eq!(point.geometry().x(), 1.);
+        let MapObject::Line(line) = objects[1] else {
+            panic!("expected a line object");
+        };
+        assert_eq!(line.geometry().num_segments(), 2);
+        assert!(matches!(
+            line.geometry().geometry().0[0],
+            BezierSegment::Bezier(_)
+        ));
+        let MapObject::Area(area) = objects[2] else {
+            panic!("expected an area object");
+        };
+        assert_eq!(area.geometry().interiors().len(), 1);
+        assert_eq!(area.pattern_rotation.rotation, 0.5);
+        assert_eq!(area.pattern_rotation.coord, Coord { x: 0.5, y: 0.5 });
+        let MapObject::Text(text) = objects[3] else {
+            panic!("expected a text object");
+        };
+        let TextGeometry::WrapBox(wrap_box) = text.geometry() else {
+            panic!("expected a wrapped text");
+        };
+        assert_eq!(wrap_box.anchor, Coord { x: 4., y: 4. });
+        assert_eq!(wrap_box.width.get(), 3.);
+        map.validate()
+    }
+
+    #[test]
+    fn to_file_and_from_path_handle_xmap() -> Result<()> {
+        let path = std::env::temp_dir().join(format!(
+            "omap-rs-xmap-round-trip-test-{}.xmap",
+            std::process::id(),
+        ));
+        let mut map = Omap::from_path("./example_data/from_path.omap")?;
+        map.to_file(&path)?;
+
+        let written = fs::read_to_string(&path)?;
+        assert!(written.contains("\n    <symbol "));
+
+        let mut reread = Omap::from_path(&path)?;
+        fs::remove_file(path)?;
+
+        let mut expected = Vec::new();
+        map.to_writer(&mut expected)?;
+        let mut actual = Vec::new();
+        reread.to_writer(&mut actual)?;
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    fn legacy_fixture(version: u8) -> Result<Omap> {
+        Omap::from_path(format!("./example_data/legacy/version_{version}.omap"))
+    }
+
+    #[test]
+    fn versions_6_to_8_are_read_and_written_as_version_9() -> Result<()> {
+        for version in 6..=8 {
+            let mut map = legacy_fixture(version)?;
+            assert_eq!(map.notes, format!("Version {version} fixture"));
+            assert_eq!(map.geo_referencing.scale_denominator.get(), 10_000);
+            let geographic = map.geo_referencing.geographic_ref_point_deg;
+            assert!((geographic.y - 61.636_234_32).abs() < 1e-8, "{version}");
+            assert!((geographic.x - 8.312_745_29).abs() < 1e-8, "{version}");
+            assert_eq!(map.symbols.len(), 3);
+            assert_eq!(map.iter_all_objects().count(), 3);
+            map.validate()?;
+
+            let mut written = Vec::new();
+            map.to_writer(&mut written)?;
+            let written = String::from_utf8(written).unwrap();
+            assert!(written.contains(
+                r#"<map xmlns="http://openorienteering.org/apps/mapper/xml/v2" version="9">"#
+            ));
+            assert!(written.contains("<parts count="));
+            assert!(written.contains("<ref_point_deg "));
+
+            let mut rewritten = Vec::new();
+            Omap::from_bytes(&written)?.to_writer(&mut rewritten)?;
+            assert_eq!(String::from_utf8(rewritten).unwrap(), written);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn legacy_symbol_flags_and_parts_are_read() -> Result<()> {
+        let rough_open_land = Code::new(403, 0, 0);
+
+        let map = legacy_fixture(6)?;
+        let symbol = map.symbols.symbol_by_code(rough_open_land)?.unwrap();
+        assert!(symbol.is_helper_symbol()?);
+        assert!(!symbol.is_hidden()?);
+        assert_eq!(map.parts.len(), 1);
+        assert_eq!(map.parts.get(0).unwrap().name, "default part");
+
+        let map = legacy_fixture(7)?;
+        let symbol = map.symbols.symbol_by_code(rough_open_land)?.unwrap();
+        assert!(symbol.is_hidden()?);
+        assert!(!symbol.is_protected()?);
+        assert_eq!(map.parts.len(), 2);
+        assert_eq!(map.parts.get(1).unwrap().len(), 1);
+
+        let map = legacy_fixture(8)?;
+        let symbol = map.symbols.symbol_by_code(rough_open_land)?.unwrap();
+        assert!(symbol.is_protected()?);
+        assert!(!symbol.is_helper_symbol()?);
+        assert_eq!(map.parts.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn versions_outside_the_supported_range_are_rejected() -> Result<()> {
+        let fixture = fs::read_to_string("./example_data/legacy/version_8.omap")?;
+        for version in [5, 10] {
+            let bytes = fixture.replace(r#"version="8""#, &format!(r#"version="{version}""#));
+            assert!(matches!(
+                Omap::from_bytes(bytes),
+                Err(Error::UnsupportedOmapVersion(v)) if v == version
+            ));
+        }
+        Ok(())
+    }
 }
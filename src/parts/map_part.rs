@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use quick_xml::events::{BytesEnd, BytesStart, Event};
 use quick_xml::{Reader, Writer};
 
+use super::SpatialIndex;
 use crate::objects::MapObject;
 use crate::symbols::{SymbolSet, WeakSymbol};
 use crate::utils::try_get_attr;
@@ -94,6 +95,25 @@ impl MapPart {
         self.objects.iter_mut()
     }
 
+    /// Get an object by its index in the order of [`Self::iter_all_objects`].
+    pub fn get(&self, index: usize) -> Option<&MapObject> {
+        self.objects.get(index)
+    }
+
+    /// Get a mutable reference to an object by its index in the order of
+    /// [`Self::iter_all_objects`].
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut MapObject> {
+        self.objects.get_mut(index)
+    }
+
+    /// Build a spatial index over the objects of this map-part for envelope,
+    /// nearest-object and point-in-area queries.
+    ///
+    /// The index borrows the part, so it is rebuilt after objects are changed.
+    pub fn spatial_index(&self) -> SpatialIndex<'_> {
+        SpatialIndex::new(std::iter::once((0, self)))
+    }
+
     /// Consume this map-part and get all the objects it contains
     pub fn into_objects(self) -> Vec<MapObject> {
         self.objects
@@ -1,5 +1,7 @@
 mod map_part;
 mod map_parts;
+mod spatial_index;
 
 pub use map_part::MapPart;
 pub use map_parts::MapParts;
+pub use spatial_index::{IndexedObject, SpatialIndex};
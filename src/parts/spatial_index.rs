@@ -0,0 +1,470 @@
+use std::{cmp::Ordering, collections::BinaryHeap, ops::Range};
+
+use geo_types::{Coord, LineString, Rect};
+
+use super::MapPart;
+use crate::{
+    NonNegativeF64, Result,
+    objects::{MapObject, union_rects},
+};
+
+/// The most children of a node of the tree.
+const NODE_CAPACITY: usize = 16;
+/// The largest deviation of flattened curves in distance and containment tests,
+/// in mm of paper.
+const ALLOWED_ERROR: f64 = 0.001;
+
+/// An object found by a [`SpatialIndex`] query.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexedObject<'a> {
+    /// The index of the object's map part in the map, 0 in an index of a single part.
+    pub part_index: usize,
+    /// The index of the object in its part, in the order of [`MapPart::iter_all_objects`].
+    pub object_index: usize,
+    /// The object.
+    pub object: &'a MapObject,
+}
+
+/// A static R-tree over the exact bounding boxes of map objects, answering
+/// envelope, nearest-object and point-in-area queries without scanning every
+/// object.
+///
+/// The tree is bulk loaded with the sort-tile-recursive algorithm from
+/// [`MapPart::spatial_index`] or [`crate::Omap::spatial_index`]. It borrows the
+/// objects, so it is built again after the map changes. Empty lines and areas
+/// are not indexed. Query results are ordered by part and object index.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<'a> {
+    /// The indexed objects, ordered so that every leaf node covers a run of them.
+    entries: Vec<(Rect, IndexedObject<'a>)>,
+    /// The nodes level by level from the leaves up. A node covers a run of the
+    /// level below, or of the entries for leaves, and the last level is the root.
+    levels: Vec<Vec<Node>>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    bounds: Rect,
+    children: Range<usize>,
+}
+
+impl<'a> SpatialIndex<'a> {
+    /// Index the objects of map parts given with their part index.
+    pub(crate) fn new(parts: impl Iterator<Item = (usize, &'a MapPart)>) -> Self {
+        let mut entries = parts
+            .flat_map(|(part_index, part)| {
+                part.iter_all_objects()
+                    .enumerate()
+                    .filter_map(move |(object_index, object)| {
+                        let indexed = IndexedObject {
+                            part_index,
+                            object_index,
+                            object,
+                        };
+                        Some((object.bounding_rect()?, indexed))
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let mut levels = Vec::new();
+        let mut nodes = pack(&mut entries, |(bounds, _)| *bounds);
+        while nodes.len() > 1 {
+            let parents = pack(&mut nodes, |node| node.bounds);
+            levels.push(nodes);
+            nodes = parents;
+        }
+        levels.push(nodes);
+        Self { entries, levels }
+    }
+
+    /// Get the number of indexed objects.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no object is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The objects whose bounding box intersects or touches `envelope`.
+    ///
+    /// See [`MapObject::bounding_rect`] for the boxes of the objects.
+    pub fn intersecting(&self, envelope: Rect) -> Vec<IndexedObject<'a>> {
+        self.search(|bounds| {
+            bounds.min().x <= envelope.max().x
+                && envelope.min().x <= bounds.max().x
+                && bounds.min().y <= envelope.max().y
+                && envelope.min().y <= bounds.max().y
+        })
+    }
+
+    /// The area objects containing `point` by the even-odd rule, so not in a hole.
+    ///
+    /// Curves are flattened to within 1 µm of paper for the test.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an area has an invalid geometry.
+    pub fn areas_containing(&self, point: Coord) -> Result<Vec<IndexedObject<'a>>> {
+        let mut areas = Vec::new();
+        for candidate in self.search(|bounds| contains(bounds, point)) {
+            if matches!(candidate.object, MapObject::Area(_))
+                && even_odd_contains(&area_rings(candidate.object)?, point)
+            {
+                areas.push(candidate);
+            }
+        }
+        Ok(areas)
+    }
+
+    /// The object nearest to `point` and its distance in mm of paper, `None` if
+    /// the index is empty.
+    ///
+    /// Distances are measured to points, text anchors, lines and area rings, and are
+    /// 0 inside areas. Curves are flattened to within 1 µm of paper for the test. Of
+    /// several objects at the same distance any one may be returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line or area has an invalid geometry.
+    pub fn nearest(&self, point: Coord) -> Result<Option<(IndexedObject<'a>, f64)>> {
+        let mut queue = BinaryHeap::new();
+        if let Some(roots) = self.levels.last() {
+            let top = self.levels.len() - 1;
+            for (index, root) in roots.iter().enumerate() {
+                queue.push(Candidate {
+                    distance: rect_distance(root.bounds, point),
+                    item: Item::Node(top, index),
+                });
+            }
+        }
+        // boxes are never farther than their contents, so the first exact
+        // distance off the queue is the nearest
+        while let Some(Candidate { distance, item }) = queue.pop() {
+            match item {
+                Item::Node(level, index) => {
+                    let children = self.levels[level][index].children.clone();
+                    for child in children {
+                        let (bounds, item) = match level.checked_sub(1) {
+                            Some(below) => {
+                                (self.levels[below][child].bounds, Item::Node(below, child))
+                            }
+                            None => (self.entries[child].0, Item::Entry(child)),
+                        };
+                        queue.push(Candidate {
+                            distance: rect_distance(bounds, point),
+                            item,
+                        });
+                    }
+                }
+                Item::Entry(index) => queue.push(Candidate {
+                    distance: object_distance(self.entries[index].1.object, point)?,
+                    item: Item::Exact(index),
+                }),
+                Item::Exact(index) => return Ok(Some((self.entries[index].1, distance))),
+            }
+        }
+        Ok(None)
+    }
+
+    /// The objects whose box matches, descending into nodes whose box matches.
+    fn search(&self, matches: impl Fn(Rect) -> bool) -> Vec<IndexedObject<'a>> {
+        let mut found = Vec::new();
+        let mut stack = Vec::new();
+        if let Some(roots) = self.levels.last() {
+            stack.extend((0..roots.len()).map(|index| (self.levels.len() - 1, index)));
+        }
+        while let Some((level, index)) = stack.pop() {
+            let node = &self.levels[level][index];
+            if !matches(node.bounds) {
+                continue;
+            }
+            match level.checked_sub(1) {
+                Some(below) => stack.extend(node.children.clone().map(|child| (below, child))),
+                None => found.extend(
+                    self.entries[node.children.clone()]
+                        .iter()
+                        .filter(|(bounds, _)| matches(*bounds))
+                        .map(|(_, object)| *object),
+                ),
+            }
+        }
+        found.sort_unstable_by_key(|object| (object.part_index, object.object_index));
+        found
+    }
+}
+
+/// A node or an entry with the least possible distance of its contents.
+struct Candidate {
+    distance: f64,
+    item: Item,
+}
+
+enum Item {
+    /// A node by level and index.
+    Node(usize, usize),
+    /// An entry with the distance of its bounding box.
+    Entry(usize),
+    /// An entry with the distance of its geometry.
+    Exact(usize),
+}
+
+// ordered in reverse for a min-heap
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+/// Sort the items into tiles and group them into nodes of up to
+/// [`NODE_CAPACITY`] neighbouring items.
+fn pack<T>(items: &mut [T], bounds: impl Fn(&T) -> Rect) -> Vec<Node> {
+    let num_nodes = items.len().div_ceil(NODE_CAPACITY);
+    let num_slices = num_nodes.isqrt() + usize::from(num_nodes.isqrt().pow(2) < num_nodes);
+    let slice_len = (num_slices * NODE_CAPACITY).max(1);
+
+    items.sort_by(|a, b| bounds(a).center().x.total_cmp(&bounds(b).center().x));
+    for slice in items.chunks_mut(slice_len) {
+        slice.sort_by(|a, b| bounds(a).center().y.total_cmp(&bounds(b).center().y));
+    }
+    items
+        .chunks(NODE_CAPACITY)
+        .enumerate()
+        .filter_map(|(index, chunk)| {
+            let start = index * NODE_CAPACITY;
+            Some(Node {
+                bounds: chunk.iter().map(&bounds).reduce(union_rects)?,
+                children: start..start + chunk.len(),
+            })
+        })
+        .collect()
+}
+
+fn contains(bounds: Rect, point: Coord) -> bool {
+    (bounds.min().x..=bounds.max().x).contains(&point.x)
+        && (bounds.min().y..=bounds.max().y).contains(&point.y)
+}
+
+fn rect_distance(bounds: Rect, point: Coord) -> f64 {
+    let dx = (bounds.min().x - point.x)
+        .max(point.x - bounds.max().x)
+        .max(0.);
+    let dy = (bounds.min().y - point.y)
+        .max(point.y - bounds.max().y)
+        .max(0.);
+    dx.hypot(dy)
+}
+
+/// The distance from `point` to the geometry of an object.
+fn object_distance(object: &MapObject, point: Coord) -> Result<f64> {
+    let distance = match object {
+        MapObject::Point(object) => distance(object.geometry().0, point),
+        MapObject::Text(object) => distance(*object.geometry().anchor_coord(), point),
+        MapObject::Line(line) => {
+            let path = line.flatten(NonNegativeF64::try_from(ALLOWED_ERROR)?)?;
+            line_distance(path.geometry(), point)
+        }
+        MapObject::Area(_) => {
+            let rings = area_rings(object)?;
+            if even_odd_contains(&rings, point) {
+                0.
+            } else {
+                rings
+                    .iter()
+                    .map(|ring| line_distance(ring, point))
+                    .fold(f64::INFINITY, f64::min)
+            }
+        }
+    };
+    Ok(distance)
+}
+
+/// The flattened, closed rings of an area object, nothing for other objects.
+fn area_rings(object: &MapObject) -> Result<Vec<LineString>> {
+    let MapObject::Area(area) = object else {
+        return Ok(Vec::new());
+    };
+    let polygon = area
+        .flatten(NonNegativeF64::try_from(ALLOWED_ERROR)?)?
+        .into_polygon();
+    let (exterior, interiors) = polygon.into_inner();
+    Ok(std::iter::once(exterior).chain(interiors).collect())
+}
+
+/// Whether `point` is inside closed rings by the even-odd rule.
+fn even_odd_contains(rings: &[LineString], point: Coord) -> bool {
+    let mut inside = false;
+    for ring in rings {
+        for edge in ring.0.windows(2) {
+            let (a, b) = (edge[0], edge[1]);
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// The distance from `point` to the nearest segment of a line.
+fn line_distance(line: &LineString, point: Coord) -> f64 {
+    line.0
+        .windows(2)
+        .map(|edge| {
+            let (a, b) = (edge[0], edge[1]);
+            let direction = b - a;
+            let length_squared = direction.x * direction.x + direction.y * direction.y;
+            let t = if length_squared > 0. {
+                (((point.x - a.x) * direction.x + (point.y - a.y) * direction.y) / length_squared)
+                    .clamp(0., 1.)
+            } else {
+                0.
+            };
+            distance(a + direction * t, point)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn distance(a: Coord, b: Coord) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Weak;
+
+    use geo_types::{Coord, LineString, Point, Polygon, Rect, coord};
+
+    use crate::{
+        Result,
+        objects::{
+            AreaObject, BezierPath, BezierSegment, BezierString, LineObject, MapObject, PointObject,
+        },
+        parts::MapPart,
+        symbols::{WeakAreaPathSymbol, WeakLinePathSymbol},
+    };
+
+    /// A part with a grid of points, a curved line and a square area with a hole.
+    fn test_part() -> Result<MapPart> {
+        let mut part = MapPart::new("Map");
+        for x in 0..30 {
+            for y in 0..30 {
+                part.add_object(PointObject::new(
+                    Weak::new(),
+                    Point::new(f64::from(x) * 10., f64::from(y) * 10.),
+                ));
+            }
+        }
+        // bulges up to y = 507.5 between its ends at y = 500
+        let curve = BezierPath::new(
+            BezierString::new(vec![BezierSegment::new(
+                coord! { x: 0., y: 500. },
+                Some((coord! { x: 0., y: 510. }, coord! { x: 30., y: 510. })),
+                coord! { x: 30., y: 500. },
+            )]),
+            vec![false, false],
+        )?;
+        part.add_object(LineObject::new(
+            WeakLinePathSymbol::Line(Weak::new()),
+            curve,
+        ));
+        part.add_object(AreaObject::new(
+            WeakAreaPathSymbol::Area(Weak::new()),
+            Polygon::new(
+                LineString::from(vec![(1000., 0.), (1100., 0.), (1100., 100.), (1000., 100.)]),
+                vec![LineString::from(vec![
+                    (1040., 40.),
+                    (1060., 40.),
+                    (1060., 60.),
+                    (1040., 60.),
+                ])],
+            ),
+        ));
+        Ok(part)
+    }
+
+    #[test]
+    fn finds_objects_in_envelope() -> Result<()> {
+        let part = test_part()?;
+        let index = part.spatial_index();
+        assert_eq!(index.len(), part.len());
+
+        let envelope = Rect::new(coord! { x: 15., y: 15. }, coord! { x: 45., y: 35. });
+        let found = index.intersecting(envelope);
+        let expected = part
+            .iter_all_objects()
+            .enumerate()
+            .filter(|(_, object)| {
+                object.bounding_rect().is_some_and(|bounds| {
+                    bounds.min().x <= 45.
+                        && bounds.max().x >= 15.
+                        && bounds.min().y <= 35.
+                        && bounds.max().y >= 15.
+                })
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), 6);
+        assert_eq!(
+            found
+                .iter()
+                .map(|object| object.object_index)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn finds_nearest_object_and_containing_areas() -> Result<()> {
+        let part = test_part()?;
+        let index = part.spatial_index();
+
+        let Some((nearest, distance)) = index.nearest(coord! { x: 123., y: 204. })? else {
+            panic!("the index has objects");
+        };
+        let MapObject::Point(point) = nearest.object else {
+            panic!("the nearest object is a grid point");
+        };
+        assert_eq!(point.geometry().0, coord! { x: 120., y: 200. });
+        assert!((distance - 5.).abs() < 1e-9);
+
+        let Some((nearest, distance)) = index.nearest(coord! { x: 1050., y: 50. })? else {
+            panic!("the index has objects");
+        };
+        assert!(matches!(nearest.object, MapObject::Area(_)));
+        assert!((distance - 10.).abs() < 1e-9, "the hole edge is 10 mm away");
+
+        assert_eq!(
+            index.areas_containing(coord! { x: 1010., y: 50. })?.len(),
+            1
+        );
+        assert!(
+            index
+                .areas_containing(coord! { x: 1050., y: 50. })?
+                .is_empty()
+        );
+        assert!(
+            index
+                .areas_containing(Coord { x: 1200., y: 50. })?
+                .is_empty()
+        );
+        Ok(())
+    }
+}
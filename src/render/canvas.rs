@@ -0,0 +1,413 @@
+use std::rc::Rc;
+
+use geo_types::Coord;
+
+use super::{RgbaImage, draw::Surface};
+use crate::objects::BezierPath;
+
+/// Sample rows per pixel row when computing coverage.
+const SUBSAMPLES: usize = 5;
+
+/// How overlapping rings of a shape combine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FillRule {
+    /// Inside where the rings wind around a pixel, used for stroke outlines.
+    NonZero,
+    /// Inside where an odd number of rings cover a pixel, used for areas with holes.
+    EvenOdd,
+}
+
+/// Rings filled with one rule.
+#[derive(Debug, Clone)]
+pub(super) struct Shape {
+    pub(super) rings: Vec<Vec<Coord>>,
+    /// The exact rings the flattened `rings` come from, for surfaces that draw
+    /// curves themselves. Empty for shapes that only exist flattened, such as
+    /// stroke outlines.
+    pub(super) curves: Vec<BezierPath>,
+    pub(super) rule: FillRule,
+}
+
+/// A shape drawn in one color, optionally limited to the inside of another shape.
+#[derive(Debug)]
+struct Item {
+    layer: Layer,
+    shape: Shape,
+    clip: Option<Rc<Shape>>,
+}
+
+/// The position of a color in the drawing order.
+///
+/// Colors are drawn from the bottom of the color set to the top, with
+/// registration black above all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Layer {
+    /// A color of the color set by its priority, drawn in reverse priority order.
+    Color(std::cmp::Reverse<usize>),
+    RegistrationBlack,
+}
+
+/// Collects shapes per color and composites them onto an image.
+#[derive(Debug)]
+pub(super) struct Canvas {
+    width: usize,
+    height: usize,
+    /// The map coordinates of the top left image corner.
+    top_left: Coord,
+    pixels_per_mm: f64,
+    items: Vec<Item>,
+    /// The last clip shape in map coordinates and in pixels.
+    clip: Option<(Rc<Shape>, Rc<Shape>)>,
+}
+
+impl Canvas {
+    pub(super) fn new(
+        (width, height): (usize, usize),
+        top_left: Coord,
+        pixels_per_mm: f64,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            top_left,
+            pixels_per_mm,
+            items: Vec::new(),
+            clip: None,
+        }
+    }
+
+    fn to_pixels(&self, shape: &Shape) -> Shape {
+        let rings = shape
+            .rings
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .map(|point| Coord {
+                        x: (point.x - self.top_left.x) * self.pixels_per_mm,
+                        y: (self.top_left.y - point.y) * self.pixels_per_mm,
+                    })
+                    .collect()
+            })
+            .collect();
+        Shape {
+            rings,
+            curves: Vec::new(),
+            rule: shape.rule,
+        }
+    }
+
+    /// The clip shape in pixels, converting it only when it changes.
+    fn pixel_clip(&mut self, clip: &Rc<Shape>) -> Rc<Shape> {
+        match &self.clip {
+            Some((map, pixels)) if Rc::ptr_eq(map, clip) => Rc::clone(pixels),
+            _ => {
+                let pixels = Rc::new(self.to_pixels(clip));
+                self.clip = Some((Rc::clone(clip), Rc::clone(&pixels)));
+                pixels
+            }
+        }
+    }
+
+    /// Draw every layer onto `image`, looking up layer colors in `color_of`.
+    pub(super) fn composite(mut self, image: &mut RgbaImage, color_of: impl Fn(Layer) -> [f32; 3]) {
+        self.items.sort_by_key(|item| item.layer);
+        let mut coverage = Coverage::new(self.width, self.height);
+        let mut start = 0;
+        while start < self.items.len() {
+            let layer = self.items[start].layer;
+            let end = start
+                + self.items[start..]
+                    .iter()
+                    .take_while(|item| item.layer == layer)
+                    .count();
+            for item in &self.items[start..end] {
+                coverage.fill(&item.shape, item.clip.as_deref());
+            }
+            coverage.blend_into(image, color_of(layer));
+            coverage.clear();
+            start = end;
+        }
+    }
+}
+
+impl Surface for Canvas {
+    fn fill(&mut self, layer: Layer, shape: Shape, clip: Option<&Rc<Shape>>) {
+        if shape.rings.iter().all(|ring| ring.len() < 3) {
+            return;
+        }
+        let shape = self.to_pixels(&shape);
+        let clip = clip.map(|clip| self.pixel_clip(clip));
+        // consecutive outlines of one color merge into a single scan
+        if let Some(last) = self.items.last_mut()
+            && last.layer == layer
+            && last.shape.rule == FillRule::NonZero
+            && shape.rule == FillRule::NonZero
+            && match (&last.clip, &clip) {
+                (None, None) => true,
+                (Some(last), Some(clip)) => Rc::ptr_eq(last, clip),
+                _ => false,
+            }
+        {
+            last.shape.rings.extend(shape.rings);
+            return;
+        }
+        self.items.push(Item { layer, shape, clip });
+    }
+}
+
+/// Pixel coverage of one color, combining shapes by their maximum.
+#[derive(Debug)]
+struct Coverage {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+    /// The rows and columns touched since the last clear.
+    dirty: Option<Bounds>,
+    row: Vec<f32>,
+    clip_row: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+}
+
+impl Bounds {
+    fn union(self, other: Self) -> Self {
+        Self {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    fn intersection(self, other: Self) -> Option<Self> {
+        let bounds = Self {
+            x0: self.x0.max(other.x0),
+            y0: self.y0.max(other.y0),
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+        };
+        (bounds.x0 < bounds.x1 && bounds.y0 < bounds.y1).then_some(bounds)
+    }
+}
+
+/// A non-horizontal ring edge, stored top to bottom.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    top: Coord,
+    bottom: Coord,
+    winding: i32,
+}
+
+/// The edges of a shape sorted by their top, scanned row by row.
+struct Scan {
+    edges: Vec<Edge>,
+    rule: FillRule,
+    next_edge: usize,
+    active: Vec<Edge>,
+    crossings: Vec<(f64, i32)>,
+}
+
+impl Scan {
+    fn new(shape: &Shape) -> (Self, Option<(Coord, Coord)>) {
+        let mut edges = Vec::new();
+        let mut min = Coord {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+        };
+        let mut max = Coord {
+            x: f64::NEG_INFINITY,
+            y: f64::NEG_INFINITY,
+        };
+        for ring in &shape.rings {
+            if ring.len() < 3 {
+                continue;
+            }
+            for (&a, &b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                min = Coord {
+                    x: min.x.min(a.x),
+                    y: min.y.min(a.y),
+                };
+                max = Coord {
+                    x: max.x.max(a.x),
+                    y: max.y.max(a.y),
+                };
+                if a.y < b.y {
+                    edges.push(Edge {
+                        top: a,
+                        bottom: b,
+                        winding: 1,
+                    });
+                } else if a.y > b.y {
+                    edges.push(Edge {
+                        top: b,
+                        bottom: a,
+                        winding: -1,
+                    });
+                }
+            }
+        }
+        edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+        let extent =
+            (min.x <= max.x && min.x.is_finite() && max.x.is_finite()).then_some((min, max));
+        let scan = Self {
+            edges,
+            rule: shape.rule,
+            next_edge: 0,
+            active: Vec::new(),
+            crossings: Vec::new(),
+        };
+        (scan, extent)
+    }
+
+    /// Add the coverage of pixel row `y` for the columns from `x0` to `x0 + row.len()`.
+    fn row(&mut self, y: usize, x0: usize, row: &mut [f32]) {
+        row.fill(0.);
+        let weight = 1. / SUBSAMPLES as f32;
+        for sample in 0..SUBSAMPLES {
+            let sample_y = y as f64 + (sample as f64 + 0.5) / SUBSAMPLES as f64;
+            while self.next_edge < self.edges.len() && self.edges[self.next_edge].top.y <= sample_y
+            {
+                self.active.push(self.edges[self.next_edge]);
+                self.next_edge += 1;
+            }
+            self.active.retain(|edge| edge.bottom.y > sample_y);
+            self.crossings.clear();
+            self.crossings.extend(
+                self.active
+                    .iter()
+                    .filter(|edge| edge.top.y <= sample_y)
+                    .map(|edge| {
+                        let t = (sample_y - edge.top.y) / (edge.bottom.y - edge.top.y);
+                        (edge.top.x + (edge.bottom.x - edge.top.x) * t, edge.winding)
+                    }),
+            );
+            self.crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut winding = 0;
+            for pair in self.crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match self.rule {
+                    FillRule::NonZero => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if inside {
+                    add_span(row, x0, pair[0].0, pair[1].0, weight);
+                }
+            }
+        }
+    }
+}
+
+/// Add `weight` times the covered fraction of every pixel between `from` and `to`.
+fn add_span(row: &mut [f32], x0: usize, from: f64, to: f64, weight: f32) {
+    let from = (from - x0 as f64).max(0.);
+    let to = (to - x0 as f64).min(row.len() as f64);
+    if to <= from {
+        return;
+    }
+    let first = from.floor() as usize;
+    let last = to.floor() as usize;
+    if first == last {
+        row[first] += (to - from) as f32 * weight;
+        return;
+    }
+    row[first] += (first as f64 + 1. - from) as f32 * weight;
+    for value in &mut row[first + 1..last] {
+        *value += weight;
+    }
+    if let Some(value) = row.get_mut(last) {
+        *value += (to - last as f64) as f32 * weight;
+    }
+}
+
+impl Coverage {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![0.; width * height],
+            dirty: None,
+            row: Vec::new(),
+            clip_row: Vec::new(),
+        }
+    }
+
+    fn bounds(&self, extent: (Coord, Coord)) -> Option<Bounds> {
+        let (min, max) = extent;
+        let image = Bounds {
+            x0: 0,
+            y0: 0,
+            x1: self.width,
+            y1: self.height,
+        };
+        let shape = Bounds {
+            x0: min.x.floor().max(0.) as usize,
+            y0: min.y.floor().max(0.) as usize,
+            x1: max.x.ceil().max(0.) as usize,
+            y1: max.y.ceil().max(0.) as usize,
+        };
+        image.intersection(shape)
+    }
+
+    fn fill(&mut self, shape: &Shape, clip: Option<&Shape>) {
+        let (mut scan, Some(extent)) = Scan::new(shape) else {
+            return;
+        };
+        let mut clip_scan = None;
+        let mut bounds = self.bounds(extent);
+        if let Some(clip) = clip {
+            let (scan, clip_extent) = Scan::new(clip);
+            bounds = bounds
+                .zip(clip_extent.and_then(|extent| self.bounds(extent)))
+                .and_then(|(bounds, clip_bounds)| bounds.intersection(clip_bounds));
+            clip_scan = Some(scan);
+        }
+        let Some(bounds) = bounds else {
+            return;
+        };
+        let width = bounds.x1 - bounds.x0;
+        self.row.resize(width, 0.);
+        self.clip_row.resize(width, 0.);
+        for y in bounds.y0..bounds.y1 {
+            scan.row(y, bounds.x0, &mut self.row);
+            if let Some(clip_scan) = &mut clip_scan {
+                clip_scan.row(y, bounds.x0, &mut self.clip_row);
+                for (value, clip) in self.row.iter_mut().zip(&self.clip_row) {
+                    *value *= clip.min(1.);
+                }
+            }
+            let start = y * self.width + bounds.x0;
+            for (value, new) in self.values[start..start + width].iter_mut().zip(&self.row) {
+                *value = value.max(new.min(1.));
+            }
+        }
+        self.dirty = Some(self.dirty.map_or(bounds, |dirty| dirty.union(bounds)));
+    }
+
+    fn blend_into(&self, image: &mut RgbaImage, color: [f32; 3]) {
+        let Some(bounds) = self.dirty else {
+            return;
+        };
+        for y in bounds.y0..bounds.y1 {
+            for x in bounds.x0..bounds.x1 {
+                let alpha = self.values[y * self.width + x];
+                if alpha > 0. {
+                    image.blend(x, y, color, alpha);
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        if let Some(bounds) = self.dirty.take() {
+            for y in bounds.y0..bounds.y1 {
+                self.values[y * self.width + bounds.x0..y * self.width + bounds.x1].fill(0.);
+            }
+        }
+    }
+}
@@ -0,0 +1,1439 @@
+use std::{borrow::Cow, cmp::Reverse, rc::Rc};
+
+use geo_types::{Coord, Rect};
+use linestring2bezier::BezierString;
+
+use super::{
+    canvas::{FillRule, Layer, Shape},
+    font,
+    path::{Outlines, Polyline, circle, dash_intervals, distance},
+};
+use crate::{
+    NonNegativeF64, Result,
+    colors::{ColorSet, SymbolColor},
+    objects::{
+        BezierPath, BezierPolygon, HorizontalAlign, MapObject, PatternRotation, TextGeometry,
+        TextObject, VerticalAlign,
+    },
+    symbols::{
+        AreaOrLineSymbol, AreaSymbol, BorderStyle, CapStyle, ClippingOption, DashStyle, Element,
+        FillPattern, FramingMode, GroupDashes, JoinStyle, LineSymbol, MidSymbolPlacement,
+        PointSymbol, PublicOrPrivateSymbol, Symbol, SymbolCommon, TextSymbol,
+    },
+};
+
+/// How far outside the viewport, in mm, an object may lie and still be drawn.
+///
+/// Symbols reach beyond the geometry of their objects, so objects are only
+/// skipped when their geometry is further away than any ordinary symbol extent.
+pub(super) const CULL_MARGIN: f64 = 20.;
+/// The largest number of hatch lines or pattern points drawn for one area.
+const MAX_PATTERN_ELEMENTS: f64 = 1_000_000.;
+/// The height of capital letters relative to the font size.
+const CAP_HEIGHT: f64 = 0.716;
+/// The width of glyphs relative to their height on the glyph grid.
+const GLYPH_ASPECT: f64 = 0.8;
+/// The height of the text above the baseline relative to the font size.
+const ASCENT: f64 = 0.905;
+/// The depth of the text below the baseline relative to the font size.
+const DESCENT: f64 = 0.212;
+/// The width of glyph strokes relative to the font size.
+const STROKE_WIDTH: f64 = 0.075;
+/// The width of bold glyph strokes relative to the font size.
+const BOLD_STROKE_WIDTH: f64 = 0.12;
+/// The slant of italic glyphs.
+const ITALIC_SHEAR: f64 = 0.2;
+
+/// A rotation followed by a translation, placing a symbol on the map.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Placement {
+    pub(super) origin: Coord,
+    pub(super) rotation: f64,
+}
+
+impl Placement {
+    pub(super) const IDENTITY: Self = Self {
+        origin: Coord { x: 0., y: 0. },
+        rotation: 0.,
+    };
+
+    pub(super) fn new(origin: Coord, rotation: f64) -> Self {
+        Self { origin, rotation }
+    }
+
+    pub(super) fn apply(&self, point: Coord) -> Coord {
+        let (sin, cos) = self.rotation.sin_cos();
+        self.origin
+            + Coord {
+                x: cos * point.x - sin * point.y,
+                y: sin * point.x + cos * point.y,
+            }
+    }
+
+    /// The placement of something placed by `inner` within this placement.
+    pub(super) fn then(self, inner: Self) -> Self {
+        Self {
+            origin: self.apply(inner.origin),
+            rotation: self.rotation + inner.rotation,
+        }
+    }
+}
+
+/// A flattened path with the dash-point flag of every vertex.
+#[derive(Debug, Clone)]
+pub(super) struct Path {
+    pub(super) line: Polyline,
+    pub(super) dash_points: Vec<bool>,
+    /// The exact path in map coordinates that `line` was flattened from, `None`
+    /// for parts of a path.
+    pub(super) curve: Option<BezierPath>,
+}
+
+impl Path {
+    /// The part between two distances along the path.
+    pub(super) fn slice(&self, from: f64, to: f64) -> Self {
+        let inner = (0..self.line.points.len())
+            .filter(|&index| self.line.length_at(index) > from && self.line.length_at(index) < to);
+        let mut dash_points = vec![false];
+        dash_points.extend(inner.clone().map(|index| self.dash_points[index]));
+        dash_points.push(false);
+        let (start, _) = self.line.sample(from);
+        let (end, _) = self.line.sample(to);
+        let points = std::iter::once(start)
+            .chain(inner.map(|index| self.line.points[index]))
+            .chain(std::iter::once(end))
+            .collect::<Vec<_>>();
+        let line = Polyline::new(points, false);
+        dash_points.truncate(line.points.len());
+        Self {
+            line,
+            dash_points,
+            curve: None,
+        }
+    }
+
+    /// The parts between the dash points inside the path.
+    pub(super) fn sections(&self) -> Vec<Polyline> {
+        let last = self.line.points.len().saturating_sub(1);
+        let mut bounds = vec![0.];
+        bounds.extend(
+            (1..last)
+                .filter(|&index| self.dash_points[index])
+                .map(|index| self.line.length_at(index)),
+        );
+        if bounds.len() == 1 {
+            return vec![self.line.clone()];
+        }
+        bounds.push(self.line.length());
+        bounds
+            .windows(2)
+            .map(|pair| self.line.slice(pair[0], pair[1]))
+            .collect()
+    }
+}
+
+/// The dashes of a dashed line and the groups they form.
+#[derive(Debug)]
+pub(super) struct DashLayout {
+    pub(super) groups: Vec<(f64, f64)>,
+    pub(super) dashes: Vec<(f64, f64)>,
+}
+
+impl DashLayout {
+    pub(super) fn new(style: &DashStyle, length: f64) -> Option<Self> {
+        let DashStyle::Dashed {
+            dash_length,
+            break_length,
+            dash_group,
+        } = style
+        else {
+            return None;
+        };
+        let (dash, gap) = (dash_length.get(), break_length.get());
+        Some(match *dash_group {
+            GroupDashes::UnGrouped { half_outer_dashes } => {
+                let dashes = dash_intervals(length, dash, gap, half_outer_dashes);
+                Self {
+                    groups: dashes.clone(),
+                    dashes,
+                }
+            }
+            GroupDashes::Grouped {
+                dashes_in_group,
+                in_group_break_length,
+            } => {
+                let count = f64::from(dashes_in_group.max(1));
+                let inner_gap = in_group_break_length.get();
+                let group = count * dash + (count - 1.) * inner_gap;
+                let groups = dash_intervals(length, group, gap, false);
+                let dashes = groups
+                    .iter()
+                    .flat_map(|&(start, end)| {
+                        let scale = (end - start) / group;
+                        (0..dashes_in_group.max(1)).map(move |index| {
+                            let from = start + f64::from(index) * (dash + inner_gap) * scale;
+                            (from, from + dash * scale)
+                        })
+                    })
+                    .collect();
+                Self { groups, dashes }
+            }
+        })
+    }
+}
+
+/// A line or area symbol drawn along the paths of an object.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum PathSymbol<'s> {
+    Line(&'s LineSymbol),
+    Area(&'s AreaSymbol),
+}
+
+/// Whether objects with a symbol are drawn.
+pub(super) fn is_visible(common: &SymbolCommon, show_helper_symbols: bool) -> bool {
+    !common.is_hidden && (show_helper_symbols || !common.is_helper_symbol)
+}
+
+/// Call `visit` for every visible line and area symbol that makes up a path symbol,
+/// in the order of the components of combined symbols.
+pub(super) fn for_each_path_symbol(
+    symbol: &Symbol,
+    show_helper_symbols: bool,
+    visit: &mut dyn FnMut(PathSymbol<'_>) -> Result<()>,
+) -> Result<()> {
+    if !is_visible(&*symbol.common()?, show_helper_symbols) {
+        return Ok(());
+    }
+    match symbol {
+        Symbol::Line(line) => visit(PathSymbol::Line(&*line.try_borrow()?)),
+        Symbol::Area(area) => visit(PathSymbol::Area(&*area.try_borrow()?)),
+        Symbol::CombinedLine(combined) => {
+            for component in combined.try_borrow()?.components() {
+                match component {
+                    PublicOrPrivateSymbol::Public(weak) => {
+                        if let Some(symbol) = weak.upgrade() {
+                            for_each_path_symbol(&symbol, show_helper_symbols, visit)?;
+                        }
+                    }
+                    PublicOrPrivateSymbol::Private(line) => visit(PathSymbol::Line(line))?,
+                }
+            }
+            Ok(())
+        }
+        Symbol::CombinedArea(combined) => {
+            for component in combined.try_borrow()?.components() {
+                match component {
+                    PublicOrPrivateSymbol::Public(weak) => {
+                        if let Some(symbol) = weak.upgrade() {
+                            for_each_path_symbol(&symbol, show_helper_symbols, visit)?;
+                        }
+                    }
+                    PublicOrPrivateSymbol::Private(AreaOrLineSymbol::Line(line)) => {
+                        visit(PathSymbol::Line(line))?;
+                    }
+                    PublicOrPrivateSymbol::Private(AreaOrLineSymbol::Area(area)) => {
+                        visit(PathSymbol::Area(area))?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        Symbol::Point(_) | Symbol::Text(_) => Ok(()),
+    }
+}
+
+/// Flatten a path placed by `placement`, merging the dash-point flags of repeated points.
+pub(super) fn flatten(
+    path: &BezierPath,
+    placement: Placement,
+    tolerance: f64,
+) -> Result<Option<Path>> {
+    if path.is_empty() {
+        return Ok(None);
+    }
+    let flattened = path.flatten(NonNegativeF64::clamped_from(tolerance))?;
+    let mut points: Vec<Coord> = Vec::with_capacity(flattened.num_vertices());
+    let mut dash_points: Vec<bool> = Vec::with_capacity(flattened.num_vertices());
+    for (point, &is_dash_point) in flattened
+        .geometry()
+        .0
+        .iter()
+        .zip(flattened.vertex_is_dash_point())
+    {
+        let point = placement.apply(*point);
+        if points.last() == Some(&point) {
+            if let Some(last) = dash_points.last_mut() {
+                *last |= is_dash_point;
+            }
+        } else {
+            points.push(point);
+            dash_points.push(is_dash_point);
+        }
+    }
+    Ok(Some(Path {
+        line: Polyline::new(points, path.is_closed()),
+        dash_points,
+        curve: Some(path.clone().transform(|point| placement.apply(point))),
+    }))
+}
+
+/// The rings of an area as an even-odd shape, with their curves if every ring has them.
+fn area_shape(rings: &[Path]) -> Shape {
+    Shape {
+        rings: rings.iter().map(|ring| ring.line.points.clone()).collect(),
+        curves: rings
+            .iter()
+            .map(|ring| ring.curve.clone())
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default(),
+        rule: FillRule::EvenOdd,
+    }
+}
+
+/// The parts of a path between its interior dash points.
+pub(super) fn dash_sections(path: &BezierPath) -> Vec<BezierPath> {
+    let mut sections = Vec::new();
+    let mut segments = Vec::new();
+    for (segment, is_dash_point) in path.segments() {
+        if is_dash_point && !segments.is_empty() {
+            sections.push(std::mem::take(&mut segments));
+        }
+        segments.push(segment.clone());
+    }
+    sections.push(segments);
+    if sections.len() == 1 {
+        return vec![path.clone()];
+    }
+    sections
+        .into_iter()
+        .filter_map(|segments| {
+            let dash_points = vec![false; segments.len() + 1];
+            BezierPath::new(BezierString::new(segments), dash_points).ok()
+        })
+        .collect()
+}
+
+/// The part of a path covered by a line symbol, `None` when the start and end
+/// offsets leave nothing.
+///
+/// The offsets of lines with pointed caps are the lengths of the points and do
+/// not shorten the line.
+pub(super) fn line_extent<'p>(symbol: &LineSymbol, path: &'p Path) -> Option<Cow<'p, Path>> {
+    let length = path.line.length();
+    let (start_offset, end_offset) = (symbol.start_offset.get(), symbol.end_offset.get());
+    if matches!(symbol.cap_style, CapStyle::Pointed)
+        || path.line.closed
+        || start_offset + end_offset <= 0.
+    {
+        return Some(Cow::Borrowed(path));
+    }
+    (start_offset + end_offset < length)
+        .then(|| Cow::Owned(path.slice(start_offset, length - end_offset)))
+}
+
+/// The start, end, mid and dash symbols along a line, with their positions and
+/// the direction of the line there.
+pub(super) fn line_point_symbols<'s>(
+    symbol: &'s LineSymbol,
+    path: &Path,
+) -> Vec<(&'s PointSymbol, Coord, f64)> {
+    let mut placed = mid_symbols(symbol, path);
+    let line = &path.line;
+    let last = line.points.len().saturating_sub(1);
+    if let Some(dash) = &symbol.dash_symbol {
+        for (index, &is_dash_point) in path.dash_points.iter().enumerate() {
+            let is_end = index == 0 || index == last;
+            if !is_dash_point
+                || (line.closed && index == last)
+                || (is_end && !line.closed && dash.suppress_dash_symbol_at_ends)
+            {
+                continue;
+            }
+            placed.push((&dash.dash_symbol, line.points[index], line.angle_at(index)));
+        }
+    }
+    if !line.closed
+        && let (Some(&first), Some(&end)) = (line.points.first(), line.points.last())
+    {
+        if let Some(start_symbol) = &symbol.start_symbol {
+            placed.push((start_symbol, first, line.angle_at(0)));
+        }
+        if let Some(end_symbol) = &symbol.end_symbol {
+            placed.push((end_symbol, end, line.angle_at(last)));
+        }
+    }
+    placed
+}
+
+fn mid_symbols<'s>(symbol: &'s LineSymbol, path: &Path) -> Vec<(&'s PointSymbol, Coord, f64)> {
+    let Some(mid) = &symbol.mid_symbol else {
+        return Vec::new();
+    };
+    let count = mid.mid_symbols_per_spot;
+    let spacing = mid.mid_symbol_distance.get();
+    let mut placed = Vec::new();
+    for section in path.sections() {
+        let length = section.length();
+        let positions = match &symbol.dash_style {
+            DashStyle::Dashed { .. } => {
+                let Some(layout) = DashLayout::new(&symbol.dash_style, length) else {
+                    continue;
+                };
+                let center = |&(from, to): &(f64, f64)| f64::midpoint(from, to);
+                match mid.mid_symbol_placement {
+                    MidSymbolPlacement::CenterOfDash => layout.dashes.iter().map(center).collect(),
+                    MidSymbolPlacement::CenterOfDashGroup => {
+                        layout.groups.iter().map(center).collect()
+                    }
+                    MidSymbolPlacement::CenterOfGap => layout
+                        .groups
+                        .windows(2)
+                        .map(|pair| f64::midpoint(pair[0].1, pair[1].0))
+                        .collect(),
+                }
+            }
+            DashStyle::NotDashed {
+                segment_length,
+                end_length,
+            } => {
+                let minimum = if section.closed {
+                    mid.minimum_mid_symbol_count_when_closed
+                } else {
+                    mid.minimum_mid_symbol_count
+                };
+                spaced_positions(
+                    length,
+                    segment_length.get(),
+                    end_length.get(),
+                    minimum,
+                    mid.show_at_least_one_mid_symbol,
+                    section.closed,
+                )
+            }
+        };
+        for position in positions {
+            let (point, angle) = section.sample(position);
+            let direction = Coord {
+                x: angle.cos(),
+                y: angle.sin(),
+            };
+            for index in 0..count {
+                let shift = (f64::from(index) - f64::from(count - 1) / 2.) * spacing;
+                placed.push((&mid.mid_symbol, point + direction * shift, angle));
+            }
+        }
+    }
+    placed
+}
+
+/// Receives the colored shapes of the objects drawn by a [`Painter`].
+pub(super) trait Surface {
+    /// Fill a shape in map coordinates with the color of `layer`, only inside `clip`.
+    fn fill(&mut self, layer: Layer, shape: Shape, clip: Option<&Rc<Shape>>);
+
+    /// Whether text is set with [`Self::text`] instead of being drawn as glyph strokes.
+    fn has_fonts(&self) -> bool {
+        false
+    }
+
+    /// Set a text in a font of the surface, including its underline.
+    fn text(&mut self, _layer: Layer, _run: &TextRun<'_>) {}
+
+    /// Whether lines are stroked along their curves with [`Self::stroke`]
+    /// instead of being filled as flattened outlines.
+    fn has_strokes(&self) -> bool {
+        false
+    }
+
+    /// Stroke a path in map coordinates with the color of `layer`, only inside `clip`.
+    fn stroke(&mut self, _layer: Layer, _stroke: &Stroke<'_>, _clip: Option<&Rc<Shape>>) {}
+}
+
+/// A line drawn along a path, for surfaces with strokes.
+#[derive(Debug)]
+pub(super) struct Stroke<'p> {
+    pub(super) path: &'p BezierPath,
+    pub(super) width: f64,
+    pub(super) cap: CapStyle,
+    pub(super) join: JoinStyle,
+    /// The intervals along the path that are drawn and the length they are
+    /// measured against, `None` for a solid line.
+    pub(super) dashes: Option<(Vec<(f64, f64)>, f64)>,
+}
+
+/// The lines of a text object as placed on the map, for surfaces with fonts.
+#[derive(Debug)]
+pub(super) struct TextRun<'t> {
+    pub(super) symbol: &'t TextSymbol,
+    pub(super) layout: &'t TextLayout,
+    pub(super) placement: Placement,
+    pub(super) align: HorizontalAlign,
+    /// The width of the outline stroked around the glyphs, or `None` to fill them.
+    pub(super) outline: Option<f64>,
+}
+
+/// Turns map objects into colored shapes on a [`Surface`].
+#[derive(Debug)]
+pub(super) struct Painter<'a, S> {
+    colors: &'a ColorSet,
+    surface: S,
+    /// The largest deviation from the exact geometry in mm.
+    tolerance: f64,
+    /// The viewport grown by [`CULL_MARGIN`].
+    view: Rect,
+    show_helper_symbols: bool,
+    /// The shape that limits everything drawn, while drawing a clipped pattern.
+    clip: Option<Rc<Shape>>,
+}
+
+impl<'a, S: Surface> Painter<'a, S> {
+    /// A painter onto `surface` for objects in `viewport`, flattening curves to
+    /// within `tolerance` mm.
+    pub(super) fn new(
+        colors: &'a ColorSet,
+        surface: S,
+        viewport: Rect,
+        tolerance: f64,
+        show_helper_symbols: bool,
+    ) -> Self {
+        let margin = Coord {
+            x: CULL_MARGIN,
+            y: CULL_MARGIN,
+        };
+        Self {
+            colors,
+            surface,
+            tolerance,
+            view: Rect::new(viewport.min() - margin, viewport.max() + margin),
+            show_helper_symbols,
+            clip: None,
+        }
+    }
+
+    pub(super) fn into_surface(self) -> S {
+        self.surface
+    }
+
+    /// Draw an object with its symbol, skipping hidden symbols.
+    pub(super) fn draw_object(&mut self, object: &MapObject) -> Result<()> {
+        match object {
+            MapObject::Point(point) => {
+                let Some(symbol) = point.symbol.upgrade() else {
+                    return Ok(());
+                };
+                let symbol = symbol.try_borrow()?;
+                let position = point.geometry().0;
+                if is_visible(&symbol.common, self.show_helper_symbols)
+                    && self.in_view(Rect::new(position, position))
+                {
+                    self.place_point_symbol(&symbol, position, point.rotation)?;
+                }
+            }
+            MapObject::Line(line) => {
+                if let Some(symbol) = line.symbol.upgrade()
+                    && path_bounds(line.geometry()).is_some_and(|bounds| self.in_view(bounds))
+                    && let Some(path) = self.flatten(line.geometry(), Placement::IDENTITY)?
+                {
+                    self.path_symbol(&symbol, std::slice::from_ref(&path), None)?;
+                }
+            }
+            MapObject::Area(area) => {
+                if let Some(symbol) = area.symbol.upgrade()
+                    && path_bounds(area.geometry().exterior())
+                        .is_some_and(|bounds| self.in_view(bounds))
+                {
+                    let rings = self.flatten_polygon(area.geometry(), Placement::IDENTITY)?;
+                    self.path_symbol(&symbol, &rings, Some(&area.pattern_rotation))?;
+                }
+            }
+            MapObject::Text(text) => {
+                if let Some(symbol) = text.symbol.upgrade() {
+                    let symbol = symbol.try_borrow()?;
+                    if is_visible(&symbol.common, self.show_helper_symbols) {
+                        self.text(&symbol, text);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn in_view(&self, bounds: Rect) -> bool {
+        bounds.min().x <= self.view.max().x
+            && bounds.max().x >= self.view.min().x
+            && bounds.min().y <= self.view.max().y
+            && bounds.max().y >= self.view.min().y
+    }
+
+    fn layer(&self, color: &SymbolColor) -> Option<Layer> {
+        match color {
+            SymbolColor::Color(_) => usize::try_from(color.priority(self.colors))
+                .ok()
+                .map(|priority| Layer::Color(Reverse(priority))),
+            SymbolColor::RegistrationBlack => Some(Layer::RegistrationBlack),
+            SymbolColor::NoColor => None,
+        }
+    }
+
+    /// Fill rings given in map coordinates with a symbol color.
+    fn fill(&mut self, color: &SymbolColor, rings: Vec<Vec<Coord>>, rule: FillRule) {
+        self.fill_shape(
+            color,
+            Shape {
+                rings,
+                curves: Vec::new(),
+                rule,
+            },
+        );
+    }
+
+    /// Fill a shape given in map coordinates with a symbol color.
+    fn fill_shape(&mut self, color: &SymbolColor, shape: Shape) {
+        let Some(layer) = self.layer(color) else {
+            return;
+        };
+        self.surface.fill(layer, shape, self.clip.as_ref());
+    }
+
+    fn outlines(&self) -> Outlines {
+        Outlines::new(self.tolerance)
+    }
+
+    fn flatten(&self, path: &BezierPath, placement: Placement) -> Result<Option<Path>> {
+        flatten(path, placement, self.tolerance)
+    }
+
+    fn flatten_polygon(&self, polygon: &BezierPolygon, placement: Placement) -> Result<Vec<Path>> {
+        let mut rings = Vec::with_capacity(polygon.interiors().len() + 1);
+        for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+            if let Some(mut ring) = self.flatten(ring, placement)? {
+                ring.line.closed = true;
+                rings.push(ring);
+            }
+        }
+        Ok(rings)
+    }
+
+    /// Draw a line, area or combined symbol along the paths of an object.
+    fn path_symbol(
+        &mut self,
+        symbol: &Symbol,
+        paths: &[Path],
+        pattern_rotation: Option<&PatternRotation>,
+    ) -> Result<()> {
+        let default = PatternRotation::default();
+        let pattern_rotation = pattern_rotation.unwrap_or(&default);
+        for_each_path_symbol(
+            symbol,
+            self.show_helper_symbols,
+            &mut |component| match component {
+                PathSymbol::Line(line) => {
+                    for path in paths {
+                        self.line_symbol(line, path)?;
+                    }
+                    Ok(())
+                }
+                PathSymbol::Area(area) => self.area_symbol(area, paths, pattern_rotation, 0.),
+            },
+        )
+    }
+
+    /// Draw a point symbol at `position`, turned by `rotation` if the symbol is rotatable.
+    fn place_point_symbol(
+        &mut self,
+        symbol: &PointSymbol,
+        position: Coord,
+        rotation: f64,
+    ) -> Result<()> {
+        let rotation = if symbol.is_rotatable { rotation } else { 0. };
+        self.point_symbol(symbol, Placement::new(position, rotation))
+    }
+
+    fn point_symbol(&mut self, symbol: &PointSymbol, placement: Placement) -> Result<()> {
+        let center = placement.origin;
+        let inner_radius = symbol.inner_radius.get();
+        let outer_radius = inner_radius + symbol.outer_width.get();
+        if inner_radius > 0. {
+            let disc = circle(center, inner_radius, self.tolerance);
+            self.fill(&symbol.inner_color, vec![disc], FillRule::NonZero);
+        }
+        if outer_radius > inner_radius {
+            let mut rings = vec![circle(center, outer_radius, self.tolerance)];
+            if inner_radius > 0. {
+                rings.push(circle(center, inner_radius, self.tolerance));
+            }
+            self.fill(&symbol.outer_color, rings, FillRule::EvenOdd);
+        }
+
+        for element in &symbol.elements {
+            match element {
+                Element::Point { symbol, object } => {
+                    let rotation = if symbol.is_rotatable {
+                        object.rotation
+                    } else {
+                        0.
+                    };
+                    let inner = Placement::new(object.geometry().0, rotation);
+                    self.point_symbol(symbol, placement.then(inner))?;
+                }
+                Element::Line { symbol, object } => {
+                    if let Some(path) = self.flatten(object.geometry(), placement)? {
+                        self.line_symbol(symbol, &path)?;
+                    }
+                }
+                Element::Area { symbol, object } => {
+                    let rings = self.flatten_polygon(object.geometry(), placement)?;
+                    let pattern_rotation = PatternRotation {
+                        rotation: object.pattern_rotation.rotation,
+                        coord: placement.apply(object.pattern_rotation.coord),
+                    };
+                    self.area_symbol(symbol, &rings, &pattern_rotation, placement.rotation)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn line_symbol(&mut self, symbol: &LineSymbol, path: &Path) -> Result<()> {
+        let Some(path) = line_extent(symbol, path) else {
+            return Ok(());
+        };
+        self.main_line(symbol, &path);
+        self.borders(symbol, &path);
+        for (point_symbol, position, angle) in line_point_symbols(symbol, &path) {
+            self.place_point_symbol(point_symbol, position, angle)?;
+        }
+        Ok(())
+    }
+
+    fn main_line(&mut self, symbol: &LineSymbol, path: &Path) {
+        let width = symbol.line_width.get();
+        if width <= 0. || self.layer(&symbol.color).is_none() || self.stroke(symbol, path) {
+            return;
+        }
+        let mut outlines = self.outlines();
+        let line = &path.line;
+        if matches!(symbol.dash_style, DashStyle::Dashed { .. }) {
+            let cap = match symbol.cap_style {
+                CapStyle::Pointed => CapStyle::Flat,
+                cap => cap,
+            };
+            for section in path.sections() {
+                let Some(layout) = DashLayout::new(&symbol.dash_style, section.length()) else {
+                    continue;
+                };
+                for (from, to) in layout.dashes {
+                    outlines.stroke(&section.slice(from, to), width, cap, symbol.join_style);
+                }
+            }
+        } else if matches!(symbol.cap_style, CapStyle::Pointed) && !line.closed {
+            let length = line.length();
+            let (mut start, mut end) = (symbol.start_offset.get(), symbol.end_offset.get());
+            if start + end > length {
+                let scale = length / (start + end);
+                start *= scale;
+                end *= scale;
+            }
+            if start > 0. {
+                outlines.stroke_tapered(&line.slice(0., start), 0., width);
+            }
+            if length - end > start {
+                let middle = line.slice(start, length - end);
+                outlines.stroke(&middle, width, CapStyle::Flat, symbol.join_style);
+            }
+            if end > 0. {
+                outlines.stroke_tapered(&line.slice(length - end, length), width, 0.);
+            }
+        } else {
+            outlines.stroke(line, width, symbol.cap_style, symbol.join_style);
+        }
+        self.fill(&symbol.color, outlines.rings, FillRule::NonZero);
+    }
+
+    /// Stroke the main line along the curves of `path` if the surface has strokes,
+    /// returning whether it did. Tapered ends are left to the outlines.
+    fn stroke(&mut self, symbol: &LineSymbol, path: &Path) -> bool {
+        let is_dashed = matches!(symbol.dash_style, DashStyle::Dashed { .. });
+        let is_tapered =
+            matches!(symbol.cap_style, CapStyle::Pointed) && !is_dashed && !path.line.closed;
+        let (Some(layer), Some(curve)) = (self.layer(&symbol.color), &path.curve) else {
+            return false;
+        };
+        if !self.surface.has_strokes() || is_tapered {
+            return false;
+        }
+        let cap = match symbol.cap_style {
+            CapStyle::Pointed => CapStyle::Flat,
+            cap => cap,
+        };
+        let mut stroke = |path: &BezierPath, dashes| {
+            let stroke = Stroke {
+                path,
+                width: symbol.line_width.get(),
+                cap,
+                join: symbol.join_style,
+                dashes,
+            };
+            self.surface.stroke(layer, &stroke, self.clip.as_ref());
+        };
+        if !is_dashed {
+            stroke(curve, None);
+            return true;
+        }
+        let sections = path.sections();
+        let curves = dash_sections(curve);
+        if curves.len() != sections.len() {
+            return false;
+        }
+        for (section, curve) in sections.iter().zip(&curves) {
+            let length = section.length();
+            if let Some(layout) = DashLayout::new(&symbol.dash_style, length)
+                && !layout.dashes.is_empty()
+            {
+                stroke(curve, Some((layout.dashes, length)));
+            }
+        }
+        true
+    }
+
+    fn borders(&mut self, symbol: &LineSymbol, path: &Path) {
+        let Some(border) = &symbol.border else {
+            return;
+        };
+        let (left, right) = match border {
+            BorderStyle::SymmetricBorder { both } => (both, both),
+            BorderStyle::AsymmetricBorder { left, right } => (left, right),
+        };
+        let cap = match symbol.cap_style {
+            CapStyle::Pointed => CapStyle::Flat,
+            cap => cap,
+        };
+        for (border, side) in [(left, 1.), (right, -1.)] {
+            let width = border.width.get();
+            if width <= 0. || self.layer(&border.color).is_none() {
+                continue;
+            }
+            let shift = symbol.line_width.get() / 2. + border.shift.get();
+            let line = path.line.offset(side * shift);
+            let mut outlines = self.outlines();
+            match &border.dashed {
+                Some(dash) => {
+                    let dashes = dash_intervals(
+                        line.length(),
+                        dash.dash_length.get(),
+                        dash.break_length.get(),
+                        false,
+                    );
+                    for (from, to) in dashes {
+                        let dash = line.slice(from, to);
+                        outlines.stroke(&dash, width, CapStyle::Flat, symbol.join_style);
+                    }
+                }
+                None => outlines.stroke(&line, width, cap, symbol.join_style),
+            }
+            self.fill(&border.color, outlines.rings, FillRule::NonZero);
+        }
+    }
+
+    /// Fill rings with an area symbol, with patterns turned by `rotation` in addition
+    /// to their own angle and the pattern rotation of rotatable patterns.
+    fn area_symbol(
+        &mut self,
+        symbol: &AreaSymbol,
+        rings: &[Path],
+        pattern_rotation: &PatternRotation,
+        rotation: f64,
+    ) -> Result<()> {
+        let clip = Rc::new(area_shape(rings));
+        self.fill_shape(&symbol.color, Shape::clone(&clip));
+        let rings = &clip.rings;
+        let Some(region) = rings_bounds(rings).and_then(|bounds| intersect(bounds, self.view))
+        else {
+            return Ok(());
+        };
+        if symbol.patterns.is_empty() {
+            return Ok(());
+        }
+
+        for pattern in &symbol.patterns {
+            match pattern {
+                FillPattern::LinePattern {
+                    angle,
+                    line_spacing,
+                    line_offset,
+                    line_color,
+                    line_width,
+                    rotatable,
+                } => {
+                    let angle = angle + rotation + pattern_angle(*rotatable, pattern_rotation);
+                    let spacing = line_spacing.get();
+                    let width = line_width.get();
+                    let frame = PatternFrame::new(pattern_rotation.coord, angle, region, width);
+                    let Some(rows) = frame.rows(spacing, line_offset.get()) else {
+                        continue;
+                    };
+                    let mut outlines = self.outlines();
+                    for row in rows {
+                        let from = frame.point(frame.along.0, row);
+                        let to = frame.point(frame.along.1, row);
+                        let line = Polyline::new([from, to], false);
+                        outlines.stroke(&line, width, CapStyle::Flat, JoinStyle::Miter);
+                    }
+                    let previous = self.clip.replace(Rc::clone(&clip));
+                    self.fill(line_color, outlines.rings, FillRule::NonZero);
+                    self.clip = previous;
+                }
+                FillPattern::PointPattern {
+                    clip_options,
+                    angle,
+                    line_spacing,
+                    line_offset,
+                    offset_along_line,
+                    point_distance,
+                    point,
+                    rotatable,
+                } => {
+                    let angle = angle + rotation + pattern_angle(*rotatable, pattern_rotation);
+                    let radius = point_symbol_radius(point);
+                    let frame = PatternFrame::new(pattern_rotation.coord, angle, region, radius);
+                    let Some(rows) = frame.rows(line_spacing.get(), line_offset.get()) else {
+                        continue;
+                    };
+                    let Some(columns) =
+                        frame.columns(point_distance.get(), offset_along_line.get(), rows.len())
+                    else {
+                        continue;
+                    };
+                    let previous = if matches!(clip_options, ClippingOption::ClipElementsAtBoundary)
+                    {
+                        self.clip.replace(Rc::clone(&clip))
+                    } else {
+                        self.clip.clone()
+                    };
+                    for row in rows {
+                        for &column in &columns {
+                            let position = frame.point(column, row);
+                            let inside = contains(rings, position);
+                            let shown = match clip_options {
+                                ClippingOption::ClipElementsAtBoundary => true,
+                                ClippingOption::NoClippingIfCompletelyInside => {
+                                    inside && boundary_distance(rings, position) >= radius
+                                }
+                                ClippingOption::NoClippingIfCenterInside => inside,
+                                ClippingOption::NoClippingIfPartiallyInside => {
+                                    inside || boundary_distance(rings, position) < radius
+                                }
+                            };
+                            if shown {
+                                self.point_symbol(point, Placement::new(position, angle))?;
+                            }
+                        }
+                    }
+                    self.clip = previous;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, symbol: &TextSymbol, object: &TextObject) {
+        if symbol.font_size.get() <= 0. {
+            return;
+        }
+        let layout = TextLayout::new(symbol, object);
+        let placement = Placement::new(*object.geometry().anchor_coord(), object.rotation);
+        if self.surface.has_fonts() {
+            self.set_text(symbol, object.h_align, &layout, placement);
+        } else {
+            self.text_strokes(symbol, &layout, placement);
+        }
+
+        if let Some(line_below) = &symbol.line_below {
+            let mut outlines = self.outlines();
+            for (from, to) in &layout.paragraph_ends {
+                let shift = Coord {
+                    x: 0.,
+                    y: -line_below.distance.get(),
+                };
+                let line = Polyline::new(
+                    [placement.apply(*from + shift), placement.apply(*to + shift)],
+                    false,
+                );
+                let width = line_below.width.get();
+                outlines.stroke(&line, width, CapStyle::Flat, JoinStyle::Miter);
+            }
+            self.fill(&line_below.color, outlines.rings, FillRule::NonZero);
+        }
+    }
+
+    /// Hand the text and its framing to a surface with fonts.
+    fn set_text(
+        &mut self,
+        symbol: &TextSymbol,
+        align: HorizontalAlign,
+        layout: &TextLayout,
+        placement: Placement,
+    ) {
+        let run = |placement, outline| TextRun {
+            symbol,
+            layout,
+            placement,
+            align,
+            outline,
+        };
+        match &symbol.framing_mode {
+            Some(FramingMode::LineFraming(framing)) => {
+                if let Some(layer) = self.layer(&framing.color) {
+                    let width = 2. * framing.framing_line_half_width.get();
+                    self.surface.text(layer, &run(placement, Some(width)));
+                }
+            }
+            Some(FramingMode::ShadowFraming(framing)) => {
+                if let Some(layer) = self.layer(&framing.color) {
+                    let shadow = Placement::new(framing.shadow_offset, 0.);
+                    self.surface.text(layer, &run(shadow.then(placement), None));
+                }
+            }
+            Some(FramingMode::NoFraming) | None => {}
+        }
+        if let Some(layer) = self.layer(&symbol.color) {
+            self.surface.text(layer, &run(placement, None));
+        }
+    }
+
+    /// Draw the text and its framing with the strokes of the built-in font.
+    fn text_strokes(&mut self, symbol: &TextSymbol, layout: &TextLayout, placement: Placement) {
+        let glyphs = layout
+            .glyphs
+            .iter()
+            .map(|stroke| Polyline::new(stroke.iter().map(|&point| placement.apply(point)), false))
+            .collect::<Vec<_>>();
+        let stroke_width = symbol.font_size.get()
+            * if symbol.bold {
+                BOLD_STROKE_WIDTH
+            } else {
+                STROKE_WIDTH
+            };
+
+        match &symbol.framing_mode {
+            Some(FramingMode::LineFraming(framing)) => {
+                let width = stroke_width + 2. * framing.framing_line_half_width.get();
+                let mut outlines = self.outlines();
+                for glyph in &glyphs {
+                    outlines.stroke(glyph, width, CapStyle::Round, JoinStyle::Round);
+                }
+                self.fill(&framing.color, outlines.rings, FillRule::NonZero);
+            }
+            Some(FramingMode::ShadowFraming(framing)) => {
+                let shadow = Placement::new(framing.shadow_offset, 0.);
+                let mut outlines = self.outlines();
+                for glyph in &layout.glyphs {
+                    let line = Polyline::new(
+                        glyph
+                            .iter()
+                            .map(|&point| shadow.then(placement).apply(point)),
+                        false,
+                    );
+                    outlines.stroke(&line, stroke_width, CapStyle::Round, JoinStyle::Round);
+                }
+                self.fill(&framing.color, outlines.rings, FillRule::NonZero);
+            }
+            Some(FramingMode::NoFraming) | None => {}
+        }
+
+        let mut outlines = self.outlines();
+        for glyph in &glyphs {
+            outlines.stroke(glyph, stroke_width, CapStyle::Round, JoinStyle::Round);
+        }
+        for (from, to) in &layout.underlines {
+            let line = Polyline::new([placement.apply(*from), placement.apply(*to)], false);
+            outlines.stroke(&line, stroke_width, CapStyle::Flat, JoinStyle::Miter);
+        }
+        self.fill(&symbol.color, outlines.rings, FillRule::NonZero);
+    }
+}
+
+/// Positions of the mid symbols of an undashed line.
+///
+/// The line is split into segments of about `segment` length between two ends of
+/// `end` length, with a mid symbol at every segment boundary.
+fn spaced_positions(
+    length: f64,
+    segment: f64,
+    end: f64,
+    minimum: u16,
+    show_at_least_one: bool,
+    closed: bool,
+) -> Vec<f64> {
+    let usable = length - 2. * end;
+    if segment <= 0. || usable < 0. {
+        return if show_at_least_one {
+            vec![length / 2.]
+        } else {
+            Vec::new()
+        };
+    }
+    let segments = (usable / segment)
+        .round()
+        .max(f64::from(minimum.saturating_sub(1)))
+        .max(1.);
+    let mut positions = (0..=segments as usize)
+        .map(|index| end + index as f64 * usable / segments)
+        .collect::<Vec<_>>();
+    if closed && end <= 0. {
+        positions.pop();
+    }
+    positions
+}
+
+/// A coordinate frame for fill patterns, with its extent over a region.
+#[derive(Debug)]
+struct PatternFrame {
+    origin: Coord,
+    along_direction: Coord,
+    across_direction: Coord,
+    /// The extent of the region along the pattern lines.
+    along: (f64, f64),
+    /// The extent of the region across the pattern lines.
+    across: (f64, f64),
+}
+
+impl PatternFrame {
+    /// The frame turned by `angle` at `origin`, with the region grown by `margin`.
+    fn new(origin: Coord, angle: f64, region: Rect, margin: f64) -> Self {
+        let along_direction = Coord {
+            x: angle.cos(),
+            y: angle.sin(),
+        };
+        let across_direction = Coord {
+            x: -angle.sin(),
+            y: angle.cos(),
+        };
+        let corners = [
+            region.min(),
+            region.max(),
+            Coord {
+                x: region.min().x,
+                y: region.max().y,
+            },
+            Coord {
+                x: region.max().x,
+                y: region.min().y,
+            },
+        ];
+        let extent = |direction: Coord| {
+            corners
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), corner| {
+                    let value =
+                        (corner.x - origin.x) * direction.x + (corner.y - origin.y) * direction.y;
+                    (min.min(value - margin), max.max(value + margin))
+                })
+        };
+        Self {
+            origin,
+            along_direction,
+            across_direction,
+            along: extent(along_direction),
+            across: extent(across_direction),
+        }
+    }
+
+    fn point(&self, along: f64, across: f64) -> Coord {
+        self.origin + self.along_direction * along + self.across_direction * across
+    }
+
+    /// The offsets of the pattern lines crossing the region.
+    fn rows(&self, spacing: f64, offset: f64) -> Option<Vec<f64>> {
+        steps(self.across, spacing, offset, 1)
+    }
+
+    /// The positions of pattern points along every line crossing the region.
+    fn columns(&self, distance: f64, offset: f64, rows: usize) -> Option<Vec<f64>> {
+        steps(self.along, distance, offset, rows)
+    }
+}
+
+/// The multiples of `step` shifted by `offset` within `range`, as long as there
+/// are not too many of them when repeated `repeat` times.
+fn steps((min, max): (f64, f64), step: f64, offset: f64, repeat: usize) -> Option<Vec<f64>> {
+    if step <= 0. {
+        return None;
+    }
+    let first = ((min - offset) / step).ceil();
+    let last = ((max - offset) / step).floor();
+    if last < first || (last - first + 1.) * repeat as f64 > MAX_PATTERN_ELEMENTS {
+        return None;
+    }
+    Some(
+        (first as i64..=last as i64)
+            .map(|index| offset + index as f64 * step)
+            .collect(),
+    )
+}
+
+pub(super) fn pattern_angle(rotatable: bool, pattern_rotation: &PatternRotation) -> f64 {
+    if rotatable {
+        pattern_rotation.rotation
+    } else {
+        0.
+    }
+}
+
+/// The distance from the symbol origin to the furthest part of a point symbol.
+fn point_symbol_radius(symbol: &PointSymbol) -> f64 {
+    let own = symbol.inner_radius.get() + symbol.outer_width.get();
+    let reach = |path: &BezierPath, width: f64| {
+        path.geometry()
+            .segments()
+            .flat_map(|segment| {
+                let handles = segment.handles().map(<[Coord; 2]>::from);
+                [segment.start(), segment.end()]
+                    .into_iter()
+                    .chain(handles.into_iter().flatten())
+            })
+            .map(|point| point.x.hypot(point.y) + width / 2.)
+            .fold(0., f64::max)
+    };
+    symbol
+        .elements
+        .iter()
+        .map(|element| match element {
+            Element::Point { symbol, object } => {
+                let position = object.geometry().0;
+                position.x.hypot(position.y) + point_symbol_radius(symbol)
+            }
+            Element::Line { symbol, object } => {
+                let border = symbol.border.as_ref().map_or(0., |border| match border {
+                    BorderStyle::SymmetricBorder { both } => both.width.get() + both.shift.get(),
+                    BorderStyle::AsymmetricBorder { left, right } => (left.width.get()
+                        + left.shift.get())
+                    .max(right.width.get() + right.shift.get()),
+                });
+                reach(object.geometry(), symbol.line_width.get() + 2. * border)
+            }
+            Element::Area { object, .. } => reach(object.geometry().exterior(), 0.),
+        })
+        .fold(own, f64::max)
+}
+
+/// Whether `point` is inside the rings by the even-odd rule.
+fn contains(rings: &[Vec<Coord>], point: Coord) -> bool {
+    let mut inside = false;
+    for ring in rings {
+        for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// The distance from `point` to the nearest ring edge.
+fn boundary_distance(rings: &[Vec<Coord>], point: Coord) -> f64 {
+    rings
+        .iter()
+        .flat_map(|ring| ring.iter().zip(ring.iter().cycle().skip(1)))
+        .map(|(&a, &b)| {
+            let edge = b - a;
+            let length_squared = edge.x * edge.x + edge.y * edge.y;
+            let t = if length_squared > 0. {
+                (((point.x - a.x) * edge.x + (point.y - a.y) * edge.y) / length_squared)
+                    .clamp(0., 1.)
+            } else {
+                0.
+            };
+            distance(point, a + edge * t)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// The bounding box of the end points and handles of a path.
+pub(super) fn path_bounds(path: &BezierPath) -> Option<Rect> {
+    let points = path.geometry().segments().flat_map(|segment| {
+        let handles = segment.handles().map(<[Coord; 2]>::from);
+        [segment.start(), segment.end()]
+            .into_iter()
+            .chain(handles.into_iter().flatten())
+    });
+    bounds(points)
+}
+
+fn rings_bounds(rings: &[Vec<Coord>]) -> Option<Rect> {
+    bounds(rings.iter().flatten().copied())
+}
+
+fn bounds(points: impl Iterator<Item = Coord>) -> Option<Rect> {
+    points.fold(None, |bounds: Option<Rect>, point| {
+        Some(bounds.map_or_else(
+            || Rect::new(point, point),
+            |bounds| {
+                Rect::new(
+                    Coord {
+                        x: bounds.min().x.min(point.x),
+                        y: bounds.min().y.min(point.y),
+                    },
+                    Coord {
+                        x: bounds.max().x.max(point.x),
+                        y: bounds.max().y.max(point.y),
+                    },
+                )
+            },
+        ))
+    })
+}
+
+pub(super) fn intersect(a: Rect, b: Rect) -> Option<Rect> {
+    let min = Coord {
+        x: a.min().x.max(b.min().x),
+        y: a.min().y.max(b.min().y),
+    };
+    let max = Coord {
+        x: a.max().x.min(b.max().x),
+        y: a.max().y.min(b.max().y),
+    };
+    (min.x <= max.x && min.y <= max.y).then(|| Rect::new(min, max))
+}
+
+/// The glyph strokes and decorations of a text object, relative to its anchor.
+#[derive(Debug, Default)]
+pub(super) struct TextLayout {
+    /// The text of every line with the start and end of its baseline.
+    pub(super) lines: Vec<(String, Coord, Coord)>,
+    glyphs: Vec<Vec<Coord>>,
+    underlines: Vec<(Coord, Coord)>,
+    /// The baseline of the last line of every paragraph.
+    pub(super) paragraph_ends: Vec<(Coord, Coord)>,
+}
+
+impl TextLayout {
+    pub(super) fn new(symbol: &TextSymbol, object: &TextObject) -> Self {
+        let size = symbol.font_size.get();
+        let unit = size * CAP_HEIGHT / font::CAP_HEIGHT;
+        let spacing = symbol.character_spacing * size;
+        let advance = |character: char| {
+            let width = if character.is_whitespace() {
+                font::SPACE_WIDTH
+            } else {
+                font::GLYPH_WIDTH + font::GLYPH_SPACING
+            };
+            width * unit * GLYPH_ASPECT + spacing
+        };
+        let width_of = |line: &str| line.chars().map(advance).sum::<f64>();
+
+        let wrap_width = match object.geometry() {
+            TextGeometry::WrapBox(wrap_box) => Some(wrap_box.width.get()),
+            TextGeometry::SingleAnchor(_) => None,
+        };
+        let lines = wrap_lines(&object.text, |line| {
+            wrap_width.is_some_and(|wrap| width_of(line) > wrap)
+        });
+
+        let line_height = symbol.line_spacing.get() * (ASCENT + DESCENT) * size;
+        let mut baselines = Vec::with_capacity(lines.len());
+        let mut baseline = 0.;
+        for (_, ends_paragraph) in &lines {
+            baselines.push(baseline);
+            baseline -= line_height;
+            if *ends_paragraph {
+                baseline -= symbol.paragraph_spacing;
+            }
+        }
+        let top = ASCENT * size;
+        let bottom = baselines.last().copied().unwrap_or(0.) - DESCENT * size;
+        let shift = match (object.geometry(), object.v_align) {
+            (TextGeometry::SingleAnchor(_), VerticalAlign::Baseline) => 0.,
+            (TextGeometry::SingleAnchor(_), VerticalAlign::Top) => -top,
+            (TextGeometry::SingleAnchor(_), VerticalAlign::Bottom) => -bottom,
+            (TextGeometry::WrapBox(wrap_box), VerticalAlign::Baseline | VerticalAlign::Top) => {
+                wrap_box.height.get() / 2. - top
+            }
+            (TextGeometry::WrapBox(wrap_box), VerticalAlign::Bottom) => {
+                -wrap_box.height.get() / 2. - bottom
+            }
+            (_, VerticalAlign::VCenter) => -(top + bottom) / 2.,
+        };
+
+        let mut layout = Self::default();
+        for ((line, ends_paragraph), baseline) in lines.iter().zip(baselines) {
+            let baseline = baseline + shift;
+            let width = width_of(line) - spacing;
+            let left = match (wrap_width, object.h_align) {
+                (None, HorizontalAlign::Left) => 0.,
+                (None, HorizontalAlign::Right) => -width,
+                (_, HorizontalAlign::HCenter) => -width / 2.,
+                (Some(wrap), HorizontalAlign::Left) => -wrap / 2.,
+                (Some(wrap), HorizontalAlign::Right) => wrap / 2. - width,
+            };
+            let mut x = left;
+            for character in line.chars() {
+                if let Some(strokes) = font::glyph(character) {
+                    layout.glyphs.extend(strokes.map(|stroke| {
+                        stroke
+                            .into_iter()
+                            .map(|point| {
+                                let slant = if symbol.italic {
+                                    ITALIC_SHEAR * point.y
+                                } else {
+                                    0.
+                                };
+                                Coord {
+                                    x: x + (point.x * GLYPH_ASPECT + slant) * unit,
+                                    y: baseline + point.y * unit,
+                                }
+                            })
+                            .collect()
+                    }));
+                }
+                x += advance(character);
+            }
+            let from = Coord {
+                x: left,
+                y: baseline,
+            };
+            let to = Coord {
+                x: left + width,
+                y: baseline,
+            };
+            if symbol.underline {
+                let below = Coord {
+                    x: 0.,
+                    y: -DESCENT * size / 2.,
+                };
+                layout.underlines.push((from + below, to + below));
+            }
+            if *ends_paragraph {
+                layout.paragraph_ends.push((from, to));
+            }
+            layout.lines.push((line.clone(), from, to));
+        }
+        layout
+    }
+}
+
+/// Split text into paragraphs and break them into lines between words where a line
+/// would be too wide. Every line is paired with whether it ends a paragraph.
+fn wrap_lines(text: &str, is_too_wide: impl Fn(&str) -> bool) -> Vec<(String, bool)> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let paragraph = paragraph.trim_end_matches('\r');
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() {
+                word.to_owned()
+            } else {
+                format!("{line} {word}")
+            };
+            if is_too_wide(&candidate) && !line.is_empty() {
+                lines.push((std::mem::replace(&mut line, word.to_owned()), false));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push((line, true));
+    }
+    lines
+}
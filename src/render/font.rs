@@ -0,0 +1,125 @@
+//! A single-stroke font for previews of text objects.
+//!
+//! Glyphs are drawn on a grid four units wide with the baseline at 0, the
+//! x-height at 4 and the cap height at 6. Every stroke is a run of points
+//! written as two characters: the x digit and the y digit, where `a` and `b`
+//! stand for the descender rows -1 and -2.
+
+use geo_types::Coord;
+
+/// The width of the glyph grid.
+pub(super) const GLYPH_WIDTH: f64 = 4.;
+/// The height of capital letters on the glyph grid.
+pub(super) const CAP_HEIGHT: f64 = 6.;
+/// The advance of a space on the glyph grid.
+pub(super) const SPACE_WIDTH: f64 = 3.;
+/// The space between two glyphs on the glyph grid.
+pub(super) const GLYPH_SPACING: f64 = 1.5;
+
+/// The strokes of `character`, or `None` when the font has no glyph for it.
+pub(super) fn glyph(character: char) -> Option<impl Iterator<Item = Vec<Coord>>> {
+    let strokes =
+        strokes(character).or_else(|| character.to_uppercase().next().and_then(strokes))?;
+    Some(strokes.split(' ').map(|stroke| {
+        stroke
+            .as_bytes()
+            .chunks_exact(2)
+            .map(|point| Coord {
+                x: grid_value(point[0]),
+                y: grid_value(point[1]),
+            })
+            .collect()
+    }))
+}
+
+fn grid_value(digit: u8) -> f64 {
+    match digit {
+        b'a' => -1.,
+        b'b' => -2.,
+        digit => f64::from(digit.saturating_sub(b'0')),
+    }
+}
+
+fn strokes(character: char) -> Option<&'static str> {
+    Some(match character {
+        '0' | 'O' => "103041453616050110",
+        '1' => "142620 1030",
+        '2' => "05163645440040",
+        '3' => "05163645443323 334241301001",
+        '4' => "3630 360242",
+        '5' => "460603334241301001",
+        '6' => "453616050110304142331302",
+        '7' => "064620",
+        '8' => "130405163645443342413010010213",
+        '9' => "443313040516364541301001",
+        'A' => "002640 1232",
+        'B' => "0006 063645443303 3342413000",
+        'C' => "4536160501103041",
+        'D' => "00062644422000",
+        'E' => "46060040 0333",
+        'F' => "460600 0333",
+        'G' => "45361605011030414323",
+        'H' => "0006 4046 0343",
+        'I' => "1636 2620 1030",
+        'J' => "4641301001",
+        'K' => "0006 4602 2440",
+        'L' => "060040",
+        'M' => "0006234640",
+        'N' => "00064640",
+        'P' => "00063645443303",
+        'Q' => "103041453616050110 2240",
+        'R' => "00063645443303 2340",
+        'S' => "453616050413334241301001",
+        'T' => "0646 2620",
+        'U' => "060110304146",
+        'V' => "062046",
+        'W' => "0600234046",
+        'X' => "0046 0640",
+        'Y' => "0623 4623 2320",
+        'Z' => "06460040",
+        'Æ' => "002646 262040 1333",
+        'Ø' => "103041453616050110 0046",
+        'Å' => "002640 1232 27",
+        'a' => "4440 4334140301103041",
+        'b' => "0600 0314344341301001",
+        'c' => "4334140301103041",
+        'd' => "4640 4334140301103041",
+        'e' => "02424334140301103041",
+        'f' => "46362520 1434",
+        'g' => "444a3b1b0a 4334140301103041",
+        'h' => "0600 0314344340",
+        'i' => "2420 26",
+        'j' => "242a1b0a 26",
+        'k' => "0600 4302 1340",
+        'l' => "262130",
+        'm' => "0400 03142320 23344340",
+        'n' => "0400 0314344340",
+        'o' => "103041433414030110",
+        'p' => "040b 0314344341301001",
+        'q' => "444b 4334140301103041",
+        'r' => "0400 03143443",
+        's' => "43341403123241301001",
+        't' => "262130 1434",
+        'u' => "0401103041 4440",
+        'v' => "042044",
+        'w' => "0410223044",
+        'x' => "0044 0440",
+        'y' => "0422 441b",
+        'z' => "04440040",
+        '.' => "20",
+        ',' => "2110",
+        ':' => "20 24",
+        ';' => "2110 24",
+        '-' => "1333",
+        '+' => "1333 2125",
+        '/' => "0046",
+        '\'' => "2624",
+        '"' => "1614 3634",
+        '(' => "36151130",
+        ')' => "16353110",
+        '!' => "2622 20",
+        '?' => "05163645442422 20",
+        '=' => "0242 0444",
+        _ => return None,
+    })
+}
@@ -0,0 +1,856 @@
+//! Rasterise maps into RGBA images, e.g. for previews of generated maps, or
+//! draw them as SVG vector graphics.
+//!
+//! Objects are drawn with their symbol definitions: line widths, dashes, caps
+//! and joins, borders, start, mid, end and dash symbols, fill patterns and the
+//! elements of point symbols. Colors are painted in [`crate::colors::ColorSet`]
+//! priority order, so a color higher up in the color set covers every color
+//! below it regardless of the object order. In images, text is set in a
+//! built-in single-stroke font, which shows the placement and size of labels
+//! but not their typeface.
+//!
+//! SVG drawings have a layer for every color of the color set, keep the Bézier
+//! curves of objects and fill areas with SVG patterns. Their text elements use
+//! the font family of the text symbol.
+//!
+//! PDF files are meant for printing: every spot color is a separation, with its
+//! CMYK value as the fallback for process printing, and colors overprint the
+//! colors below them unless they knock them out. Areas and lines keep their
+//! Bézier curves, lines are stroked with their caps, joins and dashes, and the
+//! remaining shapes, such as tapered ends, borders and patterns, are outlines
+//! within 10 µm of the exact geometry. Text is set in Helvetica.
+
+mod canvas;
+mod draw;
+mod font;
+mod path;
+mod pdf;
+mod png;
+mod svg;
+mod zlib;
+
+use std::{io::Write, path::Path};
+
+use geo_types::{Coord, Rect};
+
+use crate::{Error, Omap, PositiveF64, Result, colors::Rgb};
+use canvas::{Canvas, Layer};
+use draw::Painter;
+use pdf::PdfSurface;
+use svg::SvgPainter;
+
+/// Millimetres per inch.
+const MM_PER_INCH: f64 = 25.4;
+/// The largest number of pixels in a rendered image.
+const MAX_PIXELS: u64 = 1 << 28;
+
+/// Settings for rasterising a region of a map.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    viewport: Rect,
+    dpi: PositiveF64,
+    background: Option<Rgb>,
+    show_helper_symbols: bool,
+}
+
+impl Renderer {
+    /// Create a renderer for the `viewport` in map coordinates at `dpi` dots per inch
+    /// of paper, drawing on a white background.
+    pub fn new(viewport: Rect, dpi: PositiveF64) -> Self {
+        Self {
+            viewport,
+            dpi,
+            background: Some(Rgb::default()),
+            show_helper_symbols: false,
+        }
+    }
+
+    /// Set the color behind the map, `None` for a transparent background.
+    pub fn with_background(mut self, background: Option<Rgb>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Set whether objects with helper symbols are drawn. They are left out by default,
+    /// like when Mapper prints or exports a map.
+    pub fn with_helper_symbols(mut self, show_helper_symbols: bool) -> Self {
+        self.show_helper_symbols = show_helper_symbols;
+        self
+    }
+
+    /// The width and height in pixels of the rendered images.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRenderViewport`] if the viewport is empty or the image
+    /// would have more than 2^28 pixels.
+    pub fn image_size(&self) -> Result<(u32, u32)> {
+        let pixels_per_mm = self.dpi.get() / MM_PER_INCH;
+        let width = (self.viewport.width() * pixels_per_mm).round();
+        let height = (self.viewport.height() * pixels_per_mm).round();
+        if !(width >= 1. && height >= 1. && width * height <= MAX_PIXELS as f64) {
+            return Err(Error::InvalidRenderViewport);
+        }
+        Ok((width as u32, height as u32))
+    }
+
+    /// Rasterise every object of the map within the viewport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewport is invalid, see [`Self::image_size`], a symbol
+    /// or color is already mutably borrowed or an object has an invalid geometry.
+    pub fn render(&self, omap: &Omap) -> Result<RgbaImage> {
+        let (width, height) = self.image_size()?;
+        let size = (width as usize, height as usize);
+        let pixels_per_mm = width as f64 / self.viewport.width();
+
+        let top_left = Coord {
+            x: self.viewport.min().x,
+            y: self.viewport.max().y,
+        };
+        let mut painter = Painter::new(
+            &omap.colors,
+            Canvas::new(size, top_left, pixels_per_mm),
+            self.viewport,
+            0.2 / pixels_per_mm,
+            self.show_helper_symbols,
+        );
+        for object in omap.iter_all_objects() {
+            painter.draw_object(object)?;
+        }
+
+        let colors = omap
+            .colors
+            .iter()
+            .map(|color| {
+                color
+                    .rgb()
+                    .map(|rgb| [rgb.r, rgb.g, rgb.b].map(|c| c.get() as f32))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut image = RgbaImage::new(width, height, self.background);
+        painter
+            .into_surface()
+            .composite(&mut image, |layer| match layer {
+                Layer::Color(std::cmp::Reverse(priority)) => {
+                    colors.get(priority).copied().unwrap_or_default()
+                }
+                Layer::RegistrationBlack => [0.; 3],
+            });
+        Ok(image)
+    }
+
+    /// Draw every object of the map within the viewport as SVG, in millimetres of paper.
+    ///
+    /// The DPI does not affect vector output. Point patterns are always clipped at
+    /// the area boundary, whatever their clipping option.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewport is empty, a symbol or color is already mutably
+    /// borrowed, an object has an invalid geometry or the SVG cannot be written.
+    pub fn render_svg<W: Write>(&self, omap: &Omap, writer: &mut W) -> Result<()> {
+        let (width, height) = (self.viewport.width(), self.viewport.height());
+        if !(width > 0. && height > 0.) {
+            return Err(Error::InvalidRenderViewport);
+        }
+        let mut painter = SvgPainter::new(&omap.colors, self.viewport, self.show_helper_symbols);
+        for object in omap.iter_all_objects() {
+            painter.draw_object(object)?;
+        }
+        painter.finish(writer, (width, height), self.background)
+    }
+
+    /// Draw the map as an SVG file at the given path, see [`Self::render_svg`].
+    ///
+    /// The file is replaced atomically like in [`Omap::to_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SVG cannot be drawn, the temporary file cannot be
+    /// created or written, or the temporary file cannot replace `path`.
+    pub fn render_svg_file(&self, omap: &Omap, path: impl AsRef<Path>) -> Result<()> {
+        crate::omap::write_atomically(path.as_ref(), |writer| self.render_svg(omap, writer))
+    }
+
+    /// Draw every object of the map within the viewport as a one page PDF for printing,
+    /// with the page the size of the viewport.
+    ///
+    /// Spot colors become `Separation` color spaces named after their spot color name,
+    /// mixed colors `DeviceN` combinations of their spot colors and registration black
+    /// is printed on all separations. Colors that are not knockout colors overprint.
+    /// The DPI and background do not affect PDF output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRenderViewport`] if the viewport is empty or a side is
+    /// longer than 5080 mm, the largest PDF page. Returns other errors if a symbol or
+    /// color is already mutably borrowed, a color has no CMYK value, an object has an
+    /// invalid geometry or the PDF cannot be written.
+    pub fn render_pdf<W: Write>(&self, omap: &Omap, writer: &mut W) -> Result<()> {
+        let (width, height) = (self.viewport.width(), self.viewport.height());
+        let largest = pdf::MAX_PAGE_SIZE / 72. * MM_PER_INCH;
+        if !(width > 0. && height > 0. && width <= largest && height <= largest) {
+            return Err(Error::InvalidRenderViewport);
+        }
+        let mut painter = Painter::new(
+            &omap.colors,
+            PdfSurface::default(),
+            self.viewport,
+            pdf::TOLERANCE,
+            self.show_helper_symbols,
+        );
+        for object in omap.iter_all_objects() {
+            painter.draw_object(object)?;
+        }
+        painter
+            .into_surface()
+            .finish(writer, &omap.colors, self.viewport)
+    }
+
+    /// Draw the map as a PDF file at the given path, see [`Self::render_pdf`].
+    ///
+    /// The file is replaced atomically like in [`Omap::to_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PDF cannot be drawn, the temporary file cannot be
+    /// created or written, or the temporary file cannot replace `path`.
+    pub fn render_pdf_file(&self, omap: &Omap, path: impl AsRef<Path>) -> Result<()> {
+        crate::omap::write_atomically(path.as_ref(), |writer| self.render_pdf(omap, writer))
+    }
+}
+
+impl Omap {
+    /// Rasterise the `viewport`, given in map coordinates, at `dpi` dots per inch
+    /// of paper on a white background.
+    /// See [`Renderer`] for more options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewport is empty or too large, a symbol or color is
+    /// already mutably borrowed or an object has an invalid geometry.
+    pub fn render(&self, viewport: Rect, dpi: PositiveF64) -> Result<RgbaImage> {
+        Renderer::new(viewport, dpi).render(self)
+    }
+
+    /// Write the `viewport`, given in map coordinates, as SVG to anything that
+    /// implements [`Write`], with a layer for every color.
+    /// See [`Renderer::render_svg`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewport is empty, a symbol or color is already mutably
+    /// borrowed, an object has an invalid geometry or the SVG cannot be written.
+    pub fn to_svg_writer<W: Write>(&self, writer: &mut W, viewport: Rect) -> Result<()> {
+        Renderer::new(viewport, PositiveF64::try_from(MM_PER_INCH)?).render_svg(self, writer)
+    }
+
+    /// Write the `viewport`, given in map coordinates, as an SVG file at the given path.
+    /// See [`Renderer::render_svg`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SVG cannot be drawn, the temporary file cannot be
+    /// created or written, or the temporary file cannot replace `path`.
+    pub fn to_svg_file(&self, path: impl AsRef<Path>, viewport: Rect) -> Result<()> {
+        Renderer::new(viewport, PositiveF64::try_from(MM_PER_INCH)?).render_svg_file(self, path)
+    }
+
+    /// Write the `viewport`, given in map coordinates, as a PDF with spot color
+    /// separations to anything that implements [`Write`].
+    /// See [`Renderer::render_pdf`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewport is empty or too large, a symbol or color is
+    /// already mutably borrowed, a color has no CMYK value, an object has an invalid
+    /// geometry or the PDF cannot be written.
+    pub fn to_pdf_writer<W: Write>(&self, writer: &mut W, viewport: Rect) -> Result<()> {
+        Renderer::new(viewport, PositiveF64::try_from(MM_PER_INCH)?).render_pdf(self, writer)
+    }
+
+    /// Write the `viewport`, given in map coordinates, as a PDF file at the given path.
+    /// See [`Renderer::render_pdf`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PDF cannot be drawn, the temporary file cannot be
+    /// created or written, or the temporary file cannot replace `path`.
+    pub fn to_pdf_file(&self, path: impl AsRef<Path>, viewport: Rect) -> Result<()> {
+        Renderer::new(viewport, PositiveF64::try_from(MM_PER_INCH)?).render_pdf_file(self, path)
+    }
+}
+
+/// An image of 8-bit RGBA pixels, stored row by row from the top left corner
+/// without premultiplied alpha.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    fn new(width: u32, height: u32, background: Option<Rgb>) -> Self {
+        let pixel = background.map_or([0; 4], |rgb| {
+            let [r, g, b] = [rgb.r, rgb.g, rgb.b].map(|c| (c.get() * 255.).round() as u8);
+            [r, g, b, u8::MAX]
+        });
+        Self {
+            width,
+            height,
+            pixels: pixel.repeat(width as usize * height as usize),
+        }
+    }
+
+    /// The width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The RGBA values of every pixel, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Consume the image and return its pixel values.
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// The RGBA value of the pixel in column `x` and row `y`, counted from the top left.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[start..start + 4].try_into().ok()
+    }
+
+    /// Write the image as PNG to anything that implements [`Write`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image data cannot be written.
+    pub fn to_png_writer<W: Write>(&self, writer: &mut W) -> Result<()> {
+        png::write(writer, self.width, self.height, &self.pixels)
+    }
+
+    /// Write the image as a PNG file at the given path.
+    ///
+    /// The file is replaced atomically like in [`Omap::to_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary file cannot be created or written,
+    /// or the temporary file cannot replace `path`.
+    pub fn to_png_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        crate::omap::write_atomically(path.as_ref(), |writer| self.to_png_writer(writer))
+    }
+
+    /// Paint `color` over the pixel with the opacity `alpha`.
+    fn blend(&mut self, x: usize, y: usize, color: [f32; 3], alpha: f32) {
+        let start = (y * self.width as usize + x) * 4;
+        let pixel = &mut self.pixels[start..start + 4];
+        let below = f32::from(pixel[3]) / 255.;
+        let total = alpha + below * (1. - alpha);
+        if total <= 0. {
+            return;
+        }
+        for (channel, value) in pixel.iter_mut().zip(color) {
+            let mixed = (value * alpha + f32::from(*channel) / 255. * below * (1. - alpha)) / total;
+            *channel = (mixed * 255.).round() as u8;
+        }
+        pixel[3] = (total * 255.).round() as u8;
+    }
+}
+
+/// A number rounded to three decimals, micrometres for coordinates in mm.
+fn number(value: f64) -> String {
+    let rounded = (value * 1000.).round() / 1000.;
+    // avoid writing -0
+    format!("{}", rounded + 0.)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use geo_types::{LineString, Point, Polygon, Rect, coord};
+    use linestring2bezier::{BezierSegment, BezierString};
+
+    use super::{Renderer, zlib::tests::inflate};
+    use crate::{
+        Error, NonNegativeF64, Omap, PositiveF64, Result, UnitF64,
+        colors::{
+            Cmyk, CmykMode, ColorComponent, MixedColor, Rgb, RgbMode, SpotColor, SymbolColor,
+            WeakColor,
+        },
+        objects::{AreaObject, BezierPath, BezierPolygon, LineObject, PointObject},
+        symbols::{
+            AreaSymbol, DashStyle, FillPattern, GroupDashes, LineSymbol, PointSymbol, Symbol,
+        },
+    };
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const WHITE: [u8; 4] = [255; 4];
+
+    fn rgb_color(omap: &mut Omap, name: &str, r: f64, g: f64, b: f64) -> Result<SymbolColor> {
+        let mut color = MixedColor::new(name, Vec::new());
+        color.set_rgb_mode(RgbMode::Rgb(Rgb {
+            r: UnitF64::try_from(r)?,
+            g: UnitF64::try_from(g)?,
+            b: UnitF64::try_from(b)?,
+        }))?;
+        color.set_cmyk_mode(CmykMode::FromRgb)?;
+        Ok(SymbolColor::Color(omap.colors.push(color)))
+    }
+
+    /// A red line, a blue area drawn after it and a red dot in a 20 mm by 10 mm map.
+    fn test_map() -> Result<Omap> {
+        let mut omap = Omap::new(NonZeroU32::MIN);
+        let red = rgb_color(&mut omap, "Red", 1., 0., 0.)?;
+        let blue = rgb_color(&mut omap, "Blue", 0., 0., 1.)?;
+
+        let line = omap.symbols.add_symbol(
+            LineSymbol::new(crate::Code::new(101, 0, 0), "Line")
+                .with_color(red.clone())
+                .with_line_width(NonNegativeF64::try_from(1.)?),
+        );
+        let area = omap
+            .symbols
+            .add_symbol(AreaSymbol::new(crate::Code::new(201, 0, 0), "Area").with_color(blue));
+        let point = omap.symbols.add_symbol(
+            PointSymbol::new(crate::Code::new(301, 0, 0), "Dot")
+                .with_inner_color(red)
+                .with_inner_radius(NonNegativeF64::try_from(1.)?),
+        );
+        let (Some(Symbol::Line(line)), Some(Symbol::Area(area)), Some(Symbol::Point(point))) =
+            (line.upgrade(), area.upgrade(), point.upgrade())
+        else {
+            panic!("the symbols are in the symbol set with their types");
+        };
+
+        let Some(part) = omap.parts.get_mut(0) else {
+            panic!("a new map has a part");
+        };
+        part.add_object(LineObject::new(
+            std::rc::Rc::downgrade(&line),
+            LineString::from(vec![(0., 5.), (20., 5.)]),
+        ));
+        part.add_object(AreaObject::new(
+            std::rc::Rc::downgrade(&area),
+            Polygon::new(
+                LineString::from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 0.)]),
+                Vec::new(),
+            ),
+        ));
+        part.add_object(PointObject::new(
+            std::rc::Rc::downgrade(&point),
+            Point::new(15., 2.),
+        ));
+        Ok(omap)
+    }
+
+    fn viewport() -> Rect {
+        Rect::new(coord! { x: 0., y: 0. }, coord! { x: 20., y: 10. })
+    }
+
+    #[test]
+    fn renders_objects_in_color_priority_order() -> Result<()> {
+        // 254 dpi is 10 pixels per millimetre
+        let image = test_map()?.render(viewport(), PositiveF64::try_from(254.)?)?;
+        assert_eq!((image.width(), image.height()), (200, 100));
+
+        assert_eq!(
+            image.pixel(50, 50),
+            Some(RED),
+            "the line covers the later area"
+        );
+        assert_eq!(image.pixel(50, 20), Some(BLUE), "the area is filled");
+        assert_eq!(
+            image.pixel(190, 50),
+            Some(RED),
+            "the line continues outside the area"
+        );
+        assert_eq!(
+            image.pixel(150, 80),
+            Some(RED),
+            "the dot is drawn at its position"
+        );
+        assert_eq!(image.pixel(150, 20), Some(WHITE), "the background is white");
+        assert_eq!(image.pixel(200, 0), None);
+        Ok(())
+    }
+
+    #[test]
+    fn renders_transparent_background() -> Result<()> {
+        let image = Renderer::new(viewport(), PositiveF64::try_from(254.)?)
+            .with_background(None)
+            .render(&test_map()?)?;
+        assert_eq!(image.pixel(150, 20).map(|pixel| pixel[3]), Some(0));
+        assert_eq!(image.pixel(50, 20), Some(BLUE));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_empty_viewport() -> Result<()> {
+        let empty = Rect::new(coord! { x: 0., y: 0. }, coord! { x: 20., y: 0. });
+        let result = test_map()?.render(empty, PositiveF64::try_from(300.)?);
+        assert!(matches!(result, Err(Error::InvalidRenderViewport)));
+        Ok(())
+    }
+
+    /// The contents of the layer group with the given id.
+    fn svg_layer<'s>(svg: &'s str, id: &str) -> &'s str {
+        let start = svg
+            .find(&format!("<g id=\"{id}\""))
+            .unwrap_or_else(|| panic!("the layer {id} is written"));
+        let end = svg[start..]
+            .find("</g>")
+            .map_or(svg.len(), |end| start + end);
+        &svg[start..end]
+    }
+
+    #[test]
+    fn writes_svg_layers_in_priority_order() -> Result<()> {
+        let mut bytes = Vec::new();
+        test_map()?.to_svg_writer(&mut bytes, viewport())?;
+        let svg = String::from_utf8_lossy(&bytes);
+
+        assert!(svg.contains(r#"width="20mm" height="10mm" viewBox="0 0 20 10""#));
+        let (red, blue) = (svg_layer(&svg, "color-0"), svg_layer(&svg, "color-1"));
+        assert!(
+            svg.find(blue) < svg.find(red),
+            "the layer of the lower priority color comes first"
+        );
+        assert!(red.contains(r#"inkscape:label="Red""#));
+        assert!(red.contains(r##"d="M0 5L20 5" fill="none" stroke="#ff0000" stroke-width="1""##));
+        assert!(red.contains(r##"<circle cx="15" cy="8" r="1" fill="#ff0000"/>"##));
+        assert!(blue.contains(r##"d="M0 10L10 10L10 0L0 0L0 10Z" fill="#0000ff""##));
+        Ok(())
+    }
+
+    #[test]
+    fn writes_svg_curves_dashes_and_patterns() -> Result<()> {
+        let mut omap = Omap::new(NonZeroU32::MIN);
+        let red = rgb_color(&mut omap, "Red", 1., 0., 0.)?;
+        let blue = rgb_color(&mut omap, "Blue", 0., 0., 1.)?;
+        let line = omap.symbols.add_symbol(
+            LineSymbol::new(crate::Code::new(101, 0, 0), "Dashed")
+                .with_color(blue.clone())
+                .with_line_width(NonNegativeF64::try_from(0.5)?)
+                .with_dash_style(DashStyle::Dashed {
+                    dash_length: NonNegativeF64::try_from(2.)?,
+                    break_length: NonNegativeF64::try_from(1.)?,
+                    dash_group: GroupDashes::UnGrouped {
+                        half_outer_dashes: false,
+                    },
+                }),
+        );
+        let mut area = AreaSymbol::new(crate::Code::new(201, 0, 0), "Hatched").with_color(blue);
+        area.patterns.push(FillPattern::LinePattern {
+            angle: 0.,
+            line_spacing: NonNegativeF64::try_from(1.)?,
+            line_offset: NonNegativeF64::try_from(0.25)?,
+            line_color: red,
+            line_width: NonNegativeF64::try_from(0.1)?,
+            rotatable: false,
+        });
+        let area = omap.symbols.add_symbol(area);
+        let (Some(Symbol::Line(line)), Some(Symbol::Area(area))) = (line.upgrade(), area.upgrade())
+        else {
+            panic!("the symbols are in the symbol set with their types");
+        };
+        let curve = BezierPath::new(
+            BezierString::new(vec![BezierSegment::new(
+                coord! { x: 0., y: 0. },
+                Some((coord! { x: 5., y: 5. }, coord! { x: 15., y: 5. })),
+                coord! { x: 20., y: 0. },
+            )]),
+            vec![false, false],
+        )?;
+        let Some(part) = omap.parts.get_mut(0) else {
+            panic!("a new map has a part");
+        };
+        part.add_object(LineObject::new(std::rc::Rc::downgrade(&line), curve));
+        part.add_object(AreaObject::new(
+            std::rc::Rc::downgrade(&area),
+            Polygon::new(
+                LineString::from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 0.)]),
+                Vec::new(),
+            ),
+        ));
+
+        let mut bytes = Vec::new();
+        omap.to_svg_writer(&mut bytes, viewport())?;
+        let svg = String::from_utf8_lossy(&bytes);
+
+        let (red, blue) = (svg_layer(&svg, "color-0"), svg_layer(&svg, "color-1"));
+        assert!(
+            blue.contains(r#"d="M0 10C5 5 15 5 20 10""#),
+            "curves are kept"
+        );
+        assert!(blue.contains("stroke-dasharray="), "the line is dashed");
+        assert!(svg.contains(
+            r#"<pattern id="pattern-1" patternUnits="userSpaceOnUse" width="1" height="1""#
+        ));
+        assert!(svg.contains(r##"<path d="M0 -0.25H1M0 0.75H1M0 1.75H1" stroke="#ff0000""##));
+        assert!(
+            red.contains(r##"fill="url(#pattern-1)""##),
+            "the hatch is in its own color"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn writes_png() -> Result<()> {
+        let image = test_map()?.render(viewport(), PositiveF64::try_from(25.4)?)?;
+        let mut bytes = Vec::new();
+        image.to_png_writer(&mut bytes)?;
+
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x14\0\0\0\x0a"));
+        // the empty IEND chunk has a fixed CRC
+        assert!(bytes.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+        Ok(())
+    }
+
+    /// The objects of a PDF by number, checking that the cross-reference table
+    /// gives the offset of every object.
+    fn pdf_objects(pdf: &[u8]) -> Vec<String> {
+        let text = String::from_utf8_lossy(pdf);
+        let Some((_, table)) = text.rsplit_once("\nxref\n") else {
+            panic!("the PDF has a cross-reference table");
+        };
+        let offsets = table
+            .lines()
+            .skip(2)
+            .map_while(|line| line.strip_suffix(" 00000 n "))
+            .filter_map(|offset| offset.parse::<usize>().ok())
+            .collect::<Vec<_>>();
+        offsets
+            .iter()
+            .enumerate()
+            .map(|(index, &offset)| {
+                let object = &pdf[offset..];
+                let header = format!("{} 0 obj\n", index + 1);
+                assert!(
+                    object.starts_with(header.as_bytes()),
+                    "object {index} is at its offset"
+                );
+                let object = &object[header.len()..];
+                let object = &object[..find(object, b"\nendobj").unwrap_or(object.len())];
+                let Some(start) = find(object, b"stream\n") else {
+                    return String::from_utf8_lossy(object).into_owned();
+                };
+                let dictionary = String::from_utf8_lossy(&object[..start]);
+                assert!(
+                    dictionary.contains("/FlateDecode"),
+                    "streams are compressed"
+                );
+                let Some(end) = find(object, b"\nendstream") else {
+                    panic!("object {index} ends its stream");
+                };
+                let data = inflate(&object[start + b"stream\n".len()..end]);
+                format!(
+                    "{dictionary}stream\n{}\nendstream",
+                    String::from_utf8_lossy(&data)
+                )
+            })
+            .collect()
+    }
+
+    fn find(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+        bytes
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+    }
+
+    #[test]
+    fn writes_pdf_in_priority_order() -> Result<()> {
+        let mut bytes = Vec::new();
+        test_map()?.to_pdf_writer(&mut bytes, viewport())?;
+        assert!(bytes.starts_with(b"%PDF-1.4\n"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+
+        let objects = pdf_objects(&bytes);
+        assert!(objects[2].contains("/MediaBox [0 0 56.693 28.346]"));
+        let Some(content) = objects.iter().find(|object| object.contains(" cm\n")) else {
+            panic!("the page has a content stream");
+        };
+        // colors without spot colors are printed in their CMYK values
+        let (Some(blue), Some(red)) = (content.find("1 1 0 0 k"), content.find("0 1 1 0 k")) else {
+            panic!("both colors are used");
+        };
+        assert!(blue < red, "red is above blue");
+        assert!(content.contains("/Overprint gs"));
+        Ok(())
+    }
+
+    #[test]
+    fn writes_pdf_curves_and_dashes() -> Result<()> {
+        let mut omap = Omap::new(NonZeroU32::MIN);
+        let blue = rgb_color(&mut omap, "Blue", 0., 0., 1.)?;
+        let line = omap.symbols.add_symbol(
+            LineSymbol::new(crate::Code::new(101, 0, 0), "Dashed")
+                .with_color(blue.clone())
+                .with_line_width(NonNegativeF64::try_from(0.5)?)
+                .with_dash_style(DashStyle::Dashed {
+                    dash_length: NonNegativeF64::try_from(2.)?,
+                    break_length: NonNegativeF64::try_from(1.)?,
+                    dash_group: GroupDashes::UnGrouped {
+                        half_outer_dashes: false,
+                    },
+                }),
+        );
+        let area = omap
+            .symbols
+            .add_symbol(AreaSymbol::new(crate::Code::new(201, 0, 0), "Area").with_color(blue));
+        let (Some(Symbol::Line(line)), Some(Symbol::Area(area))) = (line.upgrade(), area.upgrade())
+        else {
+            panic!("the symbols are in the symbol set with their types");
+        };
+        let curve = BezierPath::new(
+            BezierString::new(vec![BezierSegment::new(
+                coord! { x: 0., y: 0. },
+                Some((coord! { x: 5., y: 5. }, coord! { x: 15., y: 5. })),
+                coord! { x: 20., y: 0. },
+            )]),
+            vec![false, false],
+        )?;
+        let Some(part) = omap.parts.get_mut(0) else {
+            panic!("a new map has a part");
+        };
+        part.add_object(LineObject::new(
+            std::rc::Rc::downgrade(&line),
+            curve.clone(),
+        ));
+        part.add_object(AreaObject::new(
+            std::rc::Rc::downgrade(&area),
+            BezierPolygon::new(curve, Vec::new())?,
+        ));
+
+        let mut bytes = Vec::new();
+        omap.to_pdf_writer(&mut bytes, viewport())?;
+        let objects = pdf_objects(&bytes);
+        let Some(content) = objects.iter().find(|object| object.contains(" cm\n")) else {
+            panic!("the page has a content stream");
+        };
+        assert!(
+            content.contains("0 0 m\n5 5 15 5 20 0 c\n0 0 l\nh\nf*\n"),
+            "the area is filled along its curve"
+        );
+        assert!(
+            content.contains("0.5 w 0 J ") && content.contains(" d\n0 0 m\n5 5 15 5 20 0 c\nS\n"),
+            "the line is stroked along its curve"
+        );
+        let Some(dashes) = content.split(" M [").nth(1) else {
+            panic!("the line has a dash pattern");
+        };
+        // eight dashes stretched to the length of the curve, and no repetition
+        assert!(dashes.starts_with("1.905 0.952 1.905 "), "{dashes}");
+        assert!(dashes.contains(" 1.905 21.902] 0 d\n"), "{dashes}");
+        Ok(())
+    }
+
+    #[test]
+    fn writes_pdf_spot_color_separations() -> Result<()> {
+        let mut omap = Omap::new(NonZeroU32::MIN);
+        let mut purple = SpotColor::new("Purple", "PURPLE", Cmyk::new(0.35, 0.85, 0., 0.)?);
+        purple.knockout = true;
+        let WeakColor::SpotColor(spot) = omap.colors.push(purple) else {
+            panic!("a spot color is pushed as one");
+        };
+        let WeakColor::SpotColor(black) =
+            omap.colors
+                .push(SpotColor::new("Black", "Black", Cmyk::new(0., 0., 0., 1.)?))
+        else {
+            panic!("a spot color is pushed as one");
+        };
+        let mixed = omap.colors.push(MixedColor::new(
+            "Brown 50%",
+            vec![
+                ColorComponent {
+                    factor: UnitF64::try_from(0.5)?,
+                    color: spot,
+                },
+                ColorComponent {
+                    factor: UnitF64::try_from(0.25)?,
+                    color: black,
+                },
+            ],
+        ));
+        let purple = SymbolColor::Color(omap.colors.iter().next().map_or_else(
+            || panic!("the color set has colors"),
+            crate::colors::Color::downgrade,
+        ));
+        let line = omap.symbols.add_symbol(
+            LineSymbol::new(crate::Code::new(101, 0, 0), "Line")
+                .with_color(SymbolColor::Color(mixed))
+                .with_line_width(NonNegativeF64::try_from(1.)?),
+        );
+        let point = omap.symbols.add_symbol(
+            PointSymbol::new(crate::Code::new(301, 0, 0), "Dot")
+                .with_inner_color(purple)
+                .with_inner_radius(NonNegativeF64::try_from(1.)?),
+        );
+        let (Some(Symbol::Line(line)), Some(Symbol::Point(point))) =
+            (line.upgrade(), point.upgrade())
+        else {
+            panic!("the symbols are in the symbol set with their types");
+        };
+        let Some(part) = omap.parts.get_mut(0) else {
+            panic!("a new map has a part");
+        };
+        part.add_object(LineObject::new(
+            std::rc::Rc::downgrade(&line),
+            LineString::from(vec![(0., 5.), (20., 5.)]),
+        ));
+        part.add_object(PointObject::new(
+            std::rc::Rc::downgrade(&point),
+            Point::new(15., 2.),
+        ));
+
+        let mut bytes = Vec::new();
+        omap.to_pdf_writer(&mut bytes, viewport())?;
+        let objects = pdf_objects(&bytes);
+        let page = &objects[2];
+        assert!(page.contains(
+            "[/Separation /PURPLE /DeviceCMYK << /FunctionType 2 /Domain [0 1] \
+             /C0 [0 0 0 0] /C1 [0.35 0.85 0 0] /N 1 >>]"
+        ));
+        assert!(page.contains("[/DeviceN [/PURPLE /Black] /DeviceCMYK 4 0 R]"));
+        // the fallback adds up the CMYK values of the spot colors by their tints
+        assert!(objects[3].contains(
+            "{ 0 2 index 0.35 mul add 1 index 0 mul add 0 3 index 0.85 mul add 2 index 0 mul add \
+             0 4 index 0 mul add 3 index 0 mul add 0 5 index 0 mul add 4 index 1 mul add \
+             6 4 roll pop pop }"
+        ));
+        let Some(content) = objects.iter().find(|object| object.contains(" cm\n")) else {
+            panic!("the page has a content stream");
+        };
+        assert!(content.contains("q /Overprint gs\n/CS0 cs /CS0 CS 0.5 0.25 scn 0.5 0.25 SCN"));
+        assert!(content.contains("q /Knockout gs\n/CS1 cs /CS1 CS 1 scn 1 SCN"));
+        Ok(())
+    }
+
+    #[test]
+    fn renders_example_map() -> Result<()> {
+        let omap = Omap::from_path("./example_data/from_path.omap")?;
+        let viewport = Rect::new(coord! { x: 1225., y: 480. }, coord! { x: 1255., y: 505. });
+        let image = omap.render(viewport, PositiveF64::try_from(150.)?)?;
+        assert!(
+            image.pixels().chunks_exact(4).any(|pixel| pixel != WHITE),
+            "some objects are drawn"
+        );
+        Ok(())
+    }
+}
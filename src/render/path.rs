@@ -0,0 +1,394 @@
+use std::f64::consts::{PI, TAU};
+
+use geo_types::Coord;
+
+use crate::symbols::{CapStyle, JoinStyle};
+
+/// The longest miter, in half line widths, before a miter join is bevelled.
+pub(super) const MITER_LIMIT: f64 = 4.;
+
+/// A flattened path in map coordinates.
+#[derive(Debug, Clone)]
+pub(super) struct Polyline {
+    pub(super) points: Vec<Coord>,
+    /// Cumulative length at every point.
+    lengths: Vec<f64>,
+    pub(super) closed: bool,
+}
+
+impl Polyline {
+    /// Build a polyline, dropping repeated points.
+    pub(super) fn new(points: impl IntoIterator<Item = Coord>, closed: bool) -> Self {
+        let mut deduplicated: Vec<Coord> = Vec::new();
+        for point in points {
+            if deduplicated.last() != Some(&point) {
+                deduplicated.push(point);
+            }
+        }
+        let mut lengths = Vec::with_capacity(deduplicated.len());
+        let mut length = 0.;
+        for (index, point) in deduplicated.iter().enumerate() {
+            if index > 0 {
+                length += distance(deduplicated[index - 1], *point);
+            }
+            lengths.push(length);
+        }
+        Self {
+            points: deduplicated,
+            lengths,
+            closed,
+        }
+    }
+
+    pub(super) fn length(&self) -> f64 {
+        self.lengths.last().copied().unwrap_or(0.)
+    }
+
+    /// The cumulative length at point `index`.
+    pub(super) fn length_at(&self, index: usize) -> f64 {
+        self.lengths[index]
+    }
+
+    /// The position and direction angle at `distance` along the line.
+    pub(super) fn sample(&self, distance: f64) -> (Coord, f64) {
+        let Some(&last) = self.points.last() else {
+            return (Coord::zero(), 0.);
+        };
+        if self.points.len() == 1 {
+            return (last, 0.);
+        }
+        let segment = self
+            .lengths
+            .partition_point(|&length| length <= distance)
+            .clamp(1, self.points.len() - 1);
+        let (start, end) = (self.points[segment - 1], self.points[segment]);
+        let segment_length = self.lengths[segment] - self.lengths[segment - 1];
+        let t = ((distance - self.lengths[segment - 1]) / segment_length).clamp(0., 1.);
+        (start + (end - start) * t, angle(end - start))
+    }
+
+    /// The direction angle at point `index`, bisecting the adjacent segments.
+    pub(super) fn angle_at(&self, index: usize) -> f64 {
+        let last = self.points.len() - 1;
+        let before = match index {
+            0 if self.closed && last > 0 => Some(self.points[last - 1]),
+            0 => None,
+            _ => Some(self.points[index - 1]),
+        };
+        let after = match index {
+            _ if index == last && self.closed && last > 0 => Some(self.points[1]),
+            _ if index == last => None,
+            _ => Some(self.points[index + 1]),
+        };
+        let point = self.points[index];
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                let incoming = normalize(point - before);
+                let outgoing = normalize(after - point);
+                angle(incoming + outgoing)
+            }
+            (Some(before), None) => angle(point - before),
+            (None, Some(after)) => angle(after - point),
+            (None, None) => 0.,
+        }
+    }
+
+    /// The open part of the line between two distances along it.
+    pub(super) fn slice(&self, from: f64, to: f64) -> Self {
+        let (start, _) = self.sample(from);
+        let (end, _) = self.sample(to);
+        let inner = self
+            .points
+            .iter()
+            .zip(&self.lengths)
+            .filter(|&(_, &length)| length > from && length < to)
+            .map(|(point, _)| *point);
+        Self::new(
+            std::iter::once(start)
+                .chain(inner)
+                .chain(std::iter::once(end)),
+            false,
+        )
+    }
+
+    /// The line shifted sideways by `shift`, to the left for positive values.
+    pub(super) fn offset(&self, shift: f64) -> Self {
+        let count = self.points.len();
+        if count < 2 {
+            return self.clone();
+        }
+        let normals = self
+            .points
+            .windows(2)
+            .map(|pair| left_normal(pair[1] - pair[0]))
+            .collect::<Vec<_>>();
+        let points = (0..count).map(|index| {
+            let before = match index {
+                0 if self.closed => normals.last(),
+                0 => None,
+                _ => normals.get(index - 1),
+            };
+            let after = if index == count - 1 {
+                self.closed.then(|| normals[0])
+            } else {
+                Some(normals[index])
+            };
+            let normal = match (before, after) {
+                (Some(&before), Some(after)) => {
+                    let sum = before + after;
+                    let cosine = dot(normalize(sum), after);
+                    normalize(sum) / cosine.max(1. / MITER_LIMIT)
+                }
+                (Some(&normal), None) | (None, Some(normal)) => normal,
+                (None, None) => Coord::zero(),
+            };
+            self.points[index] + normal * shift
+        });
+        Self::new(points.collect::<Vec<_>>(), self.closed)
+    }
+}
+
+/// Outlines of a stroked line, all oriented counter-clockwise so that
+/// overlapping pieces merge under the non-zero fill rule.
+#[derive(Debug, Default)]
+pub(super) struct Outlines {
+    pub(super) rings: Vec<Vec<Coord>>,
+    /// The largest distance between a circle and its polygon approximation.
+    tolerance: f64,
+}
+
+impl Outlines {
+    pub(super) fn new(tolerance: f64) -> Self {
+        Self {
+            rings: Vec::new(),
+            tolerance,
+        }
+    }
+
+    /// Add a convex ring, turning it counter-clockwise.
+    pub(super) fn push(&mut self, mut ring: Vec<Coord>) {
+        if signed_area(&ring) < 0. {
+            ring.reverse();
+        }
+        self.rings.push(ring);
+    }
+
+    pub(super) fn circle(&mut self, center: Coord, radius: f64) {
+        if radius > 0. {
+            let ring = circle(center, radius, self.tolerance);
+            self.push(ring);
+        }
+    }
+
+    /// Stroke a line with a constant width.
+    pub(super) fn stroke(&mut self, line: &Polyline, width: f64, cap: CapStyle, join: JoinStyle) {
+        let half = width / 2.;
+        if half <= 0. {
+            return;
+        }
+        let points = &line.points;
+        if points.len() == 1 {
+            match cap {
+                CapStyle::Round => self.circle(points[0], half),
+                CapStyle::Square => self.push(square(points[0], half)),
+                CapStyle::Flat | CapStyle::Pointed => {}
+            }
+            return;
+        }
+        for pair in points.windows(2) {
+            let normal = left_normal(pair[1] - pair[0]) * half;
+            self.push(vec![
+                pair[0] + normal,
+                pair[0] - normal,
+                pair[1] - normal,
+                pair[1] + normal,
+            ]);
+        }
+        let last = points.len() - 1;
+        for index in 1..last {
+            self.join(
+                points[index - 1],
+                points[index],
+                points[index + 1],
+                half,
+                join,
+            );
+        }
+        if line.closed && last > 1 {
+            self.join(points[last - 1], points[0], points[1], half, join);
+            return;
+        }
+        for (end, neighbour) in [(points[0], points[1]), (points[last], points[last - 1])] {
+            match cap {
+                CapStyle::Round => self.circle(end, half),
+                CapStyle::Square => {
+                    let outwards = normalize(end - neighbour) * half;
+                    let normal = left_normal(outwards) * half;
+                    self.push(vec![
+                        end + normal,
+                        end - normal,
+                        end + outwards - normal,
+                        end + outwards + normal,
+                    ]);
+                }
+                CapStyle::Flat | CapStyle::Pointed => {}
+            }
+        }
+    }
+
+    /// Stroke a line whose width changes linearly from the first to the last point.
+    pub(super) fn stroke_tapered(&mut self, line: &Polyline, start_width: f64, end_width: f64) {
+        let length = line.length();
+        if length <= 0. {
+            return;
+        }
+        let half_width = |index: usize| {
+            let t = line.length_at(index) / length;
+            let width = start_width + (end_width - start_width) * t;
+            width / 2.
+        };
+        for (index, pair) in line.points.windows(2).enumerate() {
+            let direction = left_normal(pair[1] - pair[0]);
+            let (start, end) = (
+                direction * half_width(index),
+                direction * half_width(index + 1),
+            );
+            self.push(vec![
+                pair[0] + start,
+                pair[0] - start,
+                pair[1] - end,
+                pair[1] + end,
+            ]);
+        }
+        for index in 1..line.points.len() - 1 {
+            self.circle(line.points[index], half_width(index));
+        }
+    }
+
+    fn join(&mut self, before: Coord, point: Coord, after: Coord, half: f64, join: JoinStyle) {
+        let incoming = normalize(point - before);
+        let outgoing = normalize(after - point);
+        let turn = cross(incoming, outgoing);
+        if turn.abs() < 1e-12 && dot(incoming, outgoing) > 0. {
+            return;
+        }
+        if matches!(join, JoinStyle::Round) {
+            self.circle(point, half);
+            return;
+        }
+        // the outer side of the corner is to the right of a left turn
+        let side = if turn > 0. { -half } else { half };
+        let first = point + left_normal(incoming) * side;
+        let second = point + left_normal(outgoing) * side;
+        let bisector = normalize(left_normal(incoming) + left_normal(outgoing));
+        let cosine = dot(bisector, left_normal(outgoing));
+        if matches!(join, JoinStyle::Miter) && cosine > 1. / MITER_LIMIT {
+            let tip = point + bisector * (side / cosine);
+            self.push(vec![point, first, tip, second]);
+        } else {
+            self.push(vec![point, first, second]);
+        }
+    }
+}
+
+/// The intervals along a line of `length` covered by dashes of `dash` length
+/// separated by gaps of `gap` length.
+///
+/// The pattern is stretched so that the line starts and ends with a dash, with
+/// half-length outer dashes when `half_outer_dashes` is set.
+pub(super) fn dash_intervals(
+    length: f64,
+    dash: f64,
+    gap: f64,
+    half_outer_dashes: bool,
+) -> Vec<(f64, f64)> {
+    let period = dash + gap;
+    if dash <= 0. || length <= dash {
+        return vec![(0., length)];
+    }
+    if half_outer_dashes {
+        let count = (length / period).round().max(1.);
+        let scale = length / (count * period);
+        let (dash, period) = (dash * scale, period * scale);
+        let mut intervals = vec![(0., dash / 2.)];
+        intervals.extend((1..count as usize).map(|index| {
+            let center = index as f64 * period;
+            (center - dash / 2., center + dash / 2.)
+        }));
+        intervals.push((length - dash / 2., length));
+        return intervals;
+    }
+    let count = ((length + gap) / period).round().max(1.);
+    let scale = length / (count * dash + (count - 1.) * gap);
+    (0..count as usize)
+        .map(|index| {
+            let start = index as f64 * period * scale;
+            (start, start + dash * scale)
+        })
+        .collect()
+}
+
+/// The approximation of a circle whose edges deviate at most `tolerance` from it.
+pub(super) fn circle(center: Coord, radius: f64, tolerance: f64) -> Vec<Coord> {
+    let steps = if radius > tolerance {
+        (PI / (1. - tolerance / radius).acos())
+            .ceil()
+            .clamp(8., 256.)
+    } else {
+        8.
+    };
+    (0..steps as usize)
+        .map(|step| {
+            let angle = TAU * step as f64 / steps;
+            center + Coord::from((angle.cos(), angle.sin())) * radius
+        })
+        .collect()
+}
+
+fn square(center: Coord, half: f64) -> Vec<Coord> {
+    [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)]
+        .into_iter()
+        .map(|(x, y)| center + Coord::from((x, y)) * half)
+        .collect()
+}
+
+/// Twice the signed area of a ring, positive when counter-clockwise.
+pub(super) fn signed_area(ring: &[Coord]) -> f64 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| cross(*a, *b))
+        .sum()
+}
+
+pub(super) fn distance(a: Coord, b: Coord) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+pub(super) fn angle(direction: Coord) -> f64 {
+    direction.y.atan2(direction.x)
+}
+
+fn normalize(vector: Coord) -> Coord {
+    let length = vector.x.hypot(vector.y);
+    if length > 0. {
+        vector / length
+    } else {
+        Coord::zero()
+    }
+}
+
+fn left_normal(vector: Coord) -> Coord {
+    let unit = normalize(vector);
+    Coord {
+        x: -unit.y,
+        y: unit.x,
+    }
+}
+
+fn dot(a: Coord, b: Coord) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Coord, b: Coord) -> f64 {
+    a.x * b.y - a.y * b.x
+}
@@ -0,0 +1,693 @@
+use std::{cmp::Reverse, collections::BTreeMap, io::Write, rc::Rc};
+
+use geo_types::{Coord, Rect};
+
+use super::{
+    MM_PER_INCH,
+    canvas::{FillRule, Layer, Shape},
+    draw::{Stroke, Surface, TextRun},
+    number,
+    path::MITER_LIMIT,
+    zlib,
+};
+use crate::{
+    Result,
+    colors::{Color, ColorSet},
+    objects::{BezierPath, HorizontalAlign},
+    symbols::{CapStyle, JoinStyle},
+};
+
+/// The largest deviation of flattened parts such as outlines from the exact geometry in mm.
+pub(super) const TOLERANCE: f64 = 0.01;
+/// Points per millimetre.
+const POINTS_PER_MM: f64 = 72. / MM_PER_INCH;
+/// The largest page side in points that PDF readers have to support.
+pub(super) const MAX_PAGE_SIZE: f64 = 14_400.;
+/// The standard fonts by boldness and slant, see [`font_index`].
+const FONTS: [&str; 4] = [
+    "Helvetica",
+    "Helvetica-Bold",
+    "Helvetica-Oblique",
+    "Helvetica-BoldOblique",
+];
+/// The position of the underline below the baseline relative to the font size.
+const UNDERLINE_POSITION: f64 = 0.1;
+/// The thickness of the underline relative to the font size.
+const UNDERLINE_THICKNESS: f64 = 0.05;
+
+/// Collects the content of every color and writes it as a one page PDF.
+///
+/// Every color of the color set is printed on its own separation: spot colors
+/// as `Separation` color spaces named after their spot color, mixed colors as
+/// `DeviceN` combinations of their spot colors, both with a CMYK fallback.
+#[derive(Debug, Default)]
+pub(super) struct PdfSurface {
+    layers: BTreeMap<Layer, Content>,
+    /// Which of the [`FONTS`] are used.
+    fonts: [bool; 4],
+}
+
+/// The content stream of one color.
+#[derive(Debug, Default)]
+struct Content {
+    operators: String,
+    /// The clip shape in effect at the end of the content.
+    clip: Option<Rc<Shape>>,
+}
+
+impl Content {
+    /// The content stream, with the graphics state changed to clip with `clip`.
+    fn clipped(&mut self, clip: Option<&Rc<Shape>>) -> &mut String {
+        let unchanged = match (&self.clip, clip) {
+            (None, None) => true,
+            (Some(current), Some(clip)) => Rc::ptr_eq(current, clip),
+            _ => false,
+        };
+        if !unchanged {
+            if self.clip.take().is_some() {
+                self.operators.push_str("Q\n");
+            }
+            if let Some(clip) = clip {
+                self.operators.push_str("q\n");
+                write_shape(&mut self.operators, clip);
+                self.operators.push_str(match clip.rule {
+                    FillRule::NonZero => "W n\n",
+                    FillRule::EvenOdd => "W* n\n",
+                });
+                self.clip = Some(Rc::clone(clip));
+            }
+        }
+        &mut self.operators
+    }
+}
+
+impl Surface for PdfSurface {
+    fn fill(&mut self, layer: Layer, shape: Shape, clip: Option<&Rc<Shape>>) {
+        if shape.rings.iter().all(|ring| ring.len() < 3) {
+            return;
+        }
+        let operators = self.layers.entry(layer).or_default().clipped(clip);
+        write_shape(operators, &shape);
+        operators.push_str(match shape.rule {
+            FillRule::NonZero => "f\n",
+            FillRule::EvenOdd => "f*\n",
+        });
+    }
+
+    fn has_strokes(&self) -> bool {
+        true
+    }
+
+    fn stroke(&mut self, layer: Layer, stroke: &Stroke<'_>, clip: Option<&Rc<Shape>>) {
+        if stroke.path.is_empty() {
+            return;
+        }
+        let cap = match stroke.cap {
+            CapStyle::Flat | CapStyle::Pointed => 0,
+            CapStyle::Round => 1,
+            CapStyle::Square => 2,
+        };
+        let join = match stroke.join {
+            JoinStyle::Miter => 0,
+            JoinStyle::Round => 1,
+            JoinStyle::Bevel => 2,
+        };
+        let dashes = stroke.dashes.as_ref().map_or_else(
+            || "[] 0".to_owned(),
+            |(dashes, length)| dash_pattern(dashes, *length),
+        );
+        let operators = self.layers.entry(layer).or_default().clipped(clip);
+        operators.push_str(&format!(
+            "{} w {cap} J {join} j {} M {dashes} d\n",
+            number(stroke.width),
+            number(MITER_LIMIT)
+        ));
+        write_curve(operators, stroke.path);
+        operators.push_str(if stroke.path.is_closed() {
+            "s\n"
+        } else {
+            "S\n"
+        });
+    }
+
+    fn has_fonts(&self) -> bool {
+        true
+    }
+
+    fn text(&mut self, layer: Layer, run: &TextRun<'_>) {
+        let symbol = run.symbol;
+        let font = font_index(symbol.bold, symbol.italic);
+        self.fonts[font] = true;
+        let size = symbol.font_size.get();
+        let spacing = symbol.character_spacing * size;
+        let (sin, cos) = run.placement.rotation.sin_cos();
+
+        let operators = self.layers.entry(layer).or_default().clipped(None);
+        let mut underlines = Vec::new();
+        operators.push_str("BT\n");
+        operators.push_str(&format!(
+            "/F{font} {} Tf {} Tc\n",
+            number(size),
+            number(spacing)
+        ));
+        match run.outline {
+            Some(width) => {
+                operators.push_str(&format!("{} w 1 j 1 J 2 Tr\n", number(width)));
+            }
+            None => operators.push_str("0 Tr\n"),
+        }
+        for (line, from, to) in &run.layout.lines {
+            let text = encode(line);
+            let width = text_width(&text, symbol.bold) * size
+                + spacing * text.len().saturating_sub(1) as f64;
+            let x = match run.align {
+                HorizontalAlign::Left => from.x,
+                HorizontalAlign::HCenter => f64::midpoint(from.x, to.x) - width / 2.,
+                HorizontalAlign::Right => to.x - width,
+            };
+            let origin = run.placement.apply(Coord { x, y: from.y });
+            operators.push_str(&format!(
+                "{} {} {} {} {} {} Tm {} Tj\n",
+                number(cos),
+                number(sin),
+                number(-sin),
+                number(cos),
+                number(origin.x),
+                number(origin.y),
+                literal(&text)
+            ));
+            if symbol.underline && run.outline.is_none() {
+                let y = from.y - UNDERLINE_POSITION * size;
+                let thickness = UNDERLINE_THICKNESS * size;
+                underlines.push(
+                    [
+                        (x, y),
+                        (x + width, y),
+                        (x + width, y - thickness),
+                        (x, y - thickness),
+                    ]
+                    .map(|(x, y)| run.placement.apply(Coord { x, y }))
+                    .to_vec(),
+                );
+            }
+        }
+        operators.push_str("ET\n");
+        if !underlines.is_empty() {
+            write_rings(operators, &underlines);
+            operators.push_str("f\n");
+        }
+    }
+}
+
+impl PdfSurface {
+    /// Write the page showing `viewport` with the content of every color in
+    /// priority order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a color is already mutably borrowed, has no CMYK value
+    /// or the file cannot be written.
+    pub(super) fn finish<W: Write>(
+        self,
+        writer: &mut W,
+        colors: &ColorSet,
+        viewport: Rect,
+    ) -> Result<()> {
+        let mut objects = Objects::default();
+        let catalog = objects.reserve();
+        let pages = objects.reserve();
+        let page = objects.reserve();
+
+        let mut content = String::new();
+        let min = viewport.min();
+        content.push_str(&format!(
+            "{POINTS_PER_MM} 0 0 {POINTS_PER_MM} {} {} cm\n",
+            -min.x * POINTS_PER_MM,
+            -min.y * POINTS_PER_MM
+        ));
+        content.push_str(&format!(
+            "{} {} {} {} re W n\n",
+            number(min.x),
+            number(min.y),
+            number(viewport.width()),
+            number(viewport.height())
+        ));
+        let mut color_spaces = ColorSpaces::default();
+        let colors = colors.iter().collect::<Vec<_>>();
+        for (layer, layer_content) in self.layers {
+            let (ink, knockout) = match layer {
+                Layer::Color(Reverse(priority)) => {
+                    let Some(color) = colors.get(priority) else {
+                        continue;
+                    };
+                    (Ink::of(color)?, color.is_knockout()?)
+                }
+                Layer::RegistrationBlack => (Ink::registration(), false),
+            };
+            let tints = ink.select(&mut objects, &mut color_spaces);
+            let state = if knockout { "/Knockout" } else { "/Overprint" };
+            content.push_str(&format!("q {state} gs\n{tints}\n"));
+            content.push_str(&layer_content.operators);
+            if layer_content.clip.is_some() {
+                content.push_str("Q\n");
+            }
+            content.push_str("Q\n");
+        }
+
+        let mut fonts = String::new();
+        for (index, font) in FONTS.iter().enumerate() {
+            if self.fonts[index] {
+                let id = objects.push(format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{font} /Encoding /WinAnsiEncoding >>"
+                ));
+                fonts.push_str(&format!(" /F{index} {id} 0 R"));
+            }
+        }
+        let contents = objects.push(stream("", &content));
+
+        objects.set(catalog, format!("<< /Type /Catalog /Pages {pages} 0 R >>"));
+        objects.set(
+            pages,
+            format!("<< /Type /Pages /Kids [{page} 0 R] /Count 1 >>"),
+        );
+        objects.set(
+            page,
+            format!(
+                "<< /Type /Page /Parent {pages} 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /ColorSpace <<{} >> /Font <<{fonts} >> \
+                 /ExtGState << /Overprint << /Type /ExtGState /OP true /op true /OPM 1 >> \
+                 /Knockout << /Type /ExtGState /OP false /op false >> >> >> \
+                 /Contents {contents} 0 R >>",
+                number(viewport.width() * POINTS_PER_MM),
+                number(viewport.height() * POINTS_PER_MM),
+                color_spaces.resources,
+            ),
+        );
+        objects.write(writer, catalog)
+    }
+}
+
+/// The objects of a PDF file, numbered from 1.
+#[derive(Debug, Default)]
+struct Objects(Vec<Vec<u8>>);
+
+impl Objects {
+    /// Reserve a number for an object written later with [`Self::set`].
+    fn reserve(&mut self) -> usize {
+        self.push(String::new())
+    }
+
+    fn push(&mut self, object: impl Into<Vec<u8>>) -> usize {
+        self.0.push(object.into());
+        self.0.len()
+    }
+
+    fn set(&mut self, id: usize, object: String) {
+        self.0[id - 1] = object.into();
+    }
+
+    fn write<W: Write>(&self, writer: &mut W, root: usize) -> Result<()> {
+        // the binary comment marks the file as binary for transfer programs
+        let mut file = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.0.len());
+        for (index, object) in self.0.iter().enumerate() {
+            offsets.push(file.len());
+            file.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            file.extend_from_slice(object);
+            file.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = file.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.0.len() + 1);
+        for offset in offsets {
+            table.push_str(&format!("{offset:010} 00000 n \n"));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root {root} 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.0.len() + 1
+        ));
+        file.extend_from_slice(table.as_bytes());
+        writer.write_all(&file)?;
+        Ok(())
+    }
+}
+
+/// How a color is printed.
+#[derive(Debug)]
+enum Ink {
+    /// On the plates of spot colors.
+    Spots(Vec<Spot>),
+    /// In process colors, for mixed colors without spot color components.
+    Process([f64; 4]),
+}
+
+/// The amount of one spot color in a color.
+#[derive(Debug)]
+struct Spot {
+    name: String,
+    /// The CMYK value of the full spot color, used where it is not printed separately.
+    cmyk: [f64; 4],
+    tint: f64,
+}
+
+impl Ink {
+    fn of(color: &Color) -> Result<Self> {
+        match color {
+            Color::SpotColor(spot) => {
+                let spot = spot.try_borrow()?;
+                let name = if spot.spotcolor_name.is_empty() {
+                    &spot.color_name
+                } else {
+                    &spot.spotcolor_name
+                };
+                Ok(Self::Spots(vec![Spot {
+                    name: name.clone(),
+                    cmyk: spot.cmyk()?.as_rounded_fractions(4),
+                    tint: 1.,
+                }]))
+            }
+            Color::MixedColor(mixed) => {
+                let mixed = mixed.try_borrow()?;
+                let mut spots = Vec::<Spot>::new();
+                for component in &mixed.components {
+                    let Some(color) = component.color.upgrade() else {
+                        continue;
+                    };
+                    let Self::Spots(mut spot) = Self::of(&Color::SpotColor(color))? else {
+                        continue;
+                    };
+                    let Some(mut spot) = spot.pop() else {
+                        continue;
+                    };
+                    spot.tint = component.factor.get();
+                    // a spot color may only appear once in a DeviceN color space
+                    match spots.iter_mut().find(|other| other.name == spot.name) {
+                        Some(other) => other.tint = (other.tint + spot.tint).min(1.),
+                        None => spots.push(spot),
+                    }
+                }
+                if spots.is_empty() {
+                    Ok(Self::Process(mixed.cmyk()?.as_rounded_fractions(4)))
+                } else {
+                    Ok(Self::Spots(spots))
+                }
+            }
+        }
+    }
+
+    /// Registration black, printed on every plate.
+    fn registration() -> Self {
+        Self::Spots(vec![Spot {
+            name: "All".to_owned(),
+            cmyk: [1.; 4],
+            tint: 1.,
+        }])
+    }
+
+    /// The operators that select the ink for filling and stroking, adding its
+    /// color space to `spaces`.
+    fn select(&self, objects: &mut Objects, spaces: &mut ColorSpaces) -> String {
+        match self {
+            Self::Process(cmyk) => {
+                let cmyk = cmyk.map(number).join(" ");
+                format!("{cmyk} k {cmyk} K")
+            }
+            Self::Spots(spots) => {
+                let name = spaces.name(spots, objects);
+                let tints = spots
+                    .iter()
+                    .map(|spot| number(spot.tint))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("{name} cs {name} CS {tints} scn {tints} SCN")
+            }
+        }
+    }
+}
+
+/// The color spaces of the page, each defined once.
+#[derive(Debug, Default)]
+struct ColorSpaces {
+    /// The spot colors of every color space with their CMYK fallbacks.
+    spaces: Vec<Vec<(String, [f64; 4])>>,
+    /// The entries of the color space resource dictionary.
+    resources: String,
+}
+
+impl ColorSpaces {
+    /// The resource name of the color space of `spots`, defining it if it is new.
+    fn name(&mut self, spots: &[Spot], objects: &mut Objects) -> String {
+        let key = spots
+            .iter()
+            .map(|spot| (spot.name.clone(), spot.cmyk))
+            .collect::<Vec<_>>();
+        if let Some(index) = self.spaces.iter().position(|space| *space == key) {
+            return format!("/CS{index}");
+        }
+        let name = format!("/CS{}", self.spaces.len());
+        self.spaces.push(key);
+
+        let space = if let [spot] = spots {
+            format!(
+                "[/Separation {} /DeviceCMYK << /FunctionType 2 /Domain [0 1] \
+                 /C0 [0 0 0 0] /C1 [{}] /N 1 >>]",
+                pdf_name(&spot.name),
+                spot.cmyk.map(number).join(" ")
+            )
+        } else {
+            let names = spots
+                .iter()
+                .map(|spot| pdf_name(&spot.name))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let domain = "0 1 ".repeat(spots.len());
+            let function = objects.push(stream(
+                &format!(
+                    "/FunctionType 4 /Domain [{}] /Range [0 1 0 1 0 1 0 1] ",
+                    domain.trim_end()
+                ),
+                &tint_transform(spots),
+            ));
+            format!("[/DeviceN [{names}] /DeviceCMYK {function} 0 R]")
+        };
+        self.resources.push_str(&format!(" {name} {space}"));
+        name
+    }
+}
+
+/// A PostScript calculator function adding up the CMYK fallbacks of spot colors
+/// weighted by their tints. The function range limits the sums to 1.
+fn tint_transform(spots: &[Spot]) -> String {
+    let count = spots.len();
+    let mut code = String::from("{");
+    for channel in 0..4 {
+        code.push_str(" 0");
+        for (index, spot) in spots.iter().enumerate() {
+            // the tint lies below the other tints, the finished channels and the sum
+            let depth = channel + count - index;
+            code.push_str(&format!(
+                " {depth} index {} mul add",
+                number(spot.cmyk[channel])
+            ));
+        }
+    }
+    code.push_str(&format!(" {} 4 roll", count + 4));
+    code.push_str(&" pop".repeat(count));
+    code.push_str(" }");
+    code
+}
+
+/// A stream object of the data compressed with `FlateDecode`.
+fn stream(dictionary: &str, data: &str) -> Vec<u8> {
+    let data = zlib::compress(data.as_bytes());
+    let mut object = format!(
+        "<< {dictionary}/Filter /FlateDecode /Length {} >>\nstream\n",
+        data.len()
+    )
+    .into_bytes();
+    object.extend_from_slice(&data);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
+
+/// Write the rings of a shape, as cubic Bézier curves where the shape has them.
+fn write_shape(operators: &mut String, shape: &Shape) {
+    if shape.curves.is_empty() {
+        write_rings(operators, &shape.rings);
+        return;
+    }
+    for ring in shape.curves.iter().filter(|ring| !ring.is_empty()) {
+        write_curve(operators, ring);
+        operators.push_str("h\n");
+    }
+}
+
+/// Write a path with its straight segments and cubic Bézier curves.
+fn write_curve(operators: &mut String, path: &BezierPath) {
+    let point = |point: Coord| format!("{} {}", number(point.x), number(point.y));
+    for (index, (segment, _)) in path.segments().enumerate() {
+        if index == 0 {
+            operators.push_str(&format!("{} m\n", point(segment.start())));
+        }
+        match segment.handles() {
+            Some((first, second)) => operators.push_str(&format!(
+                "{} {} {} c\n",
+                point(first),
+                point(second),
+                point(segment.end())
+            )),
+            None => operators.push_str(&format!("{} l\n", point(segment.end()))),
+        }
+    }
+}
+
+/// A dash pattern that draws the nonempty `dashes` at their intervals along a
+/// line of `length` and nothing after the last one: the gap before the first
+/// dash closes the pattern, which starts at that gap.
+fn dash_pattern(dashes: &[(f64, f64)], length: f64) -> String {
+    let Some(&(first, _)) = dashes.first() else {
+        return "[] 0".to_owned();
+    };
+    let mut values = Vec::with_capacity(dashes.len() * 2);
+    for (index, &(from, to)) in dashes.iter().enumerate() {
+        if index > 0 {
+            values.push(from - dashes[index - 1].1);
+        }
+        values.push(to - from);
+    }
+    // long enough that the pattern does not repeat along the line
+    values.push(length + first);
+    let phase = if first > 0. {
+        values.iter().sum::<f64>() - first
+    } else {
+        0.
+    };
+    let values = values.into_iter().map(number).collect::<Vec<_>>().join(" ");
+    format!("[{values}] {}", number(phase))
+}
+
+fn write_rings(operators: &mut String, rings: &[Vec<Coord>]) {
+    for ring in rings.iter().filter(|ring| ring.len() >= 3) {
+        for (index, point) in ring.iter().enumerate() {
+            let operator = if index == 0 { "m" } else { "l" };
+            operators.push_str(&format!(
+                "{} {} {operator}\n",
+                number(point.x),
+                number(point.y)
+            ));
+        }
+        operators.push_str("h\n");
+    }
+}
+
+/// A name object, escaping bytes that cannot appear in names.
+fn pdf_name(name: &str) -> String {
+    let mut escaped = String::from("/");
+    for byte in name.bytes() {
+        if byte.is_ascii_graphic() && !b"()<>[]{}/%#".contains(&byte) {
+            escaped.push(char::from(byte));
+        } else {
+            escaped.push_str(&format!("#{byte:02X}"));
+        }
+    }
+    escaped
+}
+
+/// A literal string object.
+fn literal(text: &[u8]) -> String {
+    let mut escaped = String::from("(");
+    for &byte in text {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(char::from(byte));
+            }
+            b' '..=b'~' => escaped.push(char::from(byte)),
+            _ => {
+                escaped.push_str(&format!("\\{byte:03o}"));
+            }
+        }
+    }
+    escaped.push(')');
+    escaped
+}
+
+/// Encode text in the Latin-1 part of `WinAnsiEncoding`, replacing characters
+/// the standard fonts cannot show with question marks.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|character| {
+            u8::try_from(character)
+                .ok()
+                .filter(|byte| matches!(byte, b' '..=b'~' | 0xa0..=0xff))
+                .unwrap_or(b'?')
+        })
+        .collect()
+}
+
+fn font_index(bold: bool, italic: bool) -> usize {
+    usize::from(bold) + 2 * usize::from(italic)
+}
+
+/// The width of encoded text relative to the font size. Oblique fonts have the
+/// widths of their upright fonts.
+fn text_width(text: &[u8], bold: bool) -> f64 {
+    let (ascii, latin) = if bold {
+        (&HELVETICA_BOLD_ASCII, &HELVETICA_BOLD_LATIN)
+    } else {
+        (&HELVETICA_ASCII, &HELVETICA_LATIN)
+    };
+    let units = text
+        .iter()
+        .map(|&byte| match byte {
+            b' '..=b'~' => ascii[usize::from(byte - b' ')],
+            0xa0..=0xff => latin[usize::from(byte - 0xa0)],
+            _ => 0,
+        })
+        .map(u32::from)
+        .sum::<u32>();
+    f64::from(units) / 1000.
+}
+
+/// Glyph widths of Helvetica from space to tilde in thousandths of the font size.
+#[rustfmt::skip]
+const HELVETICA_ASCII: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Glyph widths of Helvetica from no-break space to ÿ.
+#[rustfmt::skip]
+const HELVETICA_LATIN: [u16; 96] = [
+    278, 333, 556, 556, 556, 556, 260, 556, 333, 737, 370, 556, 584, 333, 737, 333,
+    400, 584, 333, 333, 333, 556, 537, 278, 333, 333, 365, 556, 834, 834, 834, 611,
+    667, 667, 667, 667, 667, 667, 1000, 722, 667, 667, 667, 667, 278, 278, 278, 278,
+    722, 722, 778, 778, 778, 778, 778, 584, 778, 722, 722, 722, 722, 667, 667, 611,
+    556, 556, 556, 556, 556, 556, 889, 500, 556, 556, 556, 556, 278, 278, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 584, 611, 556, 556, 556, 556, 500, 556, 500,
+];
+
+/// Glyph widths of Helvetica-Bold from space to tilde.
+#[rustfmt::skip]
+const HELVETICA_BOLD_ASCII: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Glyph widths of Helvetica-Bold from no-break space to ÿ.
+#[rustfmt::skip]
+const HELVETICA_BOLD_LATIN: [u16; 96] = [
+    278, 333, 556, 556, 556, 556, 280, 556, 333, 737, 370, 556, 584, 333, 737, 333,
+    400, 584, 333, 333, 333, 611, 556, 278, 333, 333, 365, 556, 834, 834, 834, 611,
+    722, 722, 722, 722, 722, 722, 1000, 722, 667, 667, 667, 667, 278, 278, 278, 278,
+    722, 722, 778, 778, 778, 778, 778, 584, 778, 722, 722, 722, 722, 667, 667, 611,
+    556, 556, 556, 556, 556, 556, 889, 556, 556, 556, 556, 556, 278, 278, 278, 278,
+    611, 611, 611, 611, 611, 611, 611, 584, 611, 611, 611, 611, 611, 556, 611, 556,
+];
@@ -0,0 +1,71 @@
+use std::io::Write;
+
+use super::zlib;
+use crate::Result;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Bit depth 8 and color type 6, 8-bit RGBA.
+const RGBA_8: [u8; 2] = [8, 6];
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut value = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                0xedb8_8320 ^ (value >> 1)
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[index] = value;
+        index += 1;
+    }
+    table
+};
+
+/// Write RGBA pixels, row by row, as a PNG image.
+pub(super) fn write<W: Write>(
+    writer: &mut W,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> Result<()> {
+    writer.write_all(&SIGNATURE)?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&RGBA_8);
+    // deflate compression, adaptive filtering, no interlace
+    header.extend_from_slice(&[0, 0, 0]);
+    write_chunk(writer, b"IHDR", &header)?;
+
+    // every scanline starts with filter type 0
+    let row_length = width as usize * 4;
+    let mut scanlines = Vec::with_capacity((row_length + 1) * height as usize);
+    for row in pixels.chunks_exact(row_length.max(1)) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+    write_chunk(writer, b"IDAT", &zlib::compress(&scanlines))?;
+    write_chunk(writer, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> Result<()> {
+    writer.write_all(&u32::try_from(data.len())?.to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc_update(crc_update(u32::MAX, kind), data);
+    writer.write_all(&(crc ^ u32::MAX).to_be_bytes())?;
+    Ok(())
+}
+
+fn crc_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
@@ -0,0 +1,834 @@
+use std::{borrow::Cow, cmp::Reverse, collections::BTreeMap, io::Write};
+
+use geo_types::{Coord, Rect};
+use quick_xml::{
+    Writer,
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+};
+
+use super::{
+    canvas::Layer,
+    draw::{
+        CULL_MARGIN, DashLayout, Path, PathSymbol, Placement, TextLayout, dash_sections, flatten,
+        for_each_path_symbol, intersect, is_visible, line_extent, line_point_symbols, path_bounds,
+        pattern_angle,
+    },
+    number,
+    path::{Outlines, dash_intervals},
+};
+use crate::{
+    Result,
+    colors::{ColorSet, Rgb, SymbolColor},
+    objects::{BezierPath, HorizontalAlign, MapObject, PatternRotation, TextObject},
+    symbols::{
+        AreaSymbol, BorderStyle, CapStyle, DashStyle, Element, FillPattern, FramingMode, JoinStyle,
+        LineSymbol, PointSymbol, Symbol, TextSymbol,
+    },
+};
+
+/// The largest deviation, in mm, of flattened parts such as borders from the exact geometry.
+const TOLERANCE: f64 = 0.005;
+
+/// Turns map objects into SVG elements, collected per color.
+#[derive(Debug)]
+pub(super) struct SvgPainter<'a> {
+    colors: &'a ColorSet,
+    /// The map coordinates of the top left corner of the drawing.
+    top_left: Coord,
+    /// The viewport grown by [`CULL_MARGIN`].
+    view: Rect,
+    show_helper_symbols: bool,
+    layers: BTreeMap<Layer, Vec<u8>>,
+    defs: Vec<u8>,
+    patterns: usize,
+}
+
+impl<'a> SvgPainter<'a> {
+    pub(super) fn new(colors: &'a ColorSet, viewport: Rect, show_helper_symbols: bool) -> Self {
+        let margin = Coord {
+            x: CULL_MARGIN,
+            y: CULL_MARGIN,
+        };
+        Self {
+            colors,
+            top_left: Coord {
+                x: viewport.min().x,
+                y: viewport.max().y,
+            },
+            view: Rect::new(viewport.min() - margin, viewport.max() + margin),
+            show_helper_symbols,
+            layers: BTreeMap::new(),
+            defs: Vec::new(),
+            patterns: 0,
+        }
+    }
+
+    /// Write the SVG document with a layer for every color, from the lowest priority
+    /// color up to registration black.
+    pub(super) fn finish<W: Write>(
+        mut self,
+        writer: &mut W,
+        (width, height): (f64, f64),
+        background: Option<Rgb>,
+    ) -> Result<()> {
+        let mut writer = Writer::new_with_indent(writer, b' ', 1);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        let (width, height) = (number(width), number(height));
+        writer.write_event(Event::Start(BytesStart::new("svg").with_attributes([
+            ("xmlns", "http://www.w3.org/2000/svg"),
+            (
+                "xmlns:inkscape",
+                "http://www.inkscape.org/namespaces/inkscape",
+            ),
+            ("width", format!("{width}mm").as_str()),
+            ("height", format!("{height}mm").as_str()),
+            ("viewBox", format!("0 0 {width} {height}").as_str()),
+        ])))?;
+
+        if !self.defs.is_empty() {
+            writer.write_event(Event::Start(BytesStart::new("defs")))?;
+            writer.get_mut().write_all(&self.defs)?;
+            writer.write_event(Event::End(BytesEnd::new("defs")))?;
+        }
+        if let Some(background) = background {
+            writer.write_event(Event::Empty(BytesStart::new("rect").with_attributes([
+                ("width", width.as_str()),
+                ("height", height.as_str()),
+                ("fill", hex(background).as_str()),
+            ])))?;
+        }
+
+        let colors = self.colors.iter().enumerate().collect::<Vec<_>>();
+        for (priority, color) in colors.into_iter().rev() {
+            let content = self.layers.remove(&Layer::Color(Reverse(priority)));
+            let id = format!("color-{priority}");
+            write_layer(&mut writer, &id, &color.name()?, content)?;
+        }
+        if let Some(content) = self.layers.remove(&Layer::RegistrationBlack) {
+            write_layer(
+                &mut writer,
+                "registration-black",
+                "Registration black",
+                Some(content),
+            )?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("svg")))?;
+        Ok(())
+    }
+
+    /// Draw an object with its symbol, skipping hidden symbols.
+    pub(super) fn draw_object(&mut self, object: &MapObject) -> Result<()> {
+        match object {
+            MapObject::Point(point) => {
+                let Some(symbol) = point.symbol.upgrade() else {
+                    return Ok(());
+                };
+                let symbol = symbol.try_borrow()?;
+                let position = point.geometry().0;
+                if is_visible(&symbol.common, self.show_helper_symbols)
+                    && intersect(Rect::new(position, position), self.view).is_some()
+                {
+                    self.place_point_symbol(&symbol, position, point.rotation)?;
+                }
+            }
+            MapObject::Line(line) => {
+                if let Some(symbol) = line.symbol.upgrade()
+                    && self.in_view(line.geometry())
+                {
+                    let paths = std::slice::from_ref(line.geometry());
+                    self.path_symbol(&symbol, paths, &PatternRotation::default())?;
+                }
+            }
+            MapObject::Area(area) => {
+                if let Some(symbol) = area.symbol.upgrade()
+                    && self.in_view(area.geometry().exterior())
+                {
+                    let rings = std::iter::once(area.geometry().exterior())
+                        .chain(area.geometry().interiors())
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    self.path_symbol(&symbol, &rings, &area.pattern_rotation)?;
+                }
+            }
+            MapObject::Text(text) => {
+                if let Some(symbol) = text.symbol.upgrade() {
+                    let symbol = symbol.try_borrow()?;
+                    if is_visible(&symbol.common, self.show_helper_symbols) {
+                        self.text(&symbol, text)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn in_view(&self, path: &BezierPath) -> bool {
+        path_bounds(path).is_some_and(|bounds| intersect(bounds, self.view).is_some())
+    }
+
+    /// The layer and SVG color value of a symbol color.
+    fn paint(&self, color: &SymbolColor) -> Result<Option<(Layer, String)>> {
+        Ok(match color {
+            SymbolColor::Color(weak) => {
+                let Some(color) = weak.upgrade() else {
+                    return Ok(None);
+                };
+                let Some(priority) = self.colors.priority_of_color(&color) else {
+                    return Ok(None);
+                };
+                Some((Layer::Color(Reverse(priority)), hex(color.rgb()?)))
+            }
+            SymbolColor::RegistrationBlack => {
+                Some((Layer::RegistrationBlack, "#000000".to_owned()))
+            }
+            SymbolColor::NoColor => None,
+        })
+    }
+
+    fn element(&mut self, layer: Layer, element: BytesStart<'_>) -> Result<()> {
+        self.layer(layer).write_event(Event::Empty(element))?;
+        Ok(())
+    }
+
+    /// The SVG coordinates of a map coordinate.
+    fn coordinates(&self, point: Coord) -> (String, String) {
+        (
+            number(point.x - self.top_left.x),
+            number(self.top_left.y - point.y),
+        )
+    }
+
+    fn point(&self, point: Coord) -> String {
+        let (x, y) = self.coordinates(point);
+        format!("{x} {y}")
+    }
+
+    /// SVG path data with the cubic Bézier curves of a path.
+    fn bezier_data(&self, path: &BezierPath, data: &mut String) {
+        for (index, (segment, _)) in path.segments().enumerate() {
+            if index == 0 {
+                data.push_str(&format!("M{}", self.point(segment.start())));
+            }
+            match segment.handles() {
+                Some((first, second)) => data.push_str(&format!(
+                    "C{} {} {}",
+                    self.point(first),
+                    self.point(second),
+                    self.point(segment.end())
+                )),
+                None => data.push_str(&format!("L{}", self.point(segment.end()))),
+            }
+        }
+        if path.is_closed() {
+            data.push('Z');
+        }
+    }
+
+    /// SVG path data with the straight segments of rings or lines.
+    fn polyline_data(&self, points: &[Coord], closed: bool, data: &mut String) {
+        for (index, point) in points.iter().enumerate() {
+            let command = if index == 0 { 'M' } else { 'L' };
+            data.push(command);
+            data.push_str(&self.point(*point));
+        }
+        if closed {
+            data.push('Z');
+        }
+    }
+
+    /// Draw a line, area or combined symbol along the paths of an object.
+    fn path_symbol(
+        &mut self,
+        symbol: &Symbol,
+        paths: &[BezierPath],
+        pattern_rotation: &PatternRotation,
+    ) -> Result<()> {
+        for_each_path_symbol(
+            symbol,
+            self.show_helper_symbols,
+            &mut |component| match component {
+                PathSymbol::Line(line) => {
+                    for path in paths {
+                        self.line_symbol(line, path)?;
+                    }
+                    Ok(())
+                }
+                PathSymbol::Area(area) => self.area_symbol(area, paths, pattern_rotation, 0.),
+            },
+        )
+    }
+
+    /// Draw a point symbol at `position`, turned by `rotation` if the symbol is rotatable.
+    fn place_point_symbol(
+        &mut self,
+        symbol: &PointSymbol,
+        position: Coord,
+        rotation: f64,
+    ) -> Result<()> {
+        let rotation = if symbol.is_rotatable { rotation } else { 0. };
+        self.point_symbol(symbol, Placement::new(position, rotation))
+    }
+
+    fn point_symbol(&mut self, symbol: &PointSymbol, placement: Placement) -> Result<()> {
+        let (cx, cy) = self.coordinates(placement.origin);
+        let (cx, cy) = (cx.as_str(), cy.as_str());
+        let inner_radius = symbol.inner_radius.get();
+        let outer_width = symbol.outer_width.get();
+        if inner_radius > 0.
+            && let Some((layer, color)) = self.paint(&symbol.inner_color)?
+        {
+            self.element(
+                layer,
+                BytesStart::new("circle").with_attributes([
+                    ("cx", cx),
+                    ("cy", cy),
+                    ("r", number(inner_radius).as_str()),
+                    ("fill", color.as_str()),
+                ]),
+            )?;
+        }
+        if outer_width > 0.
+            && let Some((layer, color)) = self.paint(&symbol.outer_color)?
+        {
+            self.element(
+                layer,
+                BytesStart::new("circle").with_attributes([
+                    ("cx", cx),
+                    ("cy", cy),
+                    ("r", number(inner_radius + outer_width / 2.).as_str()),
+                    ("fill", "none"),
+                    ("stroke", color.as_str()),
+                    ("stroke-width", number(outer_width).as_str()),
+                ]),
+            )?;
+        }
+
+        for element in &symbol.elements {
+            match element {
+                Element::Point { symbol, object } => {
+                    let rotation = if symbol.is_rotatable {
+                        object.rotation
+                    } else {
+                        0.
+                    };
+                    let inner = Placement::new(object.geometry().0, rotation);
+                    self.point_symbol(symbol, placement.then(inner))?;
+                }
+                Element::Line { symbol, object } => {
+                    let path = object
+                        .geometry()
+                        .clone()
+                        .transform(|point| placement.apply(point));
+                    self.line_symbol(symbol, &path)?;
+                }
+                Element::Area { symbol, object } => {
+                    let rings = std::iter::once(object.geometry().exterior())
+                        .chain(object.geometry().interiors())
+                        .map(|ring| ring.clone().transform(|point| placement.apply(point)))
+                        .collect::<Vec<_>>();
+                    let pattern_rotation = PatternRotation {
+                        rotation: object.pattern_rotation.rotation,
+                        coord: placement.apply(object.pattern_rotation.coord),
+                    };
+                    self.area_symbol(symbol, &rings, &pattern_rotation, placement.rotation)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn line_symbol(&mut self, symbol: &LineSymbol, path: &BezierPath) -> Result<()> {
+        let Some(flattened) = flatten(path, Placement::IDENTITY, TOLERANCE)? else {
+            return Ok(());
+        };
+        let Some(extent) = line_extent(symbol, &flattened) else {
+            return Ok(());
+        };
+        let native = matches!(extent, Cow::Borrowed(_));
+        self.main_line(symbol, native.then_some(path), &extent)?;
+        self.borders(symbol, &extent)?;
+        for (point_symbol, position, angle) in line_point_symbols(symbol, &extent) {
+            self.place_point_symbol(point_symbol, position, angle)?;
+        }
+        Ok(())
+    }
+
+    /// Stroke the main line, as the Bézier curves of `path` if given, or else as the
+    /// flattened `extent`.
+    fn main_line(
+        &mut self,
+        symbol: &LineSymbol,
+        path: Option<&BezierPath>,
+        extent: &Path,
+    ) -> Result<()> {
+        let width = symbol.line_width.get();
+        let Some((layer, color)) = self.paint(&symbol.color)? else {
+            return Ok(());
+        };
+        if width <= 0. {
+            return Ok(());
+        }
+        let is_dashed = matches!(symbol.dash_style, DashStyle::Dashed { .. });
+        if matches!(symbol.cap_style, CapStyle::Pointed) && !is_dashed && !extent.line.closed {
+            // tapered ends have no SVG equivalent, so the line is drawn as its outline
+            let mut outlines = Outlines::new(TOLERANCE);
+            let line = &extent.line;
+            let length = line.length();
+            let (mut start, mut end) = (symbol.start_offset.get(), symbol.end_offset.get());
+            if start + end > length {
+                let scale = length / (start + end);
+                start *= scale;
+                end *= scale;
+            }
+            outlines.stroke_tapered(&line.slice(0., start), 0., width);
+            let middle = line.slice(start, length - end);
+            outlines.stroke(&middle, width, CapStyle::Flat, symbol.join_style);
+            outlines.stroke_tapered(&line.slice(length - end, length), width, 0.);
+            let mut data = String::new();
+            for ring in &outlines.rings {
+                self.polyline_data(ring, true, &mut data);
+            }
+            return self.element(
+                layer,
+                BytesStart::new("path")
+                    .with_attributes([("d", data.as_str()), ("fill", color.as_str())]),
+            );
+        }
+
+        let polylines = extent.sections();
+        let beziers = path
+            .map(dash_sections)
+            .filter(|beziers| beziers.len() == polylines.len())
+            .unwrap_or_default();
+        let cap = line_cap(symbol.cap_style);
+        let join = match symbol.join_style {
+            JoinStyle::Bevel => "bevel",
+            JoinStyle::Miter => "miter",
+            JoinStyle::Round => "round",
+        };
+        for (index, section) in polylines.iter().enumerate() {
+            let mut data = String::new();
+            match beziers.get(index) {
+                Some(bezier) => self.bezier_data(bezier, &mut data),
+                None => self.polyline_data(&section.points, section.closed, &mut data),
+            }
+            let length = section.length();
+            let dashes = DashLayout::new(&symbol.dash_style, length)
+                .map(|layout| dash_array(&layout.dashes, length));
+            let mut element = BytesStart::new("path").with_attributes([
+                ("d", data.as_str()),
+                ("fill", "none"),
+                ("stroke", color.as_str()),
+                ("stroke-width", number(width).as_str()),
+                ("stroke-linecap", cap),
+                ("stroke-linejoin", join),
+            ]);
+            if let Some(dashes) = &dashes {
+                element.push_attribute(("pathLength", number(length).as_str()));
+                element.push_attribute(("stroke-dasharray", dashes.as_str()));
+            }
+            self.element(layer, element)?;
+        }
+        Ok(())
+    }
+
+    fn borders(&mut self, symbol: &LineSymbol, path: &Path) -> Result<()> {
+        let Some(border) = &symbol.border else {
+            return Ok(());
+        };
+        let (left, right) = match border {
+            BorderStyle::SymmetricBorder { both } => (both, both),
+            BorderStyle::AsymmetricBorder { left, right } => (left, right),
+        };
+        let cap = line_cap(symbol.cap_style);
+        for (border, side) in [(left, 1.), (right, -1.)] {
+            let width = border.width.get();
+            let Some((layer, color)) = self.paint(&border.color)? else {
+                continue;
+            };
+            if width <= 0. {
+                continue;
+            }
+            let shift = symbol.line_width.get() / 2. + border.shift.get();
+            let line = path.line.offset(side * shift);
+            let mut data = String::new();
+            self.polyline_data(&line.points, line.closed, &mut data);
+            let mut element = BytesStart::new("path").with_attributes([
+                ("d", data.as_str()),
+                ("fill", "none"),
+                ("stroke", color.as_str()),
+                ("stroke-width", number(width).as_str()),
+            ]);
+            match &border.dashed {
+                Some(dash) => {
+                    let length = line.length();
+                    let dashes = dash_intervals(
+                        length,
+                        dash.dash_length.get(),
+                        dash.break_length.get(),
+                        false,
+                    );
+                    element.push_attribute(("pathLength", number(length).as_str()));
+                    let dashes = dash_array(&dashes, length);
+                    element.push_attribute(("stroke-dasharray", dashes.as_str()));
+                }
+                None => element.push_attribute(("stroke-linecap", cap)),
+            }
+            self.element(layer, element)?;
+        }
+        Ok(())
+    }
+
+    /// Fill rings with an area symbol, with patterns turned by `rotation` in addition
+    /// to their own angle and the pattern rotation of rotatable patterns.
+    fn area_symbol(
+        &mut self,
+        symbol: &AreaSymbol,
+        rings: &[BezierPath],
+        pattern_rotation: &PatternRotation,
+        rotation: f64,
+    ) -> Result<()> {
+        let mut data = String::new();
+        for ring in rings {
+            self.bezier_data(ring, &mut data);
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        if let Some((layer, color)) = self.paint(&symbol.color)? {
+            self.fill(layer, &data, &color)?;
+        }
+        for pattern in &symbol.patterns {
+            match pattern {
+                FillPattern::LinePattern {
+                    angle,
+                    line_spacing,
+                    line_offset,
+                    line_color,
+                    line_width,
+                    rotatable,
+                } => {
+                    let spacing = line_spacing.get();
+                    let Some((layer, color)) = self.paint(line_color)? else {
+                        continue;
+                    };
+                    if spacing <= 0. || line_width.get() <= 0. {
+                        continue;
+                    }
+                    let angle = angle + rotation + pattern_angle(*rotatable, pattern_rotation);
+                    // the pattern y axis points against the map direction across the lines
+                    let row = (-line_offset.get()).rem_euclid(spacing);
+                    let lines = [row - spacing, row, row + spacing]
+                        .map(|y| format!("M0 {}H{}", number(y), number(spacing)))
+                        .concat();
+                    let id =
+                        self.begin_pattern(pattern_rotation.coord, angle, (spacing, spacing))?;
+                    self.defs().write_event(Event::Empty(
+                        BytesStart::new("path").with_attributes([
+                            ("d", lines.as_str()),
+                            ("stroke", color.as_str()),
+                            ("stroke-width", number(line_width.get()).as_str()),
+                        ]),
+                    ))?;
+                    self.defs()
+                        .write_event(Event::End(BytesEnd::new("pattern")))?;
+                    self.fill(layer, &data, &format!("url(#{id})"))?;
+                }
+                FillPattern::PointPattern {
+                    angle,
+                    line_spacing,
+                    line_offset,
+                    offset_along_line,
+                    point_distance,
+                    point,
+                    rotatable,
+                    ..
+                } => {
+                    let (distance, spacing) = (point_distance.get(), line_spacing.get());
+                    if distance <= 0. || spacing <= 0. {
+                        continue;
+                    }
+                    let angle = angle + rotation + pattern_angle(*rotatable, pattern_rotation);
+                    let column = offset_along_line.get().rem_euclid(distance);
+                    let row = (-line_offset.get()).rem_euclid(spacing);
+                    let tiles =
+                        self.point_pattern_tiles(point, (column, row), (distance, spacing))?;
+                    for (layer, content) in tiles {
+                        let id =
+                            self.begin_pattern(pattern_rotation.coord, angle, (distance, spacing))?;
+                        self.defs.extend_from_slice(&content);
+                        self.defs()
+                            .write_event(Event::End(BytesEnd::new("pattern")))?;
+                        self.fill(layer, &data, &format!("url(#{id})"))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fill(&mut self, layer: Layer, data: &str, paint: &str) -> Result<()> {
+        self.element(
+            layer,
+            BytesStart::new("path").with_attributes([
+                ("d", data),
+                ("fill", paint),
+                ("fill-rule", "evenodd"),
+            ]),
+        )
+    }
+
+    /// Start a pattern definition whose x axis runs along the pattern lines.
+    fn begin_pattern(
+        &mut self,
+        origin: Coord,
+        angle: f64,
+        (width, height): (f64, f64),
+    ) -> Result<String> {
+        self.patterns += 1;
+        let id = format!("pattern-{}", self.patterns);
+        let transform = format!(
+            "translate({}) rotate({})",
+            self.point(origin),
+            number(-angle.to_degrees())
+        );
+        self.defs()
+            .write_event(Event::Start(BytesStart::new("pattern").with_attributes([
+                ("id", id.as_str()),
+                ("patternUnits", "userSpaceOnUse"),
+                ("width", number(width).as_str()),
+                ("height", number(height).as_str()),
+                ("patternTransform", transform.as_str()),
+            ])))?;
+        Ok(id)
+    }
+
+    /// The contents of the pattern tiles of a point pattern, one for every color of
+    /// the point symbol.
+    ///
+    /// The point is drawn in all neighbouring tiles as well, so that the parts
+    /// reaching over the tile edges wrap around.
+    fn point_pattern_tiles(
+        &mut self,
+        point: &PointSymbol,
+        (column, row): (f64, f64),
+        (distance, spacing): (f64, f64),
+    ) -> Result<Vec<(Layer, Vec<u8>)>> {
+        let layers = std::mem::take(&mut self.layers);
+        let result = [column - distance, column, column + distance]
+            .into_iter()
+            .flat_map(|x| [row - spacing, row, row + spacing].map(|y| (x, y)))
+            .try_for_each(|(x, y)| {
+                // the tile y axis points down, as the SVG y axis
+                let origin = Coord {
+                    x: self.top_left.x + x,
+                    y: self.top_left.y - y,
+                };
+                self.point_symbol(point, Placement::new(origin, 0.))
+            });
+        let tiles = std::mem::replace(&mut self.layers, layers);
+        result?;
+        Ok(tiles.into_iter().collect())
+    }
+
+    fn text(&mut self, symbol: &TextSymbol, object: &TextObject) -> Result<()> {
+        let size = symbol.font_size.get();
+        if size <= 0. {
+            return Ok(());
+        }
+        let layout = TextLayout::new(symbol, object);
+        let anchor = *object.geometry().anchor_coord();
+        let transform = format!(
+            "translate({}) rotate({})",
+            self.point(anchor),
+            number(-object.rotation.to_degrees())
+        );
+        let text = SvgText {
+            symbol,
+            align: object.h_align,
+            layout: &layout,
+        };
+
+        match &symbol.framing_mode {
+            Some(FramingMode::LineFraming(framing)) => {
+                if let Some((layer, color)) = self.paint(&framing.color)? {
+                    let width = number(2. * framing.framing_line_half_width.get());
+                    let stroke = [
+                        ("stroke", color.as_str()),
+                        ("stroke-width", width.as_str()),
+                        ("stroke-linejoin", "round"),
+                    ];
+                    text.write(self.layer(layer), &transform, &color, &stroke)?;
+                }
+            }
+            Some(FramingMode::ShadowFraming(framing)) => {
+                if let Some((layer, color)) = self.paint(&framing.color)? {
+                    let offset = framing.shadow_offset;
+                    let shadow = format!(
+                        "translate({} {}) {transform}",
+                        number(offset.x),
+                        number(-offset.y)
+                    );
+                    text.write(self.layer(layer), &shadow, &color, &[])?;
+                }
+            }
+            Some(FramingMode::NoFraming) | None => {}
+        }
+        if let Some((layer, color)) = self.paint(&symbol.color)? {
+            text.write(self.layer(layer), &transform, &color, &[])?;
+        }
+
+        if let Some(line_below) = &symbol.line_below
+            && let Some((layer, color)) = self.paint(&line_below.color)?
+        {
+            let placement = Placement::new(anchor, object.rotation);
+            let shift = Coord {
+                x: 0.,
+                y: -line_below.distance.get(),
+            };
+            let mut data = String::new();
+            for (from, to) in &layout.paragraph_ends {
+                let line = [placement.apply(*from + shift), placement.apply(*to + shift)];
+                self.polyline_data(&line, false, &mut data);
+            }
+            self.element(
+                layer,
+                BytesStart::new("path").with_attributes([
+                    ("d", data.as_str()),
+                    ("stroke", color.as_str()),
+                    ("stroke-width", number(line_below.width.get()).as_str()),
+                ]),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn layer(&mut self, layer: Layer) -> Writer<&mut Vec<u8>> {
+        Writer::new(self.layers.entry(layer).or_default())
+    }
+
+    fn defs(&mut self) -> Writer<&mut Vec<u8>> {
+        Writer::new(&mut self.defs)
+    }
+}
+
+/// The lines of a text object as an SVG text element.
+#[derive(Debug)]
+struct SvgText<'t> {
+    symbol: &'t TextSymbol,
+    align: HorizontalAlign,
+    layout: &'t TextLayout,
+}
+
+impl SvgText<'_> {
+    fn write(
+        &self,
+        mut writer: Writer<&mut Vec<u8>>,
+        transform: &str,
+        color: &str,
+        stroke: &[(&str, &str)],
+    ) -> Result<()> {
+        let symbol = self.symbol;
+        let family = if symbol.font_family.is_empty() {
+            "sans-serif"
+        } else {
+            symbol.font_family.as_str()
+        };
+        let anchor = match self.align {
+            HorizontalAlign::Left => "start",
+            HorizontalAlign::HCenter => "middle",
+            HorizontalAlign::Right => "end",
+        };
+        let mut element = BytesStart::new("text").with_attributes([
+            ("transform", transform),
+            ("font-family", family),
+            ("font-size", number(symbol.font_size.get()).as_str()),
+            ("text-anchor", anchor),
+            ("fill", color),
+            ("xml:space", "preserve"),
+        ]);
+        if symbol.bold {
+            element.push_attribute(("font-weight", "bold"));
+        }
+        if symbol.italic {
+            element.push_attribute(("font-style", "italic"));
+        }
+        if symbol.underline {
+            element.push_attribute(("text-decoration", "underline"));
+        }
+        if symbol.character_spacing != 0. {
+            let spacing = symbol.character_spacing * symbol.font_size.get();
+            element.push_attribute(("letter-spacing", number(spacing).as_str()));
+        }
+        element.extend_attributes(stroke.iter().copied());
+        writer.write_event(Event::Start(element))?;
+        for (line, from, to) in &self.layout.lines {
+            let x = match self.align {
+                HorizontalAlign::Left => from.x,
+                HorizontalAlign::HCenter => f64::midpoint(from.x, to.x),
+                HorizontalAlign::Right => to.x,
+            };
+            writer.write_event(Event::Start(BytesStart::new("tspan").with_attributes([
+                ("x", number(x).as_str()),
+                ("y", number(-from.y).as_str()),
+            ])))?;
+            writer.write_event(Event::Text(BytesText::new(line)))?;
+            writer.write_event(Event::End(BytesEnd::new("tspan")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("text")))?;
+        Ok(())
+    }
+}
+
+fn write_layer<W: Write>(
+    writer: &mut Writer<W>,
+    id: &str,
+    name: &str,
+    content: Option<Vec<u8>>,
+) -> Result<()> {
+    let group = BytesStart::new("g").with_attributes([
+        ("id", id),
+        ("inkscape:groupmode", "layer"),
+        ("inkscape:label", name),
+    ]);
+    match content {
+        Some(content) => {
+            writer.write_event(Event::Start(group))?;
+            writer.get_mut().write_all(&content)?;
+            writer.write_event(Event::End(BytesEnd::new("g")))?;
+        }
+        None => writer.write_event(Event::Empty(group))?,
+    }
+    Ok(())
+}
+
+/// A dash array that draws the dashes at the given intervals along a line of
+/// `length`, and nothing after the last one.
+fn dash_array(dashes: &[(f64, f64)], length: f64) -> String {
+    let mut values = Vec::with_capacity(dashes.len() * 2 + 2);
+    let mut position = 0.;
+    for (index, &(from, to)) in dashes.iter().enumerate() {
+        if index == 0 && from > 0. {
+            values.extend([0., from]);
+        } else if index > 0 {
+            values.push(from - position);
+        }
+        values.push(to - from);
+        position = to;
+    }
+    values.push(length);
+    values.into_iter().map(number).collect::<Vec<_>>().join(" ")
+}
+
+fn line_cap(cap: CapStyle) -> &'static str {
+    match cap {
+        CapStyle::Flat | CapStyle::Pointed => "butt",
+        CapStyle::Round => "round",
+        CapStyle::Square => "square",
+    }
+}
+
+fn hex(rgb: Rgb) -> String {
+    let [r, g, b] = [rgb.r, rgb.g, rgb.b].map(|c| (c.get() * 255.).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
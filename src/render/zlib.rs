@@ -0,0 +1,43 @@
+/// The compression level, zlib's default balance of size and speed.
+const LEVEL: u8 = 6;
+
+/// Compress data into a zlib stream, as used by PNG image data and PDF
+/// `FlateDecode` streams.
+pub(super) fn compress(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, LEVEL)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::compress;
+
+    /// Decompress a zlib stream.
+    pub(in crate::render) fn inflate(stream: &[u8]) -> Vec<u8> {
+        miniz_oxide::inflate::decompress_to_vec_zlib(stream)
+            .unwrap_or_else(|error| panic!("invalid zlib stream: {error:?}"))
+    }
+
+    #[test]
+    fn compressed_data_inflates_to_the_input() {
+        let mut data = Vec::new();
+        for row in 0..200_u32 {
+            for column in 0..300_u32 {
+                let shade = if (row / 20 + column / 30) % 2 == 0 {
+                    40
+                } else {
+                    220
+                };
+                data.extend_from_slice(&[shade, shade, (row % 256) as u8, 255]);
+            }
+        }
+
+        let stream = compress(&data);
+        assert!(stream.len() * 20 < data.len(), "{} bytes", stream.len());
+        assert_eq!(inflate(&stream), data);
+        // the stream the reference zlib writes for "hello world" at the default level
+        assert_eq!(
+            compress(b"hello world"),
+            b"\x78\x9c\xcb\x48\xcd\xc9\xc9\x57\x28\xcf\x2f\xca\x49\x01\x00\x1a\x0b\x04\x5d"
+        );
+    }
+}
@@ -7,7 +7,7 @@ use quick_xml::{
 
 use super::{PointSymbol, SymbolCommon};
 use crate::{
-    Code, Error, NonNegativeF64, OmapSection, Result,
+    Code, Error, NonNegativeF64, OmapSection, PositiveF64, Result,
     colors::{ColorSet, SymbolColor, WeakColor},
     notes,
     utils::{parse_attr, try_get_attr_raw},
@@ -360,6 +360,40 @@ impl AreaSymbol {
         colors
     }
 
+    /// Scale every dimension of the symbol and its fill patterns. The minimum
+    /// area grows with the square of `factor`.
+    pub fn scale(&mut self, factor: PositiveF64) {
+        for pattern in &mut self.patterns {
+            match pattern {
+                FillPattern::LinePattern {
+                    line_spacing,
+                    line_offset,
+                    line_width,
+                    ..
+                } => {
+                    *line_spacing = line_spacing.scaled(factor);
+                    *line_offset = line_offset.scaled(factor);
+                    *line_width = line_width.scaled(factor);
+                }
+                FillPattern::PointPattern {
+                    line_spacing,
+                    line_offset,
+                    offset_along_line,
+                    point_distance,
+                    point,
+                    ..
+                } => {
+                    *line_spacing = line_spacing.scaled(factor);
+                    *line_offset = line_offset.scaled(factor);
+                    *offset_along_line = offset_along_line.scaled(factor);
+                    *point_distance = point_distance.scaled(factor);
+                    point.scale(factor);
+                }
+            }
+        }
+        self.minimum_area = self.minimum_area.scaled(factor).scaled(factor);
+    }
+
     pub(super) fn parse<R: std::io::BufRead>(
         reader: &mut Reader<R>,
         color_set: &ColorSet,
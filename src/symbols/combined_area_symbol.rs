@@ -8,7 +8,7 @@ use quick_xml::{
 
 use super::{AreaSymbol, LineSymbol, PublicOrPrivateSymbol, SymbolCommon, SymbolSet};
 use crate::{
-    Code, Error, OmapSection, Result,
+    Code, Error, OmapSection, PositiveF64, Result,
     colors::{ColorSet, WeakColor},
     notes,
     symbols::{AreaOrLineSymbol, CombinedLineSymbol, WeakPathSymbol, WeakSymbol},
@@ -267,6 +267,18 @@ impl CombinedAreaSymbol {
         Ok(colors)
     }
 
+    /// Scale the private components of the symbol. Public components are
+    /// symbols of their own and are scaled with the symbol set.
+    pub fn scale(&mut self, factor: PositiveF64) {
+        for component in &mut self.parts {
+            match component {
+                PublicOrPrivateSymbol::Private(AreaOrLineSymbol::Area(sym)) => sym.scale(factor),
+                PublicOrPrivateSymbol::Private(AreaOrLineSymbol::Line(sym)) => sym.scale(factor),
+                PublicOrPrivateSymbol::Public(_) => {}
+            }
+        }
+    }
+
     // This will recurse forever if any cycles exist,
     // but it should not as the components are private and the addition of components are shielded
     /// Check if the symbol references the other symbol
@@ -8,7 +8,7 @@ use quick_xml::{
 
 use super::{LineSymbol, PublicOrPrivateSymbol, SymbolCommon, SymbolSet};
 use crate::{
-    Code, Error, Result,
+    Code, Error, PositiveF64, Result,
     colors::{ColorSet, WeakColor},
     symbols::{Symbol, WeakLinePathSymbol, WeakSymbol},
 };
@@ -196,6 +196,16 @@ impl CombinedLineSymbol {
         Ok(colors)
     }
 
+    /// Scale the private components of the symbol. Public components are
+    /// symbols of their own and are scaled with the symbol set.
+    pub fn scale(&mut self, factor: PositiveF64) {
+        for component in &mut self.parts {
+            if let PublicOrPrivateSymbol::Private(sym) = component {
+                sym.scale(factor);
+            }
+        }
+    }
+
     /// Check if this symbol definition is cyclic.
     ///
     /// Uses an explicit visited set to detect cycles reliably.
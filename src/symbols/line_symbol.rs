@@ -7,7 +7,7 @@ use quick_xml::{
 
 use super::{PointSymbol, SymbolCommon};
 use crate::{
-    Code, Error, NonNegativeF64, OmapSection, Result,
+    Code, Error, NonNegativeF64, OmapSection, PositiveF64, Result,
     colors::{ColorSet, SymbolColor, WeakColor},
     notes,
     utils::{parse_attr, parse_attr_raw, try_get_attr_raw},
@@ -187,6 +187,61 @@ impl LineSymbol {
 
         colors
     }
+
+    /// Scale every dimension of the symbol, including its border, point
+    /// symbols and dash pattern, e.g. by 1.5 from 1:15 000 to 1:10 000.
+    pub fn scale(&mut self, factor: PositiveF64) {
+        match &mut self.border {
+            Some(BorderStyle::SymmetricBorder { both }) => both.scale(factor),
+            Some(BorderStyle::AsymmetricBorder { left, right }) => {
+                left.scale(factor);
+                right.scale(factor);
+            }
+            None => {}
+        }
+        if let Some(start) = &mut self.start_symbol {
+            start.scale(factor);
+        }
+        if let Some(mid) = &mut self.mid_symbol {
+            mid.mid_symbol_distance = mid.mid_symbol_distance.scaled(factor);
+            mid.mid_symbol.scale(factor);
+        }
+        if let Some(end) = &mut self.end_symbol {
+            end.scale(factor);
+        }
+        if let Some(dash) = &mut self.dash_symbol {
+            dash.dash_symbol.scale(factor);
+        }
+
+        self.line_width = self.line_width.scaled(factor);
+        self.minimum_length = self.minimum_length.scaled(factor);
+        self.start_offset = self.start_offset.scaled(factor);
+        self.end_offset = self.end_offset.scaled(factor);
+        match &mut self.dash_style {
+            DashStyle::Dashed {
+                dash_length,
+                break_length,
+                dash_group,
+            } => {
+                *dash_length = dash_length.scaled(factor);
+                *break_length = break_length.scaled(factor);
+                if let GroupDashes::Grouped {
+                    in_group_break_length,
+                    ..
+                } = dash_group
+                {
+                    *in_group_break_length = in_group_break_length.scaled(factor);
+                }
+            }
+            DashStyle::NotDashed {
+                segment_length,
+                end_length,
+            } => {
+                *segment_length = segment_length.scaled(factor);
+                *end_length = end_length.scaled(factor);
+            }
+        }
+    }
 }
 
 /// A dash symbol placed on dashes of a dashed line.
@@ -329,6 +384,15 @@ pub struct LineSymbolBorder {
 }
 
 impl LineSymbolBorder {
+    fn scale(&mut self, factor: PositiveF64) {
+        self.width = self.width.scaled(factor);
+        self.shift = self.shift.scaled(factor);
+        if let Some(dashed) = &mut self.dashed {
+            dashed.dash_length = dashed.dash_length.scaled(factor);
+            dashed.break_length = dashed.break_length.scaled(factor);
+        }
+    }
+
     fn parse(element: &BytesStart<'_>, color_set: &ColorSet) -> Result<Self> {
         let color_index = try_get_attr_raw(element, "color")?.unwrap_or(-1);
         let color = SymbolColor::from_index(color_index, color_set);
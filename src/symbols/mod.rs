@@ -3,6 +3,8 @@ mod combined_area_symbol;
 mod combined_line_symbol;
 mod line_symbol;
 mod point_symbol;
+mod purge;
+mod replace;
 mod symbol;
 mod symbol_set;
 mod text_symbol;
@@ -17,6 +19,8 @@ pub use line_symbol::{
     LineSymbolBorder, MidSymbol, MidSymbolPlacement,
 };
 pub use point_symbol::{Element, PointSymbol};
+pub use purge::PurgeReport;
+pub use replace::UnmatchedObject;
 pub use symbol::{Symbol, SymbolCommon, WeakSymbol};
 pub use symbol_set::SymbolSet;
 pub use text_symbol::{FramingMode, LineBelow, LineFraming, ShadowFraming, TextSymbol};
@@ -42,6 +46,16 @@ impl WeakLinePathSymbol {
     }
 }
 
+impl PartialEq for WeakLinePathSymbol {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Line(l0), Self::Line(r0)) => l0.ptr_eq(r0),
+            (Self::CombinedLine(l0), Self::CombinedLine(r0)) => l0.ptr_eq(r0),
+            _ => false,
+        }
+    }
+}
+
 impl TryFrom<WeakSymbol> for WeakLinePathSymbol {
     type Error = Error;
 
@@ -94,6 +108,16 @@ impl WeakAreaPathSymbol {
     }
 }
 
+impl PartialEq for WeakAreaPathSymbol {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Area(l0), Self::Area(r0)) => l0.ptr_eq(r0),
+            (Self::CombinedArea(l0), Self::CombinedArea(r0)) => l0.ptr_eq(r0),
+            _ => false,
+        }
+    }
+}
+
 impl TryFrom<WeakSymbol> for WeakAreaPathSymbol {
     type Error = Error;
 
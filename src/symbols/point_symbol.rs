@@ -1,5 +1,6 @@
 use std::rc::Weak;
 
+use geo_types::Coord;
 use quick_xml::{
     Reader, Writer,
     events::{BytesEnd, BytesStart, BytesText, Event},
@@ -7,7 +8,7 @@ use quick_xml::{
 
 use super::{AreaSymbol, LineSymbol};
 use crate::{
-    Code, Error, NonNegativeF64, OmapSection, Result,
+    Code, Error, NonNegativeF64, OmapSection, PositiveF64, Result,
     colors::{ColorSet, SymbolColor, WeakColor},
     notes,
     objects::{AreaObject, LineObject, PointObject},
@@ -311,6 +312,31 @@ impl PointSymbol {
         colors
     }
 
+    /// Scale every dimension of the symbol, including the geometry and
+    /// symbols of its elements.
+    pub fn scale(&mut self, factor: PositiveF64) {
+        self.inner_radius = self.inner_radius.scaled(factor);
+        self.outer_width = self.outer_width.scaled(factor);
+
+        let scale = |coord: Coord| coord * factor.get();
+        for element in &mut self.elements {
+            match element {
+                Element::Point { symbol, object } => {
+                    symbol.scale(factor);
+                    object.transform(scale);
+                }
+                Element::Line { symbol, object } => {
+                    symbol.scale(factor);
+                    object.transform(scale);
+                }
+                Element::Area { symbol, object } => {
+                    symbol.scale(factor);
+                    object.transform(scale);
+                }
+            }
+        }
+    }
+
     pub(super) fn parse<R: std::io::BufRead>(
         reader: &mut Reader<R>,
         color_set: &ColorSet,
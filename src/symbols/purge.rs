@@ -0,0 +1,226 @@
+use std::rc::Weak;
+
+use super::{PublicOrPrivateSymbol, Symbol, SymbolSet, WeakSymbol};
+use crate::{
+    Omap, Result,
+    colors::{Color, ColorSet, WeakColor},
+};
+
+/// The symbols and colors that no object of a map uses, see
+/// [`Omap::find_unused`] and [`Omap::purge_unused`].
+#[derive(Debug, Clone, Default)]
+pub struct PurgeReport {
+    /// The unused symbols in the order of the symbol set.
+    pub symbols: Vec<Symbol>,
+    /// The unused colors in priority order.
+    pub colors: Vec<Color>,
+}
+
+impl PurgeReport {
+    /// Whether nothing is unused.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty() && self.colors.is_empty()
+    }
+}
+
+impl Omap {
+    /// Find the symbols without objects and the colors of no other symbol,
+    /// without changing the map. This is the dry run of [`Self::purge_unused`].
+    ///
+    /// Symbols that are public components of a used combined symbol are used,
+    /// as are the spot colors of a used mixed color. The symbols of point
+    /// symbol elements belong to their point symbol and are not in the set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol or color is mutably borrowed somewhere else.
+    pub fn find_unused(&self) -> Result<PurgeReport> {
+        let mut used = Vec::<Symbol>::new();
+        let mut pending = self
+            .iter_all_objects()
+            .filter_map(|object| object.symbol().upgrade())
+            .collect::<Vec<_>>();
+        while let Some(symbol) = pending.pop() {
+            if used.contains(&symbol) {
+                continue;
+            }
+            pending.extend(public_components(&symbol)?);
+            used.push(symbol);
+        }
+
+        let mut used_colors = Vec::<WeakColor>::new();
+        for symbol in &used {
+            used_colors.extend(symbol.colors()?);
+        }
+        for color in self.colors.iter() {
+            if let Color::MixedColor(mixed) = color
+                && used_colors.contains(&color.downgrade())
+            {
+                used_colors.extend(
+                    mixed
+                        .try_borrow()?
+                        .components
+                        .iter()
+                        .map(|component| WeakColor::SpotColor(Weak::clone(&component.color))),
+                );
+            }
+        }
+
+        Ok(PurgeReport {
+            symbols: self
+                .symbols
+                .iter()
+                .filter(|symbol| !used.contains(symbol))
+                .cloned()
+                .collect(),
+            colors: self
+                .colors
+                .iter()
+                .filter(|color| !used_colors.contains(&color.downgrade()))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Remove the symbols without objects and the colors of no other symbol,
+    /// see [`Self::find_unused`]. The remaining symbols and colors keep their
+    /// order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol or color is mutably borrowed somewhere else.
+    /// The map is unchanged on failure.
+    pub fn purge_unused(&mut self) -> Result<PurgeReport> {
+        let unused = self.find_unused()?;
+
+        let mut symbols = SymbolSet::new(self.symbols.name.clone());
+        for symbol in self.symbols.iter() {
+            if !unused.symbols.contains(symbol) {
+                symbols.add_symbol(symbol.clone());
+            }
+        }
+        let mut colors = ColorSet::new();
+        for color in self.colors.iter() {
+            if !unused.colors.contains(color) {
+                colors.push(color.clone());
+            }
+        }
+
+        self.symbols = symbols;
+        self.colors = colors;
+        Ok(unused)
+    }
+}
+
+/// The symbols of the set that are public components of a combined symbol.
+pub(super) fn public_components(symbol: &Symbol) -> Result<Vec<Symbol>> {
+    Ok(match symbol {
+        Symbol::CombinedArea(combined) => combined
+            .try_borrow()?
+            .components()
+            .filter_map(|component| match component {
+                PublicOrPrivateSymbol::Public(weak) => weak.upgrade(),
+                PublicOrPrivateSymbol::Private(_) => None,
+            })
+            .collect(),
+        Symbol::CombinedLine(combined) => combined
+            .try_borrow()?
+            .components()
+            .filter_map(|component| match component {
+                PublicOrPrivateSymbol::Public(weak) => WeakSymbol::from(weak.clone()).upgrade(),
+                PublicOrPrivateSymbol::Private(_) => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, num::NonZeroU32, rc::Rc};
+
+    use geo_types::LineString;
+
+    use super::PurgeReport;
+    use crate::{
+        Code, Error, Omap, Result,
+        colors::{Cmyk, SpotColor, SymbolColor},
+        objects::LineObject,
+        symbols::{
+            CombinedLineSymbol, LineSymbol, PointSymbol, PublicOrPrivateSymbol, Symbol,
+            WeakLinePathSymbol,
+        },
+    };
+
+    #[test]
+    fn components_of_used_combined_symbols_are_kept() -> Result<()> {
+        let mut map = Omap::new(NonZeroU32::new(10_000).ok_or(Error::ObjectError)?);
+        let mut colors = Vec::new();
+        for name in ["Black", "Brown", "Purple"] {
+            colors.push(
+                map.colors
+                    .push(SpotColor::new(name, name, Cmyk::new(0., 0., 0., 1.)?)),
+            );
+        }
+        let mut fence = LineSymbol::new(Code::new(516, 0, 0), "Fence");
+        fence.color = SymbolColor::Color(colors[0].clone());
+        let fence = Rc::new(RefCell::new(fence));
+        let mut combined = CombinedLineSymbol::new(Code::new(518, 0, 0), "Uncrossable fence");
+        combined.add_component(PublicOrPrivateSymbol::Public(WeakLinePathSymbol::Line(
+            Rc::downgrade(&fence),
+        )))?;
+        let combined = Rc::new(RefCell::new(combined));
+        let mut marsh = PointSymbol::new(Code::new(310, 0, 0), "Marsh");
+        marsh.inner_color = SymbolColor::Color(colors[1].clone());
+        map.symbols.add_symbol(Symbol::Line(Rc::clone(&fence)));
+        map.symbols
+            .add_symbol(Symbol::Point(Rc::new(RefCell::new(marsh))));
+        map.symbols
+            .add_symbol(Symbol::CombinedLine(Rc::clone(&combined)));
+        map.parts
+            .get_mut(0)
+            .ok_or(Error::ObjectError)?
+            .add_object(LineObject::new(
+                WeakLinePathSymbol::CombinedLine(Rc::downgrade(&combined)),
+                LineString::from(vec![(0., 0.), (10., 0.)]),
+            ));
+
+        let unused = map.find_unused()?;
+        assert_eq!(map.symbols.len(), 3);
+        assert_eq!(map.colors.len(), 3);
+
+        let purged = map.purge_unused()?;
+        let names = |report: &PurgeReport| -> Result<(Vec<String>, Vec<String>)> {
+            Ok((
+                report
+                    .symbols
+                    .iter()
+                    .map(|symbol| Ok(symbol.common()?.name.clone()))
+                    .collect::<Result<_>>()?,
+                report
+                    .colors
+                    .iter()
+                    .map(|color| color.name())
+                    .collect::<Result<_>>()?,
+            ))
+        };
+        assert_eq!(names(&unused)?, names(&purged)?);
+        assert_eq!(
+            names(&purged)?,
+            (
+                vec!["Marsh".to_owned()],
+                vec!["Brown".to_owned(), "Purple".to_owned()]
+            )
+        );
+        assert_eq!(map.symbols.len(), 2);
+        assert_eq!(
+            map.colors
+                .iter()
+                .map(|c| c.name())
+                .collect::<Result<Vec<_>>>()?,
+            ["Black"]
+        );
+        assert!(map.purge_unused()?.is_empty());
+        map.validate()
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use quick_xml::{Reader, Writer, events::Event};
+
+use super::{Symbol, SymbolSet, WeakAreaPathSymbol, WeakLinePathSymbol, purge::public_components};
+use crate::{
+    Code, Error, Omap, OmapSection, Result,
+    colors::{Color, ColorSet, WeakColor},
+    objects::MapObject,
+};
+
+/// An object whose symbol has no counterpart in the new symbol set of
+/// [`Omap::replace_symbol_set`]. It keeps its old symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnmatchedObject {
+    /// The index of the object's part in [`Omap::parts`].
+    pub part_index: usize,
+    /// The index of the object in its part, see [`crate::parts::MapPart::get`].
+    pub object_index: usize,
+    /// The code of the object's symbol.
+    pub code: Code,
+}
+
+impl Omap {
+    /// Replace the symbol set of the map with a copy of the symbols of `source`,
+    /// like Mapper's "Replace symbol set", e.g. to upgrade a map from ISOM 2000
+    /// to ISOM 2017-2.
+    ///
+    /// Every object gets the symbol of `source` whose code is the code of its
+    /// old symbol in `mapping`, or else the code of its old symbol, and whose
+    /// type fits the object. The colors the new symbols need are imported into
+    /// the color set in the order of `source`, and colors whose name is already
+    /// in the map are used instead of a copy. Objects without a counterpart keep
+    /// their old symbol, which stays in the symbol set, and are returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol or color is already mutably borrowed or the
+    /// symbols of `source` cannot be copied. The map is unchanged on failure.
+    pub fn replace_symbol_set(
+        &mut self,
+        source: &Self,
+        mapping: &HashMap<Code, Code>,
+    ) -> Result<Vec<UnmatchedObject>> {
+        let (colors, lookup) = merge_colors(&self.colors, source)?;
+        let mut symbols = copy_symbols(source, &lookup)?;
+        let codes = symbols
+            .iter()
+            .map(|symbol| Ok((symbol.code()?, symbol.clone())))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut parts = self.parts.clone();
+        let mut unmatched = Vec::new();
+        let mut kept = Vec::<Symbol>::new();
+        for (part_index, part) in parts.iter_mut().enumerate() {
+            for (object_index, object) in part.iter_all_objects_mut().enumerate() {
+                let Some(old) = object.symbol().upgrade() else {
+                    continue;
+                };
+                let code = old.code()?;
+                let new_code = mapping.get(&code).copied().unwrap_or(code);
+                let is_matched = codes
+                    .iter()
+                    .filter(|(code, _)| *code == new_code)
+                    .any(|(_, symbol)| repoint(object, symbol));
+                if !is_matched {
+                    unmatched.push(UnmatchedObject {
+                        part_index,
+                        object_index,
+                        code,
+                    });
+                    keep(&mut kept, old)?;
+                }
+            }
+        }
+        for symbol in kept {
+            symbols.add_symbol(symbol);
+        }
+
+        self.colors = colors;
+        self.symbols = symbols;
+        self.parts = parts;
+        Ok(unmatched)
+    }
+}
+
+/// The color set of the map with the colors the symbols of `source` need, and
+/// a color set with the merged color for every color of `source` at the same
+/// priority.
+fn merge_colors(colors: &ColorSet, source: &Omap) -> Result<(ColorSet, ColorSet)> {
+    let mut used = Vec::<WeakColor>::new();
+    for symbol in source.symbols.iter() {
+        used.extend(symbol.colors()?);
+    }
+    for color in source.colors.iter() {
+        if let Color::MixedColor(mixed) = color
+            && used.contains(&color.downgrade())
+        {
+            used.extend(
+                mixed
+                    .try_borrow()?
+                    .components
+                    .iter()
+                    .map(|component| WeakColor::SpotColor(Weak::clone(&component.color))),
+            );
+        }
+    }
+
+    // the colors of the map by name, and copies of the others in use
+    let mut merged = Vec::new();
+    for color in source.colors.iter() {
+        let name = color.name()?;
+        let existing = colors
+            .iter()
+            .map(|color| Ok((color.name()? == name).then(|| color.clone())))
+            .find_map(Result::transpose)
+            .transpose()?;
+        merged.push(match existing {
+            Some(existing) => (existing, false),
+            None => (color.clone(), used.contains(&color.downgrade())),
+        });
+    }
+    for (index, color) in source.colors.iter().enumerate() {
+        if !merged[index].1 {
+            continue;
+        }
+        merged[index].0 =
+            match color {
+                Color::SpotColor(spot) => Color::from(spot.try_borrow()?.clone()),
+                Color::MixedColor(mixed) => {
+                    let mut mixed = mixed.try_borrow()?.clone();
+                    mixed.components.retain_mut(|component| {
+                        let merged_spot = source.colors.iter().zip(&merged).find_map(
+                            |(color, (merged, _))| match (color, merged) {
+                                (Color::SpotColor(spot), Color::SpotColor(merged))
+                                    if Weak::ptr_eq(&component.color, &Rc::downgrade(spot)) =>
+                                {
+                                    Some(Rc::downgrade(merged))
+                                }
+                                _ => None,
+                            },
+                        );
+                        merged_spot.is_some_and(|merged_spot| {
+                            component.color = merged_spot;
+                            true
+                        })
+                    });
+                    Color::from(mixed)
+                }
+            };
+    }
+
+    // new colors go after the color before them in `source`
+    let mut result = ColorSet::new();
+    for color in colors.iter() {
+        result.push(color.clone());
+    }
+    let mut position = 0;
+    for (color, is_new) in &merged {
+        if *is_new {
+            result.insert(position, color.clone())?;
+            position += 1;
+        } else if let Some(index) = result.iter().position(|other| other == color) {
+            position = index + 1;
+        }
+    }
+    let mut lookup = ColorSet::new();
+    for (color, _) in merged {
+        lookup.push(color);
+    }
+    Ok((result, lookup))
+}
+
+/// Copy the symbols of `source` with the colors of `lookup` at the priorities
+/// of the colors of `source`.
+fn copy_symbols(source: &Omap, lookup: &ColorSet) -> Result<SymbolSet> {
+    let mut bytes = Vec::new();
+    source
+        .symbols
+        .write(&mut Writer::new(&mut bytes), &source.colors)?;
+    let mut reader = Reader::from_reader(bytes.as_slice());
+    reader.config_mut().expand_empty_elements = true;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(start) if start.local_name().as_ref() == b"symbols" => {
+                let start = start.into_owned();
+                return SymbolSet::parse(&mut reader, &start, lookup);
+            }
+            Event::Eof => return Err(Error::UnexpectedEof(OmapSection::Symbols)),
+            _ => {}
+        }
+    }
+}
+
+/// Point an object at `symbol` if the symbol fits the object's type.
+fn repoint(object: &mut MapObject, symbol: &Symbol) -> bool {
+    match (object, symbol) {
+        (MapObject::Point(object), Symbol::Point(symbol)) => {
+            object.symbol = Rc::downgrade(symbol);
+        }
+        (MapObject::Text(object), Symbol::Text(symbol)) => {
+            object.symbol = Rc::downgrade(symbol);
+        }
+        (MapObject::Line(object), Symbol::Line(symbol)) => {
+            object.symbol = WeakLinePathSymbol::Line(Rc::downgrade(symbol));
+        }
+        (MapObject::Line(object), Symbol::CombinedLine(symbol)) => {
+            object.symbol = WeakLinePathSymbol::CombinedLine(Rc::downgrade(symbol));
+        }
+        (MapObject::Area(object), Symbol::Area(symbol)) => {
+            object.symbol = WeakAreaPathSymbol::Area(Rc::downgrade(symbol));
+        }
+        (MapObject::Area(object), Symbol::CombinedArea(symbol)) => {
+            object.symbol = WeakAreaPathSymbol::CombinedArea(Rc::downgrade(symbol));
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Add an old symbol and the public parts of a combined symbol to `kept`.
+fn keep(kept: &mut Vec<Symbol>, symbol: Symbol) -> Result<()> {
+    if kept.contains(&symbol) {
+        return Ok(());
+    }
+    let parts = public_components(&symbol)?;
+    kept.push(symbol);
+    for part in parts {
+        keep(kept, part)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap, num::NonZeroU32, rc::Rc};
+
+    use geo_types::{LineString, Point};
+
+    use super::UnmatchedObject;
+    use crate::{
+        Code, Error, Omap, Result,
+        colors::{Cmyk, Color, SpotColor, SymbolColor},
+        objects::{LineObject, PointObject},
+        symbols::{LineSymbol, PointSymbol, Symbol, WeakLinePathSymbol},
+    };
+
+    fn line_symbol(map: &mut Omap, code: Code, name: &str, color: &str) -> Result<()> {
+        let mut symbol = LineSymbol::new(code, name);
+        let existing = map
+            .colors
+            .iter()
+            .find(|other| other.name().is_ok_and(|name| name == color))
+            .map(Color::downgrade);
+        let color = match existing {
+            Some(existing) => existing,
+            None => map
+                .colors
+                .push(SpotColor::new(color, color, Cmyk::new(0., 0., 0., 1.)?)),
+        };
+        symbol.color = SymbolColor::Color(color);
+        map.symbols
+            .add_symbol(Symbol::Line(Rc::new(RefCell::new(symbol))));
+        Ok(())
+    }
+
+    #[test]
+    fn objects_are_matched_by_code_or_mapping_and_colors_by_name() -> Result<()> {
+        let scale = NonZeroU32::new(10_000).ok_or(Error::ObjectError)?;
+        let mut source = Omap::new(scale);
+        line_symbol(&mut source, Code::new(505, 0, 0), "Path", "Black")?;
+        line_symbol(&mut source, Code::new(506, 0, 0), "Small path", "Brown")?;
+
+        let mut map = Omap::new(scale);
+        line_symbol(&mut map, Code::new(505, 0, 0), "Old path", "Black")?;
+        line_symbol(&mut map, Code::new(507, 0, 0), "Old small path", "Black")?;
+        let old = map.symbols.iter().cloned().collect::<Vec<_>>();
+        let marsh = Rc::new(RefCell::new(PointSymbol::new(
+            Code::new(310, 0, 0),
+            "Marsh",
+        )));
+        map.symbols.add_symbol(Symbol::Point(Rc::clone(&marsh)));
+        let part = map.parts.get_mut(0).ok_or(Error::ObjectError)?;
+        for symbol in &old {
+            let Symbol::Line(symbol) = symbol else {
+                panic!("expected a line symbol");
+            };
+            part.add_object(LineObject::new(
+                WeakLinePathSymbol::Line(Rc::downgrade(symbol)),
+                LineString::from(vec![(0., 0.), (10., 0.)]),
+            ));
+        }
+        part.add_object(PointObject::new(Rc::downgrade(&marsh), Point::new(1., 2.)));
+
+        let mapping = HashMap::from([(Code::new(507, 0, 0), Code::new(506, 0, 0))]);
+        let unmatched = map.replace_symbol_set(&source, &mapping)?;
+
+        assert_eq!(
+            unmatched,
+            [UnmatchedObject {
+                part_index: 0,
+                object_index: 2,
+                code: Code::new(310, 0, 0),
+            }]
+        );
+        let names = map
+            .iter_all_objects()
+            .map(|object| match object.symbol().upgrade() {
+                Some(symbol) => symbol.name(),
+                None => panic!("expected a symbol for {object:?}"),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(names, ["Path", "Small path", "Marsh"]);
+        let colors = map
+            .colors
+            .iter()
+            .map(|color| color.name())
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(colors, ["Black", "Brown"]);
+        assert_eq!(map.symbols.iter().count(), 3);
+        map.validate()
+    }
+}
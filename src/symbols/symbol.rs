@@ -9,7 +9,7 @@ use super::{
     AreaSymbol, CombinedAreaSymbol, CombinedLineSymbol, LineSymbol, PointSymbol, SymbolSet,
     TextSymbol,
 };
-use crate::{Code, Error, Result, colors::ColorSet};
+use crate::{Code, Error, PositiveF64, Result, colors::ColorSet};
 use crate::{
     colors::WeakColor,
     utils::{parse_attr, parse_attr_raw},
@@ -170,6 +170,24 @@ impl Symbol {
         }
     }
 
+    /// Scale every dimension of the symbol definition by `factor`, see
+    /// [`SymbolSet::scale`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbol is borrowed somewhere else
+    pub fn scale(&self, factor: PositiveF64) -> Result<()> {
+        match self {
+            Self::Line(ref_cell) => ref_cell.try_borrow_mut()?.scale(factor),
+            Self::Area(ref_cell) => ref_cell.try_borrow_mut()?.scale(factor),
+            Self::Point(ref_cell) => ref_cell.try_borrow_mut()?.scale(factor),
+            Self::Text(ref_cell) => ref_cell.try_borrow_mut()?.scale(factor),
+            Self::CombinedArea(ref_cell) => ref_cell.try_borrow_mut()?.scale(factor),
+            Self::CombinedLine(ref_cell) => ref_cell.try_borrow_mut()?.scale(factor),
+        }
+        Ok(())
+    }
+
     /// Creates a new `WeakSymbol` pointer to this Symbol allocation
     pub fn downgrade(&self) -> WeakSymbol {
         match self {
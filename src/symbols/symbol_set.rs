@@ -7,7 +7,7 @@ use quick_xml::{
 
 use super::{Symbol, WeakSymbol};
 use crate::{
-    Code, Error, OmapSection, Result,
+    Code, Error, OmapSection, PositiveF64, Result,
     colors::ColorSet,
     symbols::{
         AreaOrLineSymbol, AreaSymbol, CombinedAreaSymbol, CombinedLineSymbol, LineSymbol,
@@ -134,6 +134,23 @@ impl SymbolSet {
         Ok(None)
     }
 
+    /// Scale every dimension of every symbol by `factor`, e.g. by 1.5 when an
+    /// ISOM map goes from 1:15 000 to 1:10 000.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a symbol is borrowed somewhere else. The symbols are
+    /// unchanged on failure.
+    pub fn scale(&self, factor: PositiveF64) -> Result<()> {
+        for s in &self.symbols {
+            s.common_mut()?;
+        }
+        for s in &self.symbols {
+            s.scale(factor)?;
+        }
+        Ok(())
+    }
+
     /// Iterate over non-owning references to all symbols.
     pub fn iter_weak(&self) -> impl Iterator<Item = WeakSymbol> {
         self.symbols.iter().map(|s| s.downgrade())
@@ -6,7 +6,7 @@ use quick_xml::{
 
 use super::SymbolCommon;
 use crate::{
-    Code, Error, NonNegativeF64, OmapSection, Result,
+    Code, Error, NonNegativeF64, OmapSection, PositiveF64, Result,
     colors::{ColorSet, SymbolColor, WeakColor},
     notes,
     utils::{self, try_get_attr_raw},
@@ -218,6 +218,30 @@ impl TextSymbol {
         colors
     }
 
+    /// Scale the font size and every other length of the symbol. Line and
+    /// character spacing are relative to the font and stay unchanged.
+    pub fn scale(&mut self, factor: PositiveF64) {
+        self.font_size = self.font_size.scaled(factor);
+        self.paragraph_spacing *= factor.get();
+        for tab in &mut self.custom_tabs {
+            *tab = tab.scaled(factor);
+        }
+        if let Some(line_below) = &mut self.line_below {
+            line_below.width = line_below.width.scaled(factor);
+            line_below.distance = line_below.distance.scaled(factor);
+        }
+        match &mut self.framing_mode {
+            Some(FramingMode::LineFraming(line_framing)) => {
+                line_framing.framing_line_half_width =
+                    line_framing.framing_line_half_width.scaled(factor);
+            }
+            Some(FramingMode::ShadowFraming(shadow_framing)) => {
+                shadow_framing.shadow_offset = shadow_framing.shadow_offset * factor.get();
+            }
+            Some(FramingMode::NoFraming) | None => {}
+        }
+    }
+
     #[expect(
         clippy::too_many_lines,
         reason = "text-symbol parsing maps a large file-format record"
@@ -219,6 +219,11 @@ impl NonNegativeF64 {
         Self(0.)
     }
 
+    /// Multiply by a positive factor, clamping overflow to 0.
+    pub(crate) fn scaled(self, factor: PositiveF64) -> Self {
+        Self::clamped_from(self.0 * factor.get())
+    }
+
     /// The files uses 1/1000 mm as the unit
     pub(crate) fn to_file_value(self) -> Result<u32> {
         Ok(to_file_value(self.0)?.try_into()?)
@@ -0,0 +1,362 @@
+use geo_types::{
+    Coord, Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon,
+    Point, Polygon,
+};
+
+use crate::{Error, Result};
+
+/// The deepest nesting of geometries, which keeps the recursive parser within
+/// the stack on hostile input.
+const MAX_DEPTH: usize = 64;
+
+/// The EWKB flag for a Z ordinate in the geometry type.
+const EWKB_Z: u32 = 0x8000_0000;
+/// The EWKB flag for an M ordinate in the geometry type.
+const EWKB_M: u32 = 0x4000_0000;
+/// The EWKB flag for an SRID after the geometry type.
+const EWKB_SRID: u32 = 0x2000_0000;
+
+const POINT: u32 = 1;
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POINT: u32 = 4;
+const MULTI_LINE_STRING: u32 = 5;
+const MULTI_POLYGON: u32 = 6;
+const GEOMETRY_COLLECTION: u32 = 7;
+
+/// Parse a WKB geometry in either byte order, also accepting the SRID and
+/// dimension flags of `PostGIS` EWKB.
+///
+/// Z and M ordinates of ISO and EWKB geometries are read and dropped, and an
+/// empty point, which has NaN coordinates, is returned as an empty `MultiPoint`
+/// as `geo_types` has no empty point.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidWkb`] with the byte offset of the first error.
+pub fn parse_wkb(bytes: &[u8]) -> Result<Geometry> {
+    let mut reader = Reader { bytes, offset: 0 };
+    let geometry = reader.geometry(0)?;
+    if reader.offset < bytes.len() {
+        return Err(reader.error());
+    }
+    Ok(geometry)
+}
+
+/// Write a geometry as little endian 2D WKB, with `Line`s as `LineString`s and
+/// `Rect`s and `Triangle`s as `Polygon`s.
+pub fn to_wkb(geometry: &Geometry) -> Vec<u8> {
+    let mut wkb = Vec::new();
+    write_geometry(&mut wkb, geometry);
+    wkb
+}
+
+fn write_geometry(wkb: &mut Vec<u8>, geometry: &Geometry) {
+    match geometry {
+        Geometry::Point(point) => {
+            write_header(wkb, POINT);
+            write_coord(wkb, point.0);
+        }
+        Geometry::Line(line) => write_line_string(wkb, &LineString(vec![line.start, line.end])),
+        Geometry::LineString(line) => write_line_string(wkb, line),
+        Geometry::Polygon(polygon) => write_polygon(wkb, polygon),
+        Geometry::MultiPoint(points) => {
+            write_header(wkb, MULTI_POINT);
+            write_count(wkb, points.0.len());
+            for point in &points.0 {
+                write_geometry(wkb, &Geometry::Point(*point));
+            }
+        }
+        Geometry::MultiLineString(lines) => {
+            write_header(wkb, MULTI_LINE_STRING);
+            write_count(wkb, lines.0.len());
+            for line in &lines.0 {
+                write_line_string(wkb, line);
+            }
+        }
+        Geometry::MultiPolygon(polygons) => {
+            write_header(wkb, MULTI_POLYGON);
+            write_count(wkb, polygons.0.len());
+            for polygon in &polygons.0 {
+                write_polygon(wkb, polygon);
+            }
+        }
+        Geometry::GeometryCollection(collection) => {
+            write_header(wkb, GEOMETRY_COLLECTION);
+            write_count(wkb, collection.0.len());
+            for geometry in &collection.0 {
+                write_geometry(wkb, geometry);
+            }
+        }
+        Geometry::Rect(rect) => write_polygon(wkb, &rect.to_polygon()),
+        Geometry::Triangle(triangle) => write_polygon(wkb, &triangle.to_polygon()),
+    }
+}
+
+fn write_header(wkb: &mut Vec<u8>, geometry_type: u32) {
+    // little endian
+    wkb.push(1);
+    wkb.extend_from_slice(&geometry_type.to_le_bytes());
+}
+
+fn write_count(wkb: &mut Vec<u8>, count: usize) {
+    let count = u32::try_from(count).unwrap_or(u32::MAX);
+    wkb.extend_from_slice(&count.to_le_bytes());
+}
+
+fn write_coord(wkb: &mut Vec<u8>, coord: Coord) {
+    wkb.extend_from_slice(&coord.x.to_le_bytes());
+    wkb.extend_from_slice(&coord.y.to_le_bytes());
+}
+
+fn write_line_string(wkb: &mut Vec<u8>, line: &LineString) {
+    write_header(wkb, LINE_STRING);
+    write_ring(wkb, line);
+}
+
+fn write_ring(wkb: &mut Vec<u8>, ring: &LineString) {
+    write_count(wkb, ring.0.len());
+    for &coord in &ring.0 {
+        write_coord(wkb, coord);
+    }
+}
+
+fn write_polygon(wkb: &mut Vec<u8>, polygon: &Polygon) {
+    write_header(wkb, POLYGON);
+    if polygon.exterior().0.is_empty() {
+        write_count(wkb, 0);
+        return;
+    }
+    write_count(wkb, polygon.interiors().len() + 1);
+    for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+        write_ring(wkb, ring);
+    }
+}
+
+/// A reader over the bytes of a WKB geometry.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+/// The byte order and number of ordinates of a geometry.
+#[derive(Clone, Copy)]
+struct Layout {
+    little_endian: bool,
+    dimensions: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self) -> Error {
+        Error::InvalidWkb(self.offset)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let Some(bytes) = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .and_then(|bytes| <[u8; N]>::try_from(bytes).ok())
+        else {
+            return Err(self.error());
+        };
+        self.offset += N;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self, layout: Layout) -> Result<u32> {
+        let bytes = self.take()?;
+        Ok(if layout.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn f64(&mut self, layout: Layout) -> Result<f64> {
+        let bytes = self.take()?;
+        Ok(if layout.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn geometry(&mut self, depth: usize) -> Result<Geometry> {
+        if depth > MAX_DEPTH {
+            return Err(self.error());
+        }
+        let mut layout = Layout {
+            little_endian: match self.take::<1>()? {
+                [0] => false,
+                [1] => true,
+                _ => {
+                    self.offset -= 1;
+                    return Err(self.error());
+                }
+            },
+            dimensions: 2,
+        };
+        let type_offset = self.offset;
+        let raw_type = self.u32(layout)?;
+        let code = raw_type & 0x0fff_ffff;
+        // ISO WKB adds 1000 for Z, 2000 for M and 3000 for ZM to the type
+        let iso_dimensions = match code / 1000 {
+            0 => 0,
+            1 | 2 => 1,
+            3 => 2,
+            _ => {
+                self.offset = type_offset;
+                return Err(self.error());
+            }
+        };
+        let geometry_type = code % 1000;
+        layout.dimensions += iso_dimensions
+            + usize::from(raw_type & EWKB_Z != 0)
+            + usize::from(raw_type & EWKB_M != 0);
+        if raw_type & EWKB_SRID != 0 {
+            let _srid = self.u32(layout)?;
+        }
+
+        let geometry = match geometry_type {
+            POINT => {
+                let coord = self.coord(layout)?;
+                if coord.x.is_nan() && coord.y.is_nan() {
+                    MultiPoint(Vec::new()).into()
+                } else {
+                    Point(coord).into()
+                }
+            }
+            LINE_STRING => self.line_string(layout)?.into(),
+            POLYGON => self.polygon(layout)?.into(),
+            MULTI_POINT => MultiPoint(self.members(layout, depth, |geometry| match geometry {
+                Geometry::Point(point) => Some(point),
+                _ => None,
+            })?)
+            .into(),
+            MULTI_LINE_STRING => {
+                MultiLineString(self.members(layout, depth, |geometry| match geometry {
+                    Geometry::LineString(line) => Some(line),
+                    _ => None,
+                })?)
+                .into()
+            }
+            MULTI_POLYGON => {
+                MultiPolygon(self.members(layout, depth, |geometry| match geometry {
+                    Geometry::Polygon(polygon) => Some(polygon),
+                    _ => None,
+                })?)
+                .into()
+            }
+            GEOMETRY_COLLECTION => {
+                Geometry::GeometryCollection(GeometryCollection(self.members(layout, depth, Some)?))
+            }
+            _ => {
+                self.offset = type_offset;
+                return Err(self.error());
+            }
+        };
+        Ok(geometry)
+    }
+
+    /// Read the count and the member geometries of a multi-geometry, failing for
+    /// members of the wrong type.
+    fn members<T>(
+        &mut self,
+        layout: Layout,
+        depth: usize,
+        member: impl Fn(Geometry) -> Option<T>,
+    ) -> Result<Vec<T>> {
+        let count = self.u32(layout)?;
+        let mut members = Vec::new();
+        for _ in 0..count {
+            let start = self.offset;
+            let Some(geometry) = member(self.geometry(depth + 1)?) else {
+                self.offset = start;
+                return Err(self.error());
+            };
+            members.push(geometry);
+        }
+        Ok(members)
+    }
+
+    fn coord(&mut self, layout: Layout) -> Result<Coord> {
+        let x = self.f64(layout)?;
+        let y = self.f64(layout)?;
+        for _ in 2..layout.dimensions {
+            let _ordinate = self.f64(layout)?;
+        }
+        Ok(Coord { x, y })
+    }
+
+    fn line_string(&mut self, layout: Layout) -> Result<LineString> {
+        let count = self.u32(layout)?;
+        // the count may be corrupt, so the coordinates are not preallocated
+        (0..count).map(|_| self.coord(layout)).collect()
+    }
+
+    fn polygon(&mut self, layout: Layout) -> Result<Polygon> {
+        let count = self.u32(layout)?;
+        let mut rings = Vec::new();
+        for _ in 0..count {
+            rings.push(self.line_string(layout)?);
+        }
+        let mut rings = rings.into_iter();
+        let exterior = rings.next().unwrap_or_else(|| LineString(Vec::new()));
+        Ok(Polygon::new(exterior, rings.collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{Geometry, Point};
+
+    use super::{parse_wkb, to_wkb};
+    use crate::{
+        Error, Result,
+        wkt::{parse_wkt, to_wkt},
+    };
+
+    #[test]
+    fn round_trips_geometries() -> Result<()> {
+        for wkt in [
+            "POINT (1 2.5)",
+            "POLYGON ((0 0, 10 0, 10 10, 0 0), (1 1, 2 1, 2 2, 1 1))",
+            "MULTILINESTRING ((0 0, 1 1), (2 2, 3 3))",
+            "GEOMETRYCOLLECTION (MULTIPOINT ((1 2)), POLYGON EMPTY)",
+            "MULTIPOINT EMPTY",
+        ] {
+            assert_eq!(to_wkt(&parse_wkb(&to_wkb(&parse_wkt(wkt)?))?), wkt);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reads_big_endian_ewkb() -> Result<()> {
+        // a PostGIS point with Z ordinate and SRID 25832
+        let mut ewkb = vec![0, 0xa0, 0, 0, 1, 0, 0, 0x64, 0xe8];
+        for ordinate in [1_f64, 2., 3.] {
+            ewkb.extend_from_slice(&ordinate.to_be_bytes());
+        }
+        assert_eq!(parse_wkb(&ewkb)?, Geometry::Point(Point::new(1., 2.)));
+        Ok(())
+    }
+
+    #[test]
+    fn reports_offset_of_errors() {
+        let point = to_wkb(&Geometry::Point(Point::new(1., 2.)));
+        for (wkb, offset) in [
+            (&[2_u8][..], 0),
+            (&[1, 8, 0, 0, 0][..], 1),
+            (&point[..point.len() - 1], 13),
+            (
+                &[1, 4, 0, 0, 0, 1, 0, 0, 0, 1, 2, 0, 0, 0, 0, 0, 0, 0][..],
+                9,
+            ),
+        ] {
+            let Err(Error::InvalidWkb(error_offset)) = parse_wkb(wkb) else {
+                panic!("{wkb:?} is not valid WKB");
+            };
+            assert_eq!(error_offset, offset, "{wkb:?}");
+        }
+    }
+}
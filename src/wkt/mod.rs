@@ -0,0 +1,301 @@
+//! Well-known text (WKT) and binary (WKB) geometries, the vector exchange
+//! formats of `PostGIS` and most GIS software.
+//!
+//! [`parse_wkt`](crate::wkt::parse_wkt) and [`parse_wkb`](crate::wkt::parse_wkb)
+//! read single geometries into [`geo_types`], and [`to_wkt`](crate::wkt::to_wkt)
+//! and [`to_wkb`](crate::wkt::to_wkb) write them.
+//! [`MapObject::from_geometry`](crate::objects::MapObject::from_geometry) and
+//! [`MapObject::to_geometry`](crate::objects::MapObject::to_geometry) convert
+//! between geometries in the projected CRS of the map and objects in map
+//! coordinates, with the [`MapTransform`](crate::geo_referencing::MapTransform)
+//! of the map's georeferencing.
+
+mod binary;
+mod text;
+
+pub use binary::{parse_wkb, to_wkb};
+pub use text::{parse_wkt, to_wkt};
+
+use std::rc::Weak;
+
+use geo_types::{Coord, Geometry, LineString, Point, Polygon};
+
+use crate::{
+    Error, NonNegativeF64, Result,
+    geo_referencing::MapTransform,
+    objects::{AreaObject, LineObject, MapObject, PointObject, TextGeometry, TextObject},
+    symbols::{WeakAreaPathSymbol, WeakLinePathSymbol, WeakSymbol},
+};
+
+impl MapObject {
+    /// Create objects of `symbol` from a geometry in projected coordinates, with
+    /// one object per member of a multi-geometry or geometry collection.
+    ///
+    /// Points become point objects, or text objects without text for text
+    /// symbols. Line strings become line objects and polygons area objects.
+    /// Empty geometries, lines with fewer than two points and polygons without a
+    /// ring of at least three points give no objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SymbolConversionError`] if a geometry does not fit the
+    /// symbol type and [`Error::MapCoordOutOfBounds`] for coordinates that are not
+    /// finite.
+    pub fn from_geometry(
+        geometry: Geometry,
+        symbol: &WeakSymbol,
+        transform: &MapTransform,
+    ) -> Result<Vec<Self>> {
+        let mut objects = Vec::new();
+        add_objects(&mut objects, geometry, symbol, transform)?;
+        Ok(objects)
+    }
+
+    /// Create objects of `symbol` from a WKT geometry in projected coordinates.
+    /// See [`Self::from_geometry`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidWkt`] if the text is not a WKT geometry, or an error
+    /// if the geometry does not fit the symbol.
+    pub fn from_wkt(wkt: &str, symbol: &WeakSymbol, transform: &MapTransform) -> Result<Vec<Self>> {
+        Self::from_geometry(parse_wkt(wkt)?, symbol, transform)
+    }
+
+    /// Create objects of `symbol` from a WKB geometry in projected coordinates.
+    /// See [`Self::from_geometry`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidWkb`] if the bytes are not a WKB geometry, or an
+    /// error if the geometry does not fit the symbol.
+    pub fn from_wkb(
+        wkb: &[u8],
+        symbol: &WeakSymbol,
+        transform: &MapTransform,
+    ) -> Result<Vec<Self>> {
+        Self::from_geometry(parse_wkb(wkb)?, symbol, transform)
+    }
+
+    /// The geometry of the object in projected coordinates, with curves flattened
+    /// to within `allowed_error` mm of paper.
+    ///
+    /// Points and texts give their anchor as a `Point`, lines a `LineString` and
+    /// areas a `Polygon` with holes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tolerance is too small or the geometry is invalid.
+    pub fn to_geometry(
+        &self,
+        transform: &MapTransform,
+        allowed_error: NonNegativeF64,
+    ) -> Result<Geometry> {
+        let geometry = match self {
+            Self::Point(point) => transform.to_projected_point(*point.geometry()).into(),
+            Self::Text(text) => {
+                Point(transform.to_projected(*text.geometry().anchor_coord())).into()
+            }
+            Self::Line(line) => transform
+                .to_projected_linestring(line.flatten(allowed_error)?.into_parts().0)
+                .into(),
+            Self::Area(area) => transform
+                .to_projected_polygon(area.flatten(allowed_error)?.into_polygon())
+                .into(),
+        };
+        Ok(geometry)
+    }
+
+    /// The geometry of the object as WKT in projected coordinates.
+    /// See [`Self::to_geometry`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tolerance is too small or the geometry is invalid.
+    pub fn to_wkt(
+        &self,
+        transform: &MapTransform,
+        allowed_error: NonNegativeF64,
+    ) -> Result<String> {
+        Ok(to_wkt(&self.to_geometry(transform, allowed_error)?))
+    }
+
+    /// The geometry of the object as little endian WKB in projected coordinates.
+    /// See [`Self::to_geometry`] for more docs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tolerance is too small or the geometry is invalid.
+    pub fn to_wkb(
+        &self,
+        transform: &MapTransform,
+        allowed_error: NonNegativeF64,
+    ) -> Result<Vec<u8>> {
+        Ok(to_wkb(&self.to_geometry(transform, allowed_error)?))
+    }
+}
+
+fn add_objects(
+    objects: &mut Vec<MapObject>,
+    geometry: Geometry,
+    symbol: &WeakSymbol,
+    transform: &MapTransform,
+) -> Result<()> {
+    match geometry {
+        Geometry::Point(point) => {
+            let coord = to_map(transform, point.0)?;
+            let object = match symbol {
+                WeakSymbol::Point(symbol) => {
+                    PointObject::new(Weak::clone(symbol), Point(coord)).into()
+                }
+                WeakSymbol::Text(symbol) => TextObject::new(
+                    Weak::clone(symbol),
+                    TextGeometry::SingleAnchor(coord),
+                    String::new(),
+                )
+                .into(),
+                _ => return Err(Error::SymbolConversionError),
+            };
+            objects.push(object);
+        }
+        Geometry::Line(line) => add_objects(
+            objects,
+            LineString(vec![line.start, line.end]).into(),
+            symbol,
+            transform,
+        )?,
+        Geometry::LineString(line) => {
+            let symbol = WeakLinePathSymbol::try_from(symbol.clone())?;
+            if line.0.len() >= 2 {
+                let line = to_map_line_string(transform, line)?;
+                objects.push(LineObject::new(symbol, line).into());
+            }
+        }
+        Geometry::Polygon(polygon) => {
+            let symbol = WeakAreaPathSymbol::try_from(symbol.clone())?;
+            // a closed ring repeats its first point
+            if polygon.exterior().0.len() >= 4 {
+                let (exterior, interiors) = polygon.into_inner();
+                let polygon = Polygon::new(
+                    to_map_line_string(transform, exterior)?,
+                    interiors
+                        .into_iter()
+                        .map(|ring| to_map_line_string(transform, ring))
+                        .collect::<Result<_>>()?,
+                );
+                objects.push(AreaObject::new(symbol, polygon).into());
+            }
+        }
+        Geometry::MultiPoint(points) => {
+            for point in points {
+                add_objects(objects, point.into(), symbol, transform)?;
+            }
+        }
+        Geometry::MultiLineString(lines) => {
+            for line in lines {
+                add_objects(objects, line.into(), symbol, transform)?;
+            }
+        }
+        Geometry::MultiPolygon(polygons) => {
+            for polygon in polygons {
+                add_objects(objects, polygon.into(), symbol, transform)?;
+            }
+        }
+        Geometry::GeometryCollection(collection) => {
+            for geometry in collection {
+                add_objects(objects, geometry, symbol, transform)?;
+            }
+        }
+        Geometry::Rect(rect) => add_objects(objects, rect.to_polygon().into(), symbol, transform)?,
+        Geometry::Triangle(triangle) => {
+            add_objects(objects, triangle.to_polygon().into(), symbol, transform)?;
+        }
+    }
+    Ok(())
+}
+
+fn to_map(transform: &MapTransform, coord: Coord) -> Result<Coord> {
+    if !(coord.x.is_finite() && coord.y.is_finite()) {
+        return Err(Error::MapCoordOutOfBounds);
+    }
+    Ok(transform.to_map(coord))
+}
+
+fn to_map_line_string(transform: &MapTransform, line: LineString) -> Result<LineString> {
+    line.0
+        .into_iter()
+        .map(|coord| to_map(transform, coord))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroU32, rc::Rc};
+
+    use geo_types::coord;
+
+    use crate::{
+        Code, Error, NonNegativeF64, Omap, Result,
+        objects::MapObject,
+        symbols::{AreaSymbol, LineSymbol, Symbol, WeakSymbol},
+    };
+
+    #[test]
+    fn round_trips_objects_through_wkt_and_wkb() -> Result<()> {
+        let mut omap = Omap::new(NonZeroU32::new(10_000).unwrap_or(NonZeroU32::MIN));
+        omap.geo_referencing.projected_ref_point = coord! { x: 500_000., y: 6_600_000. };
+        let transform = omap.geo_referencing.create_transform();
+        let Some(Symbol::Area(area)) = omap
+            .symbols
+            .add_symbol(AreaSymbol::new(Code::new(401, 0, 0), "Open land"))
+            .upgrade()
+        else {
+            panic!("the symbol is an area symbol");
+        };
+        let symbol = WeakSymbol::Area(Rc::downgrade(&area));
+
+        let wkt = "MULTIPOLYGON (((500000 6600000, 500100 6600000, 500100 6600100, 500000 6600000)), \
+            ((500200 6600000, 500300 6600000, 500300 6600100, 500200 6600000), \
+            (500210 6600010, 500220 6600010, 500220 6600020, 500210 6600010)))";
+        let objects = MapObject::from_wkt(wkt, &symbol, &transform)?;
+        assert_eq!(objects.len(), 2);
+        let Some(MapObject::Area(second)) = objects.get(1) else {
+            panic!("a polygon gives an area object");
+        };
+        assert_eq!(second.geometry().interiors().len(), 1);
+        assert!(objects[0].symbol() == symbol);
+
+        let allowed_error = NonNegativeF64::clamped_from(0.01);
+        assert_eq!(
+            objects[0].to_wkt(&transform, allowed_error)?,
+            "POLYGON ((500000 6600000, 500100 6600000, 500100 6600100, 500000 6600000))"
+        );
+        let wkb = objects[1].to_wkb(&transform, allowed_error)?;
+        let [MapObject::Area(read_back)] = &MapObject::from_wkb(&wkb, &symbol, &transform)?[..]
+        else {
+            panic!("the WKB holds one polygon");
+        };
+        assert_eq!(
+            read_back.geometry().exterior().num_vertices(),
+            second.geometry().exterior().num_vertices()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_geometry_unfit_for_symbol() {
+        let mut omap = Omap::new(NonZeroU32::new(10_000).unwrap_or(NonZeroU32::MIN));
+        let transform = omap.geo_referencing.create_transform();
+        let symbol = omap
+            .symbols
+            .add_symbol(LineSymbol::new(Code::new(505, 0, 0), "Path"));
+        assert!(matches!(
+            MapObject::from_wkt("POINT (1 2)", &symbol, &transform),
+            Err(Error::SymbolConversionError)
+        ));
+        assert!(matches!(
+            MapObject::from_wkt("LINESTRING EMPTY", &symbol, &transform),
+            Ok(objects) if objects.is_empty()
+        ));
+    }
+}
@@ -0,0 +1,384 @@
+use geo_types::{
+    Coord, Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon,
+    Point, Polygon,
+};
+
+use crate::{Error, Result};
+
+/// The deepest nesting of geometry collections, which keeps the recursive
+/// parser within the stack on hostile input.
+const MAX_DEPTH: usize = 64;
+
+/// The geometry types, which may carry a dimension suffix like `POINTZ`.
+const GEOMETRY_NAMES: [&str; 7] = [
+    "POINT",
+    "LINESTRING",
+    "POLYGON",
+    "MULTIPOINT",
+    "MULTILINESTRING",
+    "MULTIPOLYGON",
+    "GEOMETRYCOLLECTION",
+];
+
+/// Parse a WKT geometry, also accepting the `SRID=…;` prefix of `PostGIS` EWKT.
+///
+/// Keywords are case insensitive. Z and M ordinates are read and dropped, and an
+/// empty point is returned as an empty `MultiPoint` as `geo_types` has no empty
+/// point.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidWkt`] with the byte offset of the first error.
+pub fn parse_wkt(text: &str) -> Result<Geometry> {
+    let mut parser = Parser {
+        bytes: text.as_bytes(),
+        offset: 0,
+    };
+    parser.whitespace();
+    if parser.bytes[parser.offset..]
+        .get(..5)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(b"SRID="))
+    {
+        let Some(end) = parser.bytes.iter().position(|&byte| byte == b';') else {
+            return Err(parser.error());
+        };
+        parser.offset = end + 1;
+    }
+    let geometry = parser.geometry(0)?;
+    parser.whitespace();
+    if parser.offset < parser.bytes.len() {
+        return Err(parser.error());
+    }
+    Ok(geometry)
+}
+
+/// Write a geometry as WKT, with `Line`s as `LINESTRING` and `Rect`s and
+/// `Triangle`s as `POLYGON`.
+pub fn to_wkt(geometry: &Geometry) -> String {
+    let mut wkt = String::new();
+    write_geometry(&mut wkt, geometry);
+    wkt
+}
+
+fn write_geometry(wkt: &mut String, geometry: &Geometry) {
+    match geometry {
+        Geometry::Point(point) => {
+            wkt.push_str("POINT ");
+            write_coords(wkt, std::iter::once(&point.0));
+        }
+        Geometry::Line(line) => {
+            wkt.push_str("LINESTRING ");
+            write_coords(wkt, [&line.start, &line.end].into_iter());
+        }
+        Geometry::LineString(line) => {
+            wkt.push_str("LINESTRING ");
+            write_coords(wkt, line.0.iter());
+        }
+        Geometry::Polygon(polygon) => {
+            wkt.push_str("POLYGON ");
+            write_polygon(wkt, polygon);
+        }
+        Geometry::MultiPoint(points) => {
+            wkt.push_str("MULTIPOINT ");
+            write_list(wkt, &points.0, |wkt, point| {
+                write_coords(wkt, std::iter::once(&point.0));
+            });
+        }
+        Geometry::MultiLineString(lines) => {
+            wkt.push_str("MULTILINESTRING ");
+            write_list(wkt, &lines.0, |wkt, line| write_coords(wkt, line.0.iter()));
+        }
+        Geometry::MultiPolygon(polygons) => {
+            wkt.push_str("MULTIPOLYGON ");
+            write_list(wkt, &polygons.0, write_polygon);
+        }
+        Geometry::GeometryCollection(collection) => {
+            wkt.push_str("GEOMETRYCOLLECTION ");
+            write_list(wkt, &collection.0, write_geometry);
+        }
+        Geometry::Rect(rect) => {
+            wkt.push_str("POLYGON ");
+            write_polygon(wkt, &rect.to_polygon());
+        }
+        Geometry::Triangle(triangle) => {
+            wkt.push_str("POLYGON ");
+            write_polygon(wkt, &triangle.to_polygon());
+        }
+    }
+}
+
+fn write_polygon(wkt: &mut String, polygon: &Polygon) {
+    if polygon.exterior().0.is_empty() {
+        wkt.push_str("EMPTY");
+        return;
+    }
+    let rings = std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .collect::<Vec<_>>();
+    write_list(wkt, &rings, |wkt, ring| write_coords(wkt, ring.0.iter()));
+}
+
+/// Write `(x y, x y)`, or `EMPTY` without coordinates.
+fn write_coords<'a>(wkt: &mut String, coords: impl Iterator<Item = &'a Coord>) {
+    let coords = coords.map(|coord| format!("{} {}", coord.x, coord.y));
+    write_list(wkt, &coords.collect::<Vec<_>>(), |wkt, coord| {
+        wkt.push_str(coord);
+    });
+}
+
+/// Write `(item, item)`, or `EMPTY` without items.
+fn write_list<T>(wkt: &mut String, items: &[T], mut write_item: impl FnMut(&mut String, &T)) {
+    if items.is_empty() {
+        wkt.push_str("EMPTY");
+        return;
+    }
+    wkt.push('(');
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            wkt.push_str(", ");
+        }
+        write_item(wkt, item);
+    }
+    wkt.push(')');
+}
+
+/// A recursive descent parser over the bytes of a WKT text.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> Error {
+        Error::InvalidWkt(self.offset)
+    }
+
+    fn whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.offset)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.offset += 1;
+        }
+    }
+
+    /// Step over `byte` if it is next after whitespace.
+    fn skip(&mut self, byte: u8) -> bool {
+        self.whitespace();
+        let next = self.bytes.get(self.offset) == Some(&byte);
+        if next {
+            self.offset += 1;
+        }
+        next
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.skip(byte) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// The next keyword in upper case, `None` if there is none.
+    fn keyword(&mut self) -> Option<String> {
+        self.whitespace();
+        let start = self.offset;
+        while self
+            .bytes
+            .get(self.offset)
+            .is_some_and(u8::is_ascii_alphabetic)
+        {
+            self.offset += 1;
+        }
+        (self.offset > start)
+            .then(|| String::from_utf8_lossy(&self.bytes[start..self.offset]).to_ascii_uppercase())
+    }
+
+    fn geometry(&mut self, depth: usize) -> Result<Geometry> {
+        if depth > MAX_DEPTH {
+            return Err(self.error());
+        }
+        let start = self.offset;
+        let Some(keyword) = self.keyword() else {
+            return Err(self.error());
+        };
+        // the dimension follows the type name, with or without a space
+        let name = ["ZM", "Z", "M"]
+            .iter()
+            .find_map(|dimension| {
+                keyword
+                    .strip_suffix(dimension)
+                    .filter(|name| GEOMETRY_NAMES.contains(name))
+            })
+            .unwrap_or(&keyword)
+            .to_owned();
+        let dimension_start = self.offset;
+        if !matches!(self.keyword().as_deref(), Some("Z" | "M" | "ZM")) {
+            self.offset = dimension_start;
+        }
+
+        let geometry = match name.as_str() {
+            "POINT" => match self.list(Self::coord)?.as_slice() {
+                [] => MultiPoint(Vec::new()).into(),
+                [coord] => Point(*coord).into(),
+                _ => return Err(self.error()),
+            },
+            "LINESTRING" => self.line_string()?.into(),
+            "POLYGON" => self.polygon()?.into(),
+            "MULTIPOINT" => MultiPoint(self.list(|parser| {
+                // the points may be bare coordinates or in parentheses
+                let coord = if parser.skip(b'(') {
+                    let coord = parser.coord()?;
+                    parser.expect(b')')?;
+                    coord
+                } else {
+                    parser.coord()?
+                };
+                Ok(Point(coord))
+            })?)
+            .into(),
+            "MULTILINESTRING" => MultiLineString(self.list(Self::line_string)?).into(),
+            "MULTIPOLYGON" => MultiPolygon(self.list(Self::polygon)?).into(),
+            "GEOMETRYCOLLECTION" => Geometry::GeometryCollection(GeometryCollection(
+                self.list(|parser| parser.geometry(depth + 1))?,
+            )),
+            _ => {
+                self.offset = start;
+                return Err(self.error());
+            }
+        };
+        Ok(geometry)
+    }
+
+    /// Parse `EMPTY` or `(item, item)`.
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        let start = self.offset;
+        match self.keyword().as_deref() {
+            Some("EMPTY") => return Ok(Vec::new()),
+            Some(_) => {
+                self.offset = start;
+                return Err(self.error());
+            }
+            None => (),
+        }
+        self.expect(b'(')?;
+        let mut items = vec![item(self)?];
+        while self.skip(b',') {
+            items.push(item(self)?);
+        }
+        self.expect(b')')?;
+        Ok(items)
+    }
+
+    fn line_string(&mut self) -> Result<LineString> {
+        Ok(LineString(self.list(Self::coord)?))
+    }
+
+    fn polygon(&mut self) -> Result<Polygon> {
+        let mut rings = self.list(Self::line_string)?.into_iter();
+        let exterior = rings.next().unwrap_or_else(|| LineString(Vec::new()));
+        Ok(Polygon::new(exterior, rings.collect()))
+    }
+
+    /// A position of two to four ordinates, keeping x and y.
+    fn coord(&mut self) -> Result<Coord> {
+        let x = self.number()?;
+        let y = self.number()?;
+        for _ in 0..2 {
+            self.whitespace();
+            if !matches!(
+                self.bytes.get(self.offset),
+                Some(b'-' | b'+' | b'.' | b'0'..=b'9')
+            ) {
+                break;
+            }
+            let _ordinate = self.number()?;
+        }
+        Ok(Coord { x, y })
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        self.whitespace();
+        let start = self.offset;
+        while self
+            .bytes
+            .get(self.offset)
+            .is_some_and(|&byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.offset += 1;
+        }
+        let number = std::str::from_utf8(&self.bytes[start..self.offset])
+            .ok()
+            .and_then(|number| number.parse::<f64>().ok())
+            .filter(|number| number.is_finite());
+        number.ok_or_else(|| Error::InvalidWkt(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{Geometry, LineString, MultiPoint, Point, Polygon};
+
+    use super::{parse_wkt, to_wkt};
+    use crate::{Error, Result};
+
+    #[test]
+    fn round_trips_geometries() -> Result<()> {
+        for wkt in [
+            "POINT (1 2.5)",
+            "LINESTRING (0 0, 10 -0.5)",
+            "POLYGON ((0 0, 10 0, 10 10, 0 0), (1 1, 2 1, 2 2, 1 1))",
+            "MULTIPOINT ((1 2), (3 4))",
+            "MULTILINESTRING ((0 0, 1 1), (2 2, 3 3))",
+            "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), EMPTY)",
+            "GEOMETRYCOLLECTION (POINT (1 2), LINESTRING EMPTY)",
+            "MULTIPOINT EMPTY",
+        ] {
+            assert_eq!(to_wkt(&parse_wkt(wkt)?), wkt);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reads_dialects() -> Result<()> {
+        assert_eq!(
+            parse_wkt("SRID=25832;pointz(1 2 3)")?,
+            Geometry::Point(Point::new(1., 2.))
+        );
+        assert_eq!(
+            parse_wkt(" MULTIPOINT ZM (1 2 3 4, 5 6 7 8) ")?,
+            Geometry::MultiPoint(MultiPoint(vec![Point::new(1., 2.), Point::new(5., 6.)]))
+        );
+        assert_eq!(
+            parse_wkt("POLYGON((0 0,1 0,1 1))")?,
+            Geometry::Polygon(Polygon::new(
+                LineString::from(vec![(0., 0.), (1., 0.), (1., 1.), (0., 0.)]),
+                Vec::new()
+            ))
+        );
+        assert_eq!(
+            parse_wkt("POINT EMPTY")?,
+            Geometry::MultiPoint(MultiPoint(Vec::new()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_offset_of_errors() {
+        for (wkt, offset) in [
+            ("CIRCLE (1 2)", 0),
+            ("POINT (1)", 8),
+            ("POINT (1 2, 3 4)", 16),
+            ("LINESTRING (0 0, 1 1", 20),
+            ("POINT (1 2) x", 12),
+            ("POINT (1 nan)", 9),
+        ] {
+            let Err(Error::InvalidWkt(error_offset)) = parse_wkt(wkt) else {
+                panic!("{wkt} is not valid WKT");
+            };
+            assert_eq!(error_offset, offset, "{wkt}");
+        }
+    }
+}